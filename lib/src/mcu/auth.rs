@@ -1,12 +1,28 @@
 #![allow(clippy::module_name_repetitions)]
 
 mod basic;
+mod bearer;
 mod oauth2;
 
 use async_trait::async_trait;
 pub use basic::BasicAuth;
+pub use bearer::BearerAuth;
 pub use oauth2::AuthToken as OAuth2AccessToken;
 pub use oauth2::OAuth2;
+use strum::Display;
+
+/// The kind of credentials used to authenticate requests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum CredentialType {
+    #[strum(serialize = "no")]
+    None,
+    #[strum(serialize = "basic")]
+    Basic,
+    #[strum(serialize = "bearer token")]
+    Bearer,
+    #[strum(serialize = "OAuth2")]
+    OAuth2,
+}
 
 pub struct NoAuth;
 
@@ -18,6 +34,10 @@ impl ApiClientAuth for NoAuth {
     ) -> anyhow::Result<reqwest::RequestBuilder> {
         Ok(request)
     }
+
+    fn credential_type(&self) -> CredentialType {
+        CredentialType::None
+    }
 }
 
 #[async_trait]
@@ -26,6 +46,9 @@ pub trait ApiClientAuth: Send + Sync {
         &self,
         request: reqwest::RequestBuilder,
     ) -> anyhow::Result<reqwest::RequestBuilder>;
+
+    /// Gets the kind of credentials this authenticates requests with.
+    fn credential_type(&self) -> CredentialType;
 }
 
 #[allow(opaque_hidden_inferred_bound)]
@@ -3,7 +3,7 @@ use log::debug;
 
 use crate::util::SensitiveString;
 
-use super::ApiClientAuth;
+use super::{ApiClientAuth, CredentialType};
 
 pub struct BasicAuth {
     username: String,
@@ -26,4 +26,27 @@ impl ApiClientAuth for BasicAuth {
         debug!("Configuring request with basic authentication");
         Ok(request.basic_auth(&self.username, Some(&self.password.secret())))
     }
+
+    fn credential_type(&self) -> CredentialType {
+        CredentialType::Basic
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_credential_type() {
+        // Arrange
+        let auth = BasicAuth::new(String::from("username"), SensitiveString::from("password"));
+
+        // Act
+        let credential_type = auth.credential_type();
+
+        // Assert
+        assert_that!(credential_type, eq(CredentialType::Basic));
+    }
 }
@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+use log::debug;
+
+use crate::util::SensitiveString;
+
+use super::{ApiClientAuth, CredentialType};
+
+/// Authenticates requests with a bearer token that was issued elsewhere, e.g. by an external identity service.
+///
+/// The token is sent as is, so it is up to the caller to replace it before it expires.
+pub struct BearerAuth {
+    token: SensitiveString,
+}
+
+impl BearerAuth {
+    #[must_use]
+    pub const fn new(token: SensitiveString) -> Self {
+        Self { token }
+    }
+}
+
+#[async_trait]
+impl ApiClientAuth for BearerAuth {
+    async fn add_auth(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        debug!("Configuring request with bearer token authentication");
+        Ok(request.bearer_auth(self.token.secret()))
+    }
+
+    fn credential_type(&self) -> CredentialType {
+        CredentialType::Bearer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_credential_type() {
+        // Arrange
+        let auth = BearerAuth::new(SensitiveString::from("token"));
+
+        // Act
+        let credential_type = auth.credential_type();
+
+        // Assert
+        assert_that!(credential_type, eq(CredentialType::Bearer));
+    }
+
+    #[tokio::test]
+    async fn test_add_auth_sets_authorization_header() {
+        // Arrange
+        let auth = BearerAuth::new(SensitiveString::from("token"));
+        let request = reqwest::Client::new().get("http://localhost/");
+
+        // Act
+        let request = auth.add_auth(request).await.unwrap().build().unwrap();
+
+        // Assert
+        assert_that!(
+            request
+                .headers()
+                .get(reqwest::header::AUTHORIZATION)
+                .map(|value| value.to_str().unwrap()),
+            some(eq("Bearer token"))
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use std::{fmt, str::FromStr};
+
+/// The version segment of API paths, unless it is overridden for a management node.
+const API_VERSION: &str = "v1";
+
+/// Management node versions before this report errors as plain text rather than a JSON object.
+const JSON_ERRORS_SINCE: u32 = 26;
+
+/// Management node versions from this one return a body describing the result of command API requests.
+const COMMAND_RESPONSE_BODIES_SINCE: u32 = 29;
+
+/// Differences in behaviour between management node versions.
+///
+/// The default is used when the management node version is not known, and does not return command API responses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Compat {
+    /// The version segment of API paths, e.g. `v1`.
+    pub api_version: String,
+    /// Whether responses to command API requests contain a body that should be returned.
+    pub command_response_bodies: bool,
+    /// Whether error responses are JSON objects with an `error` field.
+    pub json_errors: bool,
+}
+
+impl Default for Compat {
+    fn default() -> Self {
+        Self {
+            api_version: String::from(API_VERSION),
+            command_response_bodies: false,
+            json_errors: true,
+        }
+    }
+}
+
+impl Compat {
+    /// Gets the behaviour of a given management node version.
+    #[must_use]
+    pub fn for_version(version: Version) -> Self {
+        Self {
+            api_version: String::from(API_VERSION),
+            command_response_bodies: version.major >= COMMAND_RESPONSE_BODIES_SINCE,
+            json_errors: version.major >= JSON_ERRORS_SINCE,
+        }
+    }
+}
+
+/// A management node software version, e.g. `29` or `29.1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid management node version: {s}");
+        let (major, minor) = s.trim().split_once('.').unwrap_or_else(|| (s.trim(), "0"));
+        Ok(Self {
+            major: major.parse().map_err(|_| invalid())?,
+            minor: minor.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::prelude::*;
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("29", Version { major: 29, minor: 0 } ; "major only")]
+    #[test_case("25.4", Version { major: 25, minor: 4 } ; "major and minor")]
+    #[test_case(" 30.1 ", Version { major: 30, minor: 1 } ; "whitespace")]
+    fn test_parse_version(version: &str, expected: Version) {
+        assert_that!(version.parse::<Version>(), ok(eq(&expected)));
+    }
+
+    #[test_case("" ; "empty")]
+    #[test_case("v29" ; "prefix")]
+    #[test_case("29.1.2" ; "patch")]
+    fn test_parse_invalid_version(version: &str) {
+        assert_that!(
+            version.parse::<Version>(),
+            err(eq(&format!("invalid management node version: {version}")))
+        );
+    }
+
+    #[test_case(25, false, false ; "before json errors")]
+    #[test_case(26, false, true ; "json errors")]
+    #[test_case(29, true, true ; "command response bodies")]
+    fn test_compat_for_version(major: u32, command_response_bodies: bool, json_errors: bool) {
+        // Act
+        let compat = Compat::for_version(Version { major, minor: 0 });
+
+        // Assert
+        assert_that!(
+            compat,
+            eq(&Compat {
+                api_version: String::from("v1"),
+                command_response_bodies,
+                json_errors,
+            })
+        );
+    }
+}
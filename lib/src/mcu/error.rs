@@ -2,6 +2,7 @@
 use std::{
     error::Error,
     fmt::{Debug, Display},
+    time::Duration,
 };
 
 /// A wrapper for [`reqwest::Error`] that prevents it from printing repetitive error chains.
@@ -24,10 +25,23 @@ impl Debug for ReqwestDebugPrintWrapper {
 
 impl Error for ReqwestDebugPrintWrapper {}
 
+/// The kinds of error response that callers may want to handle differently.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApiErrorKind {
+    /// The object or endpoint requested doesn't exist.
+    NotFound,
+    /// The credentials were missing or weren't accepted.
+    Unauthorized,
+    /// The user isn't allowed to make the request.
+    Forbidden,
+    Other,
+}
+
 pub struct ApiError {
     status: Option<reqwest::StatusCode>,
     message: String,
     inner: Option<anyhow::Error>,
+    retry_after: Option<Duration>,
 }
 
 impl ApiError {
@@ -41,9 +55,17 @@ impl ApiError {
             status,
             message: message.into(),
             inner,
+            retry_after: None,
         }
     }
 
+    /// Sets how long the server asked for the request to be delayed before retrying it.
+    #[must_use]
+    pub fn with_retry_after(mut self, retry_after: Option<Duration>) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
     #[must_use]
     pub const fn status(&self) -> Option<reqwest::StatusCode> {
         self.status
@@ -58,6 +80,51 @@ impl ApiError {
     pub fn inner(&self) -> Option<&(dyn Error + Send + Sync)> {
         self.inner.as_ref().map(std::convert::AsRef::as_ref)
     }
+
+    /// How long the server asked for the request to be delayed before retrying it.
+    #[must_use]
+    pub const fn retry_after(&self) -> Option<Duration> {
+        self.retry_after
+    }
+
+    /// The kind of error response, from its status.
+    #[must_use]
+    pub fn kind(&self) -> ApiErrorKind {
+        match self.status {
+            Some(reqwest::StatusCode::NOT_FOUND) => ApiErrorKind::NotFound,
+            Some(reqwest::StatusCode::UNAUTHORIZED) => ApiErrorKind::Unauthorized,
+            Some(reqwest::StatusCode::FORBIDDEN) => ApiErrorKind::Forbidden,
+            _ => ApiErrorKind::Other,
+        }
+    }
+
+    /// Whether the management node responded that the object or endpoint requested doesn't exist.
+    #[must_use]
+    pub fn is_not_found(&self) -> bool {
+        self.kind() == ApiErrorKind::NotFound
+    }
+
+    /// Whether the request might succeed if it is sent again, e.g. because the management node was briefly
+    /// unavailable or the connection to it failed.
+    #[must_use]
+    pub fn is_transient(&self) -> bool {
+        self.status.map_or_else(
+            || {
+                self.inner
+                    .as_ref()
+                    .and_then(|inner| inner.downcast_ref::<reqwest::Error>())
+                    .is_some_and(|error| error.is_connect() || error.is_timeout())
+            },
+            |status| {
+                matches!(
+                    status,
+                    reqwest::StatusCode::BAD_GATEWAY
+                        | reqwest::StatusCode::SERVICE_UNAVAILABLE
+                        | reqwest::StatusCode::GATEWAY_TIMEOUT
+                )
+            },
+        )
+    }
 }
 
 impl Display for ApiError {
@@ -90,6 +157,7 @@ impl Error for ApiError {}
 mod tests {
     use googletest::prelude::*;
     use reqwest::StatusCode;
+    use test_case::test_case;
     use test_helpers::googletest::debugs_as;
 
     use super::*;
@@ -121,4 +189,20 @@ mod tests {
             ))
         );
     }
+
+    #[test_case(Some(StatusCode::NOT_FOUND), ApiErrorKind::NotFound ; "not found")]
+    #[test_case(Some(StatusCode::UNAUTHORIZED), ApiErrorKind::Unauthorized ; "unauthorized")]
+    #[test_case(Some(StatusCode::FORBIDDEN), ApiErrorKind::Forbidden ; "forbidden")]
+    #[test_case(Some(StatusCode::INTERNAL_SERVER_ERROR), ApiErrorKind::Other ; "server error")]
+    #[test_case(None, ApiErrorKind::Other ; "no response")]
+    fn test_api_error_kind(status: Option<StatusCode>, expected: ApiErrorKind) {
+        // Arrange
+        let error = ApiError::new(status, "Test message.", None);
+
+        // Act
+        let kind = error.kind();
+
+        // Assert
+        assert_that!(kind, eq(expected));
+    }
 }
@@ -0,0 +1,101 @@
+#![allow(clippy::module_name_repetitions)]
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use log::debug;
+
+use crate::util;
+
+/// Processes the requests sent by an [`ApiClient`](super::ApiClient) and the responses it receives, e.g. to sign
+/// requests or collect metrics.
+///
+/// Middlewares are called in the order they were given to the client, each time a request is sent (including when it
+/// is retried). If a middleware returns an error, the request fails with that error.
+pub trait Middleware: Send + Sync {
+    /// Called before a request is sent, with the request that will be sent.
+    fn before_send(&self, _request: &mut reqwest::Request) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Called when a response is received, before its status is checked.
+    fn after_receive(&self, _response: &reqwest::Response) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Logs each request that is sent and the status of each response at debug level, with any sensitive query parameters
+/// redacted.
+pub struct RequestLogger;
+
+impl Middleware for RequestLogger {
+    fn before_send(&self, request: &mut reqwest::Request) -> anyhow::Result<()> {
+        debug!(
+            "Sending {} {}",
+            request.method(),
+            util::Redacted(request.url())
+        );
+        Ok(())
+    }
+
+    fn after_receive(&self, response: &reqwest::Response) -> anyhow::Result<()> {
+        debug!(
+            "Received {} from {}",
+            response.status(),
+            util::Redacted(response.url())
+        );
+        Ok(())
+    }
+}
+
+/// Counts the requests sent and the responses received.
+///
+/// Clones share their counts, so a clone can be kept to read the counts after the original is given to a client.
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    requests: Arc<AtomicUsize>,
+    responses: Arc<AtomicUsize>,
+    error_responses: Arc<AtomicUsize>,
+}
+
+impl Metrics {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the number of requests sent.
+    #[must_use]
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// Gets the number of responses received, including error responses.
+    #[must_use]
+    pub fn responses(&self) -> usize {
+        self.responses.load(Ordering::Relaxed)
+    }
+
+    /// Gets the number of responses received with a client or server error status.
+    #[must_use]
+    pub fn error_responses(&self) -> usize {
+        self.error_responses.load(Ordering::Relaxed)
+    }
+}
+
+impl Middleware for Metrics {
+    fn before_send(&self, _request: &mut reqwest::Request) -> anyhow::Result<()> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn after_receive(&self, response: &reqwest::Response) -> anyhow::Result<()> {
+        self.responses.fetch_add(1, Ordering::Relaxed);
+        if response.status().is_client_error() || response.status().is_server_error() {
+            self.error_responses.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
 pub mod auth;
+pub mod compat;
 mod error;
+pub mod middleware;
+pub mod retry;
 pub mod schema;
 
 use std::fmt;
@@ -9,7 +12,8 @@ use std::{collections::HashMap, error::Error};
 
 use async_stream::try_stream;
 use async_trait::async_trait;
-use futures::stream::StreamExt;
+use chrono::Utc;
+use futures::stream::{StreamExt, TryStreamExt};
 use futures::Stream;
 use log::{debug, info, trace, warn};
 use serde::Deserialize;
@@ -23,6 +27,9 @@ pub use error::*;
 use crate::util;
 
 use self::auth::{ApiClientAuth, AuthWith};
+use self::compat::Compat;
+use self::middleware::Middleware;
+use self::retry::RetryPolicy;
 
 #[derive(EnumIter, Clone, Copy, Debug, PartialEq, Eq, Hash, Display)]
 #[strum(serialize_all = "snake_case")]
@@ -124,6 +131,13 @@ pub struct ApiClient<'auth> {
     base_address: String,
     auth: Arc<Box<dyn ApiClientAuth + 'auth>>,
     semaphore: Arc<Semaphore>,
+    retry_policy: RetryPolicy,
+    compat: Compat,
+    /// How many pages of a `GetAll` request are fetched ahead of the objects being consumed.
+    max_concurrent_pages: usize,
+    /// Sent as the `X-Request-ID` header of every request, to correlate requests with the management node's logs.
+    request_id: Option<reqwest::header::HeaderValue>,
+    middlewares: Arc<[Box<dyn Middleware>]>,
 }
 
 impl Clone for ApiClient<'_> {
@@ -133,6 +147,11 @@ impl Clone for ApiClient<'_> {
             base_address: self.base_address.clone(),
             auth: self.auth.clone(),
             semaphore: self.semaphore.clone(),
+            retry_policy: self.retry_policy,
+            compat: self.compat.clone(),
+            max_concurrent_pages: self.max_concurrent_pages,
+            request_id: self.request_id.clone(),
+            middlewares: self.middlewares.clone(),
         }
     }
 }
@@ -162,6 +181,11 @@ impl<'auth> ApiClient<'auth> {
             base_address: mcu_address,
             auth: Arc::new(Box::new(auth)),
             semaphore: Arc::new(Semaphore::new(5)),
+            retry_policy: RetryPolicy::default(),
+            compat: Compat::default(),
+            max_concurrent_pages: 1,
+            request_id: None,
+            middlewares: Arc::from(Vec::new()),
         }
     }
 
@@ -178,28 +202,269 @@ impl<'auth> ApiClient<'auth> {
             base_address,
             auth: Arc::new(auth),
             semaphore: Arc::new(Semaphore::new(5)), // This limit is fairly arbitrary, but too many requests causes the management node to get bogged down!
+            retry_policy: RetryPolicy::default(),
+            compat: Compat::default(),
+            max_concurrent_pages: 1,
+            request_id: None,
+            middlewares: Arc::from(Vec::new()),
         }
     }
 
+    #[must_use]
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    #[must_use]
+    pub fn with_compat(mut self, compat: Compat) -> Self {
+        self.compat = compat;
+        self
+    }
+
+    /// Fetches up to `max_concurrent_pages` pages of `GetAll` requests at once, instead of one page at a time.
+    #[must_use]
+    pub fn with_max_concurrent_pages(mut self, max_concurrent_pages: usize) -> Self {
+        self.max_concurrent_pages = max_concurrent_pages;
+        self
+    }
+
+    /// Sends `request_id` as the `X-Request-ID` header of every request.
+    #[must_use]
+    pub fn with_request_id(mut self, request_id: Option<reqwest::header::HeaderValue>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
+    /// Calls `middlewares`, in order, for every request sent and response received.
+    #[must_use]
+    pub fn with_middlewares(mut self, middlewares: Vec<Box<dyn Middleware>>) -> Self {
+        self.middlewares = Arc::from(middlewares);
+        self
+    }
+
+    /// Sends a request and collects all of the objects in the response, fetching every page of a list request.
+    ///
+    /// A single object is returned as the only element, and responses without content give an empty list.
+    pub async fn send_paginated_all(&self, request: ApiRequest) -> anyhow::Result<Vec<Value>> {
+        match self.send(request).await? {
+            ApiResponse::ContentStream(objects) => Ok(objects.try_collect::<Vec<_>>().await?),
+            ApiResponse::Content(Value::Array(objects)) => Ok(objects),
+            ApiResponse::Content(object) => Ok(vec![object]),
+            ApiResponse::Location(_) | ApiResponse::Nothing => Ok(vec![]),
+        }
+    }
+
+    /// Gets the number of objects that a `GetAll` request matches from the `total_count` of a page with a single
+    /// object, without fetching the objects themselves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `request` is not a `GetAll` request.
+    pub async fn count(&self, request: ApiRequest) -> anyhow::Result<usize> {
+        let ApiRequest::GetAll {
+            api,
+            resource,
+            filter_args,
+            ..
+        } = request
+        else {
+            panic!("Request was not GetAll - its objects cannot be counted!");
+        };
+        let request = self
+            .build_request(ApiRequest::GetAll {
+                api,
+                resource,
+                filter_args,
+                page_size: 1,
+                limit: 1,
+                offset: 0,
+                order_by: None,
+            })
+            .await
+            .map_err(build_request_error)?;
+        Ok(self.fetch_page(request).await?.meta.total_count)
+    }
+
+    /// Sends several independent requests at once, returning their responses in the same order as the requests.
+    ///
+    /// reqwest does not support HTTP/1.1 pipelining, so the requests are sent concurrently instead, sharing the
+    /// client's connection pool and limited by the same semaphore as any other request.
+    pub async fn pipeline_requests(
+        &self,
+        requests: Vec<ApiRequest>,
+    ) -> Vec<Result<ApiResponse<'_>, ApiClientError>> {
+        futures::future::join_all(
+            requests
+                .into_iter()
+                .map(|request| async move { self.send(request).await.map_err(api_client_error) }),
+        )
+        .await
+    }
+
     fn get_base_uri_for_api(&self, api: Api) -> String {
         match api {
             Api::Command(command) => {
                 format!(
-                    "{}/api/admin/command/v1/{}",
+                    "{}/api/admin/command/{}/{}",
                     &self.base_address,
+                    &self.compat.api_version,
                     &command.to_string(),
                 )
             }
             _ => {
                 format!(
-                    "{}/api/admin/{}/v1",
+                    "{}/api/admin/{}/{}",
                     &self.base_address,
-                    &api.to_string().to_lowercase()
+                    &api.to_string().to_lowercase(),
+                    &self.compat.api_version,
                 )
             }
         }
     }
 
+    /// Gets the URI of a single object of `resource`.
+    fn get_object_uri(&self, api: Api, resource: &str, object_id: &str) -> String {
+        format!("{}/{resource}/{object_id}/", self.get_base_uri_for_api(api))
+    }
+
+    /// Gets the URI of a page of objects of `resource`, leaving out any filters.
+    fn get_page_uri(
+        &self,
+        api: Api,
+        resource: &str,
+        page_size: usize,
+        offset: usize,
+        order_by: Option<&str>,
+    ) -> String {
+        let uri = format!(
+            "{}/{resource}/?limit={page_size}&offset={offset}",
+            self.get_base_uri_for_api(api)
+        );
+        match order_by {
+            Some(order_by) => format!("{uri}&order_by={order_by}"),
+            None => uri,
+        }
+    }
+
+    /// Describes what sending `request` would do, without authenticating or sending it, e.g. for a dry run.
+    #[must_use]
+    pub fn preview(&self, request: &ApiRequest) -> RequestPreview {
+        let preview = |method, uri, body: Option<&Value>| RequestPreview {
+            method,
+            uri,
+            query_redacted: false,
+            body: body.cloned(),
+        };
+        match request {
+            ApiRequest::ApiSchema { api } => preview(
+                reqwest::Method::GET,
+                self.get_base_uri_for_api(*api) + "/",
+                None,
+            ),
+            ApiRequest::Schema { api, resource } => preview(
+                reqwest::Method::GET,
+                format!("{}/{resource}/schema/", self.get_base_uri_for_api(*api)),
+                None,
+            ),
+            ApiRequest::Get {
+                api,
+                resource,
+                object_id,
+            } => preview(
+                reqwest::Method::GET,
+                self.get_object_uri(*api, resource, object_id),
+                None,
+            ),
+            ApiRequest::Head {
+                api,
+                resource,
+                object_id,
+            } => preview(
+                reqwest::Method::HEAD,
+                self.get_object_uri(*api, resource, object_id),
+                None,
+            ),
+            ApiRequest::GetAll {
+                api,
+                resource,
+                filter_args,
+                page_size,
+                limit: _,
+                offset,
+                order_by,
+            } => {
+                let uri =
+                    self.get_page_uri(*api, resource, *page_size, *offset, order_by.as_deref());
+                RequestPreview {
+                    query_redacted: !filter_args.is_empty(),
+                    ..preview(reqwest::Method::GET, uri, None)
+                }
+            }
+            ApiRequest::Post {
+                api,
+                resource,
+                args,
+            } => preview(
+                reqwest::Method::POST,
+                format!("{}/{resource}/", self.get_base_uri_for_api(*api)),
+                Some(args),
+            ),
+            ApiRequest::Patch {
+                api,
+                resource,
+                object_id,
+                args,
+            } => preview(
+                reqwest::Method::PATCH,
+                self.get_object_uri(*api, resource, object_id),
+                Some(args),
+            ),
+            ApiRequest::Put {
+                api,
+                resource,
+                object_id,
+                args,
+            } => preview(
+                reqwest::Method::PUT,
+                self.get_object_uri(*api, resource, object_id),
+                Some(args),
+            ),
+            ApiRequest::Delete {
+                api,
+                resource,
+                object_id,
+            } => preview(
+                reqwest::Method::DELETE,
+                self.get_object_uri(*api, resource, object_id),
+                None,
+            ),
+            ApiRequest::Raw { method, path, body } => preview(
+                method.clone(),
+                format!("{}/{}", &self.base_address, path.trim_start_matches('/')),
+                body.as_ref(),
+            ),
+        }
+    }
+
+    /// Builds the HTTP request that sending `request` would start with, including its authentication and request ID.
+    ///
+    /// For a `GetAll` request this is the request for the first page.
+    pub async fn prepare_request(&self, request: ApiRequest) -> anyhow::Result<reqwest::Request> {
+        let mut request = self.build_request(request).await?;
+        self.add_request_id(&mut request);
+        Ok(request)
+    }
+
+    fn add_request_id(&self, request: &mut reqwest::Request) {
+        if let Some(request_id) = &self.request_id {
+            debug!("X-Request-ID: {request_id:?}");
+            request
+                .headers_mut()
+                .insert("X-Request-ID", request_id.clone());
+        }
+    }
+
     #[allow(clippy::too_many_lines)]
     async fn build_request(&self, request: ApiRequest) -> anyhow::Result<reqwest::Request> {
         match request {
@@ -219,6 +484,22 @@ impl<'auth> ApiClient<'auth> {
                     .await?
                     .build()?)
             }
+            ApiRequest::Head {
+                api,
+                resource,
+                object_id,
+            } => {
+                let uri = self.get_base_uri_for_api(api);
+                let uri = format!("{uri}/{resource}/{object_id}/");
+
+                info!("HEAD {}", &uri);
+                Ok(self
+                    .http_client
+                    .head(uri)
+                    .auth_with(&**self.auth)
+                    .await?
+                    .build()?)
+            }
             ApiRequest::GetAll {
                 api,
                 resource,
@@ -226,6 +507,7 @@ impl<'auth> ApiClient<'auth> {
                 page_size,
                 limit: _,
                 offset,
+                order_by,
             } => {
                 let uri = self.get_base_uri_for_api(api);
                 let uri = format!(
@@ -237,13 +519,18 @@ impl<'auth> ApiClient<'auth> {
                     "GET_ALL {}  (query parameters are excluded since they may be sensitive)",
                     &uri
                 );
-                Ok(self
+                let mut request = self
                     .http_client
                     .get(uri)
                     .auth_with(&**self.auth)
                     .await?
-                    .query(&filter_args)
-                    .build()?)
+                    .query(&filter_args);
+                if let Some(order_by) = order_by {
+                    request = request.query(&[("order_by", order_by)]);
+                }
+                let request = request.build()?;
+                trace!("GET_ALL {}", util::Redacted(request.url()));
+                Ok(request)
             }
             ApiRequest::Post {
                 api,
@@ -280,6 +567,24 @@ impl<'auth> ApiClient<'auth> {
                     .json(&args)
                     .build()?)
             }
+            ApiRequest::Put {
+                api,
+                resource,
+                object_id,
+                args,
+            } => {
+                let uri = self.get_base_uri_for_api(api);
+                let uri = format!("{uri}/{resource}/{object_id}/");
+
+                info!("PUT {}", &uri);
+                Ok(self
+                    .http_client
+                    .put(uri)
+                    .auth_with(&**self.auth)
+                    .await?
+                    .json(&args)
+                    .build()?)
+            }
             ApiRequest::Delete {
                 api,
                 resource,
@@ -296,6 +601,22 @@ impl<'auth> ApiClient<'auth> {
                     .await?
                     .build()?)
             }
+            ApiRequest::Raw { method, path, body } => {
+                let uri = format!("{}/{}", &self.base_address, path.trim_start_matches('/'));
+
+                let request = self
+                    .http_client
+                    .request(method, uri)
+                    .auth_with(&**self.auth)
+                    .await?;
+                let request = match body {
+                    Some(body) => request.json(&body),
+                    None => request,
+                };
+                let request = request.build()?;
+                info!("{} {}", request.method(), util::Redacted(request.url()));
+                Ok(request)
+            }
             ApiRequest::ApiSchema { api } => {
                 let uri = self.get_base_uri_for_api(api) + "/";
                 debug!("API_SCHEMA {}", &uri);
@@ -322,10 +643,19 @@ impl<'auth> ApiClient<'auth> {
 
     pub async fn handle_api_errors(
         response: reqwest::Result<reqwest::Response>,
+        compat: &Compat,
     ) -> Result<reqwest::Response, ApiError> {
         match response {
             Err(error) => {
-                if let Some(inner) = error.source() {
+                if error.is_timeout() {
+                    Err(ApiError::new(
+                        error.status(),
+                        "timed out waiting for the management node to respond",
+                        Some(error.into()),
+                    ))
+                } else if let Some(message) = describe_connect_error(&error) {
+                    Err(ApiError::new(error.status(), message, Some(error.into())))
+                } else if let Some(inner) = error.source() {
                     if let Some(inner) = inner.downcast_ref::<hyper::Error>() {
                         Err(ApiError::new(
                             error.status(),
@@ -352,10 +682,24 @@ impl<'auth> ApiClient<'auth> {
                 if status.is_success() {
                     Ok(response)
                 } else {
+                    let header = response
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok());
+                    let retry_after = match status {
+                        reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                            Some(retry::parse_retry_after(header, Utc::now()))
+                        }
+                        reqwest::StatusCode::SERVICE_UNAVAILABLE => {
+                            header.map(|header| retry::parse_retry_after(Some(header), Utc::now()))
+                        }
+                        _ => None,
+                    };
                     let contents = response.text().await;
 
                     #[allow(clippy::option_if_let_else)]
                     let error_message = match contents {
+                        Ok(contents) if !contents.is_empty() && !compat.json_errors => contents,
                         Ok(contents) if !contents.is_empty() => {
                             if let Ok(json_error) = serde_json::from_str::<JsonError>(&contents) {
                                 json_error.error
@@ -373,16 +717,144 @@ impl<'auth> ApiClient<'auth> {
                         }
                         _ => format!("response code \"{status}\" did not indicate success"),
                     };
-                    Err(ApiError::new(
-                        Some(status),
-                        format!("http error: {error_message}"),
-                        None,
-                    ))
+                    Err(
+                        ApiError::new(Some(status), format!("http error: {error_message}"), None)
+                            .with_retry_after(retry_after),
+                    )
                 }
             }
         }
     }
 
+    /// Logs a request that is about to be sent at trace level, with any sensitive values in its URL and JSON body
+    /// redacted.
+    fn log_request(&self, request: &reqwest::Request) {
+        if !log::log_enabled!(log::Level::Trace) {
+            return;
+        }
+        trace!(
+            "--> {} {} ({} auth)",
+            request.method(),
+            util::Redacted(request.url()),
+            self.auth.credential_type()
+        );
+        let body = request
+            .body()
+            .and_then(reqwest::Body::as_bytes)
+            .and_then(|body| serde_json::from_slice::<Value>(body).ok());
+        if let Some(body) = body {
+            trace!("--> {}", util::Redacted(&body));
+        }
+    }
+
+    /// Executes a request, retrying it if the management node responds with a rate limit, or if an idempotent request
+    /// fails with a transient error.
+    async fn execute(&self, mut request: reqwest::Request) -> Result<reqwest::Response, ApiError> {
+        self.add_request_id(&mut request);
+        self.log_request(&request);
+        let mut retries = self.retry_policy.rate_limit_retries();
+        let mut transient_retries = self.retry_policy.transient_retries_for(request.method());
+        let mut backoff_retry = 0;
+        loop {
+            let retry_request = if retries > 0 || transient_retries > 0 {
+                request.try_clone()
+            } else {
+                None
+            };
+            for middleware in self.middlewares.iter() {
+                middleware
+                    .before_send(&mut request)
+                    .map_err(|e| ApiError::new(None, format!("{e:#}"), Some(e)))?;
+            }
+            let response = self.http_client.execute(request).await;
+            if let Ok(response) = &response {
+                for middleware in self.middlewares.iter() {
+                    middleware.after_receive(response).map_err(|e| {
+                        ApiError::new(Some(response.status()), format!("{e:#}"), Some(e))
+                    })?;
+                }
+            }
+            let result = Self::handle_api_errors(response, &self.compat).await;
+
+            let (Err(error), Some(retry_request)) = (&result, retry_request) else {
+                return result;
+            };
+
+            let delay =
+                if error.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) && retries > 0 {
+                    let delay = self
+                        .retry_policy
+                        .rate_limit_delay(error.retry_after().unwrap_or_default());
+                    warn!(
+                        "Rate limited by the management node - retrying in {} seconds",
+                        delay.as_secs_f64()
+                    );
+                    retries -= 1;
+                    delay
+                } else if error.is_transient() && transient_retries > 0 {
+                    let delay = error.retry_after().map_or_else(
+                        || {
+                            self.retry_policy
+                                .backoff_delay(backoff_retry, rand::random::<f64>())
+                        },
+                        |retry_after| self.retry_policy.rate_limit_delay(retry_after),
+                    );
+                    transient_retries -= 1;
+                    backoff_retry += 1;
+                    debug!(
+                        "{error} - retrying in {} seconds ({transient_retries} retries left)",
+                        delay.as_secs_f64()
+                    );
+                    delay
+                } else {
+                    return result;
+                };
+            tokio::time::sleep(delay).await;
+            request = retry_request;
+        }
+    }
+
+    /// Fetches and parses a page of a `GetAll` request.
+    async fn fetch_page(
+        &self,
+        request: reqwest::Request,
+    ) -> Result<GetApiResponse, ApiClientError> {
+        let _hold = self
+            .semaphore
+            .acquire()
+            .await
+            .expect("semaphore should never be closed");
+        let response = self.execute(request).await?;
+        let response_code = response.status();
+
+        let response_text = response.text().await?;
+        serde_json::from_str(&response_text).map_err(|e| {
+            ApiClientError::ApiError(error::ApiError::new(
+                Some(response_code),
+                format!(
+                    "failed to parse API response to JSON ({}):\n\n{}",
+                    e, &response_text
+                ),
+                Some(e.into()),
+            ))
+        })
+    }
+
+    /// Builds the request for a page of a `GetAll` request at the given offset.
+    async fn build_page_request(
+        &self,
+        api_request: &ApiRequest,
+        offset: usize,
+    ) -> Result<reqwest::Request, ApiClientError> {
+        let page_request = api_request
+            .with_offset(offset)
+            .expect("page requests are only built for GetAll requests");
+        Ok(self
+            .build_request(page_request)
+            .await
+            .map_err(build_request_error)?)
+    }
+
     fn streamed_response(
         self,
         api_request: ApiRequest,
@@ -396,59 +868,76 @@ impl<'auth> ApiClient<'auth> {
                 if limit == 0 {
                     limit = usize::MAX;
                 }
-                let mut request = client.build_request(api_request.clone()).await.map_err(|e| {
-                    ApiError::new(
-                        e.downcast_ref::<reqwest::Error>().and_then(reqwest::Error::status),
-                        format!("error building request: {e:#}"),
-                        Some(e),
-                    )
-                })?;
-
-                loop {
-                    let _hold = client.semaphore.acquire().await.expect("semaphore should never be closed");
-                    let response = Self::handle_api_errors(client.http_client.execute(request).await).await?;
-                    let response_code = response.status();
-
-                    let response_text = response.text().await?;
-                    let api_response: GetApiResponse = match serde_json::from_str(&response_text) {
-                        Ok(json) => json,
-                        Err(e) => {
-                            Err(ApiClientError::ApiError(error::ApiError::new(
-                                Some(response_code),
-                                format!(
-                                    "failed to parse API response to JSON ({}):\n\n{}",
-                                    e, &response_text
-                                ),
-                                Some(e.into()),
-                            )))?
+                let request = client.build_request(api_request.clone()).await.map_err(build_request_error)?;
+                let first_page = client.fetch_page(request).await?;
+                let Meta { limit: page_size, offset, total_count, .. } = first_page.meta;
+                let mut next = first_page.meta.next;
+
+                for obj in first_page.objects {
+                    yield obj;
+                    limit -= 1;
+                    if limit == 0 {
+                        break;
+                    }
+                }
+
+                if client.max_concurrent_pages > 1 && limit > 0 && next.is_some() {
+                    // The remaining pages are fetched by offset, up to `max_concurrent_pages` ahead of the objects
+                    // being consumed, and yielded in order. If objects are added while they are being fetched, the
+                    // pages after the ones fetched by offset are then followed one at a time.
+                    let page_size = page_size.max(1);
+                    let end = total_count.min(offset.saturating_add(page_size).saturating_add(limit));
+                    let client = &client;
+                    let api_request = &api_request;
+                    let mut pages = futures::stream::iter((offset + page_size..end).step_by(page_size))
+                        .map(|offset| async move {
+                            let request = client.build_page_request(api_request, offset).await?;
+                            client.fetch_page(request).await
+                        })
+                        .buffered(client.max_concurrent_pages);
+
+                    'pages: while let Some(page) = pages.next().await {
+                        let page = page?;
+                        if page.meta.total_count != total_count {
+                            warn!(
+                                "The number of objects changed from {total_count} to {} while they were being fetched - \
+                                 some objects may be missing or repeated",
+                                page.meta.total_count
+                            );
                         }
+                        next = if page.meta.total_count > total_count {
+                            page.meta.next
+                        } else {
+                            None
+                        };
+                        for obj in page.objects {
+                            yield obj;
+                            limit -= 1;
+                            if limit == 0 {
+                                break 'pages;
+                            }
+                        }
+                    }
+                }
+
+                while limit > 0 {
+                    let Some(uri) = next else {
+                        break;
                     };
+                    let request = client.http_client
+                        .get(format!("{}{}", client.base_address, uri))
+                        .auth_with(&**client.auth).await.map_err(build_request_error)?
+                        .build()?;
+                    let page = client.fetch_page(request).await?;
+                    next = page.meta.next;
 
-                    for obj in api_response.objects {
+                    for obj in page.objects {
                         yield obj;
                         limit -= 1;
                         if limit == 0 {
                             break;
                         }
                     }
-                    if limit == 0 {
-                        break;
-                    }
-
-                    if let Some(uri) = api_response.meta.next {
-                        request = client.http_client
-                                .get(format!("{}{}", client.base_address, uri))
-                                .auth_with(&**client.auth).await.map_err(|e| {
-                                    ApiError::new(
-                                        e.downcast_ref::<reqwest::Error>().and_then(reqwest::Error::status),
-                                        format!("error building request: {e:#}"),
-                                        Some(e),
-                                    )
-                                })?
-                                .build()?;
-                    } else {
-                        break;
-                    }
                 }
             } else {
                 panic!("Request was not GetAll - response cannot be streamed!");
@@ -458,6 +947,71 @@ impl<'auth> ApiClient<'auth> {
     }
 }
 
+/// Explains a failure to connect to the management node, if it was caused by a problem that the user can fix, such as
+/// a mistyped address or an untrusted certificate.
+fn describe_connect_error(error: &reqwest::Error) -> Option<String> {
+    if !error.is_connect() {
+        return None;
+    }
+    let host = error
+        .url()
+        .and_then(reqwest::Url::host_str)
+        .unwrap_or("the management node");
+    let mut sources = std::iter::successors(error.source(), |&source| source.source());
+    sources.find_map(|source| {
+        if source
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::ConnectionRefused)
+        {
+            return Some(format!(
+                "connection to {host} was refused - check the address and port, and that the management node is \
+                 running"
+            ));
+        }
+        let message = source.to_string().to_lowercase();
+        if message.starts_with("dns error") {
+            Some(format!("could not resolve host {host} - check the address"))
+        } else if message.contains("certificate") {
+            Some(format!(
+                "TLS certificate verification failed for {host} - use --cacert to trust a private CA, or \
+                 --insecure to skip verification"
+            ))
+        } else {
+            None
+        }
+    })
+}
+
+/// Wraps an error building a request for the management node.
+fn build_request_error(e: anyhow::Error) -> ApiError {
+    ApiError::new(
+        e.downcast_ref::<reqwest::Error>()
+            .and_then(reqwest::Error::status),
+        format!("error building request: {e:#}"),
+        Some(e),
+    )
+}
+
+/// Recovers the typed error of a failed request, wrapping any other error in an [`ApiError`].
+fn api_client_error(e: anyhow::Error) -> ApiClientError {
+    let e = match e.downcast::<ApiClientError>() {
+        Ok(e) => return e,
+        Err(e) => e,
+    };
+    let e = match e.downcast::<ApiError>() {
+        Ok(e) => return e.into(),
+        Err(e) => e,
+    };
+    let e = match e.downcast::<reqwest::Error>() {
+        Ok(e) => return e.into(),
+        Err(e) => e,
+    };
+    match e.downcast::<serde_json::Error>() {
+        Ok(e) => e.into(),
+        Err(e) => ApiError::new(None, format!("{e:#}"), Some(e)).into(),
+    }
+}
+
 #[derive(Error)]
 pub enum ApiClientError {
     #[error(transparent)]
@@ -487,13 +1041,14 @@ struct JsonError {
 #[async_trait]
 impl IApiClient for ApiClient<'_> {
     async fn send<'a>(&'a self, request: ApiRequest) -> anyhow::Result<ApiResponse<'a>> {
-        let is_command = matches!(
-            request,
-            ApiRequest::Post {
-                api: Api::Command(_),
-                ..
-            }
-        );
+        let discard_response = !self.compat.command_response_bodies
+            && matches!(
+                request,
+                ApiRequest::Post {
+                    api: Api::Command(_),
+                    ..
+                }
+            );
         if let r @ ApiRequest::GetAll { .. } = request {
             let stream_client = self.clone();
             Ok(ApiResponse::ContentStream(util::StreamWrapper::new(
@@ -516,16 +1071,15 @@ impl IApiClient for ApiClient<'_> {
                 .acquire()
                 .await
                 .expect("semaphore should never be closed");
-            trace!("--> {} {}", method, url);
-            let response = Self::handle_api_errors(self.http_client.execute(request).await).await?;
+            let response = self.execute(request).await?;
             let response_code = response.status();
 
             let location = response.headers().get("Location").cloned();
 
             let response_text = response.text().await?;
-            trace!("<-- {} {}", method, url);
+            trace!("<-- {} {}", method, util::Redacted(&url));
             if !response_text.is_empty() {
-                if is_command {
+                if discard_response {
                     Ok(ApiResponse::Nothing)
                 } else {
                     Ok(ApiResponse::Content({
@@ -572,6 +1126,11 @@ pub enum ApiRequest {
         resource: String,
         object_id: String,
     },
+    Head {
+        api: Api,
+        resource: String,
+        object_id: String,
+    },
     GetAll {
         api: Api,
         resource: String,
@@ -579,6 +1138,8 @@ pub enum ApiRequest {
         page_size: usize,
         limit: usize,
         offset: usize,
+        /// The field to order the objects by, prefixed with `-` to order them in descending order.
+        order_by: Option<String>,
     },
     Post {
         api: Api,
@@ -591,14 +1152,75 @@ pub enum ApiRequest {
         object_id: String,
         args: serde_json::Value,
     },
+    /// Replaces an object, setting every field that is not given to its default.
+    Put {
+        api: Api,
+        resource: String,
+        object_id: String,
+        args: serde_json::Value,
+    },
     Delete {
         api: Api,
         resource: String,
         object_id: String,
     },
+    /// A request to any path on the management node, which is not checked against the schema.
+    Raw {
+        method: reqwest::Method,
+        /// Path relative to the management node address.
+        path: String,
+        body: Option<serde_json::Value>,
+    },
+}
+
+/// What an [`ApiRequest`] would send, as described by [`ApiClient::preview`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestPreview {
+    pub method: reqwest::Method,
+    pub uri: String,
+    /// Whether query parameters were left out of `uri` since they may be sensitive, as for the filters of a `GetAll`.
+    pub query_redacted: bool,
+    pub body: Option<Value>,
+}
+
+impl fmt::Display for RequestPreview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.uri)?;
+        if self.query_redacted {
+            write!(
+                f,
+                "  (query parameters are excluded since they may be sensitive)"
+            )?;
+        }
+        writeln!(f)?;
+        if let Some(body) = &self.body {
+            writeln!(
+                f,
+                "{}",
+                serde_json::to_string_pretty(body).map_err(|_| fmt::Error)?
+            )?;
+        }
+        Ok(())
+    }
 }
 
 impl ApiRequest {
+    /// Gets the API and resource that the request is for, if it is for a resource described by the schema.
+    #[must_use]
+    pub fn resource(&self) -> Option<(Api, &str)> {
+        match self {
+            Self::Schema { api, resource }
+            | Self::Get { api, resource, .. }
+            | Self::Head { api, resource, .. }
+            | Self::GetAll { api, resource, .. }
+            | Self::Post { api, resource, .. }
+            | Self::Patch { api, resource, .. }
+            | Self::Put { api, resource, .. }
+            | Self::Delete { api, resource, .. } => Some((*api, resource)),
+            Self::ApiSchema { .. } | Self::Raw { .. } => None,
+        }
+    }
+
     #[must_use]
     pub fn with_offset(&self, offset: usize) -> Option<Self> {
         if let Self::GetAll {
@@ -608,6 +1230,7 @@ impl ApiRequest {
             page_size,
             limit,
             offset: _,
+            order_by,
         } = self
         {
             Some(Self::GetAll {
@@ -617,6 +1240,7 @@ impl ApiRequest {
                 page_size: *page_size,
                 limit: *limit,
                 offset,
+                order_by: order_by.clone(),
             })
         } else {
             None
@@ -657,3 +1281,644 @@ struct GetApiResponse {
     objects: Vec<Value>,
     meta: Meta,
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::future_not_send)]
+
+    use googletest::prelude::*;
+    use serde_json::json;
+    use test_case::test_case;
+    use wiremock::{
+        matchers::{header, method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::{mcu::auth::BasicAuth, util::SensitiveString};
+
+    fn get_all_conferences() -> ApiRequest {
+        ApiRequest::GetAll {
+            api: Api::Configuration,
+            resource: String::from("conference"),
+            filter_args: HashMap::new(),
+            page_size: 1,
+            limit: 0,
+            offset: 0,
+            order_by: None,
+        }
+    }
+
+    fn page(objects: &[Value], next: Option<&str>) -> Value {
+        json!({
+            "meta": {
+                "limit": 1,
+                "next": next,
+                "offset": 0,
+                "previous": null,
+                "total_count": 2,
+            },
+            "objects": objects,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_send_paginated_all_collects_pages() {
+        // Arrange
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/"))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(
+                &[json!({"id": 1})],
+                Some("/api/admin/configuration/v1/conference/?limit=1&offset=1"),
+            )))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/"))
+            .and(query_param("offset", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(&[json!({"id": 2})], None)))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        );
+
+        // Act
+        let objects = api_client.send_paginated_all(get_all_conferences()).await;
+
+        // Assert
+        assert_that!(
+            objects,
+            ok(elements_are![eq(&json!({"id": 1})), eq(&json!({"id": 2}))])
+        );
+    }
+
+    #[test_case(Api::Configuration, "http://mcu.example.com/api/admin/configuration/v2/conference/" ; "configuration")]
+    #[test_case(Api::Command(CommandApi::Conference), "http://mcu.example.com/api/admin/command/v2/conference/conference/" ; "command")]
+    fn test_api_version_from_compat_is_used_in_paths(api: Api, expected: &str) {
+        // Arrange
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            String::from("http://mcu.example.com"),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        )
+        .with_compat(Compat {
+            api_version: String::from("v2"),
+            ..Compat::default()
+        });
+
+        // Act
+        let preview = api_client.preview(&ApiRequest::Post {
+            api,
+            resource: String::from("conference"),
+            args: json!({}),
+        });
+
+        // Assert
+        assert_that!(preview.uri, eq(expected));
+    }
+
+    #[tokio::test]
+    async fn test_count_reads_total_count_of_one_object_page() {
+        // Arrange
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/"))
+            .and(query_param("limit", "1"))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(
+                &[json!({"id": 1})],
+                Some("/api/admin/configuration/v1/conference/?limit=1&offset=1"),
+            )))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        );
+
+        // Act
+        let count = api_client
+            .count(ApiRequest::GetAll {
+                api: Api::Configuration,
+                resource: String::from("conference"),
+                filter_args: HashMap::new(),
+                page_size: 500,
+                limit: 0,
+                offset: 10,
+                order_by: Some(String::from("name")),
+            })
+            .await;
+
+        // Assert
+        assert_that!(count, ok(eq(2)));
+    }
+
+    #[tokio::test]
+    async fn test_send_paginated_all_returns_page_error() {
+        // Arrange
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/"))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(
+                &[json!({"id": 1})],
+                Some("/api/admin/configuration/v1/conference/?limit=1&offset=1"),
+            )))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/"))
+            .and(query_param("offset", "1"))
+            .respond_with(
+                ResponseTemplate::new(500).set_body_json(json!({"error": "something broke"})),
+            )
+            .mount(&server)
+            .await;
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        );
+
+        // Act
+        let objects = api_client.send_paginated_all(get_all_conferences()).await;
+
+        // Assert
+        assert_that!(
+            objects,
+            err(displays_as(contains_substring("something broke")))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_requests_keeps_order() {
+        // Arrange
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/2/"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({"error": "not found"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/status/v1/participant/3/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 3})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        );
+        let get = |api, resource: &str, object_id: &str| ApiRequest::Get {
+            api,
+            resource: String::from(resource),
+            object_id: String::from(object_id),
+        };
+
+        // Act
+        let responses = api_client
+            .pipeline_requests(vec![
+                get(Api::Configuration, "conference", "1"),
+                get(Api::Configuration, "conference", "2"),
+                get(Api::Status, "participant", "3"),
+            ])
+            .await;
+
+        // Assert
+        assert_that!(
+            matches!(&responses[1], Err(ApiClientError::ApiError(e)) if e.is_not_found()),
+            eq(true)
+        );
+        let responses: Vec<_> = responses
+            .into_iter()
+            .map(|response| response.map(ApiResponse::unwrap_content_or_default))
+            .collect();
+        assert_that!(
+            responses,
+            elements_are![
+                ok(eq(&json!({"id": 1}))),
+                err(displays_as(contains_substring("not found"))),
+                ok(eq(&json!({"id": 3}))),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_id_header_is_sent() {
+        // Arrange
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/1/"))
+            .and(header("X-Request-ID", "some-request-id"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        )
+        .with_request_id(Some(reqwest::header::HeaderValue::from_static(
+            "some-request-id",
+        )));
+
+        // Act
+        let response = api_client
+            .send(ApiRequest::Get {
+                api: Api::Configuration,
+                resource: String::from("conference"),
+                object_id: String::from("1"),
+            })
+            .await;
+
+        // Assert
+        assert_that!(
+            response.map(ApiResponse::unwrap_content_or_default),
+            ok(eq(&json!({"id": 1})))
+        );
+    }
+
+    struct RecordingMiddleware {
+        name: &'static str,
+        calls: Arc<parking_lot::Mutex<Vec<String>>>,
+    }
+
+    impl Middleware for RecordingMiddleware {
+        fn before_send(&self, request: &mut reqwest::Request) -> anyhow::Result<()> {
+            self.calls.lock().push(format!("{} before", self.name));
+            request
+                .headers_mut()
+                .insert("X-Signed-By", self.name.parse().unwrap());
+            Ok(())
+        }
+
+        fn after_receive(&self, response: &reqwest::Response) -> anyhow::Result<()> {
+            self.calls.lock().push(format!(
+                "{} after {}",
+                self.name,
+                response.status().as_u16()
+            ));
+            Ok(())
+        }
+    }
+
+    struct FailingMiddleware;
+
+    impl Middleware for FailingMiddleware {
+        fn before_send(&self, _request: &mut reqwest::Request) -> anyhow::Result<()> {
+            Err(anyhow::anyhow!("signing failed"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middlewares_are_called_in_order() {
+        // Arrange
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/1/"))
+            .and(header("X-Signed-By", "second"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let calls = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let metrics = middleware::Metrics::new();
+        let middlewares: Vec<Box<dyn Middleware>> = vec![
+            Box::new(RecordingMiddleware {
+                name: "first",
+                calls: Arc::clone(&calls),
+            }),
+            Box::new(RecordingMiddleware {
+                name: "second",
+                calls: Arc::clone(&calls),
+            }),
+            Box::new(metrics.clone()),
+        ];
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        )
+        .with_middlewares(middlewares);
+
+        // Act
+        let response = api_client
+            .send(ApiRequest::Get {
+                api: Api::Configuration,
+                resource: String::from("conference"),
+                object_id: String::from("1"),
+            })
+            .await;
+
+        // Assert
+        assert_that!(response, ok(anything()));
+        assert_that!(
+            *calls.lock(),
+            elements_are![
+                eq("first before"),
+                eq("second before"),
+                eq("first after 200"),
+                eq("second after 200"),
+            ]
+        );
+        assert_that!(metrics.requests(), eq(1));
+        assert_that!(metrics.responses(), eq(1));
+        assert_that!(metrics.error_responses(), eq(0));
+    }
+
+    #[tokio::test]
+    async fn test_middleware_error_fails_request() {
+        // Arrange
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let middlewares: Vec<Box<dyn Middleware>> = vec![Box::new(FailingMiddleware)];
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        )
+        .with_middlewares(middlewares);
+
+        // Act
+        let response = api_client
+            .send(ApiRequest::Get {
+                api: Api::Configuration,
+                resource: String::from("conference"),
+                object_id: String::from("1"),
+            })
+            .await;
+
+        // Assert
+        assert_that!(
+            response.map(ApiResponse::unwrap_content_or_default),
+            err(displays_as(contains_substring("signing failed")))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_all_with_concurrent_pages() {
+        // Arrange
+        let server = MockServer::start().await;
+        for (offset, object) in [("0", json!({"id": 1})), ("1", json!({"id": 2}))] {
+            Mock::given(method("GET"))
+                .and(path("/api/admin/configuration/v1/conference/"))
+                .and(query_param("offset", offset))
+                .respond_with(ResponseTemplate::new(200).set_body_json(page(
+                    &[object],
+                    Some("/api/admin/configuration/v1/conference/?limit=1&offset=1"),
+                )))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        )
+        .with_max_concurrent_pages(4);
+
+        // Act
+        let objects = api_client.send_paginated_all(get_all_conferences()).await;
+
+        // Assert
+        assert_that!(
+            objects,
+            ok(elements_are![eq(&json!({"id": 1})), eq(&json!({"id": 2}))])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_timed_out() {
+        // Arrange
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/1/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"id": 1}))
+                    .set_delay(std::time::Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::builder()
+                .timeout(std::time::Duration::from_millis(100))
+                .build()
+                .unwrap(),
+            server.uri(),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        )
+        .with_retry_policy(RetryPolicy {
+            transient_retries: 0,
+            ..Default::default()
+        });
+
+        // Act
+        let response = api_client
+            .send(ApiRequest::Get {
+                api: Api::Configuration,
+                resource: String::from("conference"),
+                object_id: String::from("1"),
+            })
+            .await;
+
+        // Assert
+        assert_that!(
+            response.map(ApiResponse::unwrap_content_or_default),
+            err(displays_as(contains_substring(
+                "timed out waiting for the management node to respond"
+            )))
+        );
+    }
+
+    async fn send_get_to(address: &str) -> anyhow::Result<Value> {
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            String::from(address),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        )
+        .with_retry_policy(RetryPolicy {
+            transient_retries: 0,
+            ..Default::default()
+        });
+        api_client
+            .send(ApiRequest::Get {
+                api: Api::Configuration,
+                resource: String::from("conference"),
+                object_id: String::from("1"),
+            })
+            .await
+            .map(ApiResponse::unwrap_content_or_default)
+    }
+
+    #[tokio::test]
+    async fn test_send_connection_refused() {
+        // Arrange
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        // Act
+        let response = send_get_to(&address).await;
+
+        // Assert
+        assert_that!(
+            response,
+            err(displays_as(contains_substring(
+                "connection to 127.0.0.1 was refused - check the address and port"
+            )))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_unresolvable_host() {
+        // Act
+        let response = send_get_to("http://pexshell-test.invalid").await;
+
+        // Assert
+        assert_that!(
+            response,
+            err(displays_as(contains_substring(
+                "could not resolve host pexshell-test.invalid - check the address"
+            )))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_all_with_concurrent_pages_follows_objects_added_while_fetching() {
+        // Arrange
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/"))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(
+                &[json!({"id": 1})],
+                Some("/api/admin/configuration/v1/conference/?limit=1&offset=1"),
+            )))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let mut grown_page = page(
+            &[json!({"id": 2})],
+            Some("/api/admin/configuration/v1/conference/?limit=1&offset=2"),
+        );
+        grown_page["meta"]["total_count"] = json!(3);
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/"))
+            .and(query_param("offset", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(grown_page))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/"))
+            .and(query_param("offset", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page(&[json!({"id": 3})], None)))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        )
+        .with_max_concurrent_pages(4);
+
+        // Act
+        let objects = api_client.send_paginated_all(get_all_conferences()).await;
+
+        // Assert
+        assert_that!(
+            objects,
+            ok(elements_are![
+                eq(&json!({"id": 1})),
+                eq(&json!({"id": 2})),
+                eq(&json!({"id": 3}))
+            ])
+        );
+    }
+
+    #[test]
+    fn test_preview_patch() {
+        // Arrange
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            String::from("https://mcu.example.com"),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        );
+        let request = ApiRequest::Patch {
+            api: Api::Configuration,
+            resource: String::from("conference"),
+            object_id: String::from("5"),
+            args: json!({"name": "room"}),
+        };
+
+        // Act
+        let preview = api_client.preview(&request);
+
+        // Assert
+        assert_that!(
+            preview.to_string(),
+            eq(concat!(
+                "PATCH https://mcu.example.com/api/admin/configuration/v1/conference/5/\n",
+                "{\n",
+                "  \"name\": \"room\"\n",
+                "}\n",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_preview_get_all_excludes_filters() {
+        // Arrange
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            String::from("https://mcu.example.com"),
+            BasicAuth::new(String::from("test"), SensitiveString::from("testing123")),
+        );
+        let request = ApiRequest::GetAll {
+            api: Api::Configuration,
+            resource: String::from("conference"),
+            filter_args: HashMap::from([(String::from("pin"), String::from("1234"))]),
+            page_size: 100,
+            limit: 0,
+            offset: 0,
+            order_by: Some(String::from("name")),
+        };
+
+        // Act
+        let preview = api_client.preview(&request);
+
+        // Assert
+        assert_that!(
+            preview.to_string(),
+            eq(concat!(
+                "GET https://mcu.example.com/api/admin/configuration/v1/conference/?limit=100&offset=0&order_by=name",
+                "  (query parameters are excluded since they may be sensitive)\n",
+            ))
+        );
+    }
+}
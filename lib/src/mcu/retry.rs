@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+/// Delay used when a rate limited response does not specify a valid `Retry-After` header.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Delay before the first retry of a request that failed with a transient error.
+const DEFAULT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Controls how failed requests are retried.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of times a failed request will be retried.
+    pub retries: usize,
+    /// Number of times an idempotent request will be retried after a transient error, such as a `502 Bad Gateway`
+    /// response while the management node reloads its configuration, or a failure to connect.
+    pub transient_retries: usize,
+    /// Delay before the first retry after a transient error, which doubles for each retry after that.
+    pub backoff: Duration,
+    /// The longest delay that will be waited for when the server asks for a request to be retried later.
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 0,
+            transient_retries: 3,
+            backoff: DEFAULT_BACKOFF,
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Number of times a rate limited request will be retried.
+    ///
+    /// The server has explicitly asked for the request to be retried, so this is always at least once.
+    #[must_use]
+    pub fn rate_limit_retries(&self) -> usize {
+        self.retries.max(1)
+    }
+
+    /// Gets how long to wait before retrying a rate limited request, capped at `max_retry_after`.
+    #[must_use]
+    pub fn rate_limit_delay(&self, retry_after: Duration) -> Duration {
+        retry_after.min(self.max_retry_after)
+    }
+
+    /// Gets how long to wait before a retry after a transient error, counting the retries from zero.
+    ///
+    /// The delay doubles for each retry, capped at `max_retry_after`, and is then scaled down by up to half by `jitter`
+    /// (between 0 and 1) so that several clients retrying at once do not all retry at the same time.
+    #[must_use]
+    pub fn backoff_delay(&self, retry: u32, jitter: f64) -> Duration {
+        self.backoff
+            .saturating_mul(2_u32.saturating_pow(retry))
+            .min(self.max_retry_after)
+            .mul_f64(jitter.clamp(0.0, 1.0).mul_add(0.5, 0.5))
+    }
+
+    /// Gets how many times a request with the given method may be retried after a transient error.
+    ///
+    /// Only requests that do not change anything are retried, since the management node may have acted on a request
+    /// before the error.
+    #[must_use]
+    pub fn transient_retries_for(&self, method: &reqwest::Method) -> usize {
+        if matches!(*method, reqwest::Method::GET | reqwest::Method::HEAD) {
+            self.transient_retries
+        } else {
+            0
+        }
+    }
+}
+
+/// Parses the value of a `Retry-After` header, which is either a delay in seconds or an HTTP date.
+///
+/// Falls back to a short default delay if the value cannot be parsed.
+#[must_use]
+pub fn parse_retry_after(value: Option<&str>, now: DateTime<Utc>) -> Duration {
+    let Some(value) = value.map(str::trim) else {
+        return DEFAULT_RETRY_AFTER;
+    };
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Duration::from_secs(seconds);
+    }
+
+    DateTime::parse_from_rfc2822(value).map_or(DEFAULT_RETRY_AFTER, |date| {
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use googletest::prelude::*;
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(Some("120"), Duration::from_secs(120) ; "seconds")]
+    #[test_case(Some(" 0 "), Duration::ZERO ; "zero seconds")]
+    #[test_case(Some("Wed, 21 Oct 2015 07:28:30 GMT"), Duration::from_secs(30) ; "http date")]
+    #[test_case(Some("Wed, 21 Oct 2015 07:27:00 GMT"), Duration::ZERO ; "http date in the past")]
+    #[test_case(Some("soon"), DEFAULT_RETRY_AFTER ; "invalid")]
+    #[test_case(None, DEFAULT_RETRY_AFTER ; "missing")]
+    fn test_parse_retry_after(value: Option<&str>, expected: Duration) {
+        // Arrange
+        let now = Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap();
+
+        // Act
+        let delay = parse_retry_after(value, now);
+
+        // Assert
+        assert_that!(delay, eq(expected));
+    }
+
+    #[test_case(0, 1 ; "retries disabled")]
+    #[test_case(1, 1 ; "one retry")]
+    #[test_case(3, 3 ; "several retries")]
+    fn test_rate_limit_retries(retries: usize, expected: usize) {
+        let policy = RetryPolicy {
+            retries,
+            ..Default::default()
+        };
+        assert_that!(policy.rate_limit_retries(), eq(expected));
+    }
+
+    #[test]
+    fn test_rate_limit_delay_is_capped() {
+        let policy = RetryPolicy {
+            retries: 0,
+            max_retry_after: Duration::from_secs(10),
+            ..Default::default()
+        };
+        assert_that!(
+            policy.rate_limit_delay(Duration::from_secs(3)),
+            eq(Duration::from_secs(3))
+        );
+        assert_that!(
+            policy.rate_limit_delay(Duration::from_secs(3600)),
+            eq(Duration::from_secs(10))
+        );
+    }
+
+    #[test_case(0, 1.0, Duration::from_millis(500) ; "first retry")]
+    #[test_case(2, 1.0, Duration::from_secs(2) ; "third retry")]
+    #[test_case(2, 0.0, Duration::from_secs(1) ; "third retry with jitter")]
+    #[test_case(10, 1.0, Duration::from_secs(60) ; "capped")]
+    #[test_case(u32::MAX, 1.0, Duration::from_secs(60) ; "overflow")]
+    fn test_backoff_delay(retry: u32, jitter: f64, expected: Duration) {
+        let policy = RetryPolicy::default();
+        assert_that!(policy.backoff_delay(retry, jitter), eq(expected));
+    }
+
+    #[test_case(reqwest::Method::GET, 3 ; "get")]
+    #[test_case(reqwest::Method::HEAD, 3 ; "head")]
+    #[test_case(reqwest::Method::POST, 0 ; "post")]
+    #[test_case(reqwest::Method::PATCH, 0 ; "patch")]
+    #[test_case(reqwest::Method::DELETE, 0 ; "delete")]
+    fn test_transient_retries_for(method: reqwest::Method, expected: usize) {
+        let policy = RetryPolicy::default();
+        assert_that!(policy.transient_retries_for(&method), eq(expected));
+    }
+}
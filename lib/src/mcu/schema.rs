@@ -2,8 +2,9 @@
 
 use crate::mcu::{Api, ApiRequest, IApiClient};
 use crate::mcu::{ApiClient, CommandApi};
-use crate::util::join_all_results;
 
+use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use futures::future::join_all;
 use log::{debug, error, trace};
 use serde::de::Visitor;
@@ -11,7 +12,10 @@ use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::{HashMap, HashSet};
 
 use std::fs;
+use std::io::{ErrorKind, Read, Write};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
 use std::{collections, path::Path};
 use strum::IntoEnumIterator;
 
@@ -40,6 +44,9 @@ pub struct Endpoint {
     pub filtering: HashMap<String, Vec<String>>,
     #[serde(default)]
     pub ordering: Vec<String>,
+    /// The management node version the schema was cached from.
+    #[serde(default)]
+    pub schema_version: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -79,7 +86,7 @@ pub enum RelationType {
     ToMany,
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 #[serde(rename_all = "lowercase")]
 pub enum Methods {
     Get,
@@ -89,6 +96,125 @@ pub enum Methods {
     Patch,
 }
 
+/// An old and new value of part of a schema that changed.
+#[derive(Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn between(old: T, new: T) -> Option<Self> {
+        (old != new).then_some(Self { old, new })
+    }
+}
+
+/// The changes to a field that is in both of two endpoint schemas.
+#[derive(Serialize, Clone, Default, PartialEq, Eq, Debug)]
+pub struct FieldDiff {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<Change<Type>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nullable: Option<Change<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readonly: Option<Change<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_choices: Option<Change<Option<Vec<Value>>>>,
+}
+
+impl FieldDiff {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.data_type.is_none()
+            && self.nullable.is_none()
+            && self.readonly.is_none()
+            && self.valid_choices.is_none()
+    }
+}
+
+/// The changes between two schemas of an endpoint, e.g. before and after a management node upgrade.
+#[derive(Serialize, Clone, Default, PartialEq, Eq, Debug)]
+pub struct EndpointDiff {
+    pub added_fields: Vec<String>,
+    pub removed_fields: Vec<String>,
+    pub changed_fields: HashMap<String, FieldDiff>,
+    pub added_detail_methods: Vec<Methods>,
+    pub removed_detail_methods: Vec<Methods>,
+}
+
+impl EndpointDiff {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added_fields.is_empty()
+            && self.removed_fields.is_empty()
+            && self.changed_fields.is_empty()
+            && self.added_detail_methods.is_empty()
+            && self.removed_detail_methods.is_empty()
+    }
+}
+
+impl Endpoint {
+    /// Gets the changes from this schema to `other`.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> EndpointDiff {
+        let mut added_fields: Vec<String> = other
+            .fields
+            .keys()
+            .filter(|name| !self.fields.contains_key(*name))
+            .cloned()
+            .collect();
+        added_fields.sort_unstable();
+
+        let mut removed_fields: Vec<String> = self
+            .fields
+            .keys()
+            .filter(|name| !other.fields.contains_key(*name))
+            .cloned()
+            .collect();
+        removed_fields.sort_unstable();
+
+        let changed_fields = self
+            .fields
+            .iter()
+            .filter_map(|(name, old)| {
+                let new = other.fields.get(name)?;
+                let diff = FieldDiff {
+                    data_type: Change::between(old.data_type, new.data_type),
+                    nullable: Change::between(old.nullable, new.nullable),
+                    readonly: Change::between(old.readonly, new.readonly),
+                    valid_choices: Change::between(
+                        old.valid_choices.clone(),
+                        new.valid_choices.clone(),
+                    ),
+                };
+                (!diff.is_empty()).then(|| (name.clone(), diff))
+            })
+            .collect();
+
+        let mut added_detail_methods: Vec<Methods> = other
+            .allowed_detail_http_methods
+            .difference(&self.allowed_detail_http_methods)
+            .copied()
+            .collect();
+        added_detail_methods.sort_unstable();
+
+        let mut removed_detail_methods: Vec<Methods> = self
+            .allowed_detail_http_methods
+            .difference(&other.allowed_detail_http_methods)
+            .copied()
+            .collect();
+        removed_detail_methods.sort_unstable();
+
+        EndpointDiff {
+            added_fields,
+            removed_fields,
+            changed_fields,
+            added_detail_methods,
+            removed_detail_methods,
+        }
+    }
+}
+
 fn deserialize_filtering<'de, D>(deserializer: D) -> Result<HashMap<String, Vec<String>>, D::Error>
 where
     D: Deserializer<'de>,
@@ -174,6 +300,16 @@ impl<'de> Visitor<'de> for FilteringItemVisitor {
     }
 }
 
+/// The file in the schema cache that records when the cache was generated.
+pub const CACHE_META_FILE: &str = "cache_meta.json";
+
+/// Details of the schema cache, stored in [`CACHE_META_FILE`].
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheMeta {
+    /// When the cache was last generated.
+    cached_at: DateTime<Utc>,
+}
+
 /// # Panics
 /// Will panic if retrieving a valid cache directory from the operating system fails.
 #[must_use]
@@ -193,6 +329,63 @@ pub fn cache_exists(cache_dir: &Path) -> bool {
     }
 }
 
+/// Gets the time the schema cache was last generated.
+///
+/// This is read from [`CACHE_META_FILE`], falling back to when the cache was last modified for caches generated before
+/// the time was recorded.
+pub fn cache_modified(cache_dir: &Path) -> std::io::Result<SystemTime> {
+    match fs::read_to_string(cache_dir.join(CACHE_META_FILE)) {
+        Ok(meta) => return Ok(serde_json::from_str::<CacheMeta>(&meta)?.cached_at.into()),
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+        Err(_) => {}
+    }
+    let path = get_endpoint_cache_path(cache_dir, Api::Configuration, "root");
+    match fs::metadata(get_compressed_path(&path)) {
+        Err(e) if e.kind() == ErrorKind::NotFound => fs::metadata(path),
+        metadata => metadata,
+    }?
+    .modified()
+}
+
+/// Gets the directory that schemas pinned to a version of a management node are kept in, within the schema cache.
+#[must_use]
+pub fn get_pinned_cache_dir(cache_dir: &Path, address: &str, version: &str) -> PathBuf {
+    let address = address
+        .split_once("://")
+        .map_or(address, |(_, address)| address);
+    let address: String = address
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    cache_dir.join(address).join(version)
+}
+
+/// Copies the cached schemas of every API to `pinned_dir`, replacing any schemas that were already pinned there.
+pub fn pin_schemas(cache_dir: &Path, pinned_dir: &Path) -> std::io::Result<()> {
+    match fs::remove_dir_all(pinned_dir) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+        _ => (),
+    }
+    for api in Api::iter() {
+        let from = get_root_cache_path(cache_dir, api);
+        let to = get_root_cache_path(pinned_dir, api);
+        fs::create_dir_all(&to)?;
+        for entry in fs::read_dir(&from)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                fs::copy(entry.path(), to.join(entry.file_name()))?;
+            }
+        }
+    }
+    Ok(())
+}
+
 #[must_use]
 fn get_root_cache_path(cache_dir: &Path, api: Api) -> PathBuf {
     let api_part = match api {
@@ -215,6 +408,58 @@ fn get_endpoint_cache_path(cache_dir: &Path, api: Api, endpoint: &str) -> PathBu
     path
 }
 
+/// Gets the path of a gzip-compressed schema cache file, e.g. `conference.json` -> `conference.json.gz`.
+#[must_use]
+fn get_compressed_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".gz");
+    PathBuf::from(path)
+}
+
+/// Records in [`CACHE_META_FILE`] that the schema cache was generated at `cached_at`.
+fn write_cache_meta(cache_dir: &Path, cached_at: DateTime<Utc>) -> std::io::Result<()> {
+    fs::create_dir_all(cache_dir)?;
+    fs::write(
+        cache_dir.join(CACHE_META_FILE),
+        serde_json::to_string(&CacheMeta { cached_at })?,
+    )
+}
+
+/// Reads a schema cache file, from its gzip-compressed form if there is one.
+async fn read_cache_file(path: &Path) -> std::io::Result<String> {
+    let compressed_path = get_compressed_path(path);
+    match tokio::fs::read(&compressed_path).await {
+        Ok(compressed) => {
+            trace!("Reading compressed cache file: {:?}", compressed_path);
+            let mut contents = String::new();
+            GzDecoder::new(compressed.as_slice()).read_to_string(&mut contents)?;
+            Ok(contents)
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => tokio::fs::read_to_string(path).await,
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes a schema cache file, gzip-compressed if `compress` is set, and removes the file in the other form if it
+/// exists.
+fn write_cache_file(path: &Path, contents: &str, compress: bool) -> std::io::Result<()> {
+    fs::create_dir_all(path.parent().unwrap())?;
+    let compressed_path = get_compressed_path(path);
+    let other_path = if compress {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents.as_bytes())?;
+        fs::write(&compressed_path, encoder.finish()?)?;
+        path
+    } else {
+        fs::write(path, contents)?;
+        compressed_path.as_path()
+    };
+    match fs::remove_file(other_path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
 pub async fn read_schema_from_cache(
     cache_dir: &Path,
     api: Api,
@@ -222,18 +467,50 @@ pub async fn read_schema_from_cache(
 ) -> std::io::Result<Endpoint> {
     let path = get_endpoint_cache_path(cache_dir, api, endpoint);
     trace!("Reading schema from cache file: {:?}", path);
-    let schema = tokio::fs::read_to_string(path).await?;
+    let schema = read_cache_file(&path).await?;
     let schema: Endpoint = serde_json::from_str(&schema)?;
     Ok(schema)
 }
 
+/// A cached endpoint schema that could not be read.
+#[derive(Debug, thiserror::Error)]
+#[error("{}: {error}", path.display())]
+pub struct CorruptSchema {
+    pub path: PathBuf,
+    pub error: std::io::Error,
+}
+
+/// A cached endpoint schema from a different management node version than the one in use.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StaleSchema {
+    pub api: Api,
+    pub endpoint: String,
+    pub schema_version: Option<String>,
+}
+
+/// The contents of the schema cache.
+#[derive(Debug, Default)]
+pub struct CachedSchemas {
+    pub schemas: HashMap<Api, HashMap<String, Endpoint>>,
+    /// Endpoints whose schemas could not be read.
+    pub corrupt: Vec<CorruptSchema>,
+    /// Endpoints whose schemas were cached from a different version than the one requested.
+    pub stale: Vec<StaleSchema>,
+}
+
+/// Reads all endpoint schemas from the cache.
+///
+/// Endpoints whose schemas cannot be read are skipped and returned alongside the schemas that were read successfully.
+/// If `current_version` is given, endpoints whose schemas were cached from a different version are also returned as
+/// stale, but are still included in the schemas that were read.
 pub async fn read_all_schemas(
     cache_dir: &Path,
-) -> std::io::Result<HashMap<Api, HashMap<String, Endpoint>>> {
-    let mut all_schemas = HashMap::new();
+    current_version: Option<&str>,
+) -> std::io::Result<CachedSchemas> {
+    let mut cached = CachedSchemas::default();
     for api in Api::iter() {
         let root_schema_path = get_endpoint_cache_path(cache_dir, api, "root");
-        let root_schema = tokio::fs::read_to_string(root_schema_path).await?;
+        let root_schema = read_cache_file(&root_schema_path).await?;
         let root_schema: HashMap<String, RootEntry> = serde_json::from_str(&root_schema)?;
 
         let results: Vec<(String, std::io::Result<Endpoint>)> =
@@ -247,72 +524,335 @@ pub async fn read_all_schemas(
         for (name, r) in results {
             match r {
                 Ok(schema) => {
+                    if current_version.is_some_and(|v| schema.schema_version.as_deref() != Some(v))
+                    {
+                        debug!(
+                            "Schema for endpoint \"{name}\" is stale (cached from version {:?})",
+                            schema.schema_version
+                        );
+                        cached.stale.push(StaleSchema {
+                            api,
+                            endpoint: name.clone(),
+                            schema_version: schema.schema_version.clone(),
+                        });
+                    }
                     map.insert(name, schema);
                 }
                 Err(e) => {
                     error!("Failed to read schema for endpoint \"{name}\": {e}");
+                    cached.corrupt.push(CorruptSchema {
+                        path: get_endpoint_cache_path(cache_dir, api, &name),
+                        error: e,
+                    });
                 }
             }
         }
 
-        all_schemas.insert(api, map);
+        cached.schemas.insert(api, map);
     }
 
-    Ok(all_schemas)
+    cached.corrupt.sort_by(|a, b| a.path.cmp(&b.path));
+    cached
+        .stale
+        .sort_by_cached_key(|stale| get_endpoint_cache_path(cache_dir, stale.api, &stale.endpoint));
+    Ok(cached)
+}
+
+/// The schema of an endpoint that could not be fetched from the management node while caching schemas.
+#[derive(Debug)]
+pub struct FailedSchema {
+    pub api: Api,
+    /// The endpoint whose schema could not be fetched, or `None` if the list of the API's endpoints couldn't be.
+    pub endpoint: Option<String>,
+    pub error: anyhow::Error,
 }
 
+impl std::fmt::Display for FailedSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let api = self.api.to_string().to_lowercase();
+        match &self.endpoint {
+            Some(endpoint) => write!(f, "{api} {endpoint}: {:#}", self.error),
+            None => write!(f, "{api} (all endpoints): {:#}", self.error),
+        }
+    }
+}
+
+/// The schemas that could not be fetched while caching schemas, after the schemas that could be were written to the
+/// cache.
+#[derive(Debug, thiserror::Error)]
+pub struct FailedSchemas(pub Vec<FailedSchema>);
+
+impl std::fmt::Display for FailedSchemas {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "failed to fetch {} schemas - the other schemas were cached:",
+            self.0.len()
+        )?;
+        for failed in &self.0 {
+            write!(f, "\n  {failed}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FailedSchemas {
+    /// Gets an error listing the failures, if there are any.
+    fn into_result(mut self) -> anyhow::Result<()> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        self.0.sort_by_cached_key(|failed| {
+            (
+                failed.api.to_string().to_lowercase(),
+                failed.endpoint.clone(),
+            )
+        });
+        Err(self.into())
+    }
+}
+
+/// Fetches all schemas from the management node and writes them to the cache, recording when the cache was generated in
+/// [`CACHE_META_FILE`].
+///
+/// If `schema_version` is given, it is stored with any schemas that do not already include their version.
+/// If `compress` is set, the schemas are stored as gzip-compressed JSON.
+///
+/// If the schemas of some endpoints can't be fetched, the others are still cached and a [`FailedSchemas`] error listing
+/// them is returned.
 pub async fn cache_schemas<'auth>(
     api_client: &ApiClient<'auth>,
     cache_dir: &Path,
+    schema_version: Option<&str>,
+    compress: bool,
 ) -> anyhow::Result<()> {
-    join_all_results(Api::iter().map(|api| cache_api(api_client, cache_dir, api))).await?;
+    cache_schemas_with_progress(api_client, cache_dir, schema_version, compress, |_, _| ()).await
+}
 
-    Ok(())
+/// Fetches all schemas from the management node and writes them to the cache, like [`cache_schemas`].
+///
+/// The lists of every API's endpoints are fetched first, so that `progress` can be called with the number of endpoints
+/// whose schemas have been cached and the total number of endpoints across all APIs, each time the schema of an
+/// endpoint has been cached.
+pub async fn cache_schemas_with_progress<'auth>(
+    api_client: &ApiClient<'auth>,
+    cache_dir: &Path,
+    schema_version: Option<&str>,
+    compress: bool,
+    progress: impl Fn(usize, usize) + Sync,
+) -> anyhow::Result<()> {
+    let mut failed = Vec::new();
+    let mut endpoints = Vec::new();
+    for (api, root) in
+        join_all(Api::iter().map(|api| async move {
+            (api, cache_root(api_client, cache_dir, api, compress).await)
+        }))
+        .await
+    {
+        match root {
+            Ok(names) => endpoints.extend(names.into_iter().map(|name| (api, name))),
+            Err(root_failed) => failed.push(root_failed),
+        }
+    }
+
+    let total = endpoints.len();
+    let completed = AtomicUsize::new(0);
+    progress(0, total);
+    failed.extend(
+        join_all(endpoints.iter().map(|(api, endpoint)| {
+            let completed = &completed;
+            let progress = &progress;
+            async move {
+                let failed = cache_endpoint(
+                    api_client,
+                    cache_dir,
+                    *api,
+                    endpoint,
+                    schema_version,
+                    compress,
+                )
+                .await;
+                progress(completed.fetch_add(1, Ordering::Relaxed) + 1, total);
+                failed
+            }
+        }))
+        .await
+        .into_iter()
+        .flatten(),
+    );
+    write_cache_meta(cache_dir, Utc::now())?;
+
+    FailedSchemas(failed).into_result()
 }
 
-async fn cache_api<'auth>(
+/// Fetches the schemas of stale endpoints from the management node and writes them to the cache.
+pub async fn refresh_schemas<'auth>(
+    api_client: &ApiClient<'auth>,
+    cache_dir: &Path,
+    schema_version: Option<&str>,
+    stale: &[StaleSchema],
+    compress: bool,
+) -> anyhow::Result<()> {
+    let failed = join_all(stale.iter().map(|stale| {
+        cache_endpoint(
+            api_client,
+            cache_dir,
+            stale.api,
+            &stale.endpoint,
+            schema_version,
+            compress,
+        )
+    }))
+    .await;
+
+    FailedSchemas(failed.into_iter().flatten().collect()).into_result()
+}
+
+/// Fetches the schemas of every endpoint of an API from the management node and writes them to the cache, leaving the
+/// schemas of other APIs as they are.
+///
+/// If `schema_version` is given, it is stored with any schemas that do not already include their version.
+///
+/// If the schemas of some endpoints can't be fetched, the others are still cached and a [`FailedSchemas`] error listing
+/// them is returned.
+pub async fn cache_api<'auth>(
     api_client: &ApiClient<'auth>,
     cache_dir: &Path,
     api: Api,
+    schema_version: Option<&str>,
+    compress: bool,
 ) -> anyhow::Result<()> {
-    let root_request = ApiRequest::ApiSchema { api };
-    let json = api_client
-        .send(root_request)
-        .await?
-        .unwrap_content_or_default();
-    let root_cache_file_path = get_endpoint_cache_path(cache_dir, api, "root");
-    fs::create_dir_all(root_cache_file_path.parent().unwrap())?;
-    fs::write(root_cache_file_path, json.to_string())?;
-    let root_schema: HashMap<String, RootEntry> = serde_json::from_str(&json.to_string())?;
-    join_all_results(
-        root_schema
-            .keys()
-            .map(|endpoint| cache_schema(api_client, cache_dir, api, endpoint)),
-    )
-    .await?;
+    FailedSchemas(cache_api_endpoints(api_client, cache_dir, api, schema_version, compress).await)
+        .into_result()
+}
 
-    Ok(())
+/// Caches the schemas of every endpoint of an API, like [`cache_api`], returning the schemas that could not be fetched.
+async fn cache_api_endpoints<'auth>(
+    api_client: &ApiClient<'auth>,
+    cache_dir: &Path,
+    api: Api,
+    schema_version: Option<&str>,
+    compress: bool,
+) -> Vec<FailedSchema> {
+    let endpoints = match cache_root(api_client, cache_dir, api, compress).await {
+        Ok(endpoints) => endpoints,
+        Err(failed) => return vec![failed],
+    };
+
+    join_all(endpoints.iter().map(|endpoint| {
+        cache_endpoint(
+            api_client,
+            cache_dir,
+            api,
+            endpoint,
+            schema_version,
+            compress,
+        )
+    }))
+    .await
+    .into_iter()
+    .flatten()
+    .collect()
 }
 
-async fn cache_schema<'auth>(
+/// Caches the list of an API's endpoints, returning their names, or logging and returning the failure if it can't be.
+async fn cache_root<'auth>(
+    api_client: &ApiClient<'auth>,
+    cache_dir: &Path,
+    api: Api,
+    compress: bool,
+) -> Result<Vec<String>, FailedSchema> {
+    let root_schema = async {
+        let json = api_client
+            .send(ApiRequest::ApiSchema { api })
+            .await?
+            .unwrap_content_or_default();
+        let root_cache_file_path = get_endpoint_cache_path(cache_dir, api, "root");
+        write_cache_file(&root_cache_file_path, &json.to_string(), compress)?;
+        anyhow::Ok(serde_json::from_value::<HashMap<String, RootEntry>>(json)?)
+    };
+    match root_schema.await {
+        Ok(root_schema) => Ok(root_schema.into_keys().collect()),
+        Err(error) => {
+            error!("Failed to cache the list of {api} endpoints: {error:#}");
+            Err(FailedSchema {
+                api,
+                endpoint: None,
+                error,
+            })
+        }
+    }
+}
+
+/// Caches the schema of a single endpoint, like [`cache_schema`], logging and returning the failure if it can't be.
+async fn cache_endpoint<'auth>(
     api_client: &ApiClient<'auth>,
     cache_dir: &Path,
     api: Api,
     endpoint: &str,
-) -> anyhow::Result<()> {
+    schema_version: Option<&str>,
+    compress: bool,
+) -> Option<FailedSchema> {
+    match cache_schema(
+        api_client,
+        cache_dir,
+        api,
+        endpoint,
+        schema_version,
+        compress,
+    )
+    .await
+    {
+        Ok(_) => None,
+        Err(error) => {
+            error!("Failed to cache the schema for endpoint \"{endpoint}\" of {api}: {error:#}");
+            Some(FailedSchema {
+                api,
+                endpoint: Some(String::from(endpoint)),
+                error,
+            })
+        }
+    }
+}
+
+/// Fetches the schema of a single endpoint from the management node.
+///
+/// If `schema_version` is given, it is added to the schema if it does not already include its version.
+pub async fn fetch_schema<'auth>(
+    api_client: &ApiClient<'auth>,
+    api: Api,
+    endpoint: &str,
+    schema_version: Option<&str>,
+) -> anyhow::Result<Value> {
     let request = ApiRequest::Schema {
         api,
         resource: String::from(endpoint),
     };
 
-    let cache_file_path = get_endpoint_cache_path(cache_dir, api, endpoint);
+    let mut json = api_client.send(request).await?.unwrap_content_or_default();
+    if let (Some(schema_version), Value::Object(schema)) = (schema_version, &mut json) {
+        schema
+            .entry("schema_version")
+            .or_insert_with(|| Value::String(String::from(schema_version)));
+    }
+    Ok(json)
+}
 
-    let json = api_client.send(request).await?.unwrap_content_or_default();
+/// Fetches the schema of a single endpoint from the management node and writes it to the cache, returning the schema.
+pub async fn cache_schema<'auth>(
+    api_client: &ApiClient<'auth>,
+    cache_dir: &Path,
+    api: Api,
+    endpoint: &str,
+    schema_version: Option<&str>,
+    compress: bool,
+) -> anyhow::Result<Value> {
+    let cache_file_path = get_endpoint_cache_path(cache_dir, api, endpoint);
 
-    fs::create_dir_all(cache_file_path.parent().unwrap())?;
-    fs::write(cache_file_path, json.to_string())?;
+    let json = fetch_schema(api_client, api, endpoint, schema_version).await?;
+    write_cache_file(&cache_file_path, &json.to_string(), compress)?;
 
-    Ok(())
+    Ok(json)
 }
 
 #[cfg(test)]
@@ -324,7 +864,7 @@ mod tests {
     use serde_json::json;
     use test_case::test_case;
     use wiremock::{
-        matchers::{basic_auth, method, path},
+        matchers::{basic_auth, method, path, path_regex},
         Mock, MockServer, ResponseTemplate,
     };
 
@@ -405,7 +945,7 @@ mod tests {
         );
 
         // Act
-        cache_api(&api_client, &PathBuf::from(&cache_path), api)
+        cache_api(&api_client, &PathBuf::from(&cache_path), api, None, false)
             .await
             .unwrap();
 
@@ -443,6 +983,71 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_cache_api_caches_other_endpoints_when_one_fails() {
+        // Arrange
+        let server = MockServer::start().await;
+        let test_context = get_test_context();
+        let cache_dir = test_context.get_cache_dir();
+        let root_schema = json!({
+            "test_endpoint": {
+                "list_endpoint": "/api/admin/configuration/v1/test_endpoint/",
+                "schema": "/api/admin/configuration/v1/test_endpoint/schema/"
+            },
+            "another_test_endpoint": {
+                "list_endpoint": "/api/admin/configuration/v1/another_test_endpoint/",
+                "schema": "/api/admin/configuration/v1/another_test_endpoint/schema/"
+            },
+        });
+
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&root_schema))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/test_endpoint/schema/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_schema()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/api/admin/configuration/v1/another_test_endpoint/schema/",
+            ))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from(USERNAME), SensitiveString::from(PASSWORD)),
+        );
+
+        // Act
+        let result = cache_api(&api_client, cache_dir, Api::Configuration, None, false).await;
+
+        // Assert
+        let error = result.unwrap_err();
+        let failed = error.downcast_ref::<FailedSchemas>().unwrap();
+        assert_that!(failed.0.len(), eq(1));
+        assert_that!(failed.0[0].endpoint, some(eq("another_test_endpoint")));
+        assert_that!(
+            error.to_string(),
+            contains_substring("configuration another_test_endpoint: ")
+        );
+        assert_that!(
+            cache_dir.join("configuration/test_endpoint.json").exists(),
+            eq(true)
+        );
+        assert_that!(
+            cache_dir
+                .join("configuration/another_test_endpoint.json")
+                .exists(),
+            eq(false)
+        );
+    }
+
     #[test_case(Api::Configuration, "configuration/v1", "configuration")]
     #[test_case(Api::History, "history/v1", "history")]
     #[test_case(Api::Status, "status/v1", "status")]
@@ -483,9 +1088,16 @@ mod tests {
         );
 
         // Act
-        cache_schema(&api_client, &PathBuf::from(&cache_path), api, endpoint)
-            .await
-            .unwrap();
+        cache_schema(
+            &api_client,
+            &PathBuf::from(&cache_path),
+            api,
+            endpoint,
+            None,
+            false,
+        )
+        .await
+        .unwrap();
 
         // Assert
         eprintln!("file path: {cache_path}/{cache_path_from_root}/{endpoint}.json");
@@ -712,4 +1324,411 @@ mod tests {
             ]
         })
     }
+
+    #[tokio::test]
+    async fn test_read_all_schemas_reports_corrupt_schemas() {
+        // Arrange
+        let test_context = get_test_context();
+        let cache_dir = test_context.get_cache_dir();
+        for api in Api::iter() {
+            let root_path = get_endpoint_cache_path(cache_dir, api, "root");
+            std::fs::create_dir_all(root_path.parent().unwrap()).unwrap();
+            std::fs::write(root_path, "{}").unwrap();
+        }
+        let entry = json!({"list_endpoint": "", "schema": ""});
+        std::fs::write(
+            get_endpoint_cache_path(cache_dir, Api::Configuration, "root"),
+            json!({"conference": entry, "location": entry}).to_string(),
+        )
+        .unwrap();
+        std::fs::write(
+            get_endpoint_cache_path(cache_dir, Api::Configuration, "conference"),
+            json_schema().to_string(),
+        )
+        .unwrap();
+        let corrupt_path = get_endpoint_cache_path(cache_dir, Api::Configuration, "location");
+        std::fs::write(&corrupt_path, "{ not json").unwrap();
+
+        // Act
+        let cached = read_all_schemas(cache_dir, None).await.unwrap();
+
+        // Assert
+        let configuration_schemas = &cached.schemas[&Api::Configuration];
+        assert_that!(configuration_schemas.len(), eq(1));
+        assert_that!(configuration_schemas.contains_key("conference"), eq(true));
+        assert_that!(
+            cached.corrupt,
+            elements_are![pat!(CorruptSchema {
+                path: eq(&corrupt_path),
+                error: anything(),
+            })]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_all_schemas_reports_stale_schemas() {
+        // Arrange
+        let test_context = get_test_context();
+        let cache_dir = test_context.get_cache_dir();
+        for api in Api::iter() {
+            let root_path = get_endpoint_cache_path(cache_dir, api, "root");
+            std::fs::create_dir_all(root_path.parent().unwrap()).unwrap();
+            std::fs::write(root_path, "{}").unwrap();
+        }
+        let entry = json!({"list_endpoint": "", "schema": ""});
+        std::fs::write(
+            get_endpoint_cache_path(cache_dir, Api::Configuration, "root"),
+            json!({"conference": entry, "location": entry, "theme": entry}).to_string(),
+        )
+        .unwrap();
+        for (endpoint, schema_version) in [
+            ("conference", Some("34.0")),
+            ("location", Some("33.0")),
+            ("theme", None),
+        ] {
+            let mut schema = json_schema();
+            if let Some(schema_version) = schema_version {
+                schema["schema_version"] = json!(schema_version);
+            }
+            std::fs::write(
+                get_endpoint_cache_path(cache_dir, Api::Configuration, endpoint),
+                schema.to_string(),
+            )
+            .unwrap();
+        }
+
+        // Act
+        let cached = read_all_schemas(cache_dir, Some("34.0")).await.unwrap();
+
+        // Assert
+        assert_that!(cached.schemas[&Api::Configuration].len(), eq(3));
+        assert_that!(cached.corrupt, empty());
+        assert_that!(
+            cached.stale,
+            elements_are![
+                eq(&StaleSchema {
+                    api: Api::Configuration,
+                    endpoint: String::from("location"),
+                    schema_version: Some(String::from("33.0")),
+                }),
+                eq(&StaleSchema {
+                    api: Api::Configuration,
+                    endpoint: String::from("theme"),
+                    schema_version: None,
+                }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_schema_stores_schema_version() {
+        // Arrange
+        let server = MockServer::start().await;
+        let test_context = get_test_context();
+        let cache_dir = test_context.get_cache_dir();
+
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/schema/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_schema()))
+            .mount(&server)
+            .await;
+
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from(USERNAME), SensitiveString::from(PASSWORD)),
+        );
+
+        // Act
+        cache_schema(
+            &api_client,
+            cache_dir,
+            Api::Configuration,
+            "conference",
+            Some("34.0"),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        let schema = read_schema_from_cache(cache_dir, Api::Configuration, "conference")
+            .await
+            .unwrap();
+        assert_that!(schema.schema_version, some(eq("34.0")));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_schema_does_not_write_cache() {
+        // Arrange
+        let server = MockServer::start().await;
+        let test_context = get_test_context();
+        let cache_dir = test_context.get_cache_dir();
+
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/schema/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_schema()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from(USERNAME), SensitiveString::from(PASSWORD)),
+        );
+
+        // Act
+        let schema = fetch_schema(&api_client, Api::Configuration, "conference", None).await;
+
+        // Assert
+        assert_that!(schema, ok(eq(&json_schema())));
+        assert_that!(cache_exists(cache_dir), eq(false));
+    }
+
+    #[tokio::test]
+    async fn test_cache_schema_compressed() {
+        // Arrange
+        let server = MockServer::start().await;
+        let test_context = get_test_context();
+        let cache_dir = test_context.get_cache_dir();
+        let cache_file_path = get_endpoint_cache_path(cache_dir, Api::Configuration, "conference");
+        std::fs::create_dir_all(cache_file_path.parent().unwrap()).unwrap();
+        std::fs::write(&cache_file_path, "{ not json").unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/conference/schema/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_schema()))
+            .mount(&server)
+            .await;
+
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from(USERNAME), SensitiveString::from(PASSWORD)),
+        );
+
+        // Act
+        cache_schema(
+            &api_client,
+            cache_dir,
+            Api::Configuration,
+            "conference",
+            None,
+            true,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(cache_file_path.exists(), eq(false));
+        let mut schema = String::new();
+        GzDecoder::new(
+            std::fs::read(get_compressed_path(&cache_file_path))
+                .unwrap()
+                .as_slice(),
+        )
+        .read_to_string(&mut schema)
+        .unwrap();
+        assert_that!(
+            serde_json::from_str::<Value>(&schema).unwrap(),
+            eq(&json_schema())
+        );
+        assert_that!(
+            read_schema_from_cache(cache_dir, Api::Configuration, "conference").await,
+            ok(anything())
+        );
+    }
+
+    fn endpoint_with(fields: serde_json::Value, detail_methods: &[&str]) -> Endpoint {
+        let mut schema = json_schema();
+        schema["fields"] = fields;
+        schema["allowed_detail_http_methods"] = json!(detail_methods);
+        serde_json::from_value(schema).unwrap()
+    }
+
+    fn field(data_type: &str, nullable: bool, valid_choices: Option<&[&str]>) -> serde_json::Value {
+        json!({
+            "blank": false,
+            "default": null,
+            "help_text": "",
+            "nullable": nullable,
+            "readonly": false,
+            "type": data_type,
+            "related_type": null,
+            "unique": false,
+            "valid_choices": valid_choices,
+        })
+    }
+
+    #[test]
+    fn test_endpoint_diff() {
+        // Arrange
+        let old = endpoint_with(
+            json!({
+                "name": field("string", false, None),
+                "pin": field("string", true, None),
+                "service_type": field("string", false, Some(&["conference"])),
+                "tag": field("string", true, None),
+            }),
+            &["get", "delete"],
+        );
+        let new = endpoint_with(
+            json!({
+                "name": field("string", false, None),
+                "pin": field("integer", false, None),
+                "service_type": field("string", false, Some(&["conference", "lecture"])),
+                "description": field("string", true, None),
+            }),
+            &["get", "patch"],
+        );
+
+        // Act
+        let diff = old.diff(&new);
+
+        // Assert
+        assert_that!(
+            serde_json::to_value(&diff).unwrap(),
+            eq(&json!({
+                "added_fields": ["description"],
+                "removed_fields": ["tag"],
+                "changed_fields": {
+                    "pin": {
+                        "data_type": {"old": "string", "new": "integer"},
+                        "nullable": {"old": true, "new": false},
+                    },
+                    "service_type": {
+                        "valid_choices": {"old": ["conference"], "new": ["conference", "lecture"]},
+                    },
+                },
+                "added_detail_methods": ["patch"],
+                "removed_detail_methods": ["delete"],
+            }))
+        );
+    }
+
+    #[test]
+    fn test_endpoint_diff_unchanged() {
+        // Arrange
+        let endpoint = endpoint_with(json!({"name": field("string", false, None)}), &["get"]);
+
+        // Act
+        let diff = endpoint.diff(&endpoint.clone());
+
+        // Assert
+        assert_that!(diff.is_empty(), eq(true));
+    }
+
+    #[test_case("mcu.example.com", "mcu.example.com" ; "address")]
+    #[test_case("https://mcu.example.com:8443", "mcu.example.com_8443" ; "url")]
+    fn test_get_pinned_cache_dir(address: &str, expected: &str) {
+        assert_that!(
+            get_pinned_cache_dir(Path::new("schemas"), address, "36.0"),
+            eq(&Path::new("schemas").join(expected).join("36.0"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pin_schemas() {
+        // Arrange
+        let test_context = get_test_context();
+        let cache_dir = test_context.get_cache_dir().join("schemas");
+        test_context.seed_schema("configuration", "conference", &json_schema());
+        test_context.seed_minimal_schemas();
+        let pinned_dir = get_pinned_cache_dir(&cache_dir, "mcu.example.com", "36.0");
+
+        // Act
+        let result = pin_schemas(&cache_dir, &pinned_dir);
+
+        // Assert
+        assert_that!(result, ok(anything()));
+        let pinned = read_all_schemas(&pinned_dir, None).await.unwrap();
+        assert_that!(
+            pinned.schemas[&Api::Configuration].contains_key("conference"),
+            eq(true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_schemas_with_progress() {
+        // Arrange
+        let server = MockServer::start().await;
+        let test_context = get_test_context();
+        let progress = std::sync::Mutex::new(Vec::new());
+
+        Mock::given(method("GET"))
+            .and(path("/api/admin/configuration/v1/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "test_endpoint": {
+                    "list_endpoint": "/api/admin/configuration/v1/test_endpoint/",
+                    "schema": "/api/admin/configuration/v1/test_endpoint/schema/"
+                },
+                "another_test_endpoint": {
+                    "list_endpoint": "/api/admin/configuration/v1/another_test_endpoint/",
+                    "schema": "/api/admin/configuration/v1/another_test_endpoint/schema/"
+                },
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("^/api/admin/configuration/v1/[a-z_]+/schema/$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json_schema()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .mount(&server)
+            .await;
+
+        let api_client = ApiClient::new_for_testing(
+            reqwest::Client::new(),
+            server.uri(),
+            BasicAuth::new(String::from(USERNAME), SensitiveString::from(PASSWORD)),
+        );
+
+        // Act
+        cache_schemas_with_progress(
+            &api_client,
+            test_context.get_cache_dir(),
+            None,
+            false,
+            |completed, total| progress.lock().unwrap().push((completed, total)),
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            progress.into_inner().unwrap(),
+            elements_are![eq(&(0, 2)), eq(&(1, 2)), eq(&(2, 2))]
+        );
+        assert_that!(
+            test_context
+                .get_cache_dir()
+                .join("configuration/another_test_endpoint.json")
+                .exists(),
+            eq(true)
+        );
+        assert_that!(
+            test_context.get_cache_dir().join(CACHE_META_FILE).exists(),
+            eq(true)
+        );
+    }
+
+    #[test]
+    fn test_cache_modified_reads_cache_meta() {
+        // Arrange
+        let test_context = get_test_context();
+        let cache_dir = test_context.get_cache_dir();
+        let cached_at = DateTime::parse_from_rfc3339("2024-03-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        write_cache_meta(cache_dir, cached_at).unwrap();
+
+        // Act
+        let modified = cache_modified(cache_dir).unwrap();
+
+        // Assert
+        assert_that!(modified, eq(SystemTime::from(cached_at)));
+    }
 }
@@ -1,21 +1,144 @@
 #![allow(clippy::significant_drop_tightening)]
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{fs::File, io::Write};
 
 use chrono::{SecondsFormat, Utc};
 use log::{debug, Level, LevelFilter, Metadata, Record};
 use parking_lot::Mutex;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 #[cfg(any(not(feature = "all_logs"), test))]
 const PEXSHELL_MODULE_PATH: &str = "pexshell";
 #[cfg(any(not(feature = "all_logs"), test))]
 const PEXLIB_MODULE_PATH: &str = "pexlib";
 
+/// How records are written to the log file. Records written to STDERR are always human-readable.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line, with `timestamp`, `level`, `target` and `message` fields.
+    Json,
+}
+
+impl LogFormat {
+    /// Formats a line of the log file. Lines without a level are written by the logger itself.
+    fn format(self, timestamp: &str, level: Option<Level>, target: &str, message: &str) -> String {
+        match (self, level) {
+            (Self::Text, Some(level)) => format!("{timestamp}  {level:<5}  {target} --- {message}"),
+            (Self::Text, None) => format!("{timestamp} --- {message}"),
+            (Self::Json, level) => json!({
+                "timestamp": timestamp,
+                "level": level.unwrap_or(Level::Info).as_str(),
+                "target": target,
+                "message": message,
+            })
+            .to_string(),
+        }
+    }
+}
+
+/// Rotates the log file once it grows past `max_size` bytes, keeping up to `max_files` old log files with the same
+/// path and a numbered suffix, e.g. `pexshell.log.1` for the most recent.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LogRotation {
+    pub max_size: u64,
+    pub max_files: usize,
+}
+
+/// Number of lines written between reading the size of the log file from disk, as other pexshell processes may be
+/// writing to it too. In between, the size is counted as lines are written.
+const SIZE_CHECK_INTERVAL: usize = 100;
+
+/// An open log file, tracking its size so that it can be rotated.
+struct LogFile {
+    file: File,
+    path: PathBuf,
+    size: u64,
+    lines_since_size_check: usize,
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::File::options()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            file,
+            path,
+            size,
+            lines_since_size_check: 0,
+        })
+    }
+
+    /// Writes a line to the log file, then rotates it if it has grown too large.
+    fn write_line(&mut self, line: &str, rotation: Option<LogRotation>) -> std::io::Result<()> {
+        self.file.write_all(format!("{line}\n").as_bytes())?;
+        self.size += line.len() as u64 + 1;
+        self.lines_since_size_check += 1;
+        if self.lines_since_size_check >= SIZE_CHECK_INTERVAL {
+            self.lines_since_size_check = 0;
+            self.size = self.file.metadata()?.len();
+        }
+        self.rotate_if_too_large(rotation)
+    }
+
+    fn rotate_if_too_large(&mut self, rotation: Option<LogRotation>) -> std::io::Result<()> {
+        match rotation {
+            Some(rotation) if self.size >= rotation.max_size => self.rotate(rotation.max_files),
+            _ => Ok(()),
+        }
+    }
+
+    /// Moves the log file to `<path>.1`, moving each older log file along by one and deleting the oldest, then starts
+    /// a new log file.
+    ///
+    /// Files that are missing are taken to have been rotated already by another pexshell process writing to the same
+    /// log file, in which case the new log file is opened without moving them.
+    fn rotate(&mut self, max_files: usize) -> std::io::Result<()> {
+        if max_files == 0 {
+            ignore_not_found(std::fs::remove_file(&self.path))?;
+        } else {
+            for n in (1..max_files).rev() {
+                let older = rotated_path(&self.path, n);
+                ignore_not_found(std::fs::rename(&older, rotated_path(&self.path, n + 1)))?;
+            }
+            ignore_not_found(std::fs::rename(&self.path, rotated_path(&self.path, 1)))?;
+        }
+        *self = Self::open(self.path.clone())?;
+        Ok(())
+    }
+}
+
+/// Treats a file that doesn't exist as already dealt with.
+fn ignore_not_found(result: std::io::Result<()>) -> std::io::Result<()> {
+    match result {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Gets the path of the `n`th most recent rotated log file.
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{n}"));
+    PathBuf::from(rotated)
+}
+
 pub struct SimpleLoggerConfig {
-    log_file: Option<File>,
-    log_to_stderr: bool,
+    file: Option<LogFile>,
+    to_stderr: bool,
+    format: LogFormat,
+    rotation: Option<LogRotation>,
 }
 
 pub struct SimpleLogger {
@@ -25,24 +148,12 @@ pub struct SimpleLogger {
 
 impl SimpleLogger {
     pub fn new(log_file: Option<PathBuf>) -> std::io::Result<Self> {
-        let log = match log_file {
-            None => None,
-            Some(path) => {
-                if let Some(dir) = path.parent() {
-                    std::fs::create_dir_all(dir)?;
-                }
-                Some(
-                    std::fs::File::options()
-                        .create(true)
-                        .append(true)
-                        .open(path)?,
-                )
-            }
-        };
         Ok(Self {
             config: Mutex::new(SimpleLoggerConfig {
-                log_file: log,
-                log_to_stderr: false,
+                file: log_file.map(LogFile::open).transpose()?,
+                to_stderr: false,
+                format: LogFormat::default(),
+                rotation: None,
             }),
             max_level: RwLock::new(LevelFilter::Info),
         })
@@ -60,7 +171,8 @@ impl SimpleLogger {
     pub fn set_log_file(&self, log_file: Option<PathBuf>) -> std::io::Result<()> {
         {
             let mut config = self.config.lock();
-            if let Some(ref mut log) = config.log_file {
+            let log_format = config.format;
+            if let Some(ref mut log) = config.file {
                 let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
                 let message = log_file.as_ref().map_or_else(
                     || String::from("Logging has been switched off"),
@@ -68,30 +180,39 @@ impl SimpleLogger {
                         format!("Log file changed - subsequent logs will be written to: {path:?}")
                     },
                 );
-                log.write_all(format!("{timestamp} --- {message}\n",).as_bytes())
+                let line = log_format.format(&timestamp, None, module_path!(), &message);
+                log.file
+                    .write_all(format!("{line}\n").as_bytes())
                     .expect("writing to log file failed");
             }
-            config.log_file = match log_file {
-                None => None,
-                Some(path) => {
-                    if let Some(dir) = path.parent() {
-                        std::fs::create_dir_all(dir)?;
-                    }
-                    Some(
-                        std::fs::File::options()
-                            .create(true)
-                            .append(true)
-                            .open(path)?,
-                    )
-                }
-            };
+            config.file = log_file.map(LogFile::open).transpose()?;
+            let log_rotation = config.rotation;
+            if let Some(ref mut log) = config.file {
+                log.rotate_if_too_large(log_rotation)?;
+            }
         }
         debug!("Hello, world!");
         Ok(())
     }
 
     pub fn set_log_to_stderr(&self, log_to_stderr: bool) {
-        self.config.lock().log_to_stderr = log_to_stderr;
+        self.config.lock().to_stderr = log_to_stderr;
+    }
+
+    /// Sets how records are written to the log file.
+    pub fn set_log_format(&self, log_format: LogFormat) {
+        self.config.lock().format = log_format;
+    }
+
+    /// Sets when the log file is rotated, or `None` to never rotate it. The log file is rotated straight away if it is
+    /// already too large.
+    pub fn set_log_rotation(&self, log_rotation: Option<LogRotation>) -> std::io::Result<()> {
+        let mut config = self.config.lock();
+        config.rotation = log_rotation;
+        config
+            .file
+            .as_mut()
+            .map_or(Ok(()), |log| log.rotate_if_too_large(log_rotation))
     }
 }
 
@@ -121,14 +242,20 @@ impl log::Log for SimpleLogger {
             let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
             let level = record.level();
             let target = metadata.target();
-            let args = record.args();
-            let log = format!("{timestamp}  {level:<5}  {target} --- {args}");
-            if let Some(ref mut f) = self.config.lock().log_file {
+            let message = record.args().to_string();
+            let log = LogFormat::Text.format(&timestamp, Some(level), target, &message);
+            let mut config = self.config.lock();
+            let log_format = config.format;
+            let log_rotation = config.rotation;
+            if let Some(ref mut f) = config.file {
+                let line = log_format.format(&timestamp, Some(level), target, &message);
                 // We want to explode if logging fails, because otherwise it becomes impossible to debug issues
-                f.write_all(format!("{}\n", &log).as_bytes())
+                f.write_line(&line, log_rotation)
                     .expect("writing to log file failed");
             }
-            if self.config.lock().log_to_stderr {
+            let log_to_stderr = config.to_stderr;
+            drop(config);
+            if log_to_stderr {
                 let style = if level <= Level::Error {
                     console::Style::new().fg(console::Color::Red)
                 } else if level <= Level::Warn {
@@ -143,8 +270,8 @@ impl log::Log for SimpleLogger {
     }
 
     fn flush(&self) {
-        if let Some(ref mut log) = self.config.lock().log_file {
-            log.flush().expect("flushing log file failed");
+        if let Some(ref mut log) = self.config.lock().file {
+            log.file.flush().expect("flushing log file failed");
         }
     }
 }
@@ -408,6 +535,110 @@ mod tests {
         std::fs::remove_file(log_path).unwrap();
     }
 
+    #[test]
+    fn test_json_log_format() {
+        // Arrange
+        let log_path = std::env::temp_dir().join(format!("pexshell-test-log-{}", Uuid::new_v4()));
+        let logger = SimpleLogger::new(Some(log_path.clone())).unwrap();
+        logger.set_max_level(LevelFilter::Info);
+        logger.set_log_format(LogFormat::Json);
+
+        // Act
+        logger.log(
+            &Record::builder()
+                .level(Level::Warn)
+                .args(format_args!("a \"quoted\" message"))
+                .target("pexshell::cli")
+                .build(),
+        );
+        logger.flush();
+
+        // Assert
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        let mut logs = log.lines();
+        let mut record: serde_json::Value = serde_json::from_str(logs.next().unwrap()).unwrap();
+        let timestamp = record.as_object_mut().unwrap().remove("timestamp");
+        assert_that!(
+            timestamp.as_ref().and_then(serde_json::Value::as_str),
+            some(ends_with("Z"))
+        );
+        assert_that!(
+            record,
+            eq(&json!({
+                "level": "WARN",
+                "target": "pexshell::cli",
+                "message": "a \"quoted\" message",
+            }))
+        );
+        assert_that!(logs.next(), none());
+        std::fs::remove_file(&log_path).unwrap();
+    }
+
+    #[test]
+    fn test_log_rotation() {
+        // Arrange
+        let test_context = get_test_context();
+        let log_path = test_context.get_test_dir().join("pexshell.log");
+        std::fs::write(&log_path, "a line from before rotation was configured\n").unwrap();
+        let logger = SimpleLogger::new(Some(log_path.clone())).unwrap();
+        logger.set_max_level(LevelFilter::Info);
+        let record = Record::builder()
+            .level(Level::Info)
+            .args(format_args!("0123456789"))
+            .target("pexshell")
+            .build();
+
+        // Act
+        logger
+            .set_log_rotation(Some(LogRotation {
+                max_size: 40,
+                max_files: 2,
+            }))
+            .unwrap();
+        for _ in 0..4 {
+            logger.log(&record);
+        }
+        logger.flush();
+
+        // Assert
+        let line_count = |path: PathBuf| std::fs::read_to_string(path).unwrap().lines().count();
+        assert_that!(line_count(log_path.clone()), eq(0));
+        assert_that!(line_count(rotated_path(&log_path, 1)), eq(1));
+        assert_that!(line_count(rotated_path(&log_path, 2)), eq(1));
+        assert_that!(rotated_path(&log_path, 3).exists(), eq(false));
+    }
+
+    #[test]
+    fn test_log_rotation_when_log_file_was_rotated_by_another_process() {
+        // Arrange
+        let test_context = get_test_context();
+        let log_path = test_context.get_test_dir().join("pexshell.log");
+        let logger = SimpleLogger::new(Some(log_path.clone())).unwrap();
+        logger.set_max_level(LevelFilter::Info);
+        logger
+            .set_log_rotation(Some(LogRotation {
+                max_size: 40,
+                max_files: 2,
+            }))
+            .unwrap();
+        let record = Record::builder()
+            .level(Level::Info)
+            .args(format_args!("0123456789"))
+            .target("pexshell")
+            .build();
+        logger.log(&record);
+
+        // Act
+        std::fs::remove_file(&log_path).unwrap();
+        logger.log(&record);
+        logger.log(&record);
+        logger.flush();
+
+        // Assert
+        assert_that!(log_path.exists(), eq(true));
+        assert_that!(rotated_path(&log_path, 1).exists(), eq(true));
+    }
+
     #[test]
     fn test_new_creates_log_file_parent_directories() {
         let test_context = get_test_context();
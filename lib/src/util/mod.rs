@@ -1,9 +1,11 @@
 mod future;
 mod logging;
+mod redact;
 mod sensitive_string;
 mod stream;
 
 pub use future::*;
 pub use logging::*;
+pub use redact::*;
 pub use sensitive_string::*;
 pub use stream::*;
@@ -0,0 +1,340 @@
+use crate::{
+    config::{Configurer, Manager as ConfigManager},
+    pexshell::PexShell,
+};
+use clap::{builder::styling::Style, Arg, ArgAction, ArgMatches, Command};
+use lib::error;
+use std::{collections::BTreeMap, fmt::Display, fmt::Write as _, io::Write};
+
+pub struct Alias;
+
+impl Display for Alias {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "alias")
+    }
+}
+
+impl Alias {
+    pub fn command(&self) -> Command {
+        Command::new(self.to_string())
+            .about("Manage command aliases")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("add")
+                    .about("Add an alias that expands to the given command")
+                    .arg(
+                        Arg::new("name")
+                            .help("The name of the alias")
+                            .required(true)
+                            .action(ArgAction::Set),
+                    )
+                    .arg(
+                        Arg::new("command")
+                            .help("The command the alias expands to")
+                            .required(true)
+                            .num_args(1..)
+                            .trailing_var_arg(true)
+                            .allow_hyphen_values(true)
+                            .action(ArgAction::Append),
+                    ),
+            )
+            .subcommand(Command::new("list").about("List existing aliases"))
+            .subcommand(
+                Command::new("delete").about("Delete an alias").arg(
+                    Arg::new("name")
+                        .help("The name of the alias")
+                        .required(true)
+                        .action(ArgAction::Set),
+                ),
+            )
+    }
+
+    #[allow(clippy::unused_self)]
+    pub fn run(
+        &self,
+        pexshell: &mut PexShell,
+        config: &mut ConfigManager,
+        command: &Command,
+        alias_sub: &ArgMatches,
+    ) -> Result<(), error::UserFriendly> {
+        match alias_sub.subcommand() {
+            Some(("add", add_sub)) => {
+                let name = add_sub
+                    .get_one::<String>("name")
+                    .expect("argument name is required")
+                    .to_owned();
+                let expansion: Vec<String> = add_sub
+                    .get_many::<String>("command")
+                    .expect("argument command is required")
+                    .cloned()
+                    .collect();
+
+                validate_alias(command, config.get_aliases(), &name, &expansion)?;
+                config.add_alias(name, expansion);
+                config.write_to_file()?;
+            }
+            Some(("list", _)) => {
+                let mut output = String::new();
+                for (name, expansion) in config.get_aliases() {
+                    writeln!(&mut output, "{name} = {}", expansion.join(" ")).unwrap();
+                }
+                write!(pexshell.console, "{output}").unwrap();
+            }
+            Some(("delete", delete_sub)) => {
+                let name = delete_sub
+                    .get_one::<String>("name")
+                    .expect("argument name is required");
+                config.delete_alias(name)?;
+                config.write_to_file()?;
+            }
+            _ => unreachable!("alias subcommand is required"),
+        }
+        Ok(())
+    }
+}
+
+/// Adds a section listing the configured aliases to the help output of the top level command.
+pub fn with_alias_help(command: Command, aliases: &BTreeMap<String, Vec<String>>) -> Command {
+    if aliases.is_empty() {
+        return command;
+    }
+
+    let heading = Style::new().bold().underline();
+    let width = aliases.keys().map(String::len).max().unwrap_or_default();
+    let mut help = format!("{heading}Aliases:{heading:#}\n");
+    for (name, expansion) in aliases {
+        writeln!(&mut help, "  {name:<width$}  {}", expansion.join(" ")).unwrap();
+    }
+    command.after_help(help.trim_end().to_owned())
+}
+
+/// Expands the subcommand in `args` if it is an alias.
+///
+/// Built-in subcommands take precedence over aliases with the same name.
+pub fn expand_alias(
+    command: &Command,
+    aliases: &BTreeMap<String, Vec<String>>,
+    mut args: Vec<String>,
+) -> Result<Vec<String>, error::UserFriendly> {
+    let Some(index) = subcommand_position(command, &args) else {
+        return Ok(args);
+    };
+
+    let name = &args[index];
+    if is_builtin(command, name) {
+        return Ok(args);
+    }
+    let Some(expansion) = aliases.get(name) else {
+        return Ok(args);
+    };
+
+    if let Some(next) = expansion
+        .first()
+        .filter(|next| !is_builtin(command, next) && aliases.contains_key(*next))
+    {
+        return Err(error::UserFriendly::new(format!(
+            "alias '{name}' refers to another alias '{next}' - recursive aliases are not supported"
+        )));
+    }
+
+    args.splice(index..=index, expansion.iter().cloned());
+    Ok(args)
+}
+
+/// Checks that an alias can be added without shadowing a built-in subcommand or referring to another alias.
+fn validate_alias(
+    command: &Command,
+    aliases: &BTreeMap<String, Vec<String>>,
+    name: &str,
+    expansion: &[String],
+) -> Result<(), error::UserFriendly> {
+    if name.starts_with('-') {
+        return Err(error::UserFriendly::new(format!(
+            "invalid alias name: {name}"
+        )));
+    }
+
+    if is_builtin(command, name) {
+        return Err(error::UserFriendly::new(format!(
+            "'{name}' is a built-in command and cannot be used as an alias"
+        )));
+    }
+
+    if let Some(first) = expansion.first() {
+        if first == name || (!is_builtin(command, first) && aliases.contains_key(first)) {
+            return Err(error::UserFriendly::new(format!(
+                "alias '{name}' refers to another alias '{first}' - recursive aliases are not supported"
+            )));
+        }
+    }
+
+    if let Some((other, _)) = aliases.iter().find(|(other, other_expansion)| {
+        *other != name && other_expansion.first().map(String::as_str) == Some(name)
+    }) {
+        return Err(error::UserFriendly::new(format!(
+            "alias '{other}' refers to '{name}' - recursive aliases are not supported"
+        )));
+    }
+
+    Ok(())
+}
+
+fn is_builtin(command: &Command, name: &str) -> bool {
+    name == "help" || command.find_subcommand(name).is_some()
+}
+
+/// Finds the index of the first positional argument, skipping over global options and their values.
+fn subcommand_position(command: &Command, args: &[String]) -> Option<usize> {
+    let mut iter = args.iter().enumerate().skip(1);
+    while let Some((index, arg)) = iter.next() {
+        if arg == "--" {
+            return None;
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            return Some(index);
+        }
+        if !arg.contains('=') && takes_value(command, arg) {
+            iter.next();
+        }
+    }
+    None
+}
+
+fn takes_value(command: &Command, flag: &str) -> bool {
+    command.get_arguments().any(|arg| {
+        let matches_flag = flag.strip_prefix("--").map_or_else(
+            || {
+                let mut short = flag.chars().skip(1);
+                short.next() == arg.get_short() && short.next().is_none()
+            },
+            |long| arg.get_long() == Some(long),
+        );
+        matches_flag && arg.get_action().takes_values()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use googletest::prelude::*;
+
+    use super::*;
+    use crate::argparse::CommandGen;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|&s| String::from(s)).collect()
+    }
+
+    fn aliases(aliases: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+        aliases
+            .iter()
+            .map(|(name, expansion)| (String::from(*name), args(expansion)))
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_alias() {
+        // Arrange
+        let command = CommandGen(HashMap::new()).command();
+        let aliases = aliases(&[("confs", &["configuration", "conference", "get"])]);
+
+        // Act
+        let expanded = expand_alias(
+            &command,
+            &aliases,
+            args(&["pexshell", "--log", "confs", "confs", "--limit", "5"]),
+        );
+
+        // Assert
+        assert_that!(
+            expanded,
+            ok(elements_are![
+                eq("pexshell"),
+                eq("--log"),
+                eq("confs"),
+                eq("configuration"),
+                eq("conference"),
+                eq("get"),
+                eq("--limit"),
+                eq("5"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_expand_alias_builtin_takes_precedence() {
+        // Arrange
+        let command = CommandGen(HashMap::new()).command();
+        let aliases = aliases(&[("cache", &["login", "--list"])]);
+
+        // Act
+        let expanded = expand_alias(&command, &aliases, args(&["pexshell", "cache"]));
+
+        // Assert
+        assert_that!(expanded, ok(elements_are![eq("pexshell"), eq("cache")]));
+    }
+
+    #[test]
+    fn test_expand_alias_recursive() {
+        // Arrange
+        let command = CommandGen(HashMap::new()).command();
+        let aliases = aliases(&[("a", &["b"]), ("b", &["login"])]);
+
+        // Act
+        let expanded = expand_alias(&command, &aliases, args(&["pexshell", "a"]));
+
+        // Assert
+        assert_that!(
+            expanded,
+            err(displays_as(eq(
+                "alias 'a' refers to another alias 'b' - recursive aliases are not supported"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_validate_alias() {
+        // Arrange
+        let command = CommandGen(HashMap::new()).command();
+        let aliases = aliases(&[("users", &["login", "--list"])]);
+
+        // Act & Assert
+        assert_that!(
+            validate_alias(&command, &aliases, "clear", &args(&["cache", "--clear"])),
+            ok(anything())
+        );
+        assert_that!(
+            validate_alias(&command, &aliases, "login", &args(&["cache"])),
+            err(displays_as(eq(
+                "'login' is a built-in command and cannot be used as an alias"
+            )))
+        );
+        assert_that!(
+            validate_alias(&command, &aliases, "u", &args(&["users"])),
+            err(displays_as(eq(
+                "alias 'u' refers to another alias 'users' - recursive aliases are not supported"
+            )))
+        );
+        assert_that!(
+            validate_alias(&command, &aliases, "login", &args(&["login"])),
+            err(anything())
+        );
+    }
+
+    #[test]
+    fn test_alias_help() {
+        // Arrange
+        let command = CommandGen(HashMap::new()).command();
+        let aliases = aliases(&[("users", &["login", "--list"]), ("c", &["cache"])]);
+
+        // Act
+        let help = with_alias_help(command, &aliases).render_help().to_string();
+
+        // Assert
+        assert_that!(
+            help,
+            contains_substring("Aliases:\n  c      cache\n  users  login --list")
+        );
+    }
+}
@@ -1,14 +1,51 @@
-use crate::{cli::login, config::Provider as ConfigProvider};
+use crate::{
+    cli::{
+        login::{self, Interact as _, Interactive},
+        progress::ProgressBar,
+    },
+    config::Provider as ConfigProvider,
+    pexshell::PexShell,
+};
 use clap::{Arg, ArgAction, ArgMatches, Command};
 use lib::{
     error,
-    mcu::{self, schema},
+    mcu::{self, compat::Version, retry::RetryPolicy, schema, Api, CommandApi},
+};
+use log::{debug, info};
+use std::{
+    fmt::Display,
+    io::{ErrorKind, Write as _},
+    path::Path,
 };
-use log::info;
-use std::{fmt::Display, path::Path};
 
 pub struct Cache;
 
+/// The APIs whose schemas can be cached on their own with `pexshell cache <api>`.
+const APIS: [(&str, Api); 6] = [
+    ("configuration", Api::Configuration),
+    ("status", Api::Status),
+    ("history", Api::History),
+    ("command-conference", Api::Command(CommandApi::Conference)),
+    ("command-participant", Api::Command(CommandApi::Participant)),
+    ("command-platform", Api::Command(CommandApi::Platform)),
+];
+
+/// How to fetch the schema cache.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FetchOptions<'a> {
+    /// Only fetch schemas cached from a different management node version, if the cache exists.
+    pub refresh: bool,
+    /// Store the schemas as gzip-compressed JSON, even if the config does not enable it.
+    pub compress: bool,
+    /// Print how many endpoints have been cached while generating the cache, if stderr isn't interactive. A progress
+    /// bar is shown instead when it is.
+    pub progress: bool,
+    /// Only fetch the schemas of this API, leaving the rest of the cache as it is.
+    pub api: Option<Api>,
+    /// Only fetch the schema of this endpoint of `api`.
+    pub resource: Option<&'a str>,
+}
+
 impl Display for Cache {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "cache")
@@ -19,10 +56,72 @@ impl Cache {
     pub fn command(&self) -> Command {
         Command::new(self.to_string())
             .about("fetch new schema cache")
+            .args_conflicts_with_subcommands(true)
+            .subcommand(
+                Command::new("clear")
+                    .about("Remove the cached schemas, keeping any pinned schemas")
+                    .arg(
+                        Arg::new("yes")
+                            .long("yes")
+                            .short('y')
+                            .help("Remove the schema cache without asking for confirmation")
+                            .action(ArgAction::SetTrue),
+                    )
+                    .arg(
+                        Arg::new("include_pinned")
+                            .long("include-pinned")
+                            .help("Remove the whole schema cache directory, including pinned schemas")
+                            .action(ArgAction::SetTrue),
+                    ),
+            )
+            .arg(
+                Arg::new("api")
+                    .help("Only fetch the schemas of this API, leaving the rest of the cache as it is")
+                    .value_parser(APIS.map(|(name, _)| name))
+                    .conflicts_with_all(["clear", "refresh"])
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("resource")
+                    .help("Only fetch the schema of this endpoint of the API, e.g. conference")
+                    .requires("api")
+                    .action(ArgAction::Set),
+            )
             .arg(
                 Arg::new("clear")
                     .long("clear")
-                    .help("Remove existing schema cache")
+                    .help("Deprecated: use `pexshell cache clear --yes` instead")
+                    .hide(true)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("refresh")
+                    .long("refresh")
+                    .help("Only fetch schemas cached from a different management node version")
+                    .long_help(
+                        "Only fetch schemas cached from a different management node version. \
+                         The management node version is set with --compat or the compat setting of the user. \
+                         The whole cache is generated if it does not exist.",
+                    )
+                    .conflicts_with("clear")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("progress")
+                    .long("progress")
+                    .help("Print how many endpoints have been cached while generating the cache, when no progress bar is shown")
+                    .conflicts_with("clear")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("compress_cache")
+                    .long("compress-cache")
+                    .help("Store the schema cache as gzip-compressed JSON")
+                    .long_help(
+                        "Store the schema cache as gzip-compressed JSON. \
+                         This can also be enabled with the compress_cache setting in the config file.",
+                    )
+                    .conflicts_with("clear")
                     .action(ArgAction::SetTrue),
             )
     }
@@ -32,41 +131,233 @@ impl Cache {
         config: &mut impl ConfigProvider,
         cache_dir: &Path,
         client: reqwest::Client,
+        retry_policy: RetryPolicy,
+        compat_version: Option<Version>,
         cache_matches: &ArgMatches,
     ) -> anyhow::Result<()> {
-        if cache_matches.get_flag("clear") {
-            info!("Clearing cache...");
+        self.fetch(
+            config,
+            cache_dir,
+            client,
+            retry_policy,
+            compat_version,
+            FetchOptions {
+                refresh: cache_matches.get_flag("refresh"),
+                compress: cache_matches.get_flag("compress_cache"),
+                progress: cache_matches.get_flag("progress"),
+                api: cache_matches.get_one::<String>("api").map(|api_name| {
+                    APIS.iter()
+                        .find(|&&(name, _)| name == api_name.as_str())
+                        .map(|&(_, api)| api)
+                        .expect("api is one of the possible values")
+                }),
+                resource: cache_matches
+                    .get_one::<String>("resource")
+                    .map(String::as_str),
+            },
+        )
+        .await
+    }
+
+    /// Gets whether the arguments of `pexshell cache` ask to clear the cache, with either `cache clear` or the
+    /// deprecated `--clear` flag.
+    #[allow(clippy::unused_self)]
+    pub fn is_clear(&self, cache_matches: &ArgMatches) -> bool {
+        cache_matches.get_flag("clear") || cache_matches.subcommand_matches("clear").is_some()
+    }
+
+    /// Removes the cached schemas, for `pexshell cache clear` and the deprecated `pexshell cache --clear`.
+    ///
+    /// Pinned schemas are kept, unless `--include-pinned` is given to remove the whole schema cache directory.
+    #[allow(clippy::unused_self)]
+    pub fn clear(
+        &self,
+        pexshell: &mut PexShell,
+        cache_dir: &Path,
+        cache_matches: &ArgMatches,
+    ) -> Result<(), error::UserFriendly> {
+        // --clear never asked for confirmation
+        let (include_pinned, yes) =
+            cache_matches
+                .subcommand_matches("clear")
+                .map_or((false, true), |clear_sub| {
+                    (
+                        clear_sub.get_flag("include_pinned"),
+                        clear_sub.get_flag("yes"),
+                    )
+                });
+
+        if !cache_dir.exists() {
+            debug!("Schema cache not found at {}", cache_dir.display());
+            writeln!(pexshell.console, "no schema cache found").unwrap();
+            return Ok(());
+        }
+
+        if !yes && pexshell.console.is_stderr_interactive() {
+            let prompt = if include_pinned {
+                format!(
+                    "remove the schema cache at {}, including pinned schemas?",
+                    cache_dir.display()
+                )
+            } else {
+                format!("remove the schema cache at {}?", cache_dir.display())
+            };
+            let confirmed = Interactive {}.confirm(&prompt);
+            if !confirmed {
+                return Ok(());
+            }
+        }
+
+        info!("Clearing cache...");
+        if include_pinned {
+            match std::fs::remove_dir_all(cache_dir) {
+                Err(e) if e.kind() != ErrorKind::NotFound => {
+                    return Err(error::UserFriendly::new(format!(
+                        "failed to remove {}: {e}",
+                        cache_dir.display()
+                    )))
+                }
+                _ => (),
+            }
+            debug!("Removed schema cache directory {}", cache_dir.display());
+        } else {
             clear_cache(cache_dir)
                 .map_err(|err| error::UserFriendly::new(format!("error clearing cache: {err}",)))?;
+        }
+
+        info!("Cache cleared.");
+        eprintln!("Cache cleared.");
+        Ok(())
+    }
+
+    /// Fetches the schema cache for the current user.
+    ///
+    /// The schemas are stored compressed if either the options or the config enable it.
+    pub async fn fetch(
+        &self,
+        config: &mut impl ConfigProvider,
+        cache_dir: &Path,
+        client: reqwest::Client,
+        retry_policy: RetryPolicy,
+        compat_version: Option<Version>,
+        options: FetchOptions<'_>,
+    ) -> anyhow::Result<()> {
+        let compress = options.compress || config.get_compress_cache();
+        let mut user = config.get_current_user()?.clone();
+        let address = user.address.clone();
+        let version = user.settings.version(compat_version)?;
+        let compat = user.settings.compat(version)?;
+        let schema_version = version.map(|version| version.to_string());
 
-            info!("Cache cleared.");
-            eprintln!("Cache cleared.");
+        let stale = if options.refresh && schema::cache_exists(cache_dir) {
+            let Some(schema_version) = &schema_version else {
+                return Err(error::UserFriendly::new(
+                    "the management node version is unknown - set it with --compat to refresh the cache",
+                )
+                .into());
+            };
+            let stale = schema::read_all_schemas(cache_dir, Some(schema_version))
+                .await?
+                .stale;
+            if stale.is_empty() {
+                eprintln!("Cache is up to date.");
+                return Ok(());
+            }
+            Some(stale)
         } else {
-            let mut user = config.get_current_user()?.clone();
-            let address = user.address.clone();
+            None
+        };
 
+        let api_client = mcu::ApiClient::new(
+            client.clone(),
+            &address,
+            login::auth_for_user(client, &mut user, config, true)?,
+        )
+        .with_retry_policy(retry_policy)
+        .with_compat(compat);
+        if let Some(api) = options.api {
+            let api_name = api.to_string().to_lowercase();
+            if let Some(resource) = options.resource {
+                eprintln!("Fetching schema for {api_name} {resource}...");
+                info!("Fetching schema for {api_name} {resource}...");
+                schema::cache_schema(
+                    &api_client,
+                    cache_dir,
+                    api,
+                    resource,
+                    schema_version.as_deref(),
+                    compress,
+                )
+                .await?;
+            } else {
+                eprintln!("Fetching schemas for {api_name}...");
+                info!("Fetching schemas for {api_name}...");
+                schema::cache_api(
+                    &api_client,
+                    cache_dir,
+                    api,
+                    schema_version.as_deref(),
+                    compress,
+                )
+                .await?;
+            }
+            info!("Cache updated.");
+            eprintln!("Cache updated.");
+        } else if let Some(stale) = stale {
+            eprintln!("Refreshing {} stale schemas...", stale.len());
+            info!("Refreshing {} stale schemas...", stale.len());
+            schema::refresh_schemas(
+                &api_client,
+                cache_dir,
+                schema_version.as_deref(),
+                &stale,
+                compress,
+            )
+            .await?;
+            info!("Cache refreshed.");
+            eprintln!("Cache refreshed.");
+        } else {
             eprintln!("Generating cache...");
             info!("Generating cache...");
-            let api_client = mcu::ApiClient::new(
-                client.clone(),
-                &address,
-                login::auth_for_user(client, &mut user, config, true)?,
-            );
-            schema::cache_schemas(&api_client, cache_dir).await?;
+            let progress_bar = ProgressBar::stderr("endpoints");
+            schema::cache_schemas_with_progress(
+                &api_client,
+                cache_dir,
+                schema_version.as_deref(),
+                compress,
+                |completed, total| match &progress_bar {
+                    Some(progress_bar) => progress_bar.update(completed, total),
+                    None if options.progress => {
+                        eprintln!("Cached schemas for {completed}/{total} endpoints");
+                    }
+                    None => (),
+                },
+            )
+            .await?;
+            drop(progress_bar);
             info!("Cache created.");
             eprintln!("Cache created.");
+        }
 
-            drop(api_client);
+        drop(api_client);
 
-            config.set_last_used()?;
-        }
+        config.set_last_used()?;
         Ok(())
     }
 }
 
+/// Removes the cached schemas of each API, keeping any schemas that have been pinned to a management node version.
 fn clear_cache(cache_dir: &Path) -> anyhow::Result<()> {
+    const API_DIRS: [&str; 4] = ["configuration", "history", "status", "command"];
     for f in cache_dir.read_dir()? {
         let dir = f?;
+        if dir.file_name() == schema::CACHE_META_FILE {
+            std::fs::remove_file(dir.path())?;
+            continue;
+        }
+        if !API_DIRS.iter().any(|name| dir.file_name() == *name) {
+            continue;
+        }
         std::fs::remove_dir_all(
             dir.path()
                 .to_str()
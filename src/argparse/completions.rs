@@ -1,7 +1,24 @@
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    io::{ErrorKind, Write as _},
+    path::{Path, PathBuf},
+};
 
-use crate::pexshell::PexShell;
-use clap::{Arg, ArgAction, ArgMatches, Command};
+use crate::{
+    cli::login::{Interact as _, Interactive},
+    pexshell::PexShell,
+    VERSION,
+};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use lib::error;
+
+const SHELLS: [&str; 6] = ["bash", "elvish", "fish", "nushell", "powershell", "zsh"];
+
+/// Shells with a usual location for completion scripts.
+const INSTALLED_SHELLS: [&str; 3] = ["bash", "fish", "zsh"];
+
+/// Marks the version of pexshell that generated a completion script.
+const VERSION_COMMENT: &str = "# pexshell completions version: ";
 
 pub struct Completions;
 
@@ -18,45 +35,320 @@ impl Completions {
                 "Prints generated shell completions to STDOUT. Remember to regenerate after \
                  updating the schema cache!",
             )
+            .args_conflicts_with_subcommands(true)
+            .subcommand_negates_reqs(true)
             .arg(
                 Arg::new("shell")
                     .help("The shell to generate completions for")
                     .required(true)
                     .action(ArgAction::Set)
-                    .value_parser(["bash", "elvish", "fish", "nushell", "powershell", "zsh"]),
+                    .value_parser(SHELLS),
+            )
+            .subcommand(
+                Command::new("verify")
+                    .about("Check that the installed shell completions are up to date")
+                    .arg(
+                        Arg::new("shell")
+                            .help("The shell to check completions for")
+                            .required(true)
+                            .action(ArgAction::Set)
+                            .value_parser(SHELLS),
+                    )
+                    .arg(
+                        Arg::new("path")
+                            .long("path")
+                            .help("The installed completion script (defaults to the usual location for bash, fish and zsh)")
+                            .value_parser(value_parser!(PathBuf))
+                            .action(ArgAction::Set),
+                    ),
             )
+            .subcommand(Command::new("clean").about(
+                "Remove installed shell completions that were generated by a different version of pexshell",
+            ))
     }
 
+    /// Gets the usual location of the completion script for a shell, relative to the home directory.
     #[allow(clippy::unused_self)]
-    pub fn run(&self, pexshell: &mut PexShell, command: &Command, completions_sub: &ArgMatches) {
+    pub fn default_path(&self, shell: &str, home: &Path) -> Option<PathBuf> {
+        match shell {
+            "bash" => Some(home.join(".local/share/bash-completion/completions/pexshell")),
+            "fish" => Some(home.join(".config/fish/completions/pexshell.fish")),
+            "zsh" => Some(home.join(".zfunc/_pexshell")),
+            _ => None,
+        }
+    }
+
+    pub fn run(
+        &self,
+        pexshell: &mut PexShell,
+        command: &Command,
+        completions_sub: &ArgMatches,
+    ) -> Result<(), error::UserFriendly> {
+        if let Some(verify_sub) = completions_sub.subcommand_matches("verify") {
+            return self.verify(pexshell, command, verify_sub);
+        }
+        if completions_sub.subcommand_matches("clean").is_some() {
+            return self.clean(pexshell);
+        }
+
         let shell = completions_sub
             .get_one::<String>("shell")
-            .expect("argument shell is required")
-            .as_str();
-
-        let shell = match shell {
-            "bash" => clap_complete::Shell::Bash,
-            "elvish" => clap_complete::Shell::Elvish,
-            "fish" => clap_complete::Shell::Fish,
-            "nushell" => {
-                clap_complete::generate(
-                    clap_complete_nushell::Nushell,
-                    &mut command.clone(),
-                    "pexshell",
-                    &mut pexshell.console,
-                );
-                return;
+            .expect("argument shell is required");
+        generate(shell, command, &mut pexshell.console);
+        Ok(())
+    }
+
+    fn verify(
+        &self,
+        pexshell: &mut PexShell,
+        command: &Command,
+        verify_sub: &ArgMatches,
+    ) -> Result<(), error::UserFriendly> {
+        let shell = verify_sub
+            .get_one::<String>("shell")
+            .expect("argument shell is required");
+        let path = match verify_sub.get_one::<PathBuf>("path") {
+            Some(path) => path.clone(),
+            None => directories::BaseDirs::new()
+                .and_then(|dirs| self.default_path(shell, dirs.home_dir()))
+                .ok_or_else(|| {
+                    error::UserFriendly::new(format!(
+                        "no default location for {shell} completions - specify it with --path"
+                    ))
+                })?,
+        };
+
+        let mut generated = Vec::new();
+        generate(shell, command, &mut generated);
+
+        let install = format!("pexshell completions {shell} > {}", path.display());
+        match is_up_to_date(&path, &generated) {
+            Ok(true) => {
+                writeln!(
+                    pexshell.console,
+                    "{shell} completions are up to date: {}",
+                    path.display()
+                )
+                .unwrap();
+            }
+            Ok(false) => pexshell.console.display_warning(&format!(
+                "{shell} completions are out of date - regenerate them with: {install}"
+            )),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                pexshell.console.display_warning(&format!(
+                    "{shell} completions are not installed at {} - install them with: {install}",
+                    path.display()
+                ));
             }
-            "powershell" => clap_complete::Shell::PowerShell,
-            "zsh" => clap_complete::Shell::Zsh,
-            _ => panic!("Unhandled shell!"),
+            Err(e) => {
+                return Err(error::UserFriendly::new(format!(
+                    "failed to read {}: {e}",
+                    path.display()
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    fn clean(&self, pexshell: &mut PexShell) -> Result<(), error::UserFriendly> {
+        let Some(home) = directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf())
+        else {
+            return Err(error::UserFriendly::new(
+                "could not determine the home directory",
+            ));
         };
+        let dirs: Vec<PathBuf> = INSTALLED_SHELLS
+            .iter()
+            .filter_map(|shell| self.default_path(shell, &home))
+            .filter_map(|path| path.parent().map(Path::to_path_buf))
+            .collect();
+
+        let stale = find_stale(&dirs, VERSION.as_str());
+        if stale.is_empty() {
+            writeln!(pexshell.console, "no stale completion scripts found").unwrap();
+            return Ok(());
+        }
+
+        if pexshell.console.is_stderr_interactive() {
+            for path in &stale {
+                writeln!(pexshell.console, "  {}", path.display()).unwrap();
+            }
+            let confirmed =
+                Interactive {}.confirm(&format!("remove {} completion script(s)?", stale.len()));
+            if !confirmed {
+                return Ok(());
+            }
+        }
+
+        for path in &stale {
+            std::fs::remove_file(path).map_err(|e| {
+                error::UserFriendly::new(format!("failed to remove {}: {e}", path.display()))
+            })?;
+            writeln!(pexshell.console, "removed {}", path.display()).unwrap();
+        }
+        Ok(())
+    }
+}
+
+fn generate(shell: &str, command: &Command, buf: &mut dyn std::io::Write) {
+    match shell {
+        "bash" => generate_with(clap_complete::Shell::Bash, command, buf),
+        "elvish" => generate_with(clap_complete::Shell::Elvish, command, buf),
+        "fish" => generate_with(clap_complete::Shell::Fish, command, buf),
+        "nushell" => generate_with(clap_complete_nushell::Nushell, command, buf),
+        "powershell" => generate_with(clap_complete::Shell::PowerShell, command, buf),
+        "zsh" => generate_with(clap_complete::Shell::Zsh, command, buf),
+        _ => panic!("Unhandled shell!"),
+    }
+
+    writeln!(buf, "\n{VERSION_COMMENT}{}", VERSION.as_str()).unwrap();
+}
 
-        clap_complete::generate(
-            shell,
-            &mut command.clone(),
+fn generate_with(
+    generator: impl clap_complete::Generator,
+    command: &Command,
+    buf: &mut dyn std::io::Write,
+) {
+    clap_complete::generate(generator, &mut command.clone(), "pexshell", buf);
+}
+
+/// Gets the version of pexshell that generated a completion script.
+fn completion_version(script: &str) -> Option<&str> {
+    script
+        .lines()
+        .find_map(|line| line.strip_prefix(VERSION_COMMENT))
+}
+
+/// Finds pexshell completion scripts in the given directories that were not generated by `version`.
+fn find_stale(dirs: &[PathBuf], version: &str) -> Vec<PathBuf> {
+    let mut stale = Vec::new();
+    for entry in dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(Result::ok)
+    {
+        let is_pexshell = entry
+            .file_name()
+            .to_string_lossy()
+            .trim_start_matches('_')
+            .starts_with("pexshell");
+        if !is_pexshell || !entry.path().is_file() {
+            continue;
+        }
+        if let Ok(script) = std::fs::read_to_string(entry.path()) {
+            if completion_version(&script) != Some(version) {
+                stale.push(entry.path());
+            }
+        }
+    }
+    stale.sort();
+    stale
+}
+
+/// Checks whether the installed completion script matches the generated one.
+fn is_up_to_date(installed: &Path, generated: &[u8]) -> std::io::Result<bool> {
+    Ok(std::fs::read(installed)? == generated)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use googletest::prelude::*;
+    use test_helpers::get_test_context;
+
+    use super::*;
+    use crate::argparse::CommandGen;
+
+    #[test]
+    fn test_parse_completions_verify() {
+        // Arrange
+        let command = CommandGen(HashMap::new()).command();
+
+        // Act
+        let matches = command.try_get_matches_from([
             "pexshell",
-            &mut pexshell.console,
+            "completions",
+            "verify",
+            "zsh",
+            "--path",
+            "/tmp/_pexshell",
+        ]);
+
+        // Assert
+        let matches = matches.unwrap();
+        let verify_sub = matches
+            .subcommand_matches("completions")
+            .and_then(|completions_sub| completions_sub.subcommand_matches("verify"))
+            .unwrap();
+        assert_that!(verify_sub.get_one::<String>("shell"), some(eq("zsh")));
+        assert_that!(
+            verify_sub.get_one::<PathBuf>("path"),
+            some(eq(&PathBuf::from("/tmp/_pexshell")))
+        );
+    }
+
+    #[test]
+    fn test_generate_embeds_version() {
+        // Arrange
+        let command = CommandGen(HashMap::new()).command();
+        let mut generated = Vec::new();
+
+        // Act
+        generate("zsh", &command, &mut generated);
+
+        // Assert
+        let script = String::from_utf8(generated).unwrap();
+        assert_that!(script, starts_with("#compdef pexshell"));
+        assert_that!(completion_version(&script), some(eq(VERSION.as_str())));
+    }
+
+    #[test]
+    fn test_find_stale() {
+        // Arrange
+        let test_context = get_test_context();
+        let dir = test_context.get_test_dir().join("completions");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("pexshell"),
+            format!("complete\n{VERSION_COMMENT}1.2.0\n"),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("_pexshell"),
+            format!("#compdef\n{VERSION_COMMENT}1.1.0\n"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("pexshell.fish"), "complete -c pexshell\n").unwrap();
+        std::fs::write(dir.join("git"), "complete -F _git git\n").unwrap();
+
+        // Act
+        let stale = find_stale(&[dir.clone(), dir.join("missing")], "1.2.0");
+
+        // Assert
+        assert_that!(
+            stale,
+            elements_are![eq(&dir.join("_pexshell")), eq(&dir.join("pexshell.fish"))]
         );
     }
+
+    #[test]
+    fn test_is_up_to_date() {
+        // Arrange
+        let test_context = get_test_context();
+        let command = CommandGen(HashMap::new()).command();
+        let mut generated = Vec::new();
+        generate("bash", &command, &mut generated);
+        let installed = test_context.get_test_dir().join("pexshell");
+        std::fs::write(&installed, &generated).unwrap();
+
+        // Act
+        let up_to_date = is_up_to_date(&installed, &generated);
+        let stale = is_up_to_date(&installed, b"complete -F _pexshell pexshell");
+
+        // Assert
+        assert_that!(up_to_date, ok(eq(true)));
+        assert_that!(stale, ok(eq(false)));
+    }
 }
@@ -0,0 +1,110 @@
+use std::{fmt::Write as _, io::Write, path::Path};
+
+use anyhow::anyhow;
+use lib::{error, mcu::schema};
+
+use super::doctor::{Check, Status};
+use crate::{
+    config::{Configurer as _, Manager as ConfigManager, Provider as _, User},
+    pexshell::{ExitCode, PexShell},
+};
+
+/// Checks that the config is usable, for `pexshell config doctor`, printing the results.
+///
+/// Unlike `pexshell doctor`, this doesn't connect to the management node, and checks every user rather than only the
+/// current one.
+pub(super) fn run(
+    pexshell: &mut PexShell,
+    config: &ConfigManager,
+    cache_dir: &Path,
+) -> anyhow::Result<()> {
+    let mut checks: Vec<Check> = config
+        .get_users()
+        .iter()
+        .map(|user| check_credentials(user, config.get_credentials_for_user(user)))
+        .collect();
+    if checks.is_empty() {
+        checks.push(Check::warn(
+            "users",
+            "no users are configured",
+            "log in with: pexshell login",
+        ));
+    }
+    checks.push(check_schema_cache(schema::cache_exists(cache_dir)));
+
+    let mut output = String::new();
+    for check in &checks {
+        write!(&mut output, "{check}").unwrap();
+    }
+    write!(pexshell.console, "{output}")?;
+
+    if checks.iter().any(|check| check.status == Status::Fail) {
+        return Err(anyhow!(ExitCode::from(1)));
+    }
+    Ok(())
+}
+
+/// Reports whether the credentials of a user could be read, without including the credentials themselves.
+fn check_credentials<T>(user: &User, credentials: Result<T, error::UserFriendly>) -> Check {
+    const NAME: &str = "credentials";
+    match credentials {
+        Ok(_) => Check::pass(
+            NAME,
+            format!("credentials for {} are available", user.visual_id()),
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            format!(
+                "credentials for {} could not be retrieved: {e}",
+                user.visual_id()
+            ),
+            "delete the user with pexshell login --delete, then log in again with: pexshell login",
+        ),
+    }
+}
+
+fn check_schema_cache(exists: bool) -> Check {
+    const NAME: &str = "schema cache";
+    if exists {
+        Check::pass(NAME, "schema cache exists")
+    } else {
+        Check::fail(
+            NAME,
+            "schema cache is missing",
+            "generate the schema cache with: pexshell cache",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::prelude::*;
+    use lib::util::SensitiveString;
+
+    use super::*;
+
+    #[test]
+    fn test_check_credentials_missing() {
+        // Arrange
+        let user = User::new(
+            String::from("mcu.example.com"),
+            String::from("admin"),
+            SensitiveString::from("password"),
+        );
+
+        // Act
+        let check = check_credentials::<()>(
+            &user,
+            Err(error::UserFriendly::new(
+                "Password is not configured and could not be retrieved from the system store: No matching entry found",
+            )),
+        );
+
+        // Assert
+        assert_that!(check.status, eq(Status::Fail));
+        assert_that!(
+            check.to_string(),
+            contains_substring("credentials for admin@mcu.example.com could not be retrieved")
+        );
+    }
+}
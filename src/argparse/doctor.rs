@@ -0,0 +1,481 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write as _},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::anyhow;
+use clap::Command;
+use lib::mcu::{schema, ApiClient};
+
+use crate::{
+    argparse::{CommandGen, Completions},
+    config::{Manager as ConfigManager, Provider as _},
+    pexshell::{ExitCode, PexShell},
+    Directories,
+};
+
+/// Schema caches older than this are reported as stale.
+const SCHEMA_CACHE_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// How long to wait for the management node to respond to the connectivity check.
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Shells with a usual location for completion scripts.
+const COMPLETION_SHELLS: [&str; 3] = ["bash", "fish", "zsh"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pass => write!(f, "PASS"),
+            Self::Warn => write!(f, "WARN"),
+            Self::Fail => write!(f, "FAIL"),
+        }
+    }
+}
+
+/// The result of a single diagnostic check.
+#[derive(Debug)]
+pub(super) struct Check {
+    name: &'static str,
+    pub(super) status: Status,
+    description: String,
+    hint: Option<String>,
+}
+
+impl Check {
+    pub(super) fn pass(name: &'static str, description: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Pass,
+            description: description.into(),
+            hint: None,
+        }
+    }
+
+    pub(super) fn warn(
+        name: &'static str,
+        description: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            description: description.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    pub(super) fn fail(
+        name: &'static str,
+        description: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            description: description.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}  {}: {}", self.status, self.name, self.description)?;
+        if let Some(hint) = &self.hint {
+            writeln!(f, "      hint: {hint}")?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Doctor;
+
+impl Display for Doctor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "doctor")
+    }
+}
+
+impl Doctor {
+    pub fn command(&self) -> Command {
+        Command::new(self.to_string())
+            .about("Run checks to diagnose problems with pexshell")
+            .long_about(
+                "Run checks to diagnose problems with pexshell. \
+                 Each check reports PASS, WARN or FAIL, with a hint on how to fix any problems found. \
+                 Exits with a non-zero exit code if any check fails.",
+            )
+    }
+
+    /// Checks whether the doctor command was requested, without needing the config file or schema cache.
+    ///
+    /// Used to run diagnostics when the config file cannot be read.
+    pub fn is_requested(&self, args: &[String]) -> bool {
+        CommandGen(HashMap::new())
+            .command()
+            .ignore_errors(true)
+            .try_get_matches_from(args)
+            .is_ok_and(|matches| matches.subcommand_name() == Some(&self.to_string()))
+    }
+
+    /// Runs all checks, printing the results.
+    ///
+    /// The config is passed as the error from reading it if that failed, so that the remaining checks can still run.
+    #[allow(clippy::unused_self)]
+    pub async fn run(
+        &self,
+        pexshell: &mut PexShell<'_>,
+        config: Result<&ConfigManager, &anyhow::Error>,
+        client: &reqwest::Client,
+        directories: &Directories,
+    ) -> anyhow::Result<()> {
+        let home = directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+        let checks = [
+            check_config(config),
+            check_cache_dir(&directories.cache_dir),
+            check_keyring(config),
+            check_network(config, client).await,
+            check_schema_cache(&directories.cache_dir.join("schemas"), SystemTime::now()).await,
+            check_completions(home.as_deref()),
+            check_binary(std::env::current_exe().ok().as_deref()),
+        ];
+
+        let mut output = String::new();
+        for check in &checks {
+            write!(&mut output, "{check}").unwrap();
+        }
+        write!(pexshell.console, "{output}")?;
+
+        if checks.iter().any(|check| check.status == Status::Fail) {
+            return Err(anyhow!(ExitCode::from(1)));
+        }
+        Ok(())
+    }
+}
+
+fn check_config(config: Result<&ConfigManager, &anyhow::Error>) -> Check {
+    const NAME: &str = "config file";
+    match config {
+        Ok(_) => Check::pass(NAME, "config file is valid"),
+        Err(e) => Check::fail(
+            NAME,
+            format!("failed to read config file: {e}"),
+            "fix or remove the config file, then log in again with: pexshell login",
+        ),
+    }
+}
+
+fn check_cache_dir(cache_dir: &Path) -> Check {
+    const NAME: &str = "cache directory";
+    let test_file = cache_dir.join(".doctor");
+    let result = std::fs::create_dir_all(cache_dir)
+        .and_then(|()| std::fs::write(&test_file, b"pexshell"))
+        .and_then(|()| std::fs::remove_file(&test_file));
+    match result {
+        Ok(()) => Check::pass(NAME, format!("{} is writable", cache_dir.display())),
+        Err(e) => Check::fail(
+            NAME,
+            format!("{} is not writable: {e}", cache_dir.display()),
+            "check the permissions of the cache directory",
+        ),
+    }
+}
+
+fn check_keyring(config: Result<&ConfigManager, &anyhow::Error>) -> Check {
+    const NAME: &str = "keyring";
+    let Ok(config) = config else {
+        return Check::warn(
+            NAME,
+            "skipped as the config file could not be read",
+            "fix the config file first",
+        );
+    };
+    keyring_check(config.check_keyring())
+}
+
+fn keyring_check(result: keyring::Result<()>) -> Check {
+    const NAME: &str = "keyring";
+    match result {
+        Ok(()) => Check::pass(NAME, "system credential store is available"),
+        Err(e) => Check::fail(
+            NAME,
+            format!("system credential store is not available: {e}"),
+            "make sure a credential store (e.g. Secret Service or macOS Keychain) is running and unlocked",
+        ),
+    }
+}
+
+async fn check_network(
+    config: Result<&ConfigManager, &anyhow::Error>,
+    client: &reqwest::Client,
+) -> Check {
+    const NAME: &str = "network";
+    let user = match config.map(|config| config.get_current_user()) {
+        Ok(Ok(user)) => user,
+        Ok(Err(_)) => {
+            return Check::warn(NAME, "no user is logged in", "log in with: pexshell login")
+        }
+        Err(_) => {
+            return Check::warn(
+                NAME,
+                "skipped as the config file could not be read",
+                "fix the config file first",
+            )
+        }
+    };
+    network_check(&user.address, client).await
+}
+
+async fn network_check(address: &str, client: &reqwest::Client) -> Check {
+    const NAME: &str = "network";
+    let url = ApiClient::base_url_from_input_address(address);
+    match client.get(&url).timeout(NETWORK_TIMEOUT).send().await {
+        Ok(_) => Check::pass(NAME, format!("{url} is reachable")),
+        Err(e) => Check::fail(
+            NAME,
+            format!("failed to connect to {url}: {e}"),
+            "check the management node address and your network connection - \
+             use --insecure if the management node has a self-signed certificate",
+        ),
+    }
+}
+
+async fn check_schema_cache(cache_dir: &Path, now: SystemTime) -> Check {
+    const NAME: &str = "schema cache";
+    const HINT: &str = "regenerate the schema cache with: pexshell cache";
+    if !schema::cache_exists(cache_dir) {
+        return Check::fail(NAME, "schema cache is missing", HINT);
+    }
+
+    match schema::read_all_schemas(cache_dir, None).await {
+        Ok(cached) if !cached.corrupt.is_empty() => {
+            return Check::fail(
+                NAME,
+                format!("{} cached schemas could not be read", cached.corrupt.len()),
+                HINT,
+            )
+        }
+        Ok(_) => {}
+        Err(e) => return Check::fail(NAME, format!("failed to read schema cache: {e}"), HINT),
+    }
+
+    match schema::cache_modified(cache_dir)
+        .map(|modified| now.duration_since(modified).unwrap_or_default())
+    {
+        Ok(age) if age > SCHEMA_CACHE_MAX_AGE => Check::warn(
+            NAME,
+            format!(
+                "schema cache was generated {} days ago",
+                age.as_secs() / (24 * 60 * 60)
+            ),
+            HINT,
+        ),
+        Ok(_) => Check::pass(NAME, "schema cache is up to date"),
+        Err(e) => Check::warn(
+            NAME,
+            format!("failed to read the age of the schema cache: {e}"),
+            HINT,
+        ),
+    }
+}
+
+fn check_completions(home: Option<&Path>) -> Check {
+    const NAME: &str = "completions";
+    const HINT: &str =
+        "generate completions with: pexshell completions <shell> - check they are up to date with: pexshell completions verify <shell>";
+    let installed = home.and_then(|home| {
+        COMPLETION_SHELLS
+            .iter()
+            .filter_map(|shell| Completions.default_path(shell, home))
+            .find(|path| path.is_file())
+    });
+    match installed {
+        Some(path) => Check::pass(NAME, format!("found {}", path.display())),
+        None => Check::warn(NAME, "shell completions are not installed", HINT),
+    }
+}
+
+fn check_binary(exe: Option<&Path>) -> Check {
+    const NAME: &str = "binary";
+    const HINT: &str = "reinstall pexshell to a directory you can write to in order to update it";
+    let Some(dir) = exe.and_then(Path::parent) else {
+        return Check::warn(NAME, "failed to find the pexshell binary", HINT);
+    };
+    let test_file: PathBuf = dir.join(".pexshell-doctor");
+    let result =
+        std::fs::write(&test_file, b"pexshell").and_then(|()| std::fs::remove_file(&test_file));
+    match result {
+        Ok(()) => Check::pass(NAME, format!("{} is writable", dir.display())),
+        Err(e) => Check::warn(
+            NAME,
+            format!("{} is not writable: {e}", dir.display()),
+            HINT,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::prelude::*;
+    use test_helpers::get_test_context;
+    use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    #[test]
+    fn test_doctor_is_requested() {
+        // Arrange
+        let args = ["pexshell", "--insecure", "doctor"].map(String::from);
+
+        // Act
+        let requested = Doctor.is_requested(&args);
+
+        // Assert
+        assert_that!(requested, eq(true));
+    }
+
+    #[test]
+    fn test_check_config_error() {
+        // Arrange
+        let error = anyhow!("invalid type: string \"a\", expected a sequence");
+
+        // Act
+        let check = check_config(Err(&error));
+
+        // Assert
+        assert_that!(
+            check,
+            pat!(Check {
+                status: eq(&Status::Fail),
+                description: contains_substring("expected a sequence"),
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn test_keyring_check_no_access() {
+        // Act
+        let check = keyring_check(Err(keyring::Error::NoStorageAccess(Box::new(
+            std::io::Error::other("locked"),
+        ))));
+
+        // Assert
+        assert_that!(
+            check,
+            pat!(Check {
+                status: eq(&Status::Fail),
+                hint: some(contains_substring("unlocked")),
+                ..
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_network_check_reachable() {
+        // Arrange
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // Act
+        let check = network_check(&server.uri(), &reqwest::Client::new()).await;
+
+        // Assert
+        assert_that!(check.status, eq(Status::Pass));
+    }
+
+    #[tokio::test]
+    async fn test_check_schema_cache_missing() {
+        // Arrange
+        let test_context = get_test_context();
+
+        // Act
+        let check = check_schema_cache(
+            &test_context.get_cache_dir().join("schemas"),
+            SystemTime::now(),
+        )
+        .await;
+
+        // Assert
+        assert_that!(
+            check,
+            pat!(Check {
+                status: eq(&Status::Fail),
+                hint: some(contains_substring("pexshell cache")),
+                ..
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_schema_cache_stale() {
+        // Arrange
+        let test_context = get_test_context();
+        test_context.seed_minimal_schemas();
+        let cache_dir = test_context.get_cache_dir().join("schemas");
+        let now = SystemTime::now() + SCHEMA_CACHE_MAX_AGE + Duration::from_secs(60);
+
+        // Act
+        let check = check_schema_cache(&cache_dir, now).await;
+
+        // Assert
+        assert_that!(
+            check,
+            pat!(Check {
+                status: eq(&Status::Warn),
+                description: eq("schema cache was generated 30 days ago"),
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_completions() {
+        // Arrange
+        let test_context = get_test_context();
+        let home = test_context.get_test_dir();
+        let path = home.join(".config/fish/completions/pexshell.fish");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "").unwrap();
+
+        // Act
+        let check = check_completions(Some(home));
+
+        // Assert
+        assert_that!(check.status, eq(Status::Pass));
+    }
+
+    #[test]
+    fn test_check_completions_not_installed() {
+        // Arrange
+        let test_context = get_test_context();
+
+        // Act
+        let check = check_completions(Some(test_context.get_test_dir()));
+
+        // Assert
+        assert_that!(
+            check,
+            pat!(Check {
+                status: eq(&Status::Warn),
+                hint: some(contains_substring("pexshell completions")),
+                ..
+            })
+        );
+    }
+}
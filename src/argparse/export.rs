@@ -0,0 +1,183 @@
+use crate::{
+    argparse::CommandGen,
+    cli::login,
+    config::{Manager as ConfigManager, Provider as _},
+    pexshell::PexShell,
+};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use lib::{
+    error,
+    mcu::{self, schema, Api},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+/// Version of the export file format, written to the header line.
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// The first line of an export file, describing the objects that follow it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ExportHeader {
+    pub pexshell_export: u32,
+    pub api: String,
+    pub endpoint: String,
+    pub count: usize,
+    /// The fields of the endpoint in the schema the objects were exported with.
+    pub fields: Vec<String>,
+}
+
+/// Gets the API that objects can be exported from by its name on the command line.
+pub fn exportable_api(name: &str) -> Option<Api> {
+    match name {
+        "configuration" => Some(Api::Configuration),
+        "status" => Some(Api::Status),
+        "history" => Some(Api::History),
+        _ => None,
+    }
+}
+
+/// Looks up the schema of an endpoint to export from or import to.
+pub fn export_endpoint<'a>(
+    schemas: &'a HashMap<Api, HashMap<String, schema::Endpoint>>,
+    api_name: &str,
+    endpoint: &str,
+) -> Result<(Api, &'a schema::Endpoint), error::UserFriendly> {
+    let api = exportable_api(api_name)
+        .ok_or_else(|| error::UserFriendly::new(format!("unrecognised api {api_name}")))?;
+    let endpoint = schemas
+        .get(&api)
+        .and_then(|endpoints| endpoints.get(endpoint))
+        .ok_or_else(|| error::UserFriendly::new(format!("unrecognised resource {endpoint}")))?;
+    Ok((api, endpoint))
+}
+
+pub struct Export;
+
+impl Display for Export {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "export")
+    }
+}
+
+impl Export {
+    pub fn command(&self) -> Command {
+        Command::new(self.to_string())
+            .about("Export all objects of an endpoint to a JSON Lines file")
+            .long_about(
+                "Export all objects of an endpoint to a JSON Lines file. \
+                 The first line of the file is a header describing the API and endpoint the objects were exported from, \
+                 so that the file can be validated when it is imported with: pexshell import",
+            )
+            .arg(
+                Arg::new("api")
+                    .help("The API to export from")
+                    .value_parser(["configuration", "status", "history"])
+                    .required(true)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("endpoint")
+                    .help("The endpoint to export, e.g. conference")
+                    .required(true)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("output")
+                    .long("output")
+                    .short('o')
+                    .help("The file to write the exported objects to")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(true)
+                    .action(ArgAction::Set),
+            )
+    }
+
+    #[allow(clippy::unused_self)]
+    pub async fn run(
+        &self,
+        pexshell: &mut PexShell<'_>,
+        config: &mut ConfigManager,
+        client: reqwest::Client,
+        schemas: &HashMap<Api, HashMap<String, schema::Endpoint>>,
+        matches: &ArgMatches,
+        export_sub: &ArgMatches,
+    ) -> anyhow::Result<()> {
+        let api_name = export_sub
+            .get_one::<String>("api")
+            .expect("api is required");
+        let resource = export_sub
+            .get_one::<String>("endpoint")
+            .expect("endpoint is required");
+        let output = export_sub
+            .get_one::<PathBuf>("output")
+            .expect("output is required");
+        let (api, endpoint) = export_endpoint(schemas, api_name, resource)?;
+
+        let mut user = config.get_current_user()?.clone();
+        let mcu_address = user.address.clone();
+        let compat = user.settings.compat(CommandGen::compat_version(matches))?;
+        let request_id = CommandGen::request_id(matches, config.get_enable_request_ids());
+        let retry_policy = CommandGen::retry_policy(matches, config.get_retries());
+        let api_client = mcu::ApiClient::new(
+            client.clone(),
+            &mcu_address,
+            login::auth_for_user(client, &mut user, config, true)?,
+        )
+        .with_retry_policy(retry_policy)
+        .with_compat(compat)
+        .with_max_concurrent_pages(CommandGen::max_concurrent_pages(matches, false))
+        .with_request_id(request_id);
+
+        let objects = api_client
+            .send_paginated_all(mcu::ApiRequest::GetAll {
+                api,
+                resource: resource.clone(),
+                filter_args: HashMap::new(),
+                page_size: 500,
+                limit: 0,
+                offset: 0,
+                order_by: None,
+            })
+            .await?;
+
+        let mut fields: Vec<String> = endpoint.fields.keys().cloned().collect();
+        fields.sort_unstable();
+        let header = ExportHeader {
+            pexshell_export: EXPORT_FORMAT_VERSION,
+            api: api_name.clone(),
+            endpoint: resource.clone(),
+            count: objects.len(),
+            fields,
+        };
+
+        let file = File::create(output).map_err(|e| {
+            error::UserFriendly::new(format!("failed to create {}: {e}", output.display()))
+        })?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, &header)?;
+        writeln!(writer)?;
+        for object in &objects {
+            serde_json::to_writer(&mut writer, object)?;
+            writeln!(writer)?;
+        }
+        writer.flush()?;
+
+        writeln!(
+            pexshell.console,
+            "Exported {} objects to {}",
+            objects.len(),
+            output.display()
+        )?;
+
+        drop(api_client);
+
+        config.set_last_used()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{
+    config::{Manager as ConfigManager, Provider as _},
+    history,
+    pexshell::PexShell,
+    Directories,
+};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use lib::error;
+use std::{fmt::Display, fmt::Write as _, io::Write};
+
+pub struct History;
+
+impl Display for History {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "history")
+    }
+}
+
+impl History {
+    fn list_command(&self) -> Command {
+        Command::new("list")
+            .about("List recently run pexshell commands")
+            .long_about(
+                "List recently run pexshell commands. \
+                 Commands are recorded to the file set by history_file in the config file, \
+                 or to history.jsonl in the data directory if it isn't set.",
+            )
+            .arg(
+                Arg::new("limit")
+                    .short('n')
+                    .long("limit")
+                    .help("The maximum number of entries to show")
+                    .value_parser(value_parser!(usize))
+                    .default_value("20")
+                    .action(ArgAction::Set),
+            )
+    }
+
+    /// Adds the `list` subcommand to the history command.
+    ///
+    /// The history command is shared with the history API, so it is only created if the history API is not in the
+    /// schema cache.
+    pub fn with_list_subcommand(&self, command: Command) -> Command {
+        let name = self.to_string();
+        if command.find_subcommand(&name).is_some() {
+            command.mut_subcommand(name, |history| history.subcommand(self.list_command()))
+        } else {
+            command.subcommand(
+                Command::new(name)
+                    .about("Show the history of pexshell commands")
+                    .subcommand_required(true)
+                    .subcommand(self.list_command()),
+            )
+        }
+    }
+
+    /// Gets the matches for the `list` subcommand, if it was used.
+    pub fn list_matches<'a>(&self, matches: &'a ArgMatches) -> Option<&'a ArgMatches> {
+        matches
+            .subcommand_matches(self.to_string())
+            .and_then(|history_sub| history_sub.subcommand_matches("list"))
+    }
+
+    #[allow(clippy::unused_self)]
+    pub fn run(
+        &self,
+        pexshell: &mut PexShell,
+        config: &ConfigManager,
+        list_sub: &ArgMatches,
+        directories: &Directories,
+    ) -> Result<(), error::UserFriendly> {
+        let Some(history_file) =
+            history::file_path(config.get_history_file_path(), &directories.data_dir)
+        else {
+            return Err(error::UserFriendly::new(
+                "command history is disabled - remove the empty history_file from the config file to enable it",
+            ));
+        };
+        let limit = *list_sub
+            .get_one::<usize>("limit")
+            .expect("limit has a default value");
+
+        let mut output = String::new();
+        for entry in history::read_recent(&history_file, limit)? {
+            writeln!(
+                &mut output,
+                "{}  {:>3}  {}  {}",
+                entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                entry.exit_code,
+                entry.server.as_deref().unwrap_or("-"),
+                entry.args.join(" "),
+            )
+            .unwrap();
+        }
+        write!(pexshell.console, "{output}").unwrap();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use googletest::prelude::*;
+
+    use crate::argparse::CommandGen;
+
+    #[test]
+    fn test_history_list_without_history_api() {
+        // Arrange
+        let command = CommandGen(HashMap::new()).command();
+
+        // Act
+        let matches = command.try_get_matches_from(["pexshell", "history", "list", "-n", "5"]);
+
+        // Assert
+        let matches = matches.unwrap();
+        let list_sub = super::History.list_matches(&matches);
+        assert_that!(
+            list_sub.and_then(|list_sub| list_sub.get_one::<usize>("limit")),
+            some(eq(&5))
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{
+    argparse::{
+        export::{export_endpoint, ExportHeader, EXPORT_FORMAT_VERSION},
+        CommandGen,
+    },
+    cli::login,
+    config::{Manager as ConfigManager, Provider as _},
+    pexshell::PexShell,
+};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use lib::{
+    error,
+    mcu::{self, schema, Api, IApiClient as _},
+};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+/// Reads an export file, returning its header and the objects in it.
+fn read_export_file(path: &Path) -> Result<(ExportHeader, Vec<Value>), error::UserFriendly> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| error::UserFriendly::new(format!("failed to open {}: {e}", path.display())))?;
+    let mut lines = BufReader::new(file).lines().enumerate();
+
+    let header = match lines.next() {
+        Some((_, Ok(line))) => serde_json::from_str::<ExportHeader>(&line).map_err(|e| {
+            error::UserFriendly::new(format!("invalid export header in {}: {e}", path.display()))
+        })?,
+        Some((_, Err(e))) => {
+            return Err(error::UserFriendly::new(format!(
+                "failed to read {}: {e}",
+                path.display()
+            )))
+        }
+        None => {
+            return Err(error::UserFriendly::new(format!(
+                "{} is empty - expected an export header",
+                path.display()
+            )))
+        }
+    };
+    if header.pexshell_export != EXPORT_FORMAT_VERSION {
+        return Err(error::UserFriendly::new(format!(
+            "unsupported export format version {} in {}",
+            header.pexshell_export,
+            path.display()
+        )));
+    }
+
+    let mut objects = Vec::with_capacity(header.count);
+    for (index, line) in lines {
+        let line = line.map_err(|e| {
+            error::UserFriendly::new(format!("failed to read {}: {e}", path.display()))
+        })?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let object = serde_json::from_str(&line).map_err(|e| {
+            error::UserFriendly::new(format!(
+                "invalid object on line {} of {}: {e}",
+                index + 1,
+                path.display()
+            ))
+        })?;
+        objects.push(object);
+    }
+    Ok((header, objects))
+}
+
+/// Removes the fields that the management node sets itself, so that the object can be posted.
+fn post_payload(endpoint: &schema::Endpoint, mut object: Value) -> Value {
+    if let Value::Object(fields) = &mut object {
+        fields.retain(|name, _| {
+            name != "resource_uri" && endpoint.fields.get(name).map_or(true, |f| !f.readonly)
+        });
+    }
+    object
+}
+
+pub struct Import;
+
+impl Display for Import {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "import")
+    }
+}
+
+impl Import {
+    pub fn command(&self) -> Command {
+        Command::new(self.to_string())
+            .about("Import objects from a file written by: pexshell export")
+            .long_about(
+                "Import objects from a file written by: pexshell export. \
+                 Each object is posted to the endpoint named in the header of the file, \
+                 unless an object with the same unique key already exists.",
+            )
+            .arg(
+                Arg::new("from")
+                    .long("from")
+                    .help("The export file to import objects from")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(true)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("unique_key")
+                    .long("unique-key")
+                    .help("The field used to check whether an object already exists")
+                    .default_value("id")
+                    .action(ArgAction::Set),
+            )
+    }
+
+    #[allow(clippy::unused_self)]
+    pub async fn run(
+        &self,
+        pexshell: &mut PexShell<'_>,
+        config: &mut ConfigManager,
+        client: reqwest::Client,
+        schemas: &HashMap<Api, HashMap<String, schema::Endpoint>>,
+        matches: &ArgMatches,
+        import_sub: &ArgMatches,
+    ) -> anyhow::Result<()> {
+        let from = import_sub
+            .get_one::<PathBuf>("from")
+            .expect("from is required");
+        let unique_key = import_sub
+            .get_one::<String>("unique_key")
+            .expect("unique_key has a default value");
+
+        let (header, objects) = read_export_file(from)?;
+        let (api, endpoint) = export_endpoint(schemas, &header.api, &header.endpoint)?;
+        if !endpoint
+            .allowed_list_http_methods
+            .contains(&schema::Methods::Post)
+        {
+            return Err(error::UserFriendly::new(format!(
+                "objects cannot be created in {} {}",
+                header.api, header.endpoint
+            ))
+            .into());
+        }
+        if !endpoint.fields.contains_key(unique_key) {
+            return Err(error::UserFriendly::new(format!(
+                "unique key {unique_key} is not a field of {} {}",
+                header.api, header.endpoint
+            ))
+            .into());
+        }
+        if objects.len() != header.count {
+            pexshell.console.display_warning(&format!(
+                "export file contains {} objects, but its header says it should contain {}",
+                objects.len(),
+                header.count
+            ));
+        }
+
+        let mut user = config.get_current_user()?.clone();
+        let mcu_address = user.address.clone();
+        let compat = user.settings.compat(CommandGen::compat_version(matches))?;
+        let request_id = CommandGen::request_id(matches, config.get_enable_request_ids());
+        let retry_policy = CommandGen::retry_policy(matches, config.get_retries());
+        let api_client = mcu::ApiClient::new(
+            client.clone(),
+            &mcu_address,
+            login::auth_for_user(client, &mut user, config, true)?,
+        )
+        .with_retry_policy(retry_policy)
+        .with_compat(compat)
+        .with_request_id(request_id);
+
+        let mut imported = 0;
+        let mut skipped = 0;
+        for object in objects {
+            if exists(&api_client, api, &header.endpoint, unique_key, &object).await? {
+                skipped += 1;
+                continue;
+            }
+            api_client
+                .send(mcu::ApiRequest::Post {
+                    api,
+                    resource: header.endpoint.clone(),
+                    args: post_payload(endpoint, object),
+                })
+                .await?;
+            imported += 1;
+        }
+
+        writeln!(
+            pexshell.console,
+            "Imported {imported} objects, skipped {skipped} objects that already exist"
+        )?;
+
+        drop(api_client);
+
+        config.set_last_used()?;
+        Ok(())
+    }
+}
+
+/// Checks whether an object with the same unique key as the given object already exists.
+async fn exists(
+    api_client: &mcu::ApiClient<'_>,
+    api: Api,
+    resource: &str,
+    unique_key: &str,
+    object: &Value,
+) -> anyhow::Result<bool> {
+    let key = match object.get(unique_key) {
+        None | Some(Value::Null) => return Ok(false),
+        Some(Value::String(key)) => key.clone(),
+        Some(key) => key.to_string(),
+    };
+
+    if unique_key == "id" {
+        let response = api_client
+            .send(mcu::ApiRequest::Head {
+                api,
+                resource: resource.to_owned(),
+                object_id: key,
+            })
+            .await;
+        return match response {
+            Ok(_) => Ok(true),
+            Err(error)
+                if error
+                    .downcast_ref::<mcu::ApiError>()
+                    .and_then(mcu::ApiError::status)
+                    == Some(StatusCode::NOT_FOUND) =>
+            {
+                Ok(false)
+            }
+            Err(error) => Err(error),
+        };
+    }
+
+    let existing = api_client
+        .send_paginated_all(mcu::ApiRequest::GetAll {
+            api,
+            resource: resource.to_owned(),
+            filter_args: HashMap::from([(unique_key.to_owned(), key)]),
+            page_size: 1,
+            limit: 1,
+            offset: 0,
+            order_by: None,
+        })
+        .await?;
+    Ok(!existing.is_empty())
+}
@@ -0,0 +1,86 @@
+use crate::{
+    argparse::{Cache, FetchOptions},
+    cli,
+    config::{Manager as ConfigManager, Provider as _},
+    pexshell::PexShell,
+};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lib::{
+    error,
+    mcu::{compat::Version, retry::RetryPolicy},
+};
+use std::{fmt::Display, io::Write, path::Path};
+
+pub struct Init;
+
+impl Display for Init {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "init")
+    }
+}
+
+impl Init {
+    pub fn command(&self) -> Command {
+        Command::new(self.to_string())
+            .about("Set up pexshell by adding a user and generating the schema cache")
+            .arg(
+                Arg::new("store_passwords_in_plaintext")
+                    .long("store_passwords_in_plaintext")
+                    .help("Stores passwords in plaintext instead of in the system credential store")
+                    .action(ArgAction::SetTrue),
+            )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn run(
+        &self,
+        pexshell: &mut PexShell<'_>,
+        config: &mut ConfigManager,
+        cache_dir: &Path,
+        client: reqwest::Client,
+        retry_policy: RetryPolicy,
+        compat_version: Option<Version>,
+        init_sub: &ArgMatches,
+    ) -> anyhow::Result<()> {
+        if !pexshell.console.is_stderr_interactive() {
+            return Err(error::UserFriendly::new(
+                "init is interactive - run pexshell login and then pexshell cache instead",
+            )
+            .into());
+        }
+
+        writeln!(pexshell.console.stderr(), "[1/2] Add a user")?;
+        cli::login::Login::default()
+            .add_and_select_basic_user(
+                config,
+                client.clone(),
+                true,
+                init_sub.get_flag("store_passwords_in_plaintext"),
+            )
+            .await?;
+        config.write_to_file()?;
+        writeln!(
+            pexshell.console.stderr(),
+            "Connected to {}",
+            config.get_current_user()?.address
+        )?;
+
+        writeln!(pexshell.console.stderr(), "[2/2] Fetch the schema cache")?;
+        Cache
+            .fetch(
+                config,
+                cache_dir,
+                client,
+                retry_policy,
+                compat_version,
+                FetchOptions::default(),
+            )
+            .await?;
+
+        writeln!(
+            pexshell.console.stderr(),
+            "Setup complete - try pexshell configuration conference get"
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,322 @@
+use crate::{
+    argparse::CommandGen,
+    cli::login,
+    config::{Manager as ConfigManager, Provider as _},
+    pexshell::PexShell,
+};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use lib::{
+    error,
+    mcu::{self, IApiClient as _},
+};
+use serde_json::Value;
+use std::{fmt::Display, path::PathBuf};
+
+/// The HTTP methods that requests outside the schema can be sent with.
+const METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+/// The path that the management API is under.
+const API_BASE: &str = "/api/admin/";
+
+/// Reads the JSON body of the request given with `--body`, `--body-file` or `--body-stdin`, if any.
+async fn read_body(invoke_sub: &ArgMatches) -> Result<Option<Value>, error::UserFriendly> {
+    if let Some(body) = invoke_sub.get_one::<String>("body") {
+        serde_json::from_str(body)
+            .map(Some)
+            .map_err(|e| error::UserFriendly::new(format!("invalid JSON body: {e}")))
+    } else if let Some(path) = invoke_sub.get_one::<PathBuf>("body_file") {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            error::UserFriendly::new(format!("failed to read {}: {e}", path.display()))
+        })?;
+        serde_json::from_str(&contents).map(Some).map_err(|e| {
+            error::UserFriendly::new(format!("{} is not valid JSON: {e}", path.display()))
+        })
+    } else if invoke_sub.get_flag("body_stdin") {
+        crate::read_stdin_to_json()
+            .await
+            .map_err(|e| error::UserFriendly::new(format!("failed to read JSON from STDIN: {e}")))
+    } else {
+        Ok(None)
+    }
+}
+
+/// The arguments for the JSON body of the request, which is read by [`read_body`].
+fn body_args() -> [Arg; 3] {
+    [
+        Arg::new("body")
+            .long("body")
+            .help("The JSON body of the request")
+            .conflicts_with_all(["body_file", "body_stdin"])
+            .action(ArgAction::Set),
+        Arg::new("body_file")
+            .long("body-file")
+            .help("Read the JSON body of the request from a file")
+            .value_name("path")
+            .value_parser(value_parser!(PathBuf))
+            .conflicts_with("body_stdin")
+            .action(ArgAction::Set),
+        Arg::new("body_stdin")
+            .long("body-stdin")
+            .help("Read the JSON body of the request from STDIN")
+            .action(ArgAction::SetTrue),
+    ]
+}
+
+/// Gets the path of a request to the management API, which may be given relative to `/api/admin/`.
+fn api_path(path: &str) -> String {
+    if path.starts_with(API_BASE) {
+        path.to_owned()
+    } else {
+        format!("{API_BASE}{}", path.trim_start_matches('/'))
+    }
+}
+
+/// Sends a request that isn't checked against the schema, printing the response.
+async fn send_raw(
+    pexshell: &mut PexShell<'_>,
+    config: &mut ConfigManager,
+    client: reqwest::Client,
+    matches: &ArgMatches,
+    request: mcu::ApiRequest,
+) -> anyhow::Result<()> {
+    let mut user = config.get_current_user()?.clone();
+    let mcu_address = user.address.clone();
+    let compat = user.settings.compat(CommandGen::compat_version(matches))?;
+    let request_id = CommandGen::request_id(matches, config.get_enable_request_ids());
+    let retry_policy = CommandGen::retry_policy(matches, config.get_retries());
+    let api_client = mcu::ApiClient::new(
+        client.clone(),
+        &mcu_address,
+        login::auth_for_user(client, &mut user, config, true)?,
+    )
+    .with_retry_policy(retry_policy)
+    .with_compat(compat)
+    .with_request_id(request_id);
+
+    let response = api_client.send(request).await?;
+    pexshell
+        .console
+        .set_output_format(CommandGen::output_format(matches));
+    pexshell.write_response(response, false).await?;
+
+    drop(api_client);
+
+    config.set_last_used()?;
+    Ok(())
+}
+
+pub struct Invoke;
+
+impl Display for Invoke {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invoke")
+    }
+}
+
+impl Invoke {
+    pub fn command(&self) -> Command {
+        Command::new(self.to_string())
+            .about("Send a request to any API path, without checking it against the schema")
+            .long_about(
+                "Send a request to any API path, without checking it against the schema. \
+                 This is an escape hatch for endpoints that are not in the schema cache - \
+                 the request is sent exactly as given, so it must be confirmed with --i-know-what-im-doing.",
+            )
+            .arg(
+                Arg::new("method")
+                    .long("method")
+                    .help("The HTTP method of the request")
+                    .value_parser(METHODS)
+                    .default_value("GET")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("path")
+                    .long("path")
+                    .help("The path to request, relative to the management node address, e.g. /api/admin/configuration/v1/conference/")
+                    .required(true)
+                    .action(ArgAction::Set),
+            )
+            .args(body_args())
+            .arg(
+                Arg::new("i_know_what_im_doing")
+                    .long("i-know-what-im-doing")
+                    .help("Confirm that the request should be sent without schema validation")
+                    .action(ArgAction::SetTrue),
+            )
+    }
+
+    #[allow(clippy::unused_self)]
+    pub async fn run(
+        &self,
+        pexshell: &mut PexShell<'_>,
+        config: &mut ConfigManager,
+        client: reqwest::Client,
+        matches: &ArgMatches,
+        invoke_sub: &ArgMatches,
+    ) -> anyhow::Result<()> {
+        if !invoke_sub.get_flag("i_know_what_im_doing") {
+            return Err(error::UserFriendly::new(
+                "invoke sends requests without schema validation - pass --i-know-what-im-doing to confirm",
+            )
+            .into());
+        }
+
+        let method = invoke_sub
+            .get_one::<String>("method")
+            .expect("method has a default value")
+            .parse::<reqwest::Method>()
+            .expect("clap should validate method");
+        let path = invoke_sub
+            .get_one::<String>("path")
+            .expect("path is required")
+            .clone();
+        let body = read_body(invoke_sub).await?;
+
+        pexshell.console.display_warning(
+            "invoke bypasses schema validation - the request is sent to the management node exactly as given",
+        );
+
+        send_raw(
+            pexshell,
+            config,
+            client,
+            matches,
+            mcu::ApiRequest::Raw { method, path, body },
+        )
+        .await
+    }
+}
+
+pub struct Raw;
+
+impl Display for Raw {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "raw")
+    }
+}
+
+impl Raw {
+    pub fn command(&self) -> Command {
+        Command::new(self.to_string())
+            .about("Send a request to any path under the management API, e.g. an endpoint that is not in the schema cache yet")
+            .long_about(
+                "Send a request to any path under the management API, e.g. an endpoint that is not in the schema cache yet. \
+                 Like invoke, the request is sent exactly as given, without checking it against the schema.",
+            )
+            .arg(
+                Arg::new("method")
+                    .help("The HTTP method of the request")
+                    .value_parser(METHODS)
+                    .ignore_case(true)
+                    .required(true)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("path")
+                    .help("The path to request, relative to /api/admin/, e.g. configuration/v1/conference/")
+                    .required(true)
+                    .action(ArgAction::Set),
+            )
+            .args(body_args())
+    }
+
+    #[allow(clippy::unused_self)]
+    pub async fn run(
+        &self,
+        pexshell: &mut PexShell<'_>,
+        config: &mut ConfigManager,
+        client: reqwest::Client,
+        matches: &ArgMatches,
+        raw_sub: &ArgMatches,
+    ) -> anyhow::Result<()> {
+        let (method, path) = method_and_path(raw_sub);
+        let body = read_body(raw_sub).await?;
+
+        pexshell.console.display_warning(
+            "raw bypasses schema validation - the request is sent to the management node exactly as given",
+        );
+
+        send_raw(
+            pexshell,
+            config,
+            client,
+            matches,
+            mcu::ApiRequest::Raw { method, path, body },
+        )
+        .await
+    }
+}
+
+/// Gets the method and API path of a `raw` request.
+fn method_and_path(raw_sub: &ArgMatches) -> (reqwest::Method, String) {
+    let method = raw_sub
+        .get_one::<String>("method")
+        .expect("method is required")
+        .to_uppercase()
+        .parse::<reqwest::Method>()
+        .expect("clap should validate method");
+    let path = api_path(raw_sub.get_one::<String>("path").expect("path is required"));
+    (method, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::prelude::*;
+    use std::collections::HashMap;
+    use test_case::test_case;
+
+    use super::*;
+
+    fn parse_raw(args: &[&str]) -> ArgMatches {
+        let matches = CommandGen(HashMap::new())
+            .command()
+            .try_get_matches_from(["pexshell", "raw"].into_iter().chain(args.iter().copied()))
+            .unwrap();
+        matches.subcommand_matches("raw").unwrap().clone()
+    }
+
+    #[test_case(&["GET", "configuration/v1/conference/"], reqwest::Method::GET, "/api/admin/configuration/v1/conference/" ; "relative path")]
+    #[test_case(&["post", "/command/v1/conference/lock/"], reqwest::Method::POST, "/api/admin/command/v1/conference/lock/" ; "lower case method and leading slash")]
+    #[test_case(&["DELETE", "/api/admin/configuration/v1/conference/1/"], reqwest::Method::DELETE, "/api/admin/configuration/v1/conference/1/" ; "full path")]
+    fn test_raw_method_and_path(args: &[&str], method: reqwest::Method, path: &str) {
+        // Arrange
+        let raw_sub = parse_raw(args);
+
+        // Act
+        let result = method_and_path(&raw_sub);
+
+        // Assert
+        assert_that!(result, eq(&(method, String::from(path))));
+    }
+
+    #[tokio::test]
+    async fn test_raw_reads_body() {
+        // Arrange
+        let raw_sub = parse_raw(&[
+            "PATCH",
+            "configuration/v1/conference/1/",
+            "--body",
+            r#"{"name": "test"}"#,
+        ]);
+
+        // Act
+        let body = read_body(&raw_sub).await;
+
+        // Assert
+        assert_that!(body, ok(some(eq(&serde_json::json!({"name": "test"})))));
+    }
+
+    #[test_case(&["GET"] ; "missing path")]
+    #[test_case(&["FETCH", "configuration/v1/conference/"] ; "unknown method")]
+    #[test_case(&["GET", "configuration/v1/conference/", "--body", "{}", "--body-stdin"] ; "two bodies")]
+    fn test_raw_rejects_invalid_args(args: &[&str]) {
+        // Act
+        let result = CommandGen(HashMap::new())
+            .command()
+            .try_get_matches_from(["pexshell", "raw"].into_iter().chain(args.iter().copied()));
+
+        // Assert
+        assert_that!(result, err(anything()));
+    }
+}
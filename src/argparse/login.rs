@@ -1,8 +1,73 @@
 use crate::{cli, config::Manager as ConfigManager, pexshell::PexShell};
+use chrono::TimeDelta;
 use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
 use lib::error;
 use std::fmt::Display;
 
+/// Parses an age such as `90d`, made up of a number and a unit of `h` (hours), `d` (days) or `w` (weeks).
+fn parse_age(age: &str) -> Result<TimeDelta, String> {
+    let invalid =
+        || format!("invalid age '{age}' - expected a number followed by h, d or w, e.g. 90d");
+    let unit = age.chars().last().ok_or_else(invalid)?;
+    let count: i64 = age[..age.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| invalid())?;
+    match unit {
+        'h' => TimeDelta::try_hours(count),
+        'd' => TimeDelta::try_days(count),
+        'w' => TimeDelta::try_weeks(count),
+        _ => None,
+    }
+    .filter(|age| *age >= TimeDelta::zero())
+    .ok_or_else(invalid)
+}
+
+/// Creates the `login oauth2-setup` subcommand.
+fn oauth2_setup_command() -> Command {
+    Command::new("oauth2-setup")
+        .about("Generate a key pair for a new OAuth2 client and guide its registration on the management node")
+        .arg(
+            Arg::new("address")
+                .long("address")
+                .help("The address of the management node, e.g. mcu.example.com")
+                .required(true)
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("offline")
+                .long("offline")
+                .help("Do not connect to the management node to verify the credentials")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("store_passwords_in_plaintext")
+                .long("store_passwords_in_plaintext")
+                .help("Stores the private key in plaintext instead of in the system credential store")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+/// Creates the `login prune` subcommand.
+fn prune_command() -> Command {
+    Command::new("prune")
+        .about("Delete accounts that have not been used recently")
+        .arg(
+            Arg::new("older_than")
+                .long("older-than")
+                .help("Delete accounts last used longer ago than this, e.g. 12h, 90d or 4w")
+                .value_parser(parse_age)
+                .default_value("90d")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("yes")
+                .long("yes")
+                .short('y')
+                .help("Delete the accounts without asking for confirmation")
+                .action(ArgAction::SetTrue),
+        )
+}
+
 pub struct Login;
 
 impl Display for Login {
@@ -41,6 +106,13 @@ impl Login {
                     .help("Stores passwords in plaintext instead of in the system credential store")
                     .action(ArgAction::SetTrue),
             )
+            .arg(
+                Arg::new("stdin_password")
+                    .long("stdin-password")
+                    .help("Read the password of the new user from the first line of stdin instead of prompting for it")
+                    .conflicts_with_all(["oauth2", "token"])
+                    .action(ArgAction::SetTrue),
+            )
             .arg(
                 Arg::new("oauth2")
                     .long("oauth2")
@@ -49,11 +121,29 @@ impl Login {
                     .num_args(2)
                     .action(ArgAction::Set),
             )
+            .arg(
+                Arg::new("token")
+                    .long("token")
+                    .help("Add a user that authenticates with a bearer token - specify the management node address and a name for the token as arguments, and pass the token to stdin")
+                    .value_names(["address", "name"])
+                    .num_args(2)
+                    .conflicts_with("oauth2")
+                    .action(ArgAction::Set),
+            )
             .group(
                 ArgGroup::new("function")
                     .args(["list", "delete"])
-                    .conflicts_with_all(["offline", "store_passwords_in_plaintext", "oauth2"]),
+                    .conflicts_with_all([
+                        "offline",
+                        "store_passwords_in_plaintext",
+                        "stdin_password",
+                        "oauth2",
+                        "token",
+                    ]),
             )
+            .args_conflicts_with_subcommands(true)
+            .subcommand(oauth2_setup_command())
+            .subcommand(prune_command())
     }
 
     pub async fn run<'a>(
@@ -63,8 +153,35 @@ impl Login {
         client: reqwest::Client,
         login_sub: &ArgMatches,
     ) -> Result<(), error::UserFriendly> {
-        let mut login = cli::login::Login::default();
-        if login_sub.get_flag("list") {
+        let mut login = cli::login::Login::default()
+            .with_password_from_stdin(login_sub.get_flag("stdin_password"));
+        if let Some(prune_sub) = login_sub.subcommand_matches("prune") {
+            let older_than = *prune_sub
+                .get_one::<TimeDelta>("older_than")
+                .expect("older_than has a default value");
+            login.prune_users(
+                &mut pexshell.console,
+                config,
+                chrono::Utc::now() - older_than,
+                prune_sub.get_flag("yes"),
+            )?;
+            config.write_to_file()?;
+        } else if let Some(setup_sub) = login_sub.subcommand_matches("oauth2-setup") {
+            let address = setup_sub
+                .get_one::<String>("address")
+                .expect("address is required");
+            login
+                .setup_oauth2_user(
+                    &mut pexshell.console,
+                    config,
+                    client,
+                    address.clone(),
+                    !setup_sub.get_flag("offline"),
+                    setup_sub.get_flag("store_passwords_in_plaintext"),
+                )
+                .await?;
+            config.write_to_file()?;
+        } else if login_sub.get_flag("list") {
             login.list_users(&mut pexshell.console, config);
         } else if login_sub.get_flag("delete") {
             login.delete_user(config)?;
@@ -83,6 +200,21 @@ impl Login {
                 )
                 .await?;
 
+            config.write_to_file()?;
+        } else if let Some(token_values) = login_sub.get_many("token") {
+            let token_values: Vec<&String> = token_values.collect();
+            assert!(token_values.len() == 2, "token requires 2 arguments");
+            login
+                .add_and_select_bearer_user(
+                    config,
+                    client,
+                    token_values[0].to_owned(),
+                    token_values[1].to_owned(),
+                    !login_sub.get_flag("offline"),
+                    login_sub.get_flag("store_passwords_in_plaintext"),
+                )
+                .await?;
+
             config.write_to_file()?;
         } else {
             login
@@ -100,3 +232,28 @@ impl Login {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+    use googletest::prelude::*;
+    use test_case::test_case;
+
+    use super::parse_age;
+
+    #[test_case("12h", TimeDelta::hours(12) ; "hours")]
+    #[test_case("90d", TimeDelta::days(90) ; "days")]
+    #[test_case("4w", TimeDelta::weeks(4) ; "weeks")]
+    fn test_parse_age(age: &str, expected: TimeDelta) {
+        assert_that!(parse_age(age), ok(eq(&expected)));
+    }
+
+    #[test_case("" ; "empty")]
+    #[test_case("90" ; "no unit")]
+    #[test_case("d" ; "no count")]
+    #[test_case("3m" ; "unknown unit")]
+    #[test_case("-1d" ; "negative")]
+    fn test_parse_invalid_age(age: &str) {
+        assert_that!(parse_age(age), err(contains_substring("invalid age")));
+    }
+}
@@ -1,52 +1,541 @@
+pub mod alias;
 mod cache;
 mod completions;
+mod config;
+mod config_doctor;
+mod doctor;
+mod export;
+mod history;
+mod import;
+mod init;
+mod invoke;
 mod login;
+mod pin_schema;
+mod schema;
+mod tee;
+mod token;
+mod watch;
 
-pub use cache::Cache;
+pub use alias::Alias;
+pub use cache::{Cache, FetchOptions};
 pub use completions::Completions;
+pub use config::Config;
+pub use doctor::Doctor;
+pub use export::Export;
+pub use history::History;
+pub use import::Import;
+pub use init::Init;
+pub use invoke::{Invoke, Raw};
 pub use login::Login;
+pub use pin_schema::PinSchema;
+pub use schema::Schema;
+pub use tee::Tee;
+pub use token::Token;
+pub use watch::Watch;
 
 use crate::{
-    cli::{self},
+    cli::{
+        self,
+        output::{DateFormat, OutputFormat, SqlOptions, XmlOptions, XmlStyle},
+    },
     VERSION,
 };
-use clap::{value_parser, Arg, ArgAction, Command};
+use clap::{parser::ValueSource, value_parser, Arg, ArgAction, ArgMatches, Command};
 
-use lib::mcu::{
-    schema::{self},
-    Api,
+use lib::{
+    mcu::{compat::Version, retry::RetryPolicy, schema::Endpoint, Api},
+    util::LogFormat,
 };
+use log::LevelFilter;
 
-use std::{collections::HashMap, path::PathBuf};
+use reqwest::header::HeaderValue;
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+use uuid::Uuid;
 
-pub struct CommandGen(pub HashMap<Api, HashMap<String, schema::Endpoint>>);
+/// Number of pages fetched at once by `get --parallel`, which is as many requests as are sent to the management node
+/// at once.
+const PARALLEL_PAGES: u16 = 5;
+
+pub struct CommandGen(pub HashMap<Api, HashMap<String, Endpoint>>);
 
 impl CommandGen {
     pub fn command(&self) -> clap::Command {
         let api_subcommands = cli::generate_subcommands(&self.0);
 
-        Command::new("pexshell")
+        let command = Command::new("pexshell")
             .version(VERSION.as_str())
             .about("Convenient way to manipulate the Management API.")
-            .subcommands(api_subcommands)
+            .subcommands(api_subcommands.clone())
+            .subcommand(Init.command())
             .subcommand(Login.command())
             .subcommand(Cache.command())
             .subcommand(Completions.command())
+            .subcommand(Alias.command())
+            .subcommand(Config.command())
+            .subcommand(Doctor.command())
+            .subcommand(Token.command())
+            .subcommand(Invoke.command())
+            .subcommand(Raw.command())
+            .subcommand(Export.command())
+            .subcommand(Import.command())
+            .subcommand(Schema.command())
+            .subcommand(PinSchema.command())
+            .subcommand(Tee.command())
+            .subcommand(Watch.command(api_subcommands))
             .subcommand_required(true)
-            .arg(
-                Arg::new("insecure")
-                    .long("insecure")
-                    .help("Do not verify certificates")
-                    .action(ArgAction::SetTrue),
+            .args(connection_args())
+            .args(logging_args())
+            .args(request_args())
+            .args(output_args())
+            .args(schema_args());
+        let command = History.with_list_subcommand(command);
+
+        #[cfg(feature = "transform")]
+        let command = command.arg(
+            Arg::new("transform")
+                .long("transform")
+                .help("Pipe each returned object as a line of JSON through a shell command and print its output")
+                .value_name("command")
+                .conflicts_with("output")
+                .action(ArgAction::Set),
+        );
+
+        command
+    }
+
+    /// Gets the management node version given with `--compat`.
+    pub fn compat_version(matches: &ArgMatches) -> Option<Version> {
+        matches.get_one::<Version>("compat").copied()
+    }
+
+    /// Gets whether to exit with an error if any schemas in the cache cannot be read.
+    ///
+    /// This is also used on partially parsed arguments, where defaults may not have been filled in.
+    pub fn abort_on_schema_error(matches: &ArgMatches) -> bool {
+        matches
+            .get_one::<bool>("abort_on_schema_error")
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Gets the retry policy for API requests from the global arguments, with the number of retries after transient
+    /// errors falling back to `default_retries` from the config.
+    pub fn retry_policy(matches: &ArgMatches, default_retries: Option<usize>) -> RetryPolicy {
+        RetryPolicy {
+            retries: *matches
+                .get_one::<usize>("retry")
+                .expect("retry has a default value"),
+            transient_retries: matches
+                .get_one::<usize>("retries")
+                .copied()
+                .or(default_retries)
+                .unwrap_or(RetryPolicy::default().transient_retries),
+            max_retry_after: Duration::from_secs(
+                *matches
+                    .get_one::<u64>("max_retry_after")
+                    .expect("max_retry_after has a default value"),
+            ),
+        }
+    }
+
+    /// Gets how to reformat date and time fields given with `--format-dates`.
+    pub fn date_format(matches: &ArgMatches) -> Option<DateFormat> {
+        matches.get_one::<DateFormat>("format_dates").cloned()
+    }
+
+    /// Gets the maximum number of pages of a `GetAll` request to fetch at once.
+    ///
+    /// If `parallel` is set and `--max-concurrent-pages` is not given, several pages are fetched at once.
+    pub fn max_concurrent_pages(matches: &ArgMatches, parallel: bool) -> usize {
+        if parallel
+            && matches.value_source("max_concurrent_pages") != Some(ValueSource::CommandLine)
+        {
+            return usize::from(PARALLEL_PAGES);
+        }
+        usize::from(
+            *matches
+                .get_one::<u16>("max_concurrent_pages")
+                .expect("max_concurrent_pages has a default value"),
+        )
+    }
+
+    /// Gets the value of the `X-Request-ID` header to send, which is generated if not given with `--request-id` and
+    /// `enabled` by the config.
+    pub fn request_id(matches: &ArgMatches, enabled: bool) -> Option<HeaderValue> {
+        let request_id = matches
+            .get_one::<String>("request_id")
+            .cloned()
+            .or_else(|| enabled.then(|| Uuid::new_v4().to_string()))?;
+        Some(HeaderValue::from_str(&request_id).expect("clap should validate request_id"))
+    }
+
+    /// Gets the log level given with `--verbose` or `--quiet`, which overrides the configured log level.
+    pub fn log_level(matches: &ArgMatches) -> Option<LevelFilter> {
+        if matches.get_flag("quiet") {
+            return Some(LevelFilter::Error);
+        }
+        match matches.get_count("verbose") {
+            0 => None,
+            1 => Some(LevelFilter::Debug),
+            _ => Some(LevelFilter::Trace),
+        }
+    }
+
+    /// Gets the format of the log file given with `--log-format`, which overrides the configured format.
+    pub fn log_format(matches: &ArgMatches) -> Option<LogFormat> {
+        match matches.get_one::<String>("log_format").map(String::as_str) {
+            Some("text") => Some(LogFormat::Text),
+            Some("json") => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Gets the number of seconds given with `--response-timeout`.
+    pub fn response_timeout(matches: &ArgMatches) -> Option<u64> {
+        matches.get_one::<u64>("response_timeout").copied()
+    }
+
+    /// Gets the client certificate and private key given with `--client-cert` and `--client-key`.
+    pub fn client_identity(matches: &ArgMatches) -> Option<(PathBuf, PathBuf)> {
+        Some((
+            matches.get_one::<PathBuf>("client_cert")?.clone(),
+            matches.get_one::<PathBuf>("client_key")?.clone(),
+        ))
+    }
+
+    /// Gets the timeout for each request given with `--timeout`, falling back to `default_timeout` from the config.
+    ///
+    /// A timeout of zero seconds means that requests are never timed out.
+    pub fn timeout(matches: &ArgMatches, default_timeout: Option<u64>) -> Option<Duration> {
+        matches
+            .get_one::<u64>("timeout")
+            .copied()
+            .or(default_timeout)
+            .filter(|&seconds| seconds > 0)
+            .map(Duration::from_secs)
+    }
+
+    /// Gets the format to print responses in from the global arguments.
+    pub fn output_format(matches: &ArgMatches) -> OutputFormat {
+        #[cfg(feature = "transform")]
+        if let Some(command) = matches.get_one::<String>("transform") {
+            return OutputFormat::Transform(command.clone());
+        }
+
+        match matches
+            .get_one::<String>("output")
+            .expect("output has a default value")
+            .as_str()
+        {
+            "json" => OutputFormat::Json,
+            "jsonl" => OutputFormat::JsonLines,
+            "yaml" => OutputFormat::Yaml,
+            "table" => OutputFormat::Table,
+            "pairs" => OutputFormat::Pairs,
+            "kv" => OutputFormat::Kv,
+            "prometheus" => OutputFormat::Prometheus(String::from("pexshell")),
+            "csv" => OutputFormat::Csv(Vec::new()),
+            "sql" => OutputFormat::Sql(SqlOptions {
+                table: matches.get_one::<String>("table_name").cloned(),
+                types: HashMap::new(),
+            }),
+            "xml" => OutputFormat::Xml(XmlOptions {
+                root: matches
+                    .get_one::<String>("xml_root")
+                    .expect("xml_root has a default value")
+                    .clone(),
+                style: match matches
+                    .get_one::<String>("xml_style")
+                    .expect("xml_style has a default value")
+                    .as_str()
+                {
+                    "attributes" => XmlStyle::Attributes,
+                    "elements" => XmlStyle::Elements,
+                    _ => panic!("Unhandled XML style!"),
+                },
+            }),
+            _ => panic!("Unhandled output format!"),
+        }
+    }
+}
+
+/// Global options for how requests are sent to the management node, or printed instead of being sent.
+fn connection_args() -> [Arg; 7] {
+    [
+        Arg::new("insecure")
+            .long("insecure")
+            .help("Do not verify certificates")
+            .action(ArgAction::SetTrue),
+        Arg::new("dry_run")
+            .long("dry-run")
+            .help("Print the API request that would be sent instead of sending it")
+            .long_help(
+                "Print the method, URI and body of the API request that would be sent instead of sending it. \
+                 Query parameters of a get with filters are left out, since they may be sensitive.",
             )
-            .arg(
-                Arg::new("log")
-                    .long("log")
-                    .help("Output application logs to a file")
-                    .value_parser(value_parser!(PathBuf))
-                    .action(ArgAction::Set),
+            .action(ArgAction::SetTrue),
+        Arg::new("print_curl")
+            .long("print-curl")
+            .help("Print an equivalent curl command for the API request instead of sending it")
+            .long_help(
+                "Print an equivalent curl command for the API request instead of sending it, \
+                 e.g. to share a reproduction. The Authorization header is redacted unless \
+                 --print-curl=unsafe is given.",
             )
-    }
+            .value_parser(["safe", "unsafe"])
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("safe")
+            .conflicts_with("dry_run")
+            .action(ArgAction::Set),
+        Arg::new("cacert")
+            .long("cacert")
+            .help("Trust the certificates in the given PEM file, e.g. a private CA, as well as the system's")
+            .long_help(
+                "Trust the certificates in the given PEM file, e.g. a private CA, as well as the system's. \
+                 Unlike --insecure, certificates are still verified. Overrides ca_cert in the config file.",
+            )
+            .value_name("path")
+            .value_parser(value_parser!(PathBuf))
+            .action(ArgAction::Set),
+        Arg::new("client_cert")
+            .long("client-cert")
+            .help("Present the client certificate in the given PEM file to the management node, for mutual TLS")
+            .long_help(
+                "Present the client certificate in the given PEM file to the management node, for mutual TLS. \
+                 Requires --client-key. Overrides client_cert and client_key for the user in the config file.",
+            )
+            .value_name("path")
+            .value_parser(value_parser!(PathBuf))
+            .requires("client_key")
+            .action(ArgAction::Set),
+        Arg::new("client_key")
+            .long("client-key")
+            .help("The PEM file of the private key of the client certificate given with --client-cert")
+            .value_name("path")
+            .value_parser(value_parser!(PathBuf))
+            .requires("client_cert")
+            .action(ArgAction::Set),
+        Arg::new("proxy")
+            .long("proxy")
+            .help("Send requests through the given HTTP proxy, e.g. http://proxy.example.com:3128")
+            .long_help(
+                "Send requests through the given HTTP proxy, e.g. http://proxy.example.com:3128. \
+                 Overrides the HTTPS_PROXY, HTTP_PROXY, ALL_PROXY and NO_PROXY environment variables.",
+            )
+            .value_name("url")
+            .action(ArgAction::Set),
+    ]
+}
+
+/// Global options for logging.
+fn logging_args() -> [Arg; 4] {
+    [
+        Arg::new("log")
+            .long("log")
+            .help("Output application logs to a file")
+            .value_parser(value_parser!(PathBuf))
+            .action(ArgAction::Set),
+        Arg::new("log_format")
+            .long("log-format")
+            .help("The format of the log file")
+            .long_help(
+                "The format of the log file - json writes each record as a JSON object with timestamp, \
+                 level, target and message fields, e.g. for log aggregation. Logs written to stderr are \
+                 always human-readable. Overrides the log format set in the config file or with \
+                 PEXSHELL_LOG_FORMAT.",
+            )
+            .value_parser(["text", "json"])
+            .action(ArgAction::Set),
+        Arg::new("verbose")
+            .long("verbose")
+            .short('v')
+            .help("Log more detail - debug with -v, trace with -vv")
+            .long_help(
+                "Log more detail - debug with -v, trace with -vv. \
+                 Overrides the log level set in the config file or with PEXSHELL_LOG_LEVEL.",
+            )
+            .conflicts_with("quiet")
+            .action(ArgAction::Count),
+        Arg::new("quiet")
+            .long("quiet")
+            .short('q')
+            .help("Only log errors")
+            .long_help(
+                "Only log errors. \
+                 Overrides the log level set in the config file or with PEXSHELL_LOG_LEVEL.",
+            )
+            .action(ArgAction::SetTrue),
+    ]
+}
+
+/// Global options for retrying, identifying and timing out API requests.
+fn request_args() -> [Arg; 7] {
+    [
+        Arg::new("retry")
+            .long("retry")
+            .help("Number of times to retry a request that was rate limited by the management node")
+            .long_help(
+                "Number of times to retry a request that was rate limited by the management node. \
+                 Rate limited requests are always retried at least once, after the delay requested by the server. \
+                 Retries after transient errors, such as a 502 response, are set with --retries instead.",
+            )
+            .value_parser(value_parser!(usize))
+            .default_value("0")
+            .action(ArgAction::Set),
+        Arg::new("retries")
+            .long("retries")
+            .help("Number of times to retry a GET request that failed with a transient error [default: 3]")
+            .long_help(
+                "Number of times to retry a GET request that failed with a transient error, such as a 502 \
+                 response or a failure to connect, waiting longer before each retry. \
+                 Requests that change objects are never retried after a transient error. \
+                 This can also be set with the retries key in the config file. \
+                 Unlike --retry, this does not apply to rate limited requests. [default: 3]",
+            )
+            .value_parser(value_parser!(usize))
+            .action(ArgAction::Set),
+        Arg::new("max_retry_after")
+            .long("max-retry-after")
+            .help("Maximum number of seconds to wait before retrying a rate limited request")
+            .value_parser(value_parser!(u64))
+            .default_value("60")
+            .action(ArgAction::Set),
+        Arg::new("max_concurrent_pages")
+            .long("max-concurrent-pages")
+            .help("Maximum number of pages to fetch at once when getting all objects of an endpoint")
+            .long_help(
+                "Maximum number of pages to fetch at once when getting all objects of an endpoint. \
+                 Pages are fetched ahead of the objects being printed, which can speed up large requests \
+                 over high latency connections.",
+            )
+            .value_name("n")
+            .value_parser(value_parser!(u16).range(1..))
+            .default_value("1")
+            .action(ArgAction::Set),
+        Arg::new("request_id")
+            .long("request-id")
+            .help("Send this value as the X-Request-ID header of every request")
+            .long_help(
+                "Send this value as the X-Request-ID header of every request, to find the requests in the \
+                 management node's logs. If enable_request_ids is set in the config file, a random ID is \
+                 generated when this isn't given.",
+            )
+            .value_name("value")
+            .value_parser(|value: &str| {
+                HeaderValue::from_str(value)
+                    .map(|_| String::from(value))
+                    .map_err(|_| "must only contain visible ASCII characters")
+            })
+            .action(ArgAction::Set),
+        Arg::new("response_timeout")
+            .long("response-timeout")
+            .help("Maximum time to wait for each read of a response body, in seconds")
+            .long_help(
+                "Maximum time to wait for each read of a response body, in seconds. \
+                 The timeout restarts whenever part of the body is received, so large downloads \
+                 are not cut short as long as the management node keeps sending data. \
+                 Overrides response_timeout_seconds in the config file.",
+            )
+            .value_name("seconds")
+            .value_parser(value_parser!(u64).range(1..))
+            .action(ArgAction::Set),
+        Arg::new("timeout")
+            .long("timeout")
+            .help("Maximum time to wait for each request to complete, in seconds, or 0 to wait indefinitely")
+            .long_help(
+                "Maximum time to wait for each request to complete, from connecting to the management node \
+                 until the whole response has been received, in seconds. 0 means the request is never timed \
+                 out. Overrides timeout in the config file.",
+            )
+            .value_name("seconds")
+            .value_parser(value_parser!(u64))
+            .action(ArgAction::Set),
+    ]
+}
+
+/// Global options for where the output is written and how it is formatted.
+fn output_args() -> [Arg; 6] {
+    [
+        Arg::new("output")
+            .long("output")
+            .help("The format to print responses in")
+            .value_parser(["json", "jsonl", "yaml", "table", "xml", "pairs", "kv", "prometheus", "sql", "csv"])
+            .default_value("json")
+            .action(ArgAction::Set),
+        Arg::new("output_file")
+            .long("output-file")
+            .help("Write responses to the given file instead of stdout, which is overwritten if it exists")
+            .long_help(
+                "Write responses to the given file instead of stdout, which is overwritten if it exists. \
+                 Warnings and logs are still written to stderr.",
+            )
+            .value_name("path")
+            .value_parser(value_parser!(PathBuf))
+            .action(ArgAction::Set),
+        Arg::new("table_name")
+            .long("table-name")
+            .help("Name of the table to insert into when printing responses as SQL (defaults to the resource)")
+            .value_name("table")
+            .action(ArgAction::Set),
+        Arg::new("xml_root")
+            .long("xml-root")
+            .help("Name of the root element when printing responses as XML")
+            .default_value("root")
+            .action(ArgAction::Set),
+        Arg::new("xml_style")
+            .long("xml-style")
+            .help("Whether to render fields as XML attributes or elements")
+            .value_parser(["attributes", "elements"])
+            .default_value("elements")
+            .action(ArgAction::Set),
+        Arg::new("format_dates")
+            .long("format-dates")
+            .help("Reformat date and time fields in responses")
+            .long_help(
+                "Reformat date and time fields in responses, either with a strftime format string \
+                 (e.g. \"%Y-%m-%d %H:%M\") or as a relative time (e.g. \"3 hours ago\") with the special \
+                 value relative. Fields are found using the schema, so this has no effect on invoke.",
+            )
+            .value_name("format")
+            .value_parser(value_parser!(DateFormat))
+            .action(ArgAction::Set),
+    ]
+}
+
+/// Global options for reading the schema cache.
+fn schema_args() -> [Arg; 4] {
+    [
+        Arg::new("compat")
+            .long("compat")
+            .help("The version of the management node, to stay compatible with older versions")
+            .long_help(
+                "The version of the management node (e.g. 29 or 29.1), to stay compatible with older versions. \
+                 Overrides the compat setting of the current user in the config file.",
+            )
+            .value_name("version")
+            .value_parser(value_parser!(Version))
+            .action(ArgAction::Set),
+        Arg::new("abort_on_schema_error")
+            .long("abort-on-schema-error")
+            .help("Exit with an error if any schemas in the cache cannot be read")
+            .overrides_with("ignore_schema_errors")
+            .action(ArgAction::SetTrue),
+        Arg::new("ignore_schema_errors")
+            .long("ignore-schema-errors")
+            .help("Skip endpoints whose schemas in the cache cannot be read [default]")
+            .overrides_with("abort_on_schema_error")
+            .action(ArgAction::SetTrue),
+        Arg::new("refresh_schema")
+            .long("refresh-schema")
+            .help("Regenerate the schema cache before running the command")
+            .long_help(
+                "Regenerate the schema cache before running the command, \
+                 e.g. after the management node has been upgraded. \
+                 The command is still parsed with the schemas that were cached before.",
+            )
+            .action(ArgAction::SetTrue),
+    ]
 }
 
 #[cfg(test)]
@@ -54,6 +543,7 @@ mod tests {
     use std::path::Path;
 
     use googletest::prelude::*;
+    use test_case::test_case;
 
     use super::*;
 
@@ -70,4 +560,159 @@ mod tests {
             some(eq(Path::new("/path/to/file.log")))
         );
     }
+
+    #[test_case(&[], None ; "default")]
+    #[test_case(&["-v"], Some(LevelFilter::Debug) ; "verbose")]
+    #[test_case(&["-vv"], Some(LevelFilter::Trace) ; "very verbose")]
+    #[test_case(&["--verbose", "--verbose", "--verbose"], Some(LevelFilter::Trace) ; "more verbose")]
+    #[test_case(&["--quiet"], Some(LevelFilter::Error) ; "quiet")]
+    fn test_parse_log_level_flags(flags: &[&str], expected: Option<LevelFilter>) {
+        let matches = CommandGen(HashMap::new()).command().get_matches_from(
+            std::iter::once("pexshell")
+                .chain(flags.iter().copied())
+                .chain(["cache"]),
+        );
+        assert_that!(CommandGen::log_level(&matches), eq(expected));
+    }
+
+    #[test]
+    fn test_verbose_conflicts_with_quiet() {
+        let result = CommandGen(HashMap::new())
+            .command()
+            .try_get_matches_from(vec!["pexshell", "-v", "--quiet", "cache"]);
+        assert_that!(result, err(anything()));
+    }
+
+    #[test]
+    fn test_parse_retry_flags() {
+        let matches = CommandGen(HashMap::new()).command().get_matches_from(vec![
+            "pexshell",
+            "--retry",
+            "3",
+            "--max-retry-after",
+            "10",
+            "cache",
+        ]);
+        assert_that!(
+            CommandGen::retry_policy(&matches, None),
+            eq(RetryPolicy {
+                retries: 3,
+                max_retry_after: Duration::from_secs(10),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_retries_flag_overrides_config() {
+        let matches = CommandGen(HashMap::new()).command().get_matches_from(vec![
+            "pexshell",
+            "--retries",
+            "5",
+            "cache",
+        ]);
+        assert_that!(
+            CommandGen::retry_policy(&matches, Some(1)).transient_retries,
+            eq(5)
+        );
+    }
+
+    #[test]
+    fn test_max_concurrent_pages_when_parallel() {
+        let command = CommandGen(HashMap::new()).command();
+        let default = command.clone().get_matches_from(vec!["pexshell", "cache"]);
+        let explicit =
+            command.get_matches_from(vec!["pexshell", "--max-concurrent-pages", "2", "cache"]);
+
+        assert_that!(CommandGen::max_concurrent_pages(&default, false), eq(1));
+        assert_that!(CommandGen::max_concurrent_pages(&default, true), eq(5));
+        assert_that!(CommandGen::max_concurrent_pages(&explicit, true), eq(2));
+    }
+
+    #[test]
+    fn test_parse_timeout_flag() {
+        let command = CommandGen(HashMap::new()).command();
+        let with_timeout =
+            command
+                .clone()
+                .get_matches_from(vec!["pexshell", "--timeout", "30", "cache"]);
+        let without_timeout =
+            command
+                .clone()
+                .get_matches_from(vec!["pexshell", "--timeout", "0", "cache"]);
+        let from_config = command.get_matches_from(vec!["pexshell", "cache"]);
+
+        assert_that!(
+            CommandGen::timeout(&with_timeout, Some(10)),
+            some(eq(Duration::from_secs(30)))
+        );
+        assert_that!(CommandGen::timeout(&without_timeout, Some(10)), none());
+        assert_that!(
+            CommandGen::timeout(&from_config, Some(10)),
+            some(eq(Duration::from_secs(10)))
+        );
+        assert_that!(CommandGen::timeout(&from_config, None), none());
+    }
+
+    #[test]
+    fn test_retries_default_from_config() {
+        let matches = CommandGen(HashMap::new())
+            .command()
+            .get_matches_from(vec!["pexshell", "cache"]);
+        assert_that!(
+            CommandGen::retry_policy(&matches, Some(1)).transient_retries,
+            eq(1)
+        );
+        assert_that!(
+            CommandGen::retry_policy(&matches, None).transient_retries,
+            eq(3)
+        );
+    }
+
+    #[test]
+    fn test_parse_output_flags() {
+        let matches = CommandGen(HashMap::new()).command().get_matches_from(vec![
+            "pexshell",
+            "--output",
+            "xml",
+            "--xml-root",
+            "conferences",
+            "--xml-style",
+            "attributes",
+            "cache",
+        ]);
+        assert_that!(
+            CommandGen::output_format(&matches),
+            eq(&OutputFormat::Xml(XmlOptions {
+                root: String::from("conferences"),
+                style: XmlStyle::Attributes,
+            }))
+        );
+    }
+
+    #[test_case(&[], false ; "default")]
+    #[test_case(&["--abort-on-schema-error"], true ; "abort")]
+    #[test_case(&["--abort-on-schema-error", "--ignore-schema-errors"], false ; "ignore overrides abort")]
+    #[test_case(&["--ignore-schema-errors", "--abort-on-schema-error"], true ; "abort overrides ignore")]
+    fn test_parse_schema_error_flags(flags: &[&str], expected: bool) {
+        let args = std::iter::once("pexshell")
+            .chain(flags.iter().copied())
+            .chain(std::iter::once("cache"));
+        let matches = CommandGen(HashMap::new()).command().get_matches_from(args);
+        assert_that!(CommandGen::abort_on_schema_error(&matches), eq(expected));
+    }
+
+    #[test]
+    fn test_parse_compat_flag() {
+        let matches = CommandGen(HashMap::new())
+            .command()
+            .get_matches_from(vec!["pexshell", "--compat", "28.2", "cache"]);
+        assert_that!(
+            CommandGen::compat_version(&matches),
+            some(eq(Version {
+                major: 28,
+                minor: 2
+            }))
+        );
+    }
 }
@@ -0,0 +1,76 @@
+use crate::{config::Manager as ConfigManager, pexshell::PexShell};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use lib::{
+    error,
+    mcu::{compat::Version, schema},
+};
+use std::{fmt::Display, io::Write, path::Path};
+
+pub struct PinSchema;
+
+impl Display for PinSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "pin-schema")
+    }
+}
+
+impl PinSchema {
+    pub fn command(&self) -> Command {
+        Command::new(self.to_string())
+            .about("Lock the schemas used for a management node to a copy of the current schema cache")
+            .long_about(
+                "Lock the schemas used for a management node to a copy of the current schema cache. \
+                 The copy is kept for the given version, and used for every user of the management node \
+                 instead of the schema cache, so that regenerating the cache after an upgrade does not \
+                 change the commands available for that management node.",
+            )
+            .arg(
+                Arg::new("for")
+                    .long("for")
+                    .help("The address of the management node to pin the schemas for")
+                    .value_name("address")
+                    .required(true)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("version")
+                    .long("version")
+                    .help("The version of the management node that the schemas are from, e.g. 36.0")
+                    .value_parser(value_parser!(Version))
+                    .required(true)
+                    .action(ArgAction::Set),
+            )
+    }
+
+    #[allow(clippy::unused_self)]
+    pub fn run(
+        &self,
+        pexshell: &mut PexShell,
+        config: &mut ConfigManager,
+        cache_dir: &Path,
+        pin_sub: &ArgMatches,
+    ) -> Result<(), error::UserFriendly> {
+        let address = pin_sub.get_one::<String>("for").expect("for is required");
+        let version = pin_sub
+            .get_one::<Version>("version")
+            .expect("version is required")
+            .to_string();
+
+        let pinned_dir = schema::get_pinned_cache_dir(cache_dir, address, &version);
+        config.pin_schema_version(address, &version)?;
+        schema::pin_schemas(cache_dir, &pinned_dir).map_err(|e| {
+            error::UserFriendly::new(format!(
+                "failed to copy schemas to {}: {e}",
+                pinned_dir.display()
+            ))
+        })?;
+        config.write_to_file()?;
+
+        writeln!(
+            pexshell.console,
+            "Pinned schemas for {address} to version {version}"
+        )
+        .unwrap();
+        Ok(())
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{
+    argparse::CommandGen,
+    cli::login,
+    config::{Manager as ConfigManager, Provider as _},
+    pexshell::PexShell,
+};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lib::{
+    error,
+    mcu::{
+        self,
+        schema::{self, Endpoint, Methods},
+        Api,
+    },
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fmt::Write as _,
+    io::Write,
+    path::Path,
+};
+
+/// Gets the name of a schema value as it appears in the schema, e.g. `datetime` or `get`.
+fn schema_name(value: &impl Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(Value::String(name)) => name,
+        _ => String::from("-"),
+    }
+}
+
+fn methods(allowed: &HashSet<Methods>) -> String {
+    let mut methods: Vec<String> = allowed
+        .iter()
+        .map(|method| schema_name(method).to_uppercase())
+        .collect();
+    methods.sort_unstable();
+    if methods.is_empty() {
+        String::from("-")
+    } else {
+        methods.join(", ")
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// Formats the schema of an endpoint as a table of its fields, followed by the requests it allows.
+fn format_endpoint(endpoint: &Endpoint) -> String {
+    const HEADINGS: [&str; 7] = [
+        "FIELD", "TYPE", "NULLABLE", "READONLY", "BLANK", "DEFAULT", "CHOICES",
+    ];
+
+    let mut names: Vec<&String> = endpoint.fields.keys().collect();
+    names.sort_unstable();
+    let rows: Vec<[String; 7]> = names
+        .into_iter()
+        .map(|name| {
+            let field = &endpoint.fields[name];
+            [
+                name.clone(),
+                schema_name(&field.data_type),
+                String::from(yes_no(field.nullable)),
+                String::from(yes_no(field.readonly)),
+                String::from(yes_no(field.blank)),
+                field
+                    .default
+                    .as_ref()
+                    .map_or_else(|| String::from("-"), Value::to_string),
+                field.valid_choices.as_ref().map_or_else(
+                    || String::from("-"),
+                    |choices| {
+                        choices
+                            .iter()
+                            .map(Value::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    },
+                ),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADINGS.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut output = String::new();
+    let headings = HEADINGS.map(String::from);
+    for row in std::iter::once(&headings).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(output, "{}", line.trim_end()).unwrap();
+    }
+
+    writeln!(output).unwrap();
+    writeln!(
+        output,
+        "List methods:   {}",
+        methods(&endpoint.allowed_list_http_methods)
+    )
+    .unwrap();
+    writeln!(
+        output,
+        "Detail methods: {}",
+        methods(&endpoint.allowed_detail_http_methods)
+    )
+    .unwrap();
+
+    let mut filtering: Vec<(&String, &Vec<String>)> = endpoint.filtering.iter().collect();
+    filtering.sort_unstable();
+    if filtering.is_empty() {
+        writeln!(output, "Filtering:      -").unwrap();
+    } else {
+        writeln!(output, "Filtering:").unwrap();
+        for (field, filters) in filtering {
+            writeln!(output, "  {field}: {}", filters.join(", ")).unwrap();
+        }
+    }
+
+    if endpoint.ordering.is_empty() {
+        writeln!(output, "Ordering:       -").unwrap();
+    } else {
+        writeln!(output, "Ordering:       {}", endpoint.ordering.join(", ")).unwrap();
+    }
+    output
+}
+
+pub struct Schema;
+
+impl Display for Schema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "schema")
+    }
+}
+
+impl Schema {
+    pub fn command(&self) -> Command {
+        Command::new(self.to_string())
+            .about("Inspect the cached schemas")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("show")
+                    .about("Show the fields of an endpoint and the requests it allows")
+                    .long_about(
+                        "Show the fields of an endpoint and the requests it allows. \
+                         This reads the schema cache, so no requests are sent to the management node.",
+                    )
+                    .arg(
+                        Arg::new("api")
+                            .help("The API of the endpoint, e.g. configuration or command-conference")
+                            .required(true)
+                            .action(ArgAction::Set),
+                    )
+                    .arg(
+                        Arg::new("endpoint")
+                            .help("The endpoint to show, e.g. conference")
+                            .required(true)
+                            .action(ArgAction::Set),
+                    ),
+            )
+            .subcommand(
+                Command::new("fetch")
+                    .about("Fetch the schema of a single endpoint from the management node into the cache")
+                    .long_about(
+                        "Fetch the schema of a single endpoint from the management node into the cache, \
+                         without regenerating the rest of the cache.",
+                    )
+                    .arg(
+                        Arg::new("api")
+                            .help("The API of the endpoint, e.g. configuration or command-conference")
+                            .required(true)
+                            .action(ArgAction::Set),
+                    )
+                    .arg(
+                        Arg::new("endpoint")
+                            .help("The endpoint to fetch, e.g. conference")
+                            .required(true)
+                            .action(ArgAction::Set),
+                    )
+                    .arg(
+                        Arg::new("dry_run")
+                            .long("dry-run")
+                            .help("Fetch the schema without writing it to the cache")
+                            .action(ArgAction::SetTrue),
+                    ),
+            )
+    }
+
+    #[allow(clippy::unused_self)]
+    pub fn run(
+        &self,
+        pexshell: &mut PexShell,
+        schemas: &HashMap<Api, HashMap<String, Endpoint>>,
+        schema_sub: &ArgMatches,
+    ) -> Result<(), error::UserFriendly> {
+        let Some(show_sub) = schema_sub.subcommand_matches("show") else {
+            return Ok(());
+        };
+        let api_name = show_sub.get_one::<String>("api").expect("api is required");
+        let resource = show_sub
+            .get_one::<String>("endpoint")
+            .expect("endpoint is required");
+
+        let endpoint = schemas
+            .iter()
+            .find(|(api, _)| api.to_string().to_lowercase() == *api_name)
+            .and_then(|(_, endpoints)| endpoints.get(resource))
+            .ok_or_else(|| {
+                error::UserFriendly::new(format!("unrecognised resource {api_name} {resource}"))
+            })?;
+
+        write!(pexshell.console, "{}", format_endpoint(endpoint)).unwrap();
+        Ok(())
+    }
+
+    /// Fetches the schema of a single endpoint into the cache, for `pexshell schema fetch`.
+    #[allow(clippy::unused_self, clippy::too_many_arguments)]
+    pub async fn fetch(
+        &self,
+        pexshell: &mut PexShell<'_>,
+        config: &mut ConfigManager,
+        client: reqwest::Client,
+        cache_dir: &Path,
+        schemas: &HashMap<Api, HashMap<String, Endpoint>>,
+        matches: &ArgMatches,
+        fetch_sub: &ArgMatches,
+    ) -> anyhow::Result<()> {
+        let api_name = fetch_sub.get_one::<String>("api").expect("api is required");
+        let resource = fetch_sub
+            .get_one::<String>("endpoint")
+            .expect("endpoint is required");
+        let dry_run = fetch_sub.get_flag("dry_run");
+        let api = schemas
+            .keys()
+            .copied()
+            .find(|api| api.to_string().to_lowercase() == *api_name)
+            .ok_or_else(|| error::UserFriendly::new(format!("unrecognised API {api_name}")))?;
+
+        let compress = config.get_compress_cache();
+        let mut user = config.get_current_user()?.clone();
+        let mcu_address = user.address.clone();
+        let version = user.settings.version(CommandGen::compat_version(matches))?;
+        let compat = user.settings.compat(version)?;
+        let schema_version = version.map(|version| version.to_string());
+        let request_id = CommandGen::request_id(matches, config.get_enable_request_ids());
+        let retry_policy = CommandGen::retry_policy(matches, config.get_retries());
+        let api_client = mcu::ApiClient::new(
+            client.clone(),
+            &mcu_address,
+            login::auth_for_user(client, &mut user, config, true)?,
+        )
+        .with_retry_policy(retry_policy)
+        .with_compat(compat)
+        .with_request_id(request_id);
+
+        let json = if dry_run {
+            schema::fetch_schema(&api_client, api, resource, schema_version.as_deref()).await?
+        } else {
+            schema::cache_schema(
+                &api_client,
+                cache_dir,
+                api,
+                resource,
+                schema_version.as_deref(),
+                compress,
+            )
+            .await?
+        };
+        drop(api_client);
+
+        let fields = json
+            .get("fields")
+            .and_then(Value::as_object)
+            .map_or(0, serde_json::Map::len);
+        if dry_run {
+            writeln!(
+                pexshell.console,
+                "Fetched {fields} fields for {api_name} {resource} (dry run - the cache was not changed)"
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                pexshell.console,
+                "Fetched {fields} fields for {api_name} {resource}"
+            )
+            .unwrap();
+        }
+        Ok(())
+    }
+}
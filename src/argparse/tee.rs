@@ -0,0 +1,72 @@
+use crate::pexshell::PexShell;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use lib::error;
+use std::{fmt::Display, fs::File, path::PathBuf};
+
+pub struct Tee;
+
+impl Display for Tee {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "tee")
+    }
+}
+
+impl Tee {
+    pub fn command(&self) -> Command {
+        Command::new(self.to_string())
+            .about("Run a pexshell command, writing its output to a file as well as stdout")
+            .long_about(
+                "Run a pexshell command, writing its output to a file as well as stdout, \
+                 e.g. pexshell tee output.json -- configuration conference get. \
+                 Options given before tee (such as --output) apply to the command.",
+            )
+            .arg(
+                Arg::new("file")
+                    .help("The file to write the output to, which is overwritten if it exists")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(true)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("command")
+                    .help("The pexshell command to run, after --")
+                    .num_args(1..)
+                    .last(true)
+                    .required(true)
+                    .action(ArgAction::Set),
+            )
+    }
+
+    /// Starts writing the output of `pexshell` to the file given to tee, and returns the arguments of the command to
+    /// run, keeping any options given before tee.
+    #[allow(clippy::unused_self)]
+    pub fn start(
+        &self,
+        pexshell: &mut PexShell<'_>,
+        args: &[String],
+        tee_sub: &ArgMatches,
+    ) -> Result<Vec<String>, error::UserFriendly> {
+        let path = tee_sub
+            .get_one::<PathBuf>("file")
+            .expect("file is required");
+        let file = File::create(path).map_err(|e| {
+            error::UserFriendly::new(format!("failed to create {}: {e}", path.display()))
+        })?;
+        pexshell.console.tee_stdout(file);
+
+        let tee_position = args
+            .iter()
+            .position(|arg| *arg == self.to_string())
+            .expect("tee is in the arguments");
+        Ok(args[..tee_position]
+            .iter()
+            .cloned()
+            .chain(
+                tee_sub
+                    .get_many::<String>("command")
+                    .expect("command is required")
+                    .cloned(),
+            )
+            .collect())
+    }
+}
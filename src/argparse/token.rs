@@ -0,0 +1,92 @@
+use crate::{
+    cli::login,
+    config::{self, Manager as ConfigManager, Provider as _},
+    pexshell::PexShell,
+};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lib::error;
+use log::debug;
+use std::{fmt::Display, io::Write};
+
+pub struct Token;
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "token")
+    }
+}
+
+impl Token {
+    pub fn command(&self) -> Command {
+        Command::new(self.to_string())
+            .about("Print the OAuth2 access token of the current user")
+            .long_about(
+                "Print the OAuth2 access token of the current user, fetching a new one if it has \
+                 expired. The token is printed without a trailing newline, so it can be used in \
+                 command substitution, e.g. curl -H \"Authorization: Bearer $(pexshell token)\". \
+                 To keep the token out of the terminal scrollback, it is only printed to a terminal \
+                 with --insecure-write-to-stdout.",
+            )
+            .arg(
+                Arg::new("expires_at")
+                    .long("expires-at")
+                    .help("Also print the expiry time of the token on a second line")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("insecure_write_to_stdout")
+                    .long("insecure-write-to-stdout")
+                    .help("Print the token even if stdout is a terminal, where it may be left in the scrollback")
+                    .action(ArgAction::SetTrue),
+            )
+    }
+
+    #[allow(clippy::unused_self)]
+    pub async fn run(
+        &self,
+        pexshell: &mut PexShell<'_>,
+        config: &mut ConfigManager,
+        client: reqwest::Client,
+        token_sub: &ArgMatches,
+    ) -> Result<(), error::UserFriendly> {
+        if pexshell.console.is_stdout_interactive()
+            && !token_sub.get_flag("insecure_write_to_stdout")
+        {
+            return Err(error::UserFriendly::new(
+                "refusing to print the token to a terminal - pipe it to another command instead \
+                 (e.g. pexshell token | some-command), or use --insecure-write-to-stdout",
+            ));
+        }
+
+        let mut user = config.get_current_user()?.clone();
+        let config::Credentials::OAuth2(credentials) = config.get_credentials_for_user(&user)?
+        else {
+            return Err(error::UserFriendly::new(format!(
+                "{} does not use OAuth2 - tokens are only available for OAuth2 users",
+                user.visual_id()
+            )));
+        };
+
+        if user.is_token_expired() {
+            debug!("stored OAuth2 token has expired - fetching a new one");
+        } else if let Some(expires_in) = user.token_expires_in() {
+            debug!(
+                "stored OAuth2 token expires in {} seconds",
+                expires_in.num_seconds()
+            );
+        }
+
+        let auth = login::oauth2_for_user(client, &mut user, credentials, config, true);
+        let token = auth
+            .token()
+            .await
+            .map_err(|e| error::UserFriendly::new(format!("{e:#}")))?;
+        drop(auth);
+
+        write!(pexshell.console, "{}", token.token.secret()).unwrap();
+        if token_sub.get_flag("expires_at") {
+            write!(pexshell.console, "\n{}", token.expires_at.to_rfc3339()).unwrap();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    argparse::CommandGen,
+    cli::{login, output::DateFormatter},
+    config::{Manager as ConfigManager, Provider as _},
+    pexshell::PexShell,
+};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use lib::{
+    error,
+    mcu::{self, schema, Api},
+};
+use log::debug;
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+/// Gets whether another poll can start before the timeout, if any, given how long the previous polls took.
+fn can_poll_again(elapsed: Duration, interval: Duration, timeout: Option<Duration>) -> bool {
+    timeout.map_or(true, |timeout| elapsed + interval <= timeout)
+}
+
+pub struct Watch;
+
+impl Display for Watch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "watch")
+    }
+}
+
+impl Watch {
+    pub fn command(&self, api_subcommands: Vec<Command>) -> Command {
+        Command::new(self.to_string())
+            .about("Repeat a get of all objects until at least one object matches its filters")
+            .long_about(
+                "Repeat a get of all objects until at least one object matches its filters, then print the \
+                 matching objects, e.g. to wait for a participant to join after dialling them with: \
+                 pexshell watch status participant get --conference=\"My VMR\"",
+            )
+            .subcommands(api_subcommands)
+            .subcommand_required(true)
+            .arg(
+                Arg::new("interval")
+                    .long("interval")
+                    .help("Number of seconds to wait between each get")
+                    .value_name("seconds")
+                    .value_parser(value_parser!(u64).range(1..))
+                    .default_value("5")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("timeout")
+                    .long("timeout")
+                    .help("Give up if no objects have matched after this many seconds")
+                    .long_help(
+                        "Give up if no objects have matched after this many seconds. \
+                         Without this, the get is repeated until an object matches or the command is interrupted.",
+                    )
+                    .value_name("seconds")
+                    .value_parser(value_parser!(u64))
+                    .action(ArgAction::Set),
+            )
+    }
+
+    /// Repeats the get given to `pexshell watch` until it returns at least one object, printing the objects.
+    ///
+    /// Interrupting the command while it waits aborts it as with any other command.
+    #[allow(clippy::unused_self)]
+    pub async fn run(
+        &self,
+        pexshell: &mut PexShell<'_>,
+        config: &mut ConfigManager,
+        client: reqwest::Client,
+        schemas: &HashMap<Api, HashMap<String, schema::Endpoint>>,
+        matches: &ArgMatches,
+        watch_sub: &ArgMatches,
+    ) -> anyhow::Result<()> {
+        let interval = Duration::from_secs(
+            *watch_sub
+                .get_one::<u64>("interval")
+                .expect("interval has a default"),
+        );
+        let timeout = watch_sub
+            .get_one::<u64>("timeout")
+            .map(|&timeout| Duration::from_secs(timeout));
+        let (request, _) = crate::api_request_from_matches(watch_sub, schemas).await?;
+        let mcu::ApiRequest::GetAll { api, resource, .. } = &request else {
+            return Err(error::UserFriendly::new(
+                "watch can only repeat a get of all objects, e.g. pexshell watch status participant get",
+            )
+            .into());
+        };
+        let (api, resource) = (*api, resource.clone());
+        let endpoint = schemas
+            .get(&api)
+            .and_then(|endpoints| endpoints.get(&resource));
+
+        let mut user = config.get_current_user()?.clone();
+        let mcu_address = user.address.clone();
+        let compat = user.settings.compat(CommandGen::compat_version(matches))?;
+        let request_id = CommandGen::request_id(matches, config.get_enable_request_ids());
+        let retry_policy = CommandGen::retry_policy(matches, config.get_retries());
+        let api_client = mcu::ApiClient::new(
+            client.clone(),
+            &mcu_address,
+            login::auth_for_user(client, &mut user, config, true)?,
+        )
+        .with_retry_policy(retry_policy)
+        .with_compat(compat)
+        .with_request_id(request_id);
+
+        let start = Instant::now();
+        let objects = loop {
+            let objects = api_client.send_paginated_all(request.clone()).await?;
+            if !objects.is_empty() {
+                break objects;
+            }
+            if !can_poll_again(start.elapsed(), interval, timeout) {
+                return Err(error::UserFriendly::new(format!(
+                    "no {resource} objects matched within {} seconds",
+                    timeout.unwrap_or_default().as_secs()
+                ))
+                .into());
+            }
+            debug!(
+                "no {resource} objects matched - trying again in {} seconds",
+                interval.as_secs()
+            );
+            tokio::time::sleep(interval).await;
+        };
+        drop(api_client);
+
+        pexshell.console.set_output_format(
+            CommandGen::output_format(matches)
+                .for_resource(api, &resource)
+                .for_endpoint(endpoint),
+        );
+        pexshell.console.set_date_formatter(
+            CommandGen::date_format(matches)
+                .map(|format| DateFormatter::for_endpoint(format, endpoint)),
+        );
+        pexshell
+            .console
+            .write_objects(
+                futures::stream::iter(objects.into_iter().map(Ok::<_, Infallible>)),
+                false,
+            )
+            .await?;
+
+        config.set_last_used()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::prelude::*;
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(0, 5, None, true ; "without a timeout")]
+    #[test_case(0, 5, Some(10), true ; "before the timeout")]
+    #[test_case(5, 5, Some(10), true ; "at the timeout")]
+    #[test_case(6, 5, Some(10), false ; "after the timeout")]
+    fn test_can_poll_again(elapsed: u64, interval: u64, timeout: Option<u64>, expected: bool) {
+        // Act
+        let result = can_poll_again(
+            Duration::from_secs(elapsed),
+            Duration::from_secs(interval),
+            timeout.map(Duration::from_secs),
+        );
+
+        // Assert
+        assert_that!(result, eq(expected));
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use futures::{future::BoxFuture, FutureExt as _, Stream, StreamExt as _};
+use lib::mcu::{ApiClientError, ApiError, ApiRequest, IApiClient};
+use parking_lot::Mutex;
+use serde_json::Value;
+
+/// Maximum number of related objects followed from each object of a response, so that objects which refer to each
+/// other can't be expanded forever.
+pub const MAX_EXPAND_DEPTH: usize = 3;
+
+/// Replaces the resource URIs of related fields in API responses with the objects they refer to, for `get --expand`.
+///
+/// The related fields of the inlined objects are expanded too, up to `MAX_EXPAND_DEPTH` objects deep, if they have
+/// the same names. Each related object is only fetched once, however many times it is referred to.
+pub struct Expander<'a, C> {
+    client: &'a C,
+    fields: Vec<String>,
+    fetched: Mutex<HashMap<String, Value>>,
+}
+
+impl<'a, C: IApiClient + Sync> Expander<'a, C> {
+    pub fn new(client: &'a C, fields: Vec<String>) -> Self {
+        Self {
+            client,
+            fields,
+            fetched: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Expands the related fields of an object.
+    pub async fn expand(&self, object: &mut Value) -> anyhow::Result<()> {
+        self.expand_object(object, &mut Vec::new()).await
+    }
+
+    /// Expands the related fields of each object in a stream of objects, as they are received.
+    pub fn expand_all<'s>(
+        &'s self,
+        objects: impl Stream<Item = Result<Value, ApiClientError>> + Send + 's,
+    ) -> impl Stream<Item = Result<Value, ApiClientError>> + Send + 's {
+        objects.then(move |object| async move {
+            let mut object = object?;
+            self.expand(&mut object).await.map_err(|e| {
+                ApiError::new(
+                    None,
+                    format!("failed to expand related fields: {e:#}"),
+                    Some(e),
+                )
+            })?;
+            Ok::<_, ApiClientError>(object)
+        })
+    }
+
+    /// Expands the related fields of an object that was reached by following the resource URIs in `path`.
+    fn expand_object<'s>(
+        &'s self,
+        object: &'s mut Value,
+        path: &'s mut Vec<String>,
+    ) -> BoxFuture<'s, anyhow::Result<()>> {
+        async move {
+            if path.len() >= MAX_EXPAND_DEPTH {
+                return Ok(());
+            }
+            let Value::Object(object) = object else {
+                return Ok(());
+            };
+            for name in &self.fields {
+                match object.get_mut(name) {
+                    Some(Value::Array(values)) => {
+                        for value in values {
+                            self.expand_value(value, path).await?;
+                        }
+                    }
+                    Some(value) => self.expand_value(value, path).await?,
+                    None => (),
+                }
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+
+    /// Replaces a resource URI with the object it refers to, unless the object is already being expanded.
+    async fn expand_value(&self, value: &mut Value, path: &mut Vec<String>) -> anyhow::Result<()> {
+        let Value::String(uri) = value else {
+            return Ok(());
+        };
+        if !uri.starts_with("/api/admin/") || path.contains(uri) {
+            return Ok(());
+        }
+        let uri = uri.clone();
+
+        let cached = self.fetched.lock().get(&uri).cloned();
+        let mut related = match cached {
+            Some(related) => related,
+            None => {
+                let related = self
+                    .client
+                    .send(ApiRequest::Raw {
+                        method: reqwest::Method::GET,
+                        path: uri.clone(),
+                        body: None,
+                    })
+                    .await?
+                    .unwrap_content_or_default();
+                self.fetched.lock().insert(uri.clone(), related.clone());
+                related
+            }
+        };
+        if related.is_null() {
+            return Ok(());
+        }
+
+        path.push(uri);
+        let result = self.expand_object(&mut related, path).await;
+        path.pop();
+        result?;
+
+        *value = related;
+        Ok(())
+    }
+}
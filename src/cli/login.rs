@@ -1,5 +1,5 @@
-use chrono::{Offset, TimeZone};
-use lib::mcu::auth::{ApiClientAuth, BasicAuth, OAuth2, OAuth2AccessToken};
+use chrono::{DateTime, Offset, TimeZone, Utc};
+use lib::mcu::auth::{ApiClientAuth, BasicAuth, BearerAuth, OAuth2, OAuth2AccessToken};
 use parking_lot::Mutex;
 use std::collections::HashMap;
 use std::fmt::{Display, Write as _};
@@ -10,7 +10,7 @@ use crate::{
     consts::EXIT_CODE_INTERRUPTED,
     set_abort_on_interrupt,
 };
-use dialoguer::{theme::ColorfulTheme as ColourfulTheme, FuzzySelect, Input, Password};
+use dialoguer::{theme::ColorfulTheme as ColourfulTheme, Confirm, FuzzySelect, Input, Password};
 use futures::TryStreamExt;
 use lib::{
     error,
@@ -20,6 +20,11 @@ use lib::{
 use log::error;
 #[cfg(test)]
 use mockall::automock;
+use p256::{
+    ecdsa::SigningKey,
+    pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding},
+};
+use rand::rngs::OsRng;
 use reqwest::StatusCode;
 
 use super::Console;
@@ -40,6 +45,7 @@ pub trait Interact: Send {
     fn password(&mut self, prompt: &str) -> SensitiveString;
     fn select<T: ToString + 'static>(&mut self, prompt: &str, default: usize, items: &[T])
         -> usize;
+    fn confirm(&mut self, prompt: &str) -> bool;
     fn read_to_end(&mut self) -> String;
 }
 
@@ -82,6 +88,14 @@ impl Interact for Interactive {
         )
     }
 
+    fn confirm(&mut self, prompt: &str) -> bool {
+        Confirm::with_theme(&ColourfulTheme::default())
+            .with_prompt(prompt)
+            .default(false)
+            .interact()
+            .unwrap()
+    }
+
     fn read_to_end(&mut self) -> String {
         let mut input = String::new();
         std::io::stdin().read_to_string(&mut input).unwrap();
@@ -89,6 +103,19 @@ impl Interact for Interactive {
     }
 }
 
+/// Generates an ECDSA P-256 key pair for an OAuth2 client, returning the private and public keys in PEM format.
+fn generate_oauth2_key() -> Result<(String, String), error::UserFriendly> {
+    let private_key = SigningKey::random(&mut OsRng);
+    let private_pem = private_key
+        .to_pkcs8_pem(LineEnding::LF)
+        .map_err(|e| error::UserFriendly::new(format!("failed to encode private key: {e}")))?;
+    let public_pem = private_key
+        .verifying_key()
+        .to_public_key_pem(LineEnding::LF)
+        .map_err(|e| error::UserFriendly::new(format!("failed to encode public key: {e}")))?;
+    Ok((private_pem.as_str().to_owned(), public_pem))
+}
+
 fn format_last_used(
     user: &config::User,
     tz: &impl TimeZone<Offset = impl Offset + Display>,
@@ -122,36 +149,52 @@ pub fn auth_for_user<'config>(
             credentials.username,
             credentials.password.unwrap(),
         ))),
-        config::Credentials::OAuth2(credentials) => {
-            let mcu_address = ApiClient::base_url_from_input_address(&user.address);
-            let endpoint = mcu_address + "/oauth/token/";
-            let state = Mutex::new((config, user));
-            Ok(Box::new(OAuth2::new(
-                http_client,
-                endpoint,
-                credentials.client_id,
-                credentials
-                    .private_key
-                    .expect("private key is required for OAuth2"),
-                credentials.token.map(|t| OAuth2AccessToken {
-                    token: t.access_token,
-                    expires_at: t.expiry,
-                }),
-                #[allow(clippy::significant_drop_tightening)]
-                move |token| {
-                    let mut state = state.lock();
-                    let (ref mut config, ref mut user) = *state;
-                    if let Err(e) =
-                        config.set_oauth2_token(user, token, save_credentials_if_changed)
-                    {
-                        error!("failed to save OAuth2 token: {}", e);
-                    }
-                },
-            )))
+        config::Credentials::OAuth2(credentials) => Ok(Box::new(oauth2_for_user(
+            http_client,
+            user,
+            credentials,
+            config,
+            save_credentials_if_changed,
+        ))),
+        config::Credentials::Bearer(credentials) => {
+            Ok(Box::new(BearerAuth::new(credentials.bearer_token.unwrap())))
         }
     }
 }
 
+/// Creates the OAuth2 authentication for a user, saving any new tokens to the config.
+pub fn oauth2_for_user<'config>(
+    http_client: reqwest::Client,
+    user: &'config mut config::User,
+    credentials: config::OAuth2Credentials,
+    config: &'config mut impl ConfigProvider,
+    save_credentials_if_changed: bool,
+) -> OAuth2<'config> {
+    let mcu_address = ApiClient::base_url_from_input_address(&user.address);
+    let endpoint = mcu_address + "/oauth/token/";
+    let state = Mutex::new((config, user));
+    OAuth2::new(
+        http_client,
+        endpoint,
+        credentials.client_id,
+        credentials
+            .private_key
+            .expect("private key is required for OAuth2"),
+        credentials.token.map(|t| OAuth2AccessToken {
+            token: t.access_token,
+            expires_at: t.expiry,
+        }),
+        #[allow(clippy::significant_drop_tightening)]
+        move |token| {
+            let mut state = state.lock();
+            let (ref mut config, ref mut user) = *state;
+            if let Err(e) = config.set_oauth2_token(user, token, save_credentials_if_changed) {
+                error!("failed to save OAuth2 token: {}", e);
+            }
+        },
+    )
+}
+
 async fn test_request(
     client: reqwest::Client,
     config: &mut impl ConfigProvider,
@@ -169,6 +212,7 @@ async fn test_request(
             page_size: 1,
             limit: 1,
             offset: 0,
+            order_by: None,
         })
         .await
         .map_err(|e| error::UserFriendly::new(e.to_string()))?
@@ -190,6 +234,7 @@ async fn test_request(
 
 pub struct Login<Backend: Interact> {
     interact: Backend,
+    password_from_stdin: bool,
 }
 
 impl Default for Login<Interactive> {
@@ -200,7 +245,17 @@ impl Default for Login<Interactive> {
 
 impl<Backend: Interact> Login<Backend> {
     fn new(backend: Backend) -> Self {
-        Self { interact: backend }
+        Self {
+            interact: backend,
+            password_from_stdin: false,
+        }
+    }
+
+    /// Reads passwords for new users from the first line of stdin instead of prompting for them.
+    #[must_use]
+    pub const fn with_password_from_stdin(mut self, password_from_stdin: bool) -> Self {
+        self.password_from_stdin = password_from_stdin;
+        self
     }
 
     pub async fn select_user(
@@ -278,12 +333,119 @@ impl<Backend: Interact> Login<Backend> {
         Ok(())
     }
 
+    /// Adds and selects a user that authenticates with a bearer token, read from the first line of stdin.
+    pub async fn add_and_select_bearer_user(
+        &mut self,
+        config: &mut (impl config::Configurer + config::Provider),
+        client: reqwest::Client,
+        address: String,
+        token_name: String,
+        verify_credentials: bool,
+        store_token_in_plaintext: bool,
+    ) -> Result<(), lib::error::UserFriendly> {
+        let mut user = self.input_bearer_user(address, token_name)?;
+
+        if verify_credentials {
+            test_request(client, config, &mut user).await?;
+            user.last_used = Some(chrono::offset::Utc::now());
+        }
+
+        config.add_user(user.clone(), store_token_in_plaintext)?;
+        config.set_current_user(&user);
+        Ok(())
+    }
+
+    /// Guides the user through registering a new OAuth2 client on the management node, using a newly generated key.
+    pub async fn setup_oauth2_user(
+        &mut self,
+        console: &mut Console,
+        config: &mut (impl config::Configurer + config::Provider),
+        client: reqwest::Client,
+        address: String,
+        verify_credentials: bool,
+        store_private_key_in_plaintext: bool,
+    ) -> Result<(), lib::error::UserFriendly> {
+        let (private_key, public_key) = generate_oauth2_key()?;
+        let admin_url = if address.contains("://") {
+            format!("{address}/admin/")
+        } else {
+            format!("https://{address}/admin/")
+        };
+
+        writeln!(
+            console,
+            "Generated a new ES256 key pair for pexshell.\n\
+             \n\
+             To register it on the management node:\n\
+             1. Log in to the administrator interface at {admin_url}\n\
+             2. Go to Users & Devices > OAuth2 Clients and add a new client\n\
+             3. Give the client a role with the permissions pexshell needs\n\
+             4. Paste the following public key into the client's public key field and save it\n\
+             \n\
+             {public_key}"
+        )
+        .unwrap();
+
+        if !self
+            .interact
+            .confirm("Have you registered the public key on the management node?")
+        {
+            return Err(error::UserFriendly::new(
+                "OAuth2 setup cancelled - the generated key was not saved",
+            ));
+        }
+        let client_id: String = self.interact.text("client id");
+
+        let mut user =
+            config::User::new_oauth2(address, client_id, SensitiveString::from(private_key));
+
+        if verify_credentials {
+            test_request(client, config, &mut user).await?;
+            user.last_used = Some(chrono::offset::Utc::now());
+        }
+
+        config.add_user(user.clone(), store_private_key_in_plaintext)?;
+        config.set_current_user(&user);
+        writeln!(console, "Saved OAuth2 credentials for {}", user.visual_id()).unwrap();
+        Ok(())
+    }
+
+    pub async fn add_and_select_basic_user(
+        &mut self,
+        config: &mut (impl config::Configurer + config::Provider),
+        client: reqwest::Client,
+        verify_credentials: bool,
+        store_password_in_plaintext: bool,
+    ) -> Result<(), lib::error::UserFriendly> {
+        let mut user = self.input_basic_user();
+
+        if verify_credentials {
+            test_request(client, config, &mut user).await?;
+            user.last_used = Some(chrono::offset::Utc::now());
+        }
+
+        config.add_user(user.clone(), store_password_in_plaintext)?;
+        config.set_current_user(&user);
+        Ok(())
+    }
+
     pub fn input_basic_user(&mut self) -> config::User {
         let input_address: String = self.interact.text("address");
 
         let input_username: String = self.interact.text("username");
 
-        let input_password = self.interact.password("password");
+        let input_password = if self.password_from_stdin {
+            SensitiveString::from(
+                self.interact
+                    .read_to_end()
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_owned(),
+            )
+        } else {
+            self.interact.password("password")
+        };
 
         config::User::new(input_address, input_username, input_password)
     }
@@ -293,6 +455,25 @@ impl<Backend: Interact> Login<Backend> {
         config::User::new_oauth2(address, client_id, client_cert)
     }
 
+    pub fn input_bearer_user(
+        &mut self,
+        address: String,
+        token_name: String,
+    ) -> Result<config::User, error::UserFriendly> {
+        let input = self.interact.read_to_end();
+        let token = input.lines().next().unwrap_or_default().trim();
+        if token.is_empty() {
+            return Err(error::UserFriendly::new(
+                "no bearer token given - pass the token to stdin",
+            ));
+        }
+        Ok(config::User::new_bearer(
+            address,
+            token_name,
+            SensitiveString::from(token.to_owned()),
+        ))
+    }
+
     #[allow(clippy::unused_self, clippy::needless_pass_by_ref_mut)]
     pub fn list_users(&mut self, console: &mut Console, config: &impl config::Configurer) {
         let mut output = String::new();
@@ -331,6 +512,62 @@ impl<Backend: Interact> Login<Backend> {
             Ok(())
         }
     }
+
+    /// Deletes all users that have not been used since `cutoff`, including users that have never been used.
+    ///
+    /// Asks for confirmation first unless `skip_confirmation` is set, which is required when not running
+    /// interactively.
+    pub fn prune_users(
+        &mut self,
+        console: &mut Console,
+        config: &mut impl config::Configurer,
+        cutoff: DateTime<Utc>,
+        skip_confirmation: bool,
+    ) -> Result<(), error::UserFriendly> {
+        let stale: Vec<(usize, String)> = config
+            .get_users()
+            .iter()
+            .enumerate()
+            .filter(|(_, user)| user.last_used.map_or(true, |last_used| last_used < cutoff))
+            .map(|(index, user)| (index, combine_username(user, local_timezone())))
+            .collect();
+        if stale.is_empty() {
+            writeln!(console, "no users to prune").unwrap();
+            return Ok(());
+        }
+
+        if !skip_confirmation {
+            if !console.is_stderr_interactive() {
+                return Err(error::UserFriendly::new(
+                    "not running interactively - pass --yes to prune users without confirmation",
+                ));
+            }
+            let mut output = String::new();
+            for (_, user_ident) in &stale {
+                writeln!(&mut output, "  {user_ident}").unwrap();
+            }
+            write!(console, "{output}").unwrap();
+            if !self
+                .interact
+                .confirm(&format!("delete {} user(s)?", stale.len()))
+            {
+                return Ok(());
+            }
+        }
+
+        let mut removed = Vec::with_capacity(stale.len());
+        for &(index, _) in stale.iter().rev() {
+            removed.push(config.get_users()[index].visual_id());
+            config.delete_user(index)?;
+        }
+
+        let mut output = String::new();
+        for visual_id in removed.iter().rev() {
+            writeln!(&mut output, "removed {visual_id}").unwrap();
+        }
+        write!(console, "{output}").unwrap();
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -339,7 +576,7 @@ mod tests {
 
     use chrono::{FixedOffset, TimeZone, Utc};
     use googletest::prelude::*;
-    use jsonwebtoken::{DecodingKey, Validation};
+    use jsonwebtoken::{DecodingKey, EncodingKey, Validation};
     use lib::util::SensitiveString;
     use mockall::{predicate as mp, Sequence};
     use serde_json::{json, Value};
@@ -351,7 +588,10 @@ mod tests {
 
     use crate::{
         cli::Console,
-        config::{self, BasicCredentials, Credentials, OAuth2Credentials, OAuth2Token, User},
+        config::{
+            self, BasicCredentials, BearerCredentials, Credentials, OAuth2Credentials, OAuth2Token,
+            User, UserSettings,
+        },
         test_util::sensitive_string,
     };
 
@@ -366,6 +606,7 @@ mod tests {
             }),
             current_user: false,
             last_used: None,
+            settings: UserSettings::default(),
         };
         let user_2 = User {
             address: String::from("testing.test.2"),
@@ -375,6 +616,7 @@ mod tests {
             }),
             current_user: true,
             last_used: Some(Utc.with_ymd_and_hms(2007, 10, 19, 7, 23, 4).unwrap()),
+            settings: UserSettings::default(),
         };
         let user_3 = User {
             address: String::from("testing.test.3"),
@@ -384,6 +626,7 @@ mod tests {
             }),
             current_user: false,
             last_used: None,
+            settings: UserSettings::default(),
         };
         vec![user_1, user_2, user_3]
     }
@@ -398,6 +641,7 @@ mod tests {
             }),
             current_user: false,
             last_used: None,
+            settings: UserSettings::default(),
         };
         assert_that!(
             combine_username(&user, &Utc),
@@ -415,6 +659,7 @@ mod tests {
             }),
             current_user: false,
             last_used: Some(Utc.with_ymd_and_hms(2007, 10, 19, 7, 23, 4).unwrap()),
+            settings: UserSettings::default(),
         };
         assert_that!(
             combine_username(&user, &Utc),
@@ -432,6 +677,7 @@ mod tests {
             }),
             current_user: false,
             last_used: Some(Utc.with_ymd_and_hms(2007, 10, 19, 7, 23, 4).unwrap()),
+            settings: UserSettings::default(),
         };
         let tz = FixedOffset::west_opt(5 * 60 * 60).unwrap();
         assert_that!(
@@ -467,6 +713,131 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_prune_users() {
+        // Arrange
+        let mut mock_config = config::MockConfigManager::new();
+        mock_config
+            .expect_get_users()
+            .return_const(get_test_users());
+        let mut seq = Sequence::new();
+        mock_config
+            .expect_delete_user()
+            .with(mp::eq(2))
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(()));
+        mock_config
+            .expect_delete_user()
+            .with(mp::eq(0))
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(()));
+
+        let mut backend = MockInteract::new();
+        backend
+            .expect_confirm()
+            .with(mp::eq("delete 2 user(s)?"))
+            .once()
+            .return_const(true);
+        let out = VirtualFile::new();
+        let mut console = Console::new(false, out.clone(), true, VirtualFile::new());
+
+        let mut login = Login::new(backend);
+        let cutoff = Utc.with_ymd_and_hms(2007, 1, 1, 0, 0, 0).unwrap();
+
+        // Act
+        let result = login.prune_users(&mut console, &mut mock_config, cutoff, false);
+
+        // Assert
+        assert_that!(result, ok(anything()));
+        assert_that!(
+            out.take(),
+            eq("  username.1@testing.test.1 (Last Used: Never)\n  username.3@testing.test.3 (Last Used: Never)\n\
+                removed username.1@testing.test.1\nremoved username.3@testing.test.3\n")
+        );
+    }
+
+    #[test]
+    fn test_prune_users_declined() {
+        // Arrange
+        let mut mock_config = config::MockConfigManager::new();
+        mock_config
+            .expect_get_users()
+            .return_const(get_test_users());
+        mock_config.expect_delete_user().never();
+
+        let mut backend = MockInteract::new();
+        backend.expect_confirm().once().return_const(false);
+        let mut console = Console::new(false, VirtualFile::new(), true, VirtualFile::new());
+
+        let mut login = Login::new(backend);
+        let cutoff = Utc.with_ymd_and_hms(2008, 1, 1, 0, 0, 0).unwrap();
+
+        // Act
+        let result = login.prune_users(&mut console, &mut mock_config, cutoff, false);
+
+        // Assert
+        assert_that!(result, ok(anything()));
+    }
+
+    #[test]
+    fn test_prune_users_non_interactive_requires_yes() {
+        // Arrange
+        let mut mock_config = config::MockConfigManager::new();
+        mock_config
+            .expect_get_users()
+            .return_const(get_test_users());
+        mock_config.expect_delete_user().never();
+
+        let mut backend = MockInteract::new();
+        backend.expect_confirm().never();
+        let mut console = Console::new(false, VirtualFile::new(), false, VirtualFile::new());
+
+        let mut login = Login::new(backend);
+        let cutoff = Utc.with_ymd_and_hms(2008, 1, 1, 0, 0, 0).unwrap();
+
+        // Act
+        let result = login.prune_users(&mut console, &mut mock_config, cutoff, false);
+
+        // Assert
+        assert_that!(result, err(displays_as(contains_substring("pass --yes"))));
+    }
+
+    #[test]
+    fn test_prune_users_with_yes() {
+        // Arrange
+        let mut mock_config = config::MockConfigManager::new();
+        mock_config
+            .expect_get_users()
+            .return_const(get_test_users());
+        for index in [2, 1, 0] {
+            mock_config
+                .expect_delete_user()
+                .with(mp::eq(index))
+                .once()
+                .returning(|_| Ok(()));
+        }
+
+        let mut backend = MockInteract::new();
+        backend.expect_confirm().never();
+        let out = VirtualFile::new();
+        let mut console = Console::new(false, out.clone(), false, VirtualFile::new());
+
+        let mut login = Login::new(backend);
+        let cutoff = Utc.with_ymd_and_hms(2008, 1, 1, 0, 0, 0).unwrap();
+
+        // Act
+        let result = login.prune_users(&mut console, &mut mock_config, cutoff, true);
+
+        // Assert
+        assert_that!(result, ok(anything()));
+        assert_that!(
+            out.take(),
+            eq("removed username.1@testing.test.1\nremoved username.2@testing.test.2\nremoved username.3@testing.test.3\n")
+        );
+    }
+
     #[test]
     fn test_input_user() {
         // Arrange
@@ -505,6 +876,47 @@ mod tests {
                 }))),
                 current_user: eq(&false),
                 last_used: none(),
+                settings: anything(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_input_user_with_password_from_stdin() {
+        // Arrange
+        let backend = MockInteract::new();
+        let mut login = Login::new(backend).with_password_from_stdin(true);
+        login
+            .interact
+            .expect_text()
+            .with(mp::eq("address"))
+            .once()
+            .return_const("testing.test");
+        login
+            .interact
+            .expect_text()
+            .with(mp::eq("username"))
+            .once()
+            .return_const("some_username");
+        login
+            .interact
+            .expect_read_to_end()
+            .once()
+            .return_const("some_password\nignored\n");
+        login.interact.expect_password().never();
+
+        // Act
+        let user = login.input_basic_user();
+
+        // Assert
+        assert_that!(
+            user,
+            matches_pattern!(User {
+                credentials: pat!(Credentials::Basic(pat!(BasicCredentials {
+                    username: eq("some_username"),
+                    password: some(sensitive_string(eq("some_password"))),
+                }))),
+                ..
             })
         );
     }
@@ -864,6 +1276,96 @@ mod tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_oauth2_setup_no_verify() {
+        // Arrange
+        let backend = MockInteract::new();
+        let mut mock_config = config::MockConfigManager::new();
+        let out = VirtualFile::new();
+        let mut console = Console::new(false, out.clone(), false, VirtualFile::new());
+        let mut login = Login::new(backend);
+
+        login.interact.expect_confirm().once().return_const(true);
+        login
+            .interact
+            .expect_text()
+            .with(mp::eq("client id"))
+            .once()
+            .return_const("new_client_id");
+
+        let mut login_seq = Sequence::new();
+        mock_config
+            .expect_add_user()
+            .withf(|user: &User, plaintext| {
+                user.address == "testing.new"
+                    && matches!(
+                        user.credentials,
+                        Credentials::OAuth2(OAuth2Credentials {
+                            ref client_id,
+                            private_key: Some(ref private_key),
+                            token: None,
+                        }) if client_id == "new_client_id"
+                            && EncodingKey::from_ec_pem(private_key.secret().as_bytes()).is_ok()
+                    )
+                    && !*plaintext
+            })
+            .once()
+            .in_sequence(&mut login_seq)
+            .returning(|_, _| Ok(()));
+        mock_config
+            .expect_set_current_user()
+            .withf(|user: &User| user.address == "testing.new" && user.last_used.is_none())
+            .once()
+            .in_sequence(&mut login_seq)
+            .return_const(());
+
+        // Act
+        login
+            .setup_oauth2_user(
+                &mut console,
+                &mut mock_config,
+                reqwest::Client::new(),
+                "testing.new".to_owned(),
+                false,
+                false,
+            )
+            .await
+            .unwrap();
+
+        // Assert
+        let stdout = out.take();
+        assert_that!(stdout, contains_substring("https://testing.new/admin/"));
+        assert_that!(stdout, contains_substring("-----BEGIN PUBLIC KEY-----"));
+        assert_that!(stdout, not(contains_substring("PRIVATE KEY")));
+    }
+
+    #[tokio::test]
+    async fn test_oauth2_setup_cancelled() {
+        // Arrange
+        let backend = MockInteract::new();
+        let mut mock_config = config::MockConfigManager::new();
+        let mut console = Console::new(false, VirtualFile::new(), false, VirtualFile::new());
+        let mut login = Login::new(backend);
+
+        login.interact.expect_confirm().once().return_const(false);
+        mock_config.expect_add_user().never();
+
+        // Act
+        let result = login
+            .setup_oauth2_user(
+                &mut console,
+                &mut mock_config,
+                reqwest::Client::new(),
+                "testing.new".to_owned(),
+                false,
+                false,
+            )
+            .await;
+
+        // Assert
+        assert_that!(result, err(displays_as(contains_substring("cancelled"))));
+    }
+
     #[allow(clippy::too_many_lines)]
     #[tokio::test]
     async fn test_oauth2_add_and_verify() {
@@ -1059,4 +1561,123 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn test_bearer_add_and_verify() {
+        // Arrange
+        let server = MockServer::start().await;
+        let backend = MockInteract::new();
+        let mut mock_config = config::MockConfigManager::new();
+        let mut login = Login::new(backend);
+
+        mock_config
+            .expect_get_credentials_for_user()
+            .returning(|user| Ok(user.credentials.clone()));
+        login
+            .interact
+            .expect_read_to_end()
+            .once()
+            .return_const("some_token\n");
+
+        Mock::given(method("GET"))
+            .and(path("/api/admin/status/v1/worker_vm/"))
+            .and(header("Authorization", "Bearer some_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "meta": {
+                    "limit": 1,
+                    "next": null,
+                    "offset": 0,
+                    "previous": null,
+                    "total_count": 0,
+                },
+                "objects": []
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut login_seq = Sequence::new();
+
+        {
+            let uri = server.uri();
+            mock_config
+                .expect_add_user()
+                .withf(move |user: &User, plaintext| {
+                    user.address == uri
+                        && matches!(
+                            user.credentials,
+                            Credentials::Bearer(BearerCredentials {
+                                ref token_name,
+                                bearer_token: Some(ref token),
+                            }) if token_name == "ci" && token.secret() == "some_token"
+                        )
+                        && user.last_used.is_some()
+                        && !*plaintext
+                })
+                .once()
+                .in_sequence(&mut login_seq)
+                .returning(|_, _| Ok(()));
+        }
+
+        {
+            let uri = server.uri();
+            mock_config
+                .expect_set_current_user()
+                .withf(move |user: &User| {
+                    user.address == uri
+                        && matches!(
+                            user.credentials,
+                            Credentials::Bearer(BearerCredentials { ref token_name, .. }) if token_name == "ci"
+                        )
+                })
+                .once()
+                .in_sequence(&mut login_seq)
+                .return_const(());
+        }
+
+        // Act
+        login
+            .add_and_select_bearer_user(
+                &mut mock_config,
+                reqwest::Client::new(),
+                server.uri(),
+                "ci".to_owned(),
+                true,
+                false,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bearer_add_without_token() {
+        // Arrange
+        let backend = MockInteract::new();
+        let mut mock_config = config::MockConfigManager::new();
+        let mut login = Login::new(backend);
+        login
+            .interact
+            .expect_read_to_end()
+            .once()
+            .return_const("\n");
+        mock_config.expect_add_user().never();
+
+        // Act
+        let result = login
+            .add_and_select_bearer_user(
+                &mut mock_config,
+                reqwest::Client::new(),
+                "testing.new".to_owned(),
+                "ci".to_owned(),
+                false,
+                false,
+            )
+            .await;
+
+        // Assert
+        assert_that!(
+            result,
+            err(displays_as(contains_substring("no bearer token given")))
+        );
+    }
 }
@@ -1,10 +1,16 @@
+pub mod curl;
+pub mod expand;
 pub mod login;
+pub mod output;
+pub mod progress;
+pub mod upsert;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
+use base64::prelude::{Engine as _, BASE64_STANDARD};
 use clap::{ArgAction, ArgMatches, Command};
-use colored_json::to_colored_json_auto as to_coloured_json_auto;
 use lib::mcu::schema::Methods::{Delete, Get, Patch, Post, Put};
 use lib::mcu::{
     schema::{Endpoint, Field, Type},
@@ -12,6 +18,7 @@ use lib::mcu::{
 };
 use log::{debug, warn};
 use once_cell::sync::Lazy;
+use output::{DateFormatter, OutputFormat, OutputSink};
 use serde_json::{json, Map, Value};
 
 pub struct Console {
@@ -19,6 +26,7 @@ pub struct Console {
     is_stderr_interactive: bool,
     stdout: Box<dyn Write + Send>,
     stderr: Box<dyn Write + Send>,
+    output: OutputSink,
 }
 
 impl Console {
@@ -33,6 +41,7 @@ impl Console {
             stdout: Box::new(stdout),
             is_stderr_interactive,
             stderr: Box::new(stderr),
+            output: OutputSink::new(OutputFormat::default(), is_stdout_interactive),
         }
     }
 
@@ -59,14 +68,58 @@ impl Console {
     }
 
     pub fn pretty_print_json(&mut self, json: &Value) {
-        let pretty = if self.is_stdout_interactive() {
-            debug!("Stdout is a terminal - pretty-printing json in colour");
-            to_coloured_json_auto(json).unwrap()
-        } else {
-            debug!("Stdout is not a terminal - pretty-printing json without colour");
-            serde_json::to_string_pretty(json).unwrap()
-        };
-        writeln!(&mut self.stdout, "{pretty}").unwrap();
+        self.output.write_json(&mut *self.stdout, json).unwrap();
+    }
+
+    /// Sets the format used to write API responses.
+    pub fn set_output_format(&mut self, format: OutputFormat) {
+        self.output = OutputSink::new(format, self.is_stdout_interactive);
+    }
+
+    /// Sets how date and time fields in API responses are reformatted, if at all.
+    pub fn set_date_formatter(&mut self, dates: Option<DateFormatter>) {
+        self.output = std::mem::take(&mut self.output).with_dates(dates);
+    }
+
+    /// Sets the top-level fields of API responses to write, or `None` to write every field.
+    pub fn set_fields(&mut self, fields: Option<HashSet<String>>) {
+        self.output = std::mem::take(&mut self.output).with_fields(fields);
+    }
+
+    /// Writes a single API response in the chosen output format.
+    pub async fn write_value(&mut self, value: Value) -> anyhow::Result<()> {
+        self.output.write_value(&mut *self.stdout, value).await
+    }
+
+    /// Writes a list of objects from the API in the chosen output format.
+    pub async fn write_objects<E>(
+        &mut self,
+        objects: impl futures::Stream<Item = Result<Value, E>> + Unpin,
+        stream: bool,
+    ) -> anyhow::Result<()>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.output
+            .write_objects(&mut *self.stdout, objects, stream)
+            .await
+    }
+
+    /// Also writes everything written to stdout to `file`.
+    /// Output is formatted as if stdout isn't a terminal, so that the file doesn't contain colour codes.
+    pub fn tee_stdout(&mut self, file: impl Write + Send + 'static) {
+        let stdout = std::mem::replace(&mut self.stdout, Box::new(std::io::sink()));
+        self.stdout = Box::new(TeeWriter::new(stdout, file));
+        self.is_stdout_interactive = false;
+        self.output = OutputSink::new(OutputFormat::default(), false);
+    }
+
+    /// Writes everything that would be written to stdout to `file` instead.
+    /// Output is formatted as if stdout isn't a terminal, so that the file doesn't contain colour codes.
+    pub fn redirect_stdout(&mut self, file: impl Write + Send + 'static) {
+        self.stdout = Box::new(file);
+        self.is_stdout_interactive = false;
+        self.output = OutputSink::new(OutputFormat::default(), false);
     }
 
     pub fn stderr(&mut self) -> &mut (dyn Write + Send) {
@@ -84,149 +137,397 @@ impl Write for Console {
     }
 }
 
+/// A writer that writes everything to two writers, like the `tee` command.
+pub struct TeeWriter<A: Write, B: Write> {
+    first: A,
+    second: B,
+}
+
+impl<A: Write, B: Write> TeeWriter<A, B> {
+    pub const fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A: Write, B: Write> Write for TeeWriter<A, B> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.first.write(buf)?;
+        self.second.write_all(&buf[..written])?;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.first.flush()?;
+        self.second.flush()
+    }
+}
+
 pub fn generate_subcommands(schemas: &HashMap<Api, HashMap<String, Endpoint>>) -> Vec<Command> {
     let mut commands = Vec::new();
-    let mut command_command = clap::Command::new("command").subcommand_required(true);
+    let mut command_subcommands = Vec::new();
 
     for (api, endpoint_map) in schemas {
         match api {
             Api::Command(inner_api) => {
-                command_command = command_command.subcommand(
+                command_subcommands.push(
                     clap::Command::new(inner_api.to_string().to_lowercase())
-                        .subcommands(endpoint_map.iter().map(|(endpoint_name, endpoint)| {
-                            generate_endpoint_subcommand_for_command_api(endpoint_name, endpoint)
-                        }))
+                        .subcommands(sorted(endpoint_map).into_iter().map(
+                            |(endpoint_name, endpoint)| {
+                                generate_endpoint_subcommand_for_command_api(
+                                    endpoint_name,
+                                    endpoint,
+                                )
+                            },
+                        ))
                         .subcommand_required(true),
                 );
             }
             _ => {
                 commands.push(
                     clap::Command::new(api.to_string().to_lowercase())
-                        .subcommands(endpoint_map.iter().map(|(endpoint_name, endpoint)| {
-                            generate_endpoint_subcommand(endpoint_name, endpoint)
-                        }))
+                        .subcommands(sorted(endpoint_map).into_iter().map(
+                            |(endpoint_name, endpoint)| {
+                                generate_endpoint_subcommand(endpoint_name, endpoint)
+                            },
+                        ))
                         .subcommand_required(true),
                 );
             }
         }
     }
-    commands.push(command_command);
+    command_subcommands.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+    commands.push(
+        clap::Command::new("command")
+            .subcommands(command_subcommands)
+            .subcommand_required(true),
+    );
+    commands.sort_by(|a, b| a.get_name().cmp(b.get_name()));
 
     commands
 }
 
-#[allow(clippy::option_if_let_else)]
+/// Gets the entries of a map sorted by key, so that generated commands are ordered deterministically.
+fn sorted<K: Ord, V>(map: &HashMap<K, V>) -> Vec<(&K, &V)> {
+    let mut entries: Vec<(&K, &V)> = map.iter().collect();
+    entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    entries
+}
+
+/// Gets the values a field can be given if its schema restricts them with `valid_choices`, so that clap checks them and
+/// shell completions offer them.
+fn field_choices(field: &Field) -> Option<Vec<String>> {
+    field
+        .valid_choices
+        .as_ref()?
+        .iter()
+        .map(|choice| match choice {
+            Value::String(choice) => Some(choice.clone()),
+            Value::Number(choice) => Some(choice.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
 fn generate_parser_for_field(
     _name: &str,
     field: &Field,
     include_types: bool,
 ) -> Option<clap::builder::ValueParser> {
-    match field.data_type {
-        Type::String => {
-            if let Some(ref choices) = field.valid_choices {
-                if choices.iter().all(serde_json::Value::is_string) {
-                    let choices = choices
-                        .iter()
-                        .map(|x| {
-                            String::from(x.as_str().expect("is_string implies as_str will succeed"))
-                        })
-                        .collect::<Vec<_>>();
-                    Some(clap::builder::PossibleValuesParser::new(choices).into())
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        }
-        Type::Boolean if include_types => Some(clap::value_parser!(bool)),
-        Type::Boolean if !include_types => {
-            Some(clap::builder::PossibleValuesParser::new(["true", "false"]).into())
-        }
-        Type::Integer if include_types => Some(clap::value_parser!(i64).into()),
-        Type::Float if include_types => Some(clap::value_parser!(f64).into()),
+    use clap::builder::{PossibleValuesParser, TypedValueParser as _};
+
+    match (field.data_type, field_choices(field)) {
+        (Type::String, Some(choices)) => Some(PossibleValuesParser::new(choices).into()),
+        (Type::Boolean, _) if include_types => Some(clap::value_parser!(bool)),
+        (Type::Boolean, _) => Some(PossibleValuesParser::new(["true", "false"]).into()),
+        (Type::Integer, Some(choices)) if include_types => Some(
+            PossibleValuesParser::new(choices)
+                .try_map(|value| value.parse::<i64>())
+                .into(),
+        ),
+        (Type::Integer, Some(choices)) => Some(PossibleValuesParser::new(choices).into()),
+        (Type::Integer, None) if include_types => Some(clap::value_parser!(i64).into()),
+        (Type::Float, _) if include_types => Some(clap::value_parser!(f64).into()),
         _ => None,
     }
 }
 
 fn generate_endpoint_subcommand(name: &str, endpoint: &Endpoint) -> clap::Command {
-    let mut command = clap::Command::new(String::from(name));
-    for method in &endpoint.allowed_detail_http_methods {
-        command = match method {
-            Get => command.subcommand(
-                clap::Command::new("get")
+    let mut subcommands: Vec<clap::Command> = endpoint
+        .allowed_detail_http_methods
+        .iter()
+        .filter_map(|method| match method {
+            Get => Some(generate_get_subcommand(endpoint)),
+            Delete => Some(
+                clap::Command::new("delete")
                     .arg(
                         clap::Arg::new("object_id")
-                            .action(ArgAction::Set)
-                            .conflicts_with_all(["limit", "page_size", "stream"]),
-                    )
-                    .arg(
-                        clap::Arg::new("limit")
-                            .long("limit")
-                            .action(ArgAction::Set)
-                            .default_value("0")
-                            .value_parser(clap::value_parser!(usize)),
-                    )
-                    .arg(
-                        clap::Arg::new("page_size")
-                            .long("page_size")
-                            .action(ArgAction::Set)
-                            .default_value("500")
-                            .value_parser(clap::value_parser!(usize)),
+                            .help("The ID of the object to delete - if not given, the IDs to delete are read from STDIN, one per line")
+                            .action(ArgAction::Set),
                     )
                     .arg(
-                        clap::Arg::new("stream")
-                            .long("stream")
+                        clap::Arg::new("yes")
+                            .long("yes")
+                            .short('y')
+                            .help("Delete the object without asking for confirmation")
                             .action(ArgAction::SetTrue),
-                    )
-                    .args(endpoint.fields.iter().flat_map(|(name, field)| {
-                        generate_get_field_args(
-                            name,
-                            field,
-                            endpoint.filtering.get(name).unwrap_or(&Vec::new()),
-                        )
-                    })),
-            ),
-            Delete => command.subcommand(
-                clap::Command::new("delete").arg(
-                    clap::Arg::new("object_id")
-                        .required(true)
-                        .action(ArgAction::Set),
-                ),
+                    ),
             ),
-            Post => command.subcommand(
-                clap::Command::new("post").args(
-                    endpoint
-                        .fields
-                        .iter()
-                        .filter_map(|(name, field)| generate_post_field_arg(name, field)),
-                ),
+            Post => Some(
+                clap::Command::new("post")
+                    .args(payload_source_args())
+                    .args(
+                        sorted(&endpoint.fields)
+                            .into_iter()
+                            .filter_map(|(name, field)| generate_post_field_arg(name, field))
+                            .map(|arg| {
+                                // required fields may be given in the payload file instead
+                                if arg.is_required_set() {
+                                    arg.required(false)
+                                        .required_unless_present_any(["from_file", "from_stdin"])
+                                } else {
+                                    arg
+                                }
+                            }),
+                    ),
             ),
-            Patch => command.subcommand(
+            Patch => Some(
                 clap::Command::new("patch")
                     .arg(
                         clap::Arg::new("object_id")
                             .required(true)
                             .action(ArgAction::Set),
                     )
+                    .args(payload_source_args())
                     .args(
-                        endpoint
-                            .fields
-                            .iter()
+                        sorted(&endpoint.fields)
+                            .into_iter()
                             .filter_map(|(name, field)| generate_patch_field_arg(name, field)),
                     ),
             ),
-            Put => command,
-        }
+            Put => Some(
+                clap::Command::new("put")
+                    .about("Replace an object, setting every field that is not given to its default")
+                    .arg(
+                        clap::Arg::new("object_id")
+                            .required(true)
+                            .action(ArgAction::Set),
+                    )
+                    .args(
+                        sorted(&endpoint.fields)
+                            .into_iter()
+                            .filter_map(|(name, field)| generate_put_field_arg(name, field)),
+                    ),
+            ),
+        })
+        .collect();
+    let names: Vec<&str> = subcommands.iter().map(clap::Command::get_name).collect();
+    if names.contains(&"post") && names.contains(&"patch") {
+        subcommands.extend(generate_upsert_subcommand(endpoint));
+    }
+    subcommands.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+
+    clap::Command::new(String::from(name))
+        .subcommands(subcommands)
+        .subcommand_required(true)
+}
+
+/// Generates the `upsert` subcommand, which patches the object with the same value of the `--match-on` field if there
+/// is one, or posts a new object otherwise - if the endpoint has any fields that can be filtered on exactly.
+fn generate_upsert_subcommand(endpoint: &Endpoint) -> Option<clap::Command> {
+    let mut match_fields: Vec<&String> = endpoint
+        .fields
+        .keys()
+        .filter(|name| {
+            endpoint
+                .filtering
+                .get(*name)
+                .is_some_and(|filters| filters.iter().any(|filter| filter == "exact"))
+        })
+        .collect();
+    if match_fields.is_empty() {
+        return None;
+    }
+    match_fields.sort_unstable();
+
+    let has_name = match_fields.iter().any(|field| *field == "name");
+    let mut match_on = clap::Arg::new("match_on")
+        .long("match-on")
+        .help("The field used to find an existing object to patch, which must be given a value")
+        .value_name("field")
+        .value_parser(clap::builder::PossibleValuesParser::new(match_fields))
+        .action(ArgAction::Set);
+    match_on = if has_name {
+        match_on.default_value("name")
+    } else {
+        match_on.required(true)
+    };
+
+    Some(
+        clap::Command::new("upsert")
+            .about("Patch the object with the same value of the --match-on field, or post a new object if there is none")
+            .arg(match_on)
+            .args(payload_source_args())
+            .args(
+                sorted(&endpoint.fields)
+                    .into_iter()
+                    .filter_map(|(name, field)| generate_post_field_arg(name, field))
+                    // required fields are only needed if the object doesn't exist yet, which is checked once it's known
+                    .map(|arg| arg.required(false)),
+            ),
+    )
+}
+
+/// Generates the arguments to order the objects returned by a `GetAll` request, if the endpoint can be ordered.
+fn generate_ordering_args(ordering: &[String]) -> Vec<clap::Arg> {
+    if ordering.is_empty() {
+        return Vec::new();
+    }
+    vec![
+        clap::Arg::new("order_by")
+            .long("order-by")
+            .help("Order the objects by the given field")
+            .value_name("field")
+            .value_parser(clap::builder::PossibleValuesParser::new(ordering.to_vec()))
+            .conflicts_with("object_id")
+            .action(ArgAction::Set),
+        clap::Arg::new("desc")
+            .long("desc")
+            .help("Order the objects in descending order")
+            .requires("order_by")
+            .action(ArgAction::SetTrue),
+    ]
+}
+
+/// Generates the argument to inline the objects that related fields refer to, if the endpoint has related fields.
+fn generate_expand_arg(endpoint: &Endpoint) -> Option<clap::Arg> {
+    let mut related: Vec<&String> = endpoint
+        .fields
+        .iter()
+        .filter(|(_, field)| field.data_type == Type::Related)
+        .map(|(name, _)| name)
+        .collect();
+    if related.is_empty() {
+        return None;
     }
-    command.subcommand_required(true)
+    related.sort_unstable();
+    Some(
+        clap::Arg::new("expand")
+            .long("expand")
+            .help("Replace the resource URIs of the given related field with the objects they refer to - may be repeated")
+            .value_name("field")
+            .value_parser(clap::builder::PossibleValuesParser::new(related))
+            .action(ArgAction::Append),
+    )
+}
+
+/// Generates the arguments to read the fields of a `post` or `patch` request from JSON instead of options.
+fn payload_source_args() -> [clap::Arg; 2] {
+    [
+        clap::Arg::new("from_file")
+            .long("from-file")
+            .help("Read the fields of the object from a JSON file, which fields given as options take precedence over")
+            .value_name("path")
+            .value_parser(clap::value_parser!(PathBuf))
+            .conflicts_with("from_stdin")
+            .action(ArgAction::Set),
+        clap::Arg::new("from_stdin")
+            .long("from-stdin")
+            .help("Read the fields of the object as JSON from STDIN, which fields given as options take precedence over")
+            .action(ArgAction::SetTrue),
+    ]
+}
+
+/// Generates the `get` subcommand of an endpoint, which gets a single object or lists them.
+fn generate_get_subcommand(endpoint: &Endpoint) -> clap::Command {
+    clap::Command::new("get")
+        .arg(
+            clap::Arg::new("object_id")
+                .action(ArgAction::Set)
+                .conflicts_with_all(["limit", "offset", "page_size", "parallel", "stream"]),
+        )
+        .arg(
+            clap::Arg::new("limit")
+                .long("limit")
+                .action(ArgAction::Set)
+                .default_value("0")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            clap::Arg::new("offset")
+                .long("offset")
+                .help("Skip this many objects before the first one returned, e.g. to resume an interrupted request - the limit still counts the objects returned")
+                .action(ArgAction::Set)
+                .default_value("0")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            clap::Arg::new("page_size")
+                .long("page_size")
+                .action(ArgAction::Set)
+                .default_value("500")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            clap::Arg::new("stream")
+                .long("stream")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("parallel")
+                .long("parallel")
+                .help("Fetch several pages at once, which is quicker for endpoints with many objects - the objects are still printed in order")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("filter_file")
+                .long("filter-file")
+                .help("Read filters from a JSON object of filter names and values, which filters given as options take precedence over")
+                .value_name("path")
+                .value_parser(clap::value_parser!(PathBuf))
+                .conflicts_with("object_id")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("fields")
+                .long("fields")
+                .help("Only print the given comma-separated fields of each object, e.g. id,name")
+                .value_name("fields")
+                .value_delimiter(',')
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("count")
+                .long("count")
+                .help("Print the number of objects that match the filters instead of the objects, fetching only one of them")
+                .conflicts_with_all(["object_id", "limit", "offset", "stream", "parallel", "fields"])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("exists")
+                .long("exists")
+                .help("Check whether the object exists without fetching it, exiting with a non-zero code if it does not")
+                .requires("object_id")
+                .action(ArgAction::SetTrue),
+        )
+        .args(generate_ordering_args(&endpoint.ordering))
+        .args(generate_expand_arg(endpoint))
+        .args(
+            sorted(&endpoint.fields)
+                .into_iter()
+                .flat_map(|(name, field)| {
+                    generate_get_field_args(
+                        name,
+                        field,
+                        endpoint.filtering.get(name).unwrap_or(&Vec::new()),
+                    )
+                }),
+        )
 }
 
 fn generate_endpoint_subcommand_for_command_api(name: &str, endpoint: &Endpoint) -> clap::Command {
     clap::Command::new(String::from(name)).args(
-        endpoint
-            .fields
-            .iter()
+        sorted(&endpoint.fields)
+            .into_iter()
             .filter_map(|(name, field)| generate_post_field_arg(name, field)),
     )
 }
@@ -243,16 +544,25 @@ const fn is_post_field(_name: &str, field: &Field) -> bool {
     !field.readonly
 }
 
+/// Whether a field must be given when creating an object, as it can't be blank or null and has no default.
+const fn is_required_post_field(field: &Field) -> bool {
+    !field.blank && field.default.is_none() && !field.nullable
+}
+
 fn is_patch_field(name: &str, field: &Field) -> bool {
     !field.readonly && name != "id"
 }
 
+fn is_put_field(name: &str, field: &Field) -> bool {
+    is_patch_field(name, field)
+}
+
 fn generate_post_field_arg<'a>(name: &'a str, field: &'a Field) -> Option<clap::Arg> {
     if is_post_field(name, field) {
         let mut arg = clap::Arg::new(String::from(name))
             .long(String::from(name))
             .help(String::from(&field.help_text))
-            .required((!field.blank && field.default.is_none()) && !field.nullable)
+            .required(is_required_post_field(field))
             .action(ArgAction::Set);
 
         if let Some(value_parser) = generate_parser_for_field(name, field, true) {
@@ -329,6 +639,16 @@ fn generate_patch_field_arg<'a>(name: &'a str, field: &'a Field) -> Option<clap:
     }
 }
 
+/// Generates an argument for a field of a PUT request, which replaces the whole object so needs the same fields as a
+/// POST request.
+fn generate_put_field_arg<'a>(name: &'a str, field: &'a Field) -> Option<clap::Arg> {
+    if is_put_field(name, field) {
+        generate_post_field_arg(name, field)
+    } else {
+        None
+    }
+}
+
 pub fn create_get_filters(endpoint: &Endpoint, args: &ArgMatches) -> HashMap<String, String> {
     endpoint
         .fields
@@ -348,33 +668,186 @@ pub fn create_get_filters(endpoint: &Endpoint, args: &ArgMatches) -> HashMap<Str
         .collect()
 }
 
-pub fn create_post_payload(endpoint: &Endpoint, args: &ArgMatches) -> Value {
+/// Reads the filters of a `GetAll` request from a JSON file, checking that each is a filter allowed by the schema.
+pub fn read_filter_file(
+    endpoint: &Endpoint,
+    path: &Path,
+) -> Result<HashMap<String, String>, lib::error::UserFriendly> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        lib::error::UserFriendly::new(format!("failed to read {}: {e}", path.display()))
+    })?;
+    let Ok(Value::Object(filters)) = serde_json::from_str(&contents) else {
+        return Err(lib::error::UserFriendly::new(format!(
+            "{} must contain a JSON object of filter names and values",
+            path.display()
+        )));
+    };
+
+    let allowed: Vec<String> = endpoint
+        .fields
+        .keys()
+        .flat_map(|name| {
+            get_filter_args(
+                name,
+                endpoint.filtering.get(name).unwrap_or(&Vec::new()),
+                true,
+            )
+        })
+        .collect();
+    filters
+        .into_iter()
+        .map(|(filter, value)| {
+            if !allowed.contains(&filter) {
+                return Err(lib::error::UserFriendly::new(format!(
+                    "{filter} is not a valid filter for this endpoint"
+                )));
+            }
+            let value = match value {
+                Value::String(value) => value,
+                Value::Number(_) | Value::Bool(_) => value.to_string(),
+                _ => {
+                    return Err(lib::error::UserFriendly::new(format!(
+                        "the value of filter {filter} must be a string, number or boolean"
+                    )))
+                }
+            };
+            Ok((filter, value))
+        })
+        .collect()
+}
+
+pub fn create_post_payload(
+    endpoint: &Endpoint,
+    args: &ArgMatches,
+) -> Result<Value, lib::error::UserFriendly> {
     let payload: Map<String, Value> = endpoint
         .fields
         .iter()
         .filter(|(name, field)| is_post_field(name, field))
-        .filter_map(|(name, field)| parse_arg_to_json(args, name, field).map(|v| (name.clone(), v)))
-        .collect();
+        .filter_map(|(name, field)| {
+            parse_arg_to_json(args, name, field).map(|v| v.map(|v| (name.clone(), v)))
+        })
+        .collect::<Result<_, _>>()?;
 
-    serde_json::to_value(payload).unwrap()
+    Ok(serde_json::to_value(payload).unwrap())
 }
 
-pub fn create_patch_payload(endpoint: &Endpoint, args: &ArgMatches) -> Value {
+pub fn create_patch_payload(
+    endpoint: &Endpoint,
+    args: &ArgMatches,
+) -> Result<Value, lib::error::UserFriendly> {
     let payload: Map<String, Value> = endpoint
         .fields
         .iter()
         .filter(|(name, field)| is_patch_field(name, field))
-        .filter_map(|(name, field)| parse_arg_to_json(args, name, field).map(|v| (name.clone(), v)))
-        .collect();
+        .filter_map(|(name, field)| {
+            parse_arg_to_json(args, name, field).map(|v| v.map(|v| (name.clone(), v)))
+        })
+        .collect::<Result<_, _>>()?;
 
-    serde_json::to_value(payload).unwrap()
+    Ok(serde_json::to_value(payload).unwrap())
 }
 
-fn parse_arg_to_json(args: &ArgMatches, name: &str, field: &Field) -> Option<Value> {
+pub fn create_put_payload(
+    endpoint: &Endpoint,
+    args: &ArgMatches,
+) -> Result<Value, lib::error::UserFriendly> {
+    let payload: Map<String, Value> = endpoint
+        .fields
+        .iter()
+        .filter(|(name, field)| is_put_field(name, field))
+        .filter_map(|(name, field)| {
+            parse_arg_to_json(args, name, field).map(|v| v.map(|v| (name.clone(), v)))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(serde_json::to_value(payload).unwrap())
+}
+
+/// Checks a `post` or `patch` payload against the schema of its endpoint before it is sent, so that every problem is
+/// reported at once rather than by the management node one request at a time.
+///
+/// Fields that must be given when creating an object are only checked if `require_fields` is set.
+pub fn validate_payload(
+    endpoint: &Endpoint,
+    payload: &Value,
+    require_fields: bool,
+) -> Result<(), lib::error::UserFriendly> {
+    let Value::Object(payload) = payload else {
+        return Ok(());
+    };
+    let mut names: Vec<&String> = endpoint.fields.keys().collect();
+    names.sort_unstable();
+
+    let mut violations = Vec::new();
+    for name in names {
+        let field = &endpoint.fields[name];
+        match payload.get(name) {
+            None if require_fields
+                && is_post_field(name, field)
+                && is_required_post_field(field) =>
+            {
+                violations.push(format!("{name} is required"));
+            }
+            None => {}
+            Some(Value::Null) if !field.nullable => {
+                violations.push(format!("{name} cannot be null"));
+            }
+            Some(Value::String(value)) if value.is_empty() && !field.blank => {
+                violations.push(format!("{name} cannot be blank"));
+            }
+            Some(value) => {
+                let Some(choices) = &field.valid_choices else {
+                    continue;
+                };
+                let values = match value {
+                    Value::Null => continue,
+                    Value::Array(values) => values.iter().collect(),
+                    value => vec![value],
+                };
+                if values.into_iter().any(|value| !choices.contains(value)) {
+                    let choices: Vec<String> = choices.iter().map(Value::to_string).collect();
+                    violations.push(format!("{name} must be one of: {}", choices.join(", ")));
+                }
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        return Ok(());
+    }
+    Err(lib::error::UserFriendly::new(format!(
+        "the request was not sent as it is invalid:\n  {}",
+        violations.join("\n  ")
+    )))
+}
+
+/// Parses newline-separated object IDs, e.g. piped from a previous `get`, ignoring blank lines and surrounding quotes.
+pub fn parse_object_ids(input: &str) -> impl Iterator<Item = String> + '_ {
+    input
+        .lines()
+        .map(|line| line.trim().trim_matches('"'))
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+}
+
+/// Reads a file given for a file field, encoded as base64 as the management API expects.
+fn read_file_arg(name: &str, path: &str) -> Result<Value, lib::error::UserFriendly> {
+    let contents = std::fs::read(path).map_err(|e| {
+        lib::error::UserFriendly::new(format!("failed to read {path} for {name}: {e}"))
+    })?;
+    Ok(json!(BASE64_STANDARD.encode(contents)))
+}
+
+fn parse_arg_to_json(
+    args: &ArgMatches,
+    name: &str,
+    field: &Field,
+) -> Option<Result<Value, lib::error::UserFriendly>> {
     if !args.contains_id(name) {
         return None;
     }
-    Some(
+    Some(Ok(
         if field.nullable
             && args
                 .get_raw(name)
@@ -413,25 +886,32 @@ fn parse_arg_to_json(args: &ArgMatches, name: &str, field: &Field) -> Option<Val
                     let value = args.get_one::<String>(name).unwrap();
                     serde_json::from_str(value).unwrap()
                 }
-                Type::File => todo!(),
+                Type::File => {
+                    return Some(read_file_arg(name, args.get_one::<String>(name).unwrap()));
+                }
             }
         },
-    )
+    ))
 }
 
 #[cfg(test)]
 #[allow(clippy::cognitive_complexity)]
 mod tests {
     use std::collections::{HashMap, HashSet};
+    use std::io::Write;
 
     use clap::error::ErrorKind::InvalidSubcommand;
     use clap::{arg, Command};
     use googletest::prelude::*;
     use lib::mcu::schema::{Endpoint, Field, Methods, Type};
-    use lib::mcu::Api;
+    use lib::mcu::{Api, CommandApi};
     use serde_json::json;
+    use test_helpers::get_test_context;
 
-    use super::{create_patch_payload, create_post_payload, generate_subcommands};
+    use super::{
+        create_patch_payload, create_post_payload, generate_post_field_arg, generate_subcommands,
+        parse_object_ids, validate_payload, TeeWriter,
+    };
 
     #[test]
     fn test_basic_create_post_payload() {
@@ -472,6 +952,7 @@ mod tests {
             ]),
             filtering: HashMap::new(),
             ordering: Vec::new(),
+            schema_version: None,
         };
 
         let args = Command::new("Test")
@@ -480,7 +961,7 @@ mod tests {
             .unwrap();
 
         // Act
-        let payload = create_post_payload(&endpoint, &args);
+        let payload = create_post_payload(&endpoint, &args).unwrap();
 
         // Assert
         assert_that!(
@@ -492,6 +973,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_create_post_payload_reads_file_field() {
+        // Arrange
+        let test_context = get_test_context();
+        let path = test_context.get_test_dir().join("certificate.pem");
+        std::fs::write(&path, "-----BEGIN CERTIFICATE-----").unwrap();
+        let endpoint = Endpoint {
+            allowed_detail_http_methods: HashSet::from([Methods::Post]),
+            allowed_list_http_methods: HashSet::default(),
+            default_limit: 10,
+            fields: HashMap::from([(
+                String::from("certificate"),
+                Field {
+                    blank: false,
+                    data_type: Type::File,
+                    default: None,
+                    help_text: String::new(),
+                    nullable: false,
+                    readonly: false,
+                    related_type: None,
+                    unique: false,
+                    valid_choices: None,
+                },
+            )]),
+            filtering: HashMap::new(),
+            ordering: Vec::new(),
+            schema_version: None,
+        };
+
+        let args = Command::new("Test")
+            .arg(arg!(--certificate <certificate>))
+            .try_get_matches_from(["test", "--certificate", path.to_str().unwrap()])
+            .unwrap();
+
+        // Act
+        let payload = create_post_payload(&endpoint, &args);
+
+        // Assert
+        assert_that!(
+            payload,
+            ok(eq(&json!({
+                "certificate": "LS0tLS1CRUdJTiBDRVJUSUZJQ0FURS0tLS0t",
+            })))
+        );
+    }
+
+    #[test]
+    fn test_create_post_payload_missing_file() {
+        // Arrange
+        let test_context = get_test_context();
+        let path = test_context.get_test_dir().join("missing.pem");
+        let endpoint = Endpoint {
+            allowed_detail_http_methods: HashSet::from([Methods::Post]),
+            allowed_list_http_methods: HashSet::default(),
+            default_limit: 10,
+            fields: HashMap::from([(
+                String::from("certificate"),
+                Field {
+                    blank: false,
+                    data_type: Type::File,
+                    default: None,
+                    help_text: String::new(),
+                    nullable: false,
+                    readonly: false,
+                    related_type: None,
+                    unique: false,
+                    valid_choices: None,
+                },
+            )]),
+            filtering: HashMap::new(),
+            ordering: Vec::new(),
+            schema_version: None,
+        };
+
+        let args = Command::new("Test")
+            .arg(arg!(--certificate <certificate>))
+            .try_get_matches_from(["test", "--certificate", path.to_str().unwrap()])
+            .unwrap();
+
+        // Act
+        let payload = create_post_payload(&endpoint, &args);
+
+        // Assert
+        assert_that!(
+            payload,
+            err(displays_as(contains_substring("for certificate")))
+        );
+    }
+
     #[test]
     fn test_basic_create_patch_payload() {
         // Arrange
@@ -531,6 +1101,7 @@ mod tests {
             ]),
             filtering: HashMap::new(),
             ordering: Vec::new(),
+            schema_version: None,
         };
 
         let args = Command::new("Test")
@@ -539,7 +1110,7 @@ mod tests {
             .unwrap();
 
         // Act
-        let payload = create_patch_payload(&endpoint, &args);
+        let payload = create_patch_payload(&endpoint, &args).unwrap();
 
         // Assert
         assert_that!(
@@ -561,6 +1132,7 @@ mod tests {
             fields: HashMap::new(),
             filtering: HashMap::new(),
             ordering: Vec::new(),
+            schema_version: None,
         };
         let schemas = HashMap::from([(
             Api::Status,
@@ -595,4 +1167,263 @@ mod tests {
             eq(InvalidSubcommand)
         );
     }
+
+    fn get_test_schemas() -> HashMap<Api, HashMap<String, Endpoint>> {
+        let field = Field {
+            blank: true,
+            data_type: Type::String,
+            default: None,
+            help_text: String::from("some help text"),
+            nullable: false,
+            readonly: false,
+            related_type: None,
+            unique: false,
+            valid_choices: None,
+        };
+        let endpoint = Endpoint {
+            allowed_detail_http_methods: HashSet::from([
+                Methods::Get,
+                Methods::Post,
+                Methods::Patch,
+                Methods::Delete,
+            ]),
+            allowed_list_http_methods: HashSet::default(),
+            default_limit: 10,
+            fields: ["name", "description", "id", "tag", "pin", "aliases"]
+                .into_iter()
+                .map(|name| (String::from(name), field.clone()))
+                .collect(),
+            filtering: HashMap::from([(String::from("name"), vec![String::from("exact")])]),
+            ordering: Vec::new(),
+            schema_version: None,
+        };
+        let endpoints: HashMap<String, Endpoint> =
+            ["conference", "alias", "worker_vm", "system_location"]
+                .into_iter()
+                .map(|name| (String::from(name), endpoint.clone()))
+                .collect();
+
+        HashMap::from([
+            (Api::Configuration, endpoints.clone()),
+            (Api::Status, endpoints.clone()),
+            (Api::History, endpoints.clone()),
+            (Api::Command(CommandApi::Conference), endpoints.clone()),
+            (Api::Command(CommandApi::Participant), endpoints),
+        ])
+    }
+
+    fn render_all_help(command: &mut Command) -> Vec<String> {
+        let mut help = vec![command.render_long_help().to_string()];
+        for subcommand in command.get_subcommands_mut() {
+            help.extend(render_all_help(subcommand));
+        }
+        help
+    }
+
+    #[test]
+    fn test_generate_subcommands_ordering_is_deterministic() {
+        // Arrange
+        let first_schemas = get_test_schemas();
+        let second_schemas = get_test_schemas();
+
+        // Act
+        let mut first = Command::new("test").subcommands(generate_subcommands(&first_schemas));
+        let mut second = Command::new("test").subcommands(generate_subcommands(&second_schemas));
+
+        // Assert
+        assert_that!(
+            render_all_help(&mut first),
+            eq(&render_all_help(&mut second))
+        );
+        assert_that!(
+            first
+                .get_subcommands()
+                .map(Command::get_name)
+                .collect::<Vec<_>>(),
+            elements_are![
+                eq("command"),
+                eq("configuration"),
+                eq("history"),
+                eq("status")
+            ]
+        );
+        let conference = first
+            .find_subcommand("configuration")
+            .and_then(|c| c.find_subcommand("conference"))
+            .unwrap();
+        assert_that!(
+            conference
+                .get_subcommands()
+                .map(Command::get_name)
+                .collect::<Vec<_>>(),
+            elements_are![
+                eq("delete"),
+                eq("get"),
+                eq("patch"),
+                eq("post"),
+                eq("upsert")
+            ]
+        );
+        assert_that!(
+            conference
+                .find_subcommand("post")
+                .unwrap()
+                .get_arguments()
+                .filter_map(clap::Arg::get_long)
+                .collect::<Vec<_>>(),
+            elements_are![
+                eq("aliases"),
+                eq("description"),
+                eq("id"),
+                eq("name"),
+                eq("pin"),
+                eq("tag")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tee_writer_writes_to_both() {
+        // Arrange
+        let mut first = Vec::new();
+        let mut second = Vec::new();
+
+        // Act
+        {
+            let mut tee = TeeWriter::new(&mut first, &mut second);
+            write!(tee, "hello {}", "world").unwrap();
+            tee.flush().unwrap();
+        }
+
+        // Assert
+        assert_that!(String::from_utf8(first).unwrap(), eq("hello world"));
+        assert_that!(String::from_utf8(second).unwrap(), eq("hello world"));
+    }
+
+    #[test]
+    fn test_parse_object_ids() {
+        // Arrange
+        let input = "1\n  2 \n\n\"3\"\n";
+
+        // Act
+        let ids: Vec<String> = parse_object_ids(input).collect();
+
+        // Assert
+        assert_that!(ids, elements_are![eq("1"), eq("2"), eq("3")]);
+    }
+
+    fn validation_endpoint() -> Endpoint {
+        let field = |blank, nullable, valid_choices: Option<Vec<serde_json::Value>>| Field {
+            blank,
+            data_type: Type::String,
+            default: None,
+            help_text: String::new(),
+            nullable,
+            readonly: false,
+            related_type: None,
+            unique: false,
+            valid_choices,
+        };
+        Endpoint {
+            allowed_detail_http_methods: HashSet::from([Methods::Patch]),
+            allowed_list_http_methods: HashSet::from([Methods::Post]),
+            default_limit: 10,
+            fields: HashMap::from([
+                (String::from("name"), field(false, false, None)),
+                (String::from("description"), field(true, true, None)),
+                (
+                    String::from("service_type"),
+                    field(
+                        true,
+                        false,
+                        Some(vec![json!("conference"), json!("lecture")]),
+                    ),
+                ),
+            ]),
+            filtering: HashMap::new(),
+            ordering: Vec::new(),
+            schema_version: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_payload_lists_all_violations() {
+        // Arrange
+        let endpoint = validation_endpoint();
+        let payload = json!({"description": null, "service_type": "meeting"});
+
+        // Act
+        let result = validate_payload(&endpoint, &payload, true);
+
+        // Assert
+        assert_that!(
+            result,
+            err(displays_as(eq(concat!(
+                "the request was not sent as it is invalid:\n",
+                "  name is required\n",
+                "  service_type must be one of: \"conference\", \"lecture\"",
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_validate_payload_allows_partial_patch() {
+        // Arrange
+        let endpoint = validation_endpoint();
+        let payload = json!({"service_type": "lecture"});
+
+        // Act
+        let result = validate_payload(&endpoint, &payload, false);
+
+        // Assert
+        assert_that!(result, ok(eq(&())));
+    }
+
+    #[test]
+    fn test_validate_payload_rejects_blank_and_null() {
+        // Arrange
+        let endpoint = validation_endpoint();
+        let payload = json!({"name": "", "service_type": null});
+
+        // Act
+        let result = validate_payload(&endpoint, &payload, false);
+
+        // Assert
+        assert_that!(
+            result,
+            err(displays_as(eq(concat!(
+                "the request was not sent as it is invalid:\n",
+                "  name cannot be blank\n",
+                "  service_type cannot be null",
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_integer_field_with_valid_choices() {
+        // Arrange
+        let field = Field {
+            blank: false,
+            data_type: Type::Integer,
+            default: Some(json!(720)),
+            help_text: String::new(),
+            nullable: false,
+            readonly: false,
+            related_type: None,
+            unique: false,
+            valid_choices: Some(vec![json!(720), json!(1080)]),
+        };
+        let command =
+            Command::new("test").arg(generate_post_field_arg("max_height", &field).unwrap());
+
+        // Act
+        let valid = command
+            .clone()
+            .try_get_matches_from(["test", "--max_height", "1080"]);
+        let invalid = command.try_get_matches_from(["test", "--max_height", "480"]);
+
+        // Assert
+        assert_that!(valid.unwrap().get_one::<i64>("max_height"), some(eq(&1080)));
+        assert_that!(invalid, err(anything()));
+    }
 }
@@ -0,0 +1,1943 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    io::Write,
+    str::FromStr,
+};
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, NaiveDateTime, Utc,
+};
+use colored_json::to_colored_json_auto as to_coloured_json_auto;
+use futures::{Stream, TryStreamExt};
+use lib::mcu::{
+    schema::{Endpoint, Type},
+    Api,
+};
+use log::debug;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use serde_json::Value;
+
+/// The format used to print API responses.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    /// Compact JSON with a line for each object of list responses, written as each object arrives.
+    JsonLines,
+    /// YAML documents, with a document for each object of list responses.
+    Yaml,
+    /// An aligned table of list responses, with JSON for other responses.
+    Table,
+    Xml(XmlOptions),
+    /// Shell variable assignments for each top-level field, with objects separated by blank lines.
+    Pairs,
+    /// `export` statements for each top-level field, with the fields of list responses prefixed by the object's index.
+    Kv,
+    /// Numeric fields as gauges in the Prometheus text exposition format, named with the given prefix.
+    Prometheus(String),
+    /// An SQL `INSERT` statement for each object.
+    Sql(SqlOptions),
+    /// A CSV row for each object, with a sorted column for each top-level field.
+    ///
+    /// The columns are the given fields of the endpoint if known, so that rows can be written as objects arrive, and
+    /// otherwise every field of any object.
+    Csv(Vec<String>),
+    /// Pipe each object through an external command.
+    #[cfg(feature = "transform")]
+    Transform(String),
+}
+
+impl OutputFormat {
+    /// Names Prometheus metrics after the API and resource that the objects come from.
+    #[must_use]
+    pub fn for_resource(self, api: Api, resource: &str) -> Self {
+        match self {
+            Self::Prometheus(prefix) => Self::Prometheus(format!("{prefix}_{api}_{resource}")),
+            Self::Sql(options) => Self::Sql(SqlOptions {
+                table: options.table.or_else(|| Some(String::from(resource))),
+                ..options
+            }),
+            format => format,
+        }
+    }
+
+    /// Uses the schema of the endpoint that the objects come from to decide how to write SQL values and which CSV
+    /// columns to write.
+    ///
+    /// Without a schema for the endpoint, SQL values are written according to their JSON type.
+    #[must_use]
+    pub fn for_endpoint(self, endpoint: Option<&Endpoint>) -> Self {
+        match (self, endpoint) {
+            (Self::Csv(_), Some(endpoint)) => {
+                let mut columns: Vec<String> = endpoint.fields.keys().cloned().collect();
+                columns.sort_unstable();
+                Self::Csv(columns)
+            }
+            (Self::Sql(options), Some(endpoint)) => Self::Sql(SqlOptions {
+                types: endpoint
+                    .fields
+                    .iter()
+                    .map(|(name, field)| (name.clone(), SqlType::from(field.data_type)))
+                    .collect(),
+                ..options
+            }),
+            (format, _) => format,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SqlOptions {
+    /// Name of the table to insert into, which defaults to the name of the resource.
+    pub table: Option<String>,
+    /// The type of each field of the endpoint, from its schema.
+    pub types: HashMap<String, SqlType>,
+}
+
+/// The kind of SQL literal that values of a field are written as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SqlType {
+    Numeric,
+    Boolean,
+    Text,
+}
+
+impl From<Type> for SqlType {
+    fn from(data_type: Type) -> Self {
+        match data_type {
+            Type::Integer | Type::Float => Self::Numeric,
+            Type::Boolean => Self::Boolean,
+            Type::String
+            | Type::DateTime
+            | Type::Date
+            | Type::Time
+            | Type::Related
+            | Type::List
+            | Type::File => Self::Text,
+        }
+    }
+}
+
+/// Whether scalar fields of an object are rendered as XML attributes or child elements.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum XmlStyle {
+    Attributes,
+    #[default]
+    Elements,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XmlOptions {
+    /// Name of the document root element.
+    pub root: String,
+    pub style: XmlStyle,
+}
+
+impl Default for XmlOptions {
+    fn default() -> Self {
+        Self {
+            root: String::from("root"),
+            style: XmlStyle::default(),
+        }
+    }
+}
+
+/// How date and time fields in API responses are reformatted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DateFormat {
+    /// A `strftime` format string.
+    Strftime(String),
+    /// How long ago (or until) the date and time, e.g. `3 hours ago`.
+    Relative,
+}
+
+impl FromStr for DateFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "relative" {
+            return Ok(Self::Relative);
+        }
+        if StrftimeItems::new(s).any(|item| matches!(item, Item::Error)) {
+            return Err(format!("invalid date format: {s}"));
+        }
+        Ok(Self::Strftime(String::from(s)))
+    }
+}
+
+/// Reformats the date and time fields of objects from an endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DateFormatter {
+    format: DateFormat,
+    fields: HashSet<String>,
+}
+
+impl DateFormatter {
+    /// Creates a formatter for the date and time fields of an endpoint.
+    ///
+    /// Without a schema for the endpoint, no fields are reformatted.
+    pub fn for_endpoint(format: DateFormat, endpoint: Option<&Endpoint>) -> Self {
+        let fields = endpoint
+            .map(|endpoint| {
+                endpoint
+                    .fields
+                    .iter()
+                    .filter(|(_, field)| field.data_type == Type::DateTime)
+                    .map(|(name, _)| name.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { format, fields }
+    }
+
+    /// Reformats the date and time fields of an object, or of each object in a list.
+    ///
+    /// Values that cannot be parsed as a date and time are left as they are.
+    pub fn apply(&self, value: &mut Value, now: DateTime<Utc>) {
+        match value {
+            Value::Array(objects) => {
+                for object in objects {
+                    self.apply(object, now);
+                }
+            }
+            Value::Object(fields) => {
+                for (name, field) in fields {
+                    if !self.fields.contains(name) {
+                        continue;
+                    }
+                    if let Some(datetime) = field.as_str().and_then(parse_datetime) {
+                        *field = Value::String(match &self.format {
+                            DateFormat::Strftime(format) => datetime.format(format).to_string(),
+                            DateFormat::Relative => relative_time(datetime, now),
+                        });
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Parses an ISO 8601 date and time from the API, which is in UTC if no offset is given.
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+                .map(|datetime| datetime.and_utc())
+        })
+        .ok()
+}
+
+/// Describes how long ago or until a date and time is, in the largest whole unit, e.g. `3 hours ago`.
+fn relative_time(datetime: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    const UNITS: [(&str, i64); 6] = [
+        ("year", 365 * 24 * 60 * 60),
+        ("month", 30 * 24 * 60 * 60),
+        ("week", 7 * 24 * 60 * 60),
+        ("day", 24 * 60 * 60),
+        ("hour", 60 * 60),
+        ("minute", 60),
+    ];
+
+    let seconds = (now - datetime).num_seconds();
+    let Some((unit, count)) = UNITS
+        .iter()
+        .find(|(_, length)| seconds.abs() >= *length)
+        .map(|(unit, length)| (unit, seconds.abs() / length))
+    else {
+        return String::from("just now");
+    };
+
+    let plural = if count == 1 { "" } else { "s" };
+    if seconds > 0 {
+        format!("{count} {unit}{plural} ago")
+    } else {
+        format!("in {count} {unit}{plural}")
+    }
+}
+
+/// Writes API responses in the chosen output format.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OutputSink {
+    format: OutputFormat,
+    colour: bool,
+    dates: Option<DateFormatter>,
+    fields: Option<HashSet<String>>,
+}
+
+impl OutputSink {
+    pub const fn new(format: OutputFormat, colour: bool) -> Self {
+        Self {
+            format,
+            colour,
+            dates: None,
+            fields: None,
+        }
+    }
+
+    /// Reformats date and time fields before writing responses.
+    #[must_use]
+    pub fn with_dates(mut self, dates: Option<DateFormatter>) -> Self {
+        self.dates = dates;
+        self
+    }
+
+    /// Leaves out all but the given top-level fields of objects before writing responses.
+    #[must_use]
+    pub fn with_fields(mut self, fields: Option<HashSet<String>>) -> Self {
+        if let (OutputFormat::Csv(columns), Some(fields)) = (&mut self.format, &fields) {
+            columns.retain(|column| fields.contains(column));
+        }
+        self.fields = fields;
+        self
+    }
+
+    /// Writes a single value as a complete document.
+    pub async fn write_value(
+        &self,
+        out: &mut (dyn Write + Send),
+        mut value: Value,
+    ) -> anyhow::Result<()> {
+        if let Some(dates) = &self.dates {
+            dates.apply(&mut value, Utc::now());
+        }
+        if let Some(fields) = &self.fields {
+            project(&mut value, fields);
+        }
+        match &self.format {
+            OutputFormat::Json => self.write_json(out, &value)?,
+            OutputFormat::JsonLines => {
+                if let Value::Array(objects) = &value {
+                    for object in objects {
+                        write_json_line(out, object)?;
+                    }
+                } else {
+                    write_json_line(out, &value)?;
+                }
+            }
+            OutputFormat::Yaml => {
+                write_yaml(out, &value)?;
+                self.end_yaml(out)?;
+            }
+            OutputFormat::Table => self.write_table_or_json(out, value)?,
+            OutputFormat::Xml(options) => {
+                XmlWriter::new(out, options.clone()).write_document(&value)?;
+            }
+            OutputFormat::Pairs => {
+                if let Value::Array(objects) = &value {
+                    for (i, object) in objects.iter().enumerate() {
+                        if i > 0 {
+                            writeln!(out)?;
+                        }
+                        write_pairs(out, object)?;
+                    }
+                } else {
+                    write_pairs(out, &value)?;
+                }
+            }
+            OutputFormat::Kv => {
+                if let Value::Array(objects) = &value {
+                    for (i, object) in objects.iter().enumerate() {
+                        write_kv(out, Some(i), object)?;
+                    }
+                } else {
+                    write_kv(out, None, &value)?;
+                }
+            }
+            OutputFormat::Prometheus(prefix) => match &value {
+                Value::Array(objects) => write_prometheus(out, prefix, objects)?,
+                object => write_prometheus(out, prefix, std::slice::from_ref(object))?,
+            },
+            OutputFormat::Sql(options) => {
+                if let Value::Array(objects) = &value {
+                    for object in objects {
+                        write_sql(out, options, object)?;
+                    }
+                } else {
+                    write_sql(out, options, &value)?;
+                }
+            }
+            OutputFormat::Csv(columns) => {
+                let objects = match value {
+                    Value::Array(objects) => objects,
+                    object => vec![object],
+                };
+                let columns = if columns.is_empty() {
+                    csv_columns(&objects)?
+                } else {
+                    columns.clone()
+                };
+                write_csv_header(out, &columns)?;
+                for object in &objects {
+                    write_csv_row(out, &columns, object)?;
+                }
+            }
+            #[cfg(feature = "transform")]
+            OutputFormat::Transform(command) => {
+                let objects = futures::stream::iter([Ok::<_, std::convert::Infallible>(value)]);
+                transform(out, command, objects).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a list of objects as a single document.
+    ///
+    /// If `stream` is set, JSON is written as a separate document per object so that objects are output as they
+    /// arrive.
+    pub async fn write_objects<E>(
+        &self,
+        out: &mut (dyn Write + Send),
+        objects: impl Stream<Item = Result<Value, E>> + Unpin,
+        stream: bool,
+    ) -> anyhow::Result<()>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let now = Utc::now();
+        let mut objects = objects.map_ok(|mut object| {
+            if let Some(dates) = &self.dates {
+                dates.apply(&mut object, now);
+            }
+            if let Some(fields) = &self.fields {
+                project(&mut object, fields);
+            }
+            object
+        });
+        match &self.format {
+            OutputFormat::Json if stream => {
+                while let Some(object) = objects.try_next().await? {
+                    self.write_json(out, &object)?;
+                }
+            }
+            OutputFormat::Json => {
+                let objects: Vec<Value> = objects.try_collect().await?;
+                self.write_json(out, &Value::Array(objects))?;
+            }
+            OutputFormat::JsonLines => {
+                while let Some(object) = objects.try_next().await? {
+                    write_json_line(out, &object)?;
+                }
+            }
+            OutputFormat::Yaml => {
+                while let Some(object) = objects.try_next().await? {
+                    write_yaml(out, &object)?;
+                }
+                self.end_yaml(out)?;
+            }
+            OutputFormat::Table => {
+                let objects: Vec<Value> = objects.try_collect().await?;
+                self.write_table_or_json(out, Value::Array(objects))?;
+            }
+            OutputFormat::Xml(options) => {
+                let mut xml = XmlWriter::new(out, options.clone());
+                xml.begin_objects()?;
+                while let Some(object) = objects.try_next().await? {
+                    xml.write_object(&object)?;
+                }
+                xml.end_objects()?;
+            }
+            OutputFormat::Pairs => {
+                let mut first = true;
+                while let Some(object) = objects.try_next().await? {
+                    if !first {
+                        writeln!(out)?;
+                    }
+                    first = false;
+                    write_pairs(out, &object)?;
+                }
+            }
+            OutputFormat::Kv => {
+                let mut i = 0;
+                while let Some(object) = objects.try_next().await? {
+                    write_kv(out, Some(i), &object)?;
+                    i += 1;
+                }
+            }
+            OutputFormat::Prometheus(prefix) => {
+                let objects: Vec<Value> = objects.try_collect().await?;
+                write_prometheus(out, prefix, &objects)?;
+            }
+            OutputFormat::Sql(options) => {
+                while let Some(object) = objects.try_next().await? {
+                    write_sql(out, options, &object)?;
+                }
+            }
+            OutputFormat::Csv(columns) if !columns.is_empty() => {
+                write_csv_header(out, columns)?;
+                while let Some(object) = objects.try_next().await? {
+                    write_csv_row(out, columns, &object)?;
+                }
+            }
+            OutputFormat::Csv(_) => {
+                let objects: Vec<Value> = objects.try_collect().await?;
+                let columns = csv_columns(&objects)?;
+                write_csv_header(out, &columns)?;
+                for object in &objects {
+                    write_csv_row(out, &columns, object)?;
+                }
+            }
+            #[cfg(feature = "transform")]
+            OutputFormat::Transform(command) => transform(out, command, objects).await?,
+        }
+        Ok(())
+    }
+
+    /// Pretty-prints JSON, in colour if enabled.
+    pub fn write_json(&self, out: &mut (dyn Write + Send), json: &Value) -> std::io::Result<()> {
+        let pretty = if self.colour {
+            debug!("Stdout is a terminal - pretty-printing json in colour");
+            to_coloured_json_auto(json).unwrap()
+        } else {
+            debug!("Stdout is not a terminal - pretty-printing json without colour");
+            serde_json::to_string_pretty(json).unwrap()
+        };
+        writeln!(out, "{pretty}")
+    }
+
+    /// Writes a list of objects as a table, fitted to the terminal if writing to one, or anything else as JSON.
+    fn write_table_or_json(
+        &self,
+        out: &mut (dyn Write + Send),
+        value: Value,
+    ) -> anyhow::Result<()> {
+        if let Value::Array(objects) = &value {
+            let width = if self.colour { terminal_width() } else { None };
+            if write_table(out, objects, width, self.colour)? {
+                return Ok(());
+            }
+        }
+        self.write_json(out, &value)?;
+        Ok(())
+    }
+
+    /// Ends a YAML stream with the `...` marker when not writing to a terminal, so that a reader of the pipe can tell
+    /// that the last document is complete.
+    fn end_yaml(&self, out: &mut (dyn Write + Send)) -> std::io::Result<()> {
+        if self.colour {
+            Ok(())
+        } else {
+            writeln!(out, "...")
+        }
+    }
+}
+
+/// The narrowest that a column is truncated to when fitting a table to the terminal.
+const MIN_COLUMN_WIDTH: usize = 4;
+
+/// Gets the width of the terminal that stdout is written to, if it is a terminal.
+fn terminal_width() -> Option<usize> {
+    console::Term::stdout()
+        .size_checked()
+        .map(|(_, columns)| usize::from(columns))
+}
+
+/// Writes objects as an aligned table with a column for each field that has a scalar value in any object, in sorted
+/// order.
+///
+/// Returns `false` without writing anything if the objects have no scalar fields to tabulate. If a width is given, the
+/// widest columns are truncated until the table fits.
+fn write_table(
+    out: &mut (dyn Write + Send),
+    objects: &[Value],
+    width: Option<usize>,
+    colour: bool,
+) -> anyhow::Result<bool> {
+    let columns: Vec<&String> = objects
+        .iter()
+        .filter_map(Value::as_object)
+        .flatten()
+        .filter(|(_, value)| !value.is_array() && !value.is_object())
+        .map(|(key, _)| key)
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    if columns.is_empty() {
+        return Ok(false);
+    }
+
+    let rows: Vec<Vec<String>> = objects
+        .iter()
+        .map(|object| {
+            columns
+                .iter()
+                .map(|column| match object.get(column.as_str()) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(s)) => s.replace(['\n', '\r', '\t'], " "),
+                    Some(value) => value.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .map(|column| column.chars().count())
+        .collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    if let Some(max_width) = width {
+        let separators = 2 * (widths.len() - 1);
+        while widths.iter().sum::<usize>() + separators > max_width {
+            let widest = widths
+                .iter_mut()
+                .max()
+                .expect("there is at least one column");
+            if *widest <= MIN_COLUMN_WIDTH {
+                break;
+            }
+            *widest -= 1;
+        }
+    }
+
+    let header_style = if colour {
+        console::Style::new().bold()
+    } else {
+        console::Style::new()
+    };
+    let header: Vec<String> = columns
+        .iter()
+        .zip(&widths)
+        .map(|(column, &width)| {
+            let heading = column.to_uppercase();
+            format!("{:width$}", truncate_cell(&heading, width))
+        })
+        .collect();
+    writeln!(
+        out,
+        "{}",
+        header_style.apply_to(header.join("  ").trim_end())
+    )?;
+    for row in rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:width$}", truncate_cell(cell, width)))
+            .collect();
+        writeln!(out, "{}", line.join("  ").trim_end())?;
+    }
+    Ok(true)
+}
+
+/// Shortens a cell to fit a column, marking it with `…` if it was truncated.
+fn truncate_cell(cell: &str, width: usize) -> Cow<'_, str> {
+    if cell.chars().count() <= width {
+        return Cow::Borrowed(cell);
+    }
+    let mut truncated: String = cell.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    Cow::Owned(truncated)
+}
+
+/// Writes a value as a single line of compact JSON, flushing it so that it can be read straight away.
+fn write_json_line(out: &mut (dyn Write + Send), value: &Value) -> anyhow::Result<()> {
+    writeln!(out, "{value}")?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Writes a value as a YAML document, starting with `---` so that consecutive documents form a YAML stream.
+fn write_yaml(out: &mut (dyn Write + Send), value: &Value) -> anyhow::Result<()> {
+    write!(out, "---\n{}", serde_yaml::to_string(value)?)?;
+    Ok(())
+}
+
+/// Removes all but the given top-level fields from an object, or from each object in a list.
+fn project(value: &mut Value, fields: &HashSet<String>) {
+    match value {
+        Value::Array(objects) => {
+            for object in objects {
+                project(object, fields);
+            }
+        }
+        Value::Object(object) => object.retain(|key, _| fields.contains(key)),
+        _ => (),
+    }
+}
+
+/// Writes each top-level field of an object as a shell variable assignment.
+fn write_pairs(out: &mut (dyn Write + Send), object: &Value) -> anyhow::Result<()> {
+    let Value::Object(fields) = object else {
+        return Err(lib::error::UserFriendly::new(
+            "pairs output is only available for responses made up of objects",
+        )
+        .into());
+    };
+    for (key, value) in fields {
+        let value = match value {
+            Value::Null => String::new(),
+            Value::String(s) => s.clone(),
+            value => value.to_string(),
+        };
+        writeln!(out, "{}={}", variable_name(key), shell_quote(&value))?;
+    }
+    Ok(())
+}
+
+/// Writes each top-level field of an object as an `export` statement, prefixing the variable names with `OBJ_<index>_`
+/// if the object has an index in a list.
+fn write_kv(
+    out: &mut (dyn Write + Send),
+    index: Option<usize>,
+    object: &Value,
+) -> anyhow::Result<()> {
+    let Value::Object(fields) = object else {
+        return Err(lib::error::UserFriendly::new(
+            "kv output is only available for responses made up of objects",
+        )
+        .into());
+    };
+    let prefix = index.map_or_else(String::new, |i| format!("OBJ_{i}_"));
+    for (key, value) in fields {
+        let value = match value {
+            Value::Null => String::new(),
+            Value::String(s) => s.clone(),
+            value => value.to_string(),
+        };
+        writeln!(
+            out,
+            "export {prefix}{}=\"{}\"",
+            variable_name(key).to_uppercase(),
+            double_quote_escape(&value)
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes each numeric field of the objects, other than the id, as a Prometheus gauge labelled with the id of each
+/// object.
+///
+/// Metrics are grouped by field, as each metric may only appear once in the exposition format.
+fn write_prometheus(
+    out: &mut (dyn Write + Send),
+    prefix: &str,
+    objects: &[Value],
+) -> anyhow::Result<()> {
+    let mut fields: Vec<&str> = Vec::new();
+    for (key, value) in objects.iter().filter_map(Value::as_object).flatten() {
+        if key != "id" && value.is_number() && !fields.contains(&key.as_str()) {
+            fields.push(key);
+        }
+    }
+
+    for field in fields {
+        let name = metric_name(&format!("{prefix}_{field}"));
+        writeln!(out, "# HELP {name} The {field} field of each object.")?;
+        writeln!(out, "# TYPE {name} gauge")?;
+        for object in objects {
+            let Some(value) = object.get(field).filter(|value| value.is_number()) else {
+                continue;
+            };
+            match object.get("id").and_then(scalar_text) {
+                Some(id) => writeln!(out, "{name}{{id=\"{}\"}} {value}", label_value(&id))?,
+                None => writeln!(out, "{name} {value}")?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes an object as an SQL `INSERT` statement, with a column for each top-level field.
+fn write_sql(
+    out: &mut (dyn Write + Send),
+    options: &SqlOptions,
+    object: &Value,
+) -> anyhow::Result<()> {
+    let Value::Object(fields) = object else {
+        return Err(lib::error::UserFriendly::new(
+            "sql output is only available for responses made up of objects",
+        )
+        .into());
+    };
+    let Some(table) = &options.table else {
+        return Err(lib::error::UserFriendly::new(
+            "sql output needs the name of a table to insert into - pass one with --table-name",
+        )
+        .into());
+    };
+    let table = table
+        .split('.')
+        .map(sql_identifier)
+        .collect::<Vec<_>>()
+        .join(".");
+
+    if fields.is_empty() {
+        writeln!(out, "INSERT INTO {table} DEFAULT VALUES;")?;
+        return Ok(());
+    }
+    let columns = fields
+        .keys()
+        .map(|key| sql_identifier(key))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let values = fields
+        .iter()
+        .map(|(key, value)| sql_literal(options.types.get(key).copied(), value))
+        .collect::<Vec<_>>()
+        .join(", ");
+    writeln!(out, "INSERT INTO {table} ({columns}) VALUES ({values});")?;
+    Ok(())
+}
+
+/// Writes a value as an SQL literal, using the type of its field if known and otherwise the type of the value.
+///
+/// Nested objects and lists are written as JSON text.
+fn sql_literal(sql_type: Option<SqlType>, value: &Value) -> String {
+    match (sql_type, value) {
+        (_, Value::Null) => String::from("NULL"),
+        (Some(SqlType::Numeric) | None, Value::Number(number)) => number.to_string(),
+        (Some(SqlType::Boolean) | None, Value::Bool(true)) => String::from("TRUE"),
+        (Some(SqlType::Boolean) | None, Value::Bool(false)) => String::from("FALSE"),
+        (_, Value::String(s)) => sql_quote(s),
+        (_, value) => sql_quote(&value.to_string()),
+    }
+}
+
+/// Quotes text as an SQL string literal, where the only special character is `'`.
+fn sql_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Quotes an SQL identifier, unless it is already a valid lowercase identifier.
+fn sql_identifier(name: &str) -> Cow<'_, str> {
+    let is_plain = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if is_plain {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(format!("\"{}\"", name.replace('"', "\"\"")))
+    }
+}
+
+/// Gets the sorted union of the top-level fields of the objects.
+fn csv_columns(objects: &[Value]) -> anyhow::Result<Vec<String>> {
+    let mut columns = std::collections::BTreeSet::new();
+    for object in objects {
+        let Value::Object(fields) = object else {
+            return Err(lib::error::UserFriendly::new(
+                "csv output is only available for responses made up of objects",
+            )
+            .into());
+        };
+        columns.extend(fields.keys().cloned());
+    }
+    Ok(columns.into_iter().collect())
+}
+
+fn write_csv_header(out: &mut (dyn Write + Send), columns: &[String]) -> anyhow::Result<()> {
+    let cells: Vec<Cow<'_, str>> = columns.iter().map(|column| csv_escape(column)).collect();
+    writeln!(out, "{}", cells.join(","))?;
+    Ok(())
+}
+
+/// Writes the fields of an object as a CSV row, with an empty cell for each missing or null field.
+///
+/// Fields without a column are left out, and nested objects and lists are written as JSON text.
+fn write_csv_row(
+    out: &mut (dyn Write + Send),
+    columns: &[String],
+    object: &Value,
+) -> anyhow::Result<()> {
+    let Value::Object(fields) = object else {
+        return Err(lib::error::UserFriendly::new(
+            "csv output is only available for responses made up of objects",
+        )
+        .into());
+    };
+    let cells: Vec<String> = columns
+        .iter()
+        .map(|column| {
+            let value = match fields.get(column) {
+                None | Some(Value::Null) => String::new(),
+                Some(Value::String(s)) => s.clone(),
+                Some(value) => value.to_string(),
+            };
+            csv_escape(&value).into_owned()
+        })
+        .collect();
+    writeln!(out, "{}", cells.join(","))?;
+    Ok(())
+}
+
+/// Quotes a CSV cell if it contains a comma, quote or line break, doubling any quotes.
+fn csv_escape(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Converts text into a valid Prometheus metric name.
+fn metric_name(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Escapes a Prometheus label value.
+fn label_value(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+/// Converts a field name into a valid shell variable name.
+fn variable_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// Quotes a value so that it is read back unchanged by a POSIX shell.
+fn shell_quote(value: &str) -> Cow<'_, str> {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c);
+    if !value.is_empty() && value.chars().all(is_safe) {
+        Cow::Borrowed(value)
+    } else {
+        Cow::Owned(format!("'{}'", value.replace('\'', r"'\''")))
+    }
+}
+
+/// Escapes a value to go between double quotes in a POSIX shell, where `\`, `"`, `$` and `` ` `` are special.
+fn double_quote_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '"' | '$' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(feature = "transform")]
+fn shell_command(command: &str) -> tokio::process::Command {
+    if cfg!(windows) {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = tokio::process::Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+/// Pipes each object through an external command as JSON lines, writing the output of the command.
+#[cfg(feature = "transform")]
+async fn transform<E>(
+    out: &mut (dyn Write + Send),
+    command: &str,
+    mut objects: impl Stream<Item = Result<Value, E>> + Unpin,
+) -> anyhow::Result<()>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    use std::process::Stdio;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut child = shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            lib::error::UserFriendly::new(format!("failed to run transform command: {e}"))
+        })?;
+    let mut stdin = child.stdin.take().expect("stdin should be piped");
+    let mut stdout = child.stdout.take().expect("stdout should be piped");
+    let mut stderr = child.stderr.take().expect("stderr should be piped");
+
+    let write_objects = async move {
+        while let Some(object) = objects.try_next().await? {
+            let mut line = serde_json::to_vec(&object)?;
+            line.push(b'\n');
+            match stdin.write_all(&line).await {
+                Ok(()) => (),
+                // the command has stopped reading its input, so there is no point sending it any more
+                Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        anyhow::Ok(())
+    };
+    let copy_output = async {
+        let mut buffer = [0; 8192];
+        loop {
+            let read = stdout.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+            out.write_all(&buffer[..read])?;
+        }
+        anyhow::Ok(())
+    };
+    let read_errors = async {
+        let mut errors = Vec::new();
+        stderr.read_to_end(&mut errors).await?;
+        anyhow::Ok(errors)
+    };
+    let ((), (), errors) = futures::try_join!(write_objects, copy_output, read_errors)?;
+
+    let status = child.wait().await?;
+    if !status.success() {
+        return Err(lib::error::UserFriendly::new(format!(
+            "transform command failed ({status}): {}",
+            String::from_utf8_lossy(&errors).trim_end()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Renders JSON values as XML.
+///
+/// Lists of objects are rendered as an `<objects>` element with an `<object>` child for each object, and can be
+/// written incrementally with [`XmlWriter::begin_objects`], [`XmlWriter::write_object`] and
+/// [`XmlWriter::end_objects`].
+pub struct XmlWriter<W: Write> {
+    writer: quick_xml::Writer<W>,
+    options: XmlOptions,
+}
+
+impl<W: Write> XmlWriter<W> {
+    pub fn new(inner: W, options: XmlOptions) -> Self {
+        Self {
+            writer: quick_xml::Writer::new_with_indent(inner, b' ', 2),
+            options,
+        }
+    }
+
+    /// Writes a complete XML document containing a single value.
+    pub fn write_document(&mut self, value: &Value) -> quick_xml::Result<()> {
+        if let Value::Array(objects) = value {
+            self.begin_objects()?;
+            for object in objects {
+                self.write_object(object)?;
+            }
+            return self.end_objects();
+        }
+
+        self.write_declaration()?;
+        let root = element_name(&self.options.root);
+        self.write_element(&root, value)?;
+        self.write_newline()
+    }
+
+    /// Starts a document containing a list of objects.
+    pub fn begin_objects(&mut self) -> quick_xml::Result<()> {
+        self.write_declaration()?;
+        let root = element_name(&self.options.root);
+        self.writer
+            .write_event(Event::Start(BytesStart::new(root.as_str())))?;
+        self.writer
+            .write_event(Event::Start(BytesStart::new("objects")))?;
+        Ok(())
+    }
+
+    /// Writes a single object to a document started with [`XmlWriter::begin_objects`].
+    pub fn write_object(&mut self, object: &Value) -> quick_xml::Result<()> {
+        self.write_element("object", object)
+    }
+
+    /// Ends a document started with [`XmlWriter::begin_objects`].
+    pub fn end_objects(&mut self) -> quick_xml::Result<()> {
+        let root = element_name(&self.options.root);
+        self.writer
+            .write_event(Event::End(BytesEnd::new("objects")))?;
+        self.writer
+            .write_event(Event::End(BytesEnd::new(root.as_str())))?;
+        self.write_newline()
+    }
+
+    fn write_declaration(&mut self) -> quick_xml::Result<()> {
+        self.writer
+            .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        Ok(())
+    }
+
+    fn write_newline(&mut self) -> quick_xml::Result<()> {
+        self.writer.get_mut().write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn write_element(&mut self, name: &str, value: &Value) -> quick_xml::Result<()> {
+        match value {
+            Value::Object(fields) => {
+                let use_attributes = self.options.style == XmlStyle::Attributes;
+                let mut start = BytesStart::new(name);
+                if use_attributes {
+                    for (key, field) in fields {
+                        if let Some(text) = scalar_text(field) {
+                            start.push_attribute((element_name(key).as_str(), text.as_str()));
+                        }
+                    }
+                }
+
+                let children: Vec<(&String, &Value)> = fields
+                    .iter()
+                    .filter(|(_, field)| !(use_attributes && is_scalar(field)))
+                    .collect();
+                if children.is_empty() {
+                    self.writer.write_event(Event::Empty(start))?;
+                } else {
+                    self.writer.write_event(Event::Start(start))?;
+                    for (key, field) in children {
+                        self.write_element(&element_name(key), field)?;
+                    }
+                    self.writer.write_event(Event::End(BytesEnd::new(name)))?;
+                }
+            }
+            Value::Array(items) => {
+                if items.is_empty() {
+                    self.writer
+                        .write_event(Event::Empty(BytesStart::new(name)))?;
+                } else {
+                    self.writer
+                        .write_event(Event::Start(BytesStart::new(name)))?;
+                    for item in items {
+                        self.write_element("item", item)?;
+                    }
+                    self.writer.write_event(Event::End(BytesEnd::new(name)))?;
+                }
+            }
+            Value::Null => {
+                self.writer
+                    .write_event(Event::Empty(BytesStart::new(name)))?;
+            }
+            Value::Bool(_) | Value::Number(_) | Value::String(_) => {
+                let text = scalar_text(value).unwrap_or_default();
+                self.writer
+                    .write_event(Event::Start(BytesStart::new(name)))?;
+                self.writer
+                    .write_event(Event::Text(BytesText::new(&text)))?;
+                self.writer.write_event(Event::End(BytesEnd::new(name)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+const fn is_scalar(value: &Value) -> bool {
+    !matches!(value, Value::Object(_) | Value::Array(_))
+}
+
+/// Gets the text content of a scalar value, or `None` if it is null or not a scalar.
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Null | Value::Object(_) | Value::Array(_) => None,
+    }
+}
+
+/// Converts a JSON key into a valid XML element name by replacing any disallowed characters with underscores.
+fn element_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use googletest::prelude::*;
+    use serde_json::json;
+    use test_case::test_case;
+
+    use super::*;
+
+    fn render(value: &Value, style: XmlStyle) -> String {
+        let mut output = Vec::new();
+        XmlWriter::new(
+            &mut output,
+            XmlOptions {
+                root: String::from("conferences"),
+                style,
+            },
+        )
+        .write_document(value)
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_xml_elements() {
+        // Arrange
+        let value = json!([
+            {
+                "aliases": [{"alias": "meet@example.com"}],
+                "id": 1,
+                "ivr_theme": null,
+                "name": "Tom & Jerry's <room>",
+            },
+        ]);
+
+        // Act
+        let xml = render(&value, XmlStyle::Elements);
+
+        // Assert
+        assert_that!(
+            xml,
+            eq(r#"<?xml version="1.0" encoding="UTF-8"?>
+<conferences>
+  <objects>
+    <object>
+      <aliases>
+        <item>
+          <alias>meet@example.com</alias>
+        </item>
+      </aliases>
+      <id>1</id>
+      <ivr_theme/>
+      <name>Tom &amp; Jerry&apos;s &lt;room&gt;</name>
+    </object>
+  </objects>
+</conferences>
+"#)
+        );
+    }
+
+    #[test]
+    fn test_xml_attributes() {
+        // Arrange
+        let value = json!({
+            "aliases": [{"alias": "meet@example.com"}],
+            "id": 1,
+            "ivr_theme": null,
+            "name": "\"quoted\"",
+        });
+
+        // Act
+        let xml = render(&value, XmlStyle::Attributes);
+
+        // Assert
+        assert_that!(
+            xml,
+            eq(r#"<?xml version="1.0" encoding="UTF-8"?>
+<conferences id="1" name="&quot;quoted&quot;">
+  <aliases>
+    <item alias="meet@example.com"/>
+  </aliases>
+</conferences>
+"#)
+        );
+    }
+
+    #[test]
+    fn test_xml_incremental_objects() {
+        // Arrange
+        let mut output = Vec::new();
+        let mut writer = XmlWriter::new(&mut output, XmlOptions::default());
+
+        // Act
+        writer.begin_objects().unwrap();
+        writer.write_object(&json!({"id": 1})).unwrap();
+        writer.write_object(&json!({"id": 2})).unwrap();
+        writer.end_objects().unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq(r#"<?xml version="1.0" encoding="UTF-8"?>
+<root>
+  <objects>
+    <object>
+      <id>1</id>
+    </object>
+    <object>
+      <id>2</id>
+    </object>
+  </objects>
+</root>
+"#)
+        );
+    }
+
+    #[test_case("name", "name" ; "valid name")]
+    #[test_case("1st", "_1st" ; "leading digit")]
+    #[test_case("with space", "with_space" ; "space")]
+    #[test_case("", "_" ; "empty")]
+    fn test_element_name(key: &str, expected: &str) {
+        assert_that!(element_name(key), eq(expected));
+    }
+
+    fn objects(
+        values: Vec<Value>,
+    ) -> impl Stream<Item = Result<Value, std::convert::Infallible>> + Unpin {
+        futures::stream::iter(values.into_iter().map(Ok))
+    }
+
+    #[test_case(false, "[\n  {\n    \"id\": 1\n  },\n  {\n    \"id\": 2\n  }\n]\n" ; "collected")]
+    #[test_case(true, "{\n  \"id\": 1\n}\n{\n  \"id\": 2\n}\n" ; "streamed")]
+    #[tokio::test]
+    async fn test_sink_write_json_objects(stream: bool, expected: &str) {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Json, false);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_objects(
+            &mut output,
+            objects(vec![json!({"id": 1}), json!({"id": 2})]),
+            stream,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(String::from_utf8(output).unwrap(), eq(expected));
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_xml_value() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Xml(XmlOptions::default()), false);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_value(&mut output, json!({"id": 1}))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq(r#"<?xml version="1.0" encoding="UTF-8"?>
+<root>
+  <id>1</id>
+</root>
+"#)
+        );
+    }
+
+    #[test_case("simple", "simple" ; "safe")]
+    #[test_case("", "''" ; "empty")]
+    #[test_case("two words", "'two words'" ; "space")]
+    #[test_case("Tom's room", r"'Tom'\''s room'" ; "single quote")]
+    #[test_case("$HOME; rm", "'$HOME; rm'" ; "special characters")]
+    fn test_shell_quote(value: &str, expected: &str) {
+        assert_that!(shell_quote(value), eq(expected));
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_pairs_value() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Pairs, false);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_value(
+            &mut output,
+            json!({
+                "aliases": [{"alias": "meet@example.com"}],
+                "id": 1,
+                "ivr_theme": null,
+                "name": "Tom's room",
+                "service-type": "conference",
+            }),
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq(r#"aliases='[{"alias":"meet@example.com"}]'
+id=1
+ivr_theme=''
+name='Tom'\''s room'
+service_type=conference
+"#)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_pairs_objects() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Pairs, false);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_objects(
+            &mut output,
+            objects(vec![
+                json!({"id": 1, "name": "room one"}),
+                json!({"id": 2, "name": "room two"}),
+            ]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq("id=1\nname='room one'\n\nid=2\nname='room two'\n")
+        );
+    }
+
+    #[test_case("simple", "simple" ; "safe")]
+    #[test_case("two words", "two words" ; "space")]
+    #[test_case(r#"say "hi""#, r#"say \"hi\""# ; "double quote")]
+    #[test_case("line one\nline two", "line one\nline two" ; "newline")]
+    #[test_case(r"$HOME `id` \", r"\$HOME \`id\` \\" ; "special characters")]
+    fn test_double_quote_escape(value: &str, expected: &str) {
+        assert_that!(double_quote_escape(value), eq(expected));
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_kv_value() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Kv, false);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_value(
+            &mut output,
+            json!({
+                "description": "first line\nsecond line",
+                "id": 1,
+                "ivr_theme": null,
+                "name": "Tom's \"big\" room",
+            }),
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq(r#"export DESCRIPTION="first line
+second line"
+export ID="1"
+export IVR_THEME=""
+export NAME="Tom's \"big\" room"
+"#)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_kv_objects() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Kv, false);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_objects(
+            &mut output,
+            objects(vec![
+                json!({"id": 1, "name": "MyConf"}),
+                json!({"id": 2, "name": "room two"}),
+            ]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq("export OBJ_0_ID=\"1\"\nexport OBJ_0_NAME=\"MyConf\"\nexport OBJ_1_ID=\"2\"\nexport OBJ_1_NAME=\"room two\"\n")
+        );
+    }
+
+    #[test_case("relative", DateFormat::Relative ; "relative")]
+    #[test_case("%Y-%m-%d", DateFormat::Strftime(String::from("%Y-%m-%d")) ; "strftime")]
+    fn test_parse_date_format(format: &str, expected: DateFormat) {
+        assert_that!(format.parse::<DateFormat>(), ok(eq(&expected)));
+    }
+
+    #[test]
+    fn test_parse_invalid_date_format() {
+        assert_that!(
+            "%Q".parse::<DateFormat>(),
+            err(eq("invalid date format: %Q"))
+        );
+    }
+
+    #[test_case(-30, "just now" ; "seconds")]
+    #[test_case(-60, "1 minute ago" ; "one minute ago")]
+    #[test_case(-3 * 60 * 60, "3 hours ago" ; "hours ago")]
+    #[test_case(2 * 24 * 60 * 60, "in 2 days" ; "days from now")]
+    #[test_case(-400 * 24 * 60 * 60, "1 year ago" ; "year ago")]
+    fn test_relative_time(offset_seconds: i64, expected: &str) {
+        // Arrange
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let datetime = now + chrono::TimeDelta::seconds(offset_seconds);
+
+        // Act
+        let relative = relative_time(datetime, now);
+
+        // Assert
+        assert_that!(relative, eq(expected));
+    }
+
+    #[test_case(DateFormat::Strftime(String::from("%Y-%m-%d %H:%M")), "2024-01-02 00:04" ; "strftime")]
+    #[test_case(DateFormat::Relative, "3 hours ago" ; "relative")]
+    fn test_date_formatter(format: DateFormat, expected: &str) {
+        // Arrange
+        let formatter = DateFormatter {
+            format,
+            fields: HashSet::from([String::from("start_time"), String::from("end_time")]),
+        };
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let mut value = json!([{
+            "end_time": "not a date",
+            "name": "2024-01-02T00:04:05",
+            "start_time": "2024-01-02T00:04:05.000000",
+        }]);
+
+        // Act
+        formatter.apply(&mut value, now);
+
+        // Assert
+        assert_that!(
+            value,
+            eq(&json!([{
+                "end_time": "not a date",
+                "name": "2024-01-02T00:04:05",
+                "start_time": expected,
+            }]))
+        );
+    }
+
+    #[test_case("name", "name" ; "plain")]
+    #[test_case("service_type2", "service_type2" ; "digits")]
+    #[test_case("Name", r#""Name""# ; "uppercase")]
+    #[test_case("service-type", r#""service-type""# ; "hyphen")]
+    #[test_case(r#"a"b"#, r#""a""b""# ; "double quote")]
+    fn test_sql_identifier(name: &str, expected: &str) {
+        assert_that!(sql_identifier(name), eq(expected));
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_sql_objects() {
+        // Arrange
+        let mut types = HashMap::new();
+        types.insert(String::from("id"), SqlType::Numeric);
+        types.insert(String::from("pin"), SqlType::Text);
+        types.insert(String::from("locked"), SqlType::Boolean);
+        let sink = OutputSink::new(
+            OutputFormat::Sql(SqlOptions {
+                table: Some(String::from("conferences")),
+                types,
+            }),
+            false,
+        );
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_objects(
+            &mut output,
+            objects(vec![
+                json!({"id": 1, "name": "Tom's room", "pin": 1234, "locked": true}),
+                json!({"aliases": [{"alias": "meet"}], "id": 2, "ivr_theme": null, "locked": false}),
+            ]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq(concat!(
+                "INSERT INTO conferences (id, locked, name, pin) VALUES (1, TRUE, 'Tom''s room', '1234');\n",
+                r#"INSERT INTO conferences (aliases, id, ivr_theme, locked) VALUES ('[{"alias":"meet"}]', 2, NULL, FALSE);"#,
+                "\n",
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_sql_defaults_table_to_resource() {
+        // Arrange
+        let sink = OutputSink::new(
+            OutputFormat::Sql(SqlOptions::default()).for_resource(Api::Configuration, "conference"),
+            false,
+        );
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_value(&mut output, json!({"id": 1, "name": "room"}))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq("INSERT INTO conference (id, name) VALUES (1, 'room');\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_sql_without_table_fails() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Sql(SqlOptions::default()), false);
+        let mut output = Vec::new();
+
+        // Act
+        let result = sink.write_value(&mut output, json!({"id": 1})).await;
+
+        // Assert
+        assert_that!(result, err(displays_as(contains_substring("--table-name"))));
+    }
+
+    #[test_case("simple", "simple" ; "safe")]
+    #[test_case("a,b", r#""a,b""# ; "comma")]
+    #[test_case(r#"say "hi""#, r#""say ""hi""""# ; "double quote")]
+    #[test_case("line one\nline two", "\"line one\nline two\"" ; "newline")]
+    fn test_csv_escape(value: &str, expected: &str) {
+        assert_that!(csv_escape(value), eq(expected));
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_csv_objects() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Csv(Vec::new()), false);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_objects(
+            &mut output,
+            objects(vec![
+                json!({"name": "Tom's, room", "id": 1}),
+                json!({"aliases": [{"alias": "meet"}], "id": 2, "ivr_theme": null}),
+            ]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq(concat!(
+                "aliases,id,ivr_theme,name\n",
+                ",1,,\"Tom's, room\"\n",
+                r#""[{""alias"":""meet""}]",2,,"#,
+                "\n",
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_csv_objects_with_schema_columns() {
+        // Arrange
+        let sink = OutputSink::new(
+            OutputFormat::Csv(vec![String::from("id"), String::from("name")]),
+            false,
+        );
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_objects(
+            &mut output,
+            objects(vec![
+                json!({"id": 1, "name": "room one"}),
+                json!({"id": 2, "tag": "not in schema"}),
+            ]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq("id,name\n1,room one\n2,\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_csv_value() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Csv(Vec::new()), false);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_value(&mut output, json!({"id": 1, "locked": true}))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq("id,locked\n1,true\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_json_lines_objects() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::JsonLines, true);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_objects(
+            &mut output,
+            objects(vec![
+                json!({"id": 1, "name": "room one"}),
+                json!({"aliases": [{"alias": "meet"}], "id": 2}),
+            ]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq(concat!(
+                r#"{"id":1,"name":"room one"}"#,
+                "\n",
+                r#"{"aliases":[{"alias":"meet"}],"id":2}"#,
+                "\n",
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_objects_with_fields() {
+        // Arrange
+        let sink =
+            OutputSink::new(OutputFormat::JsonLines, false).with_fields(Some(HashSet::from([
+                String::from("id"),
+                String::from("name"),
+            ])));
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_objects(
+            &mut output,
+            objects(vec![
+                json!({"id": 1, "name": "room one", "pin": "1234"}),
+                json!({"aliases": [], "id": 2}),
+            ]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq("{\"id\":1,\"name\":\"room one\"}\n{\"id\":2}\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_value_with_fields() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Pairs, false)
+            .with_fields(Some(HashSet::from([String::from("name")])));
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_value(&mut output, json!({"id": 1, "name": "room"}))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_that!(String::from_utf8(output).unwrap(), eq("name=room\n"));
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_table_objects() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Table, false);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_objects(
+            &mut output,
+            objects(vec![
+                json!({"id": 1, "name": "room one", "aliases": [{"alias": "meet"}]}),
+                json!({"id": 22, "name": null, "tag": "team"}),
+            ]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq("ID  NAME      TAG\n1   room one\n22            team\n")
+        );
+    }
+
+    #[test]
+    fn test_write_table_truncates_to_width() {
+        // Arrange
+        let objects = [json!({"description": "a very long description", "id": 1})];
+        let mut output = Vec::new();
+
+        // Act
+        let written = write_table(&mut output, &objects, Some(16), false).unwrap();
+
+        // Assert
+        assert_that!(written, eq(true));
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq("DESCRIPTION   ID\na very long…  1\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_table_single_object_falls_back_to_json() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Table, false);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_value(&mut output, json!({"id": 1}))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq("{\n  \"id\": 1\n}\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_yaml_value() {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Yaml, false);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_value(
+            &mut output,
+            json!({
+                "id": 1,
+                "ivr_theme": null,
+                "aliases": [{"alias": "meet@example.com", "conference": "/api/admin/configuration/v1/conference/1/"}],
+            }),
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        let yaml = String::from_utf8(output).unwrap();
+        assert_that!(
+            yaml,
+            eq(concat!(
+                "---\n",
+                "aliases:\n",
+                "- alias: meet@example.com\n",
+                "  conference: /api/admin/configuration/v1/conference/1/\n",
+                "id: 1\n",
+                "ivr_theme: null\n",
+                "...\n",
+            ))
+        );
+        let round_trip: Value = serde_yaml::from_str(yaml.trim_end_matches("...\n")).unwrap();
+        assert_that!(
+            round_trip,
+            eq(&json!({
+                "id": 1,
+                "ivr_theme": null,
+                "aliases": [{"alias": "meet@example.com", "conference": "/api/admin/configuration/v1/conference/1/"}],
+            }))
+        );
+    }
+
+    #[test_case(false, "---\nid: 1\n---\nid: 2\n...\n" ; "piped")]
+    #[test_case(true, "---\nid: 1\n---\nid: 2\n" ; "interactive")]
+    #[tokio::test]
+    async fn test_sink_write_yaml_objects(interactive: bool, expected: &str) {
+        // Arrange
+        let sink = OutputSink::new(OutputFormat::Yaml, interactive);
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_objects(
+            &mut output,
+            objects(vec![json!({"id": 1}), json!({"id": 2})]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(String::from_utf8(output).unwrap(), eq(expected));
+    }
+
+    #[tokio::test]
+    async fn test_sink_write_prometheus_objects() {
+        // Arrange
+        let sink = OutputSink::new(
+            OutputFormat::Prometheus(String::from("pexshell")).for_resource(
+                Api::Command(lib::mcu::CommandApi::Conference),
+                "Some-Endpoint",
+            ),
+            false,
+        );
+        let mut output = Vec::new();
+
+        // Act
+        sink.write_objects(
+            &mut output,
+            objects(vec![
+                json!({"id": 1, "name": "room one", "participants": 3, "locked": true}),
+                json!({"id": "a\"b", "participants": 1.5, "bandwidth": 1024}),
+            ]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            String::from_utf8(output).unwrap(),
+            eq(
+                r#"# HELP pexshell_command_conference_some_endpoint_participants The participants field of each object.
+# TYPE pexshell_command_conference_some_endpoint_participants gauge
+pexshell_command_conference_some_endpoint_participants{id="1"} 3
+pexshell_command_conference_some_endpoint_participants{id="a\"b"} 1.5
+# HELP pexshell_command_conference_some_endpoint_bandwidth The bandwidth field of each object.
+# TYPE pexshell_command_conference_some_endpoint_bandwidth gauge
+pexshell_command_conference_some_endpoint_bandwidth{id="a\"b"} 1024
+"#
+            )
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use console::Term;
+use parking_lot::Mutex;
+
+/// Width of the bar, excluding the counts written after it.
+const BAR_WIDTH: usize = 30;
+
+/// A progress bar drawn on one line of stderr, which is redrawn in place as it advances and removed when it is dropped.
+pub struct ProgressBar {
+    term: Term,
+    unit: &'static str,
+    completed: Mutex<Option<usize>>,
+}
+
+impl ProgressBar {
+    /// Creates a progress bar counting `unit`s, if stderr is interactive.
+    pub fn stderr(unit: &'static str) -> Option<Self> {
+        let term = Term::stderr();
+        term.is_term().then(|| Self {
+            term,
+            unit,
+            completed: Mutex::new(None),
+        })
+    }
+
+    /// Redraws the bar with the number of completed and total units.
+    ///
+    /// Updates from concurrent tasks can arrive out of order, so any that would move the bar backwards are ignored. The
+    /// lock is held while drawing so that redraws don't interleave.
+    #[allow(clippy::significant_drop_tightening)]
+    pub fn update(&self, completed: usize, total: usize) {
+        let mut drawn = self.completed.lock();
+        if drawn.is_some_and(|drawn| drawn > completed) {
+            return;
+        }
+        *drawn = Some(completed);
+
+        let filled = (BAR_WIDTH * completed)
+            .checked_div(total)
+            .unwrap_or(BAR_WIDTH)
+            .min(BAR_WIDTH);
+        _ = self.term.clear_line();
+        _ = self.term.write_str(&format!(
+            "[{}{}] {completed}/{total} {}",
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            self.unit
+        ));
+    }
+}
+
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        if self.completed.lock().is_some() {
+            _ = self.term.clear_line();
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use lib::{
+    error,
+    mcu::{self, schema::Endpoint, Api},
+};
+use serde_json::Value;
+
+/// An `upsert` of an object, which patches the object whose `match_on` field has the same value as the payload if there
+/// is one, or posts a new object otherwise.
+pub struct Upsert {
+    api: Api,
+    resource: String,
+    match_on: String,
+    key: String,
+    post_payload: Value,
+    patch_payload: Value,
+}
+
+impl Upsert {
+    /// Creates an upsert of the given payloads, which must both include a value for the `match_on` field.
+    pub fn new(
+        api: Api,
+        resource: &str,
+        match_on: &str,
+        post_payload: Value,
+        patch_payload: Value,
+    ) -> Result<Self, error::UserFriendly> {
+        let key = match post_payload.get(match_on) {
+            None | Some(Value::Null) => {
+                return Err(error::UserFriendly::new(format!(
+                    "upsert needs a value for {match_on} to find an existing object"
+                )))
+            }
+            Some(Value::String(key)) => key.clone(),
+            Some(key) => key.to_string(),
+        };
+        Ok(Self {
+            api,
+            resource: String::from(resource),
+            match_on: String::from(match_on),
+            key,
+            post_payload,
+            patch_payload,
+        })
+    }
+
+    /// Gets the request to find the objects that the upsert matches, which fetches at most two of them since more than
+    /// one is an error.
+    pub fn find_request(&self) -> mcu::ApiRequest {
+        mcu::ApiRequest::GetAll {
+            api: self.api,
+            resource: self.resource.clone(),
+            filter_args: HashMap::from([(self.match_on.clone(), self.key.clone())]),
+            page_size: 2,
+            limit: 2,
+            offset: 0,
+            order_by: None,
+        }
+    }
+
+    /// Gets the request that applies the upsert, given the objects returned by its [`find_request`](Self::find_request).
+    ///
+    /// The post payload is only checked for missing fields if the object doesn't exist yet.
+    pub fn into_request(
+        self,
+        endpoint: &Endpoint,
+        existing: &[Value],
+    ) -> Result<mcu::ApiRequest, error::UserFriendly> {
+        match existing {
+            [] => {
+                super::validate_payload(endpoint, &self.post_payload, true)?;
+                Ok(mcu::ApiRequest::Post {
+                    api: self.api,
+                    resource: self.resource,
+                    args: self.post_payload,
+                })
+            }
+            [object] => {
+                let object_id = match object.get("id") {
+                    Some(Value::String(id)) => id.clone(),
+                    Some(Value::Number(id)) => id.to_string(),
+                    _ => {
+                        return Err(error::UserFriendly::new(format!(
+                            "the {} object with {} {} has no id to patch it with",
+                            self.resource, self.match_on, self.key
+                        )))
+                    }
+                };
+                super::validate_payload(endpoint, &self.patch_payload, false)?;
+                Ok(mcu::ApiRequest::Patch {
+                    api: self.api,
+                    resource: self.resource,
+                    object_id,
+                    args: self.patch_payload,
+                })
+            }
+            _ => Err(error::UserFriendly::new(format!(
+                "more than one {} object has {} {} - use --match-on with a field that is unique",
+                self.resource, self.match_on, self.key
+            ))),
+        }
+    }
+}
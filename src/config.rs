@@ -2,34 +2,51 @@
 
 use crate::cli::Console;
 use crate::consts::{
-    ENV_LOG_FILE, ENV_LOG_LEVEL, ENV_LOG_TO_STDERR, ENV_USER_ADDRESS, ENV_USER_PASSWORD,
-    ENV_USER_USERNAME,
+    DEFAULT_LOG_MAX_FILES, ENV_LOG_FILE, ENV_LOG_FORMAT, ENV_LOG_LEVEL, ENV_LOG_TO_STDERR,
+    ENV_USER_ADDRESS, ENV_USER_PASSWORD, ENV_USER_USERNAME,
 };
 use crate::error;
 use crate::Directories;
 use chrono::{
     serde::{ts_seconds, ts_seconds_option},
-    DateTime, Utc,
+    DateTime, TimeDelta, Utc,
 };
 use fslock::LockFile;
 use lib::mcu::auth::OAuth2AccessToken;
-use lib::util::SensitiveString;
+use lib::mcu::compat::{Compat, Version};
+use lib::util::{LogFormat, LogRotation, SensitiveString};
 use log::{debug, warn};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::io::{Read, Seek, Write};
 use std::ops::Not;
 use std::path::PathBuf;
-use std::{collections::HashMap, fs::File, path::Path, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
 
 #[cfg(test)]
 use mockall::mock;
 
+/// OAuth2 tokens expiring within this many seconds are treated as expired.
+const TOKEN_EXPIRY_MARGIN_SECONDS: i64 = 60;
+
+/// Replaces secrets in exported config files.
+const REDACTED_SECRET: &str = "<REDACTED>";
+
+/// Replaces management node addresses in exported config files.
+const REDACTED_ADDRESS: &str = "<MCU_ADDRESS>";
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Credentials {
     Basic(BasicCredentials),
     OAuth2(OAuth2Credentials),
+    Bearer(BearerCredentials),
 }
 
 impl Credentials {
@@ -38,6 +55,7 @@ impl Credentials {
         match self {
             Self::Basic(BasicCredentials { username, .. }) => format!("basic:{username}"),
             Self::OAuth2(OAuth2Credentials { client_id, .. }) => format!("oauth2:{client_id}"),
+            Self::Bearer(BearerCredentials { token_name, .. }) => format!("bearer:{token_name}"),
         }
     }
 
@@ -46,6 +64,7 @@ impl Credentials {
         match self {
             Self::Basic(BasicCredentials { username, .. }) => username.to_owned(),
             Self::OAuth2(OAuth2Credentials { client_id, .. }) => client_id.to_owned(),
+            Self::Bearer(BearerCredentials { token_name, .. }) => token_name.to_owned(),
         }
     }
 }
@@ -70,6 +89,14 @@ pub struct OAuth2Credentials {
     pub token: Option<OAuth2Token>,
 }
 
+/// A bearer token issued outside of pexshell, sent as is with each request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BearerCredentials {
+    /// A name for the token, used to tell users of the same management node apart.
+    pub token_name: String,
+    pub bearer_token: Option<SensitiveString>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct User {
     pub address: String,
@@ -84,6 +111,87 @@ pub struct User {
     pub current_user: bool,
     #[serde(with = "ts_seconds_option", default)]
     pub last_used: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub settings: UserSettings,
+}
+
+/// Settings that apply when connecting to a particular management node.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserSettings {
+    /// The version of the management node, used to stay compatible with older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compat: Option<String>,
+    /// The version segment of API paths, for a management node that doesn't use the usual `v1`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
+    /// How long to wait for each read of a response body, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_timeout_seconds: Option<u64>,
+    /// The management node version whose pinned schemas are used instead of the schema cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_schema_version: Option<String>,
+    /// The PEM file of the client certificate to present to the management node, for mutual TLS.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<PathBuf>,
+    /// The PEM file of the private key of `client_cert`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<PathBuf>,
+}
+
+impl UserSettings {
+    /// Gets the version of the management node, using `version` instead of the configured version if given.
+    pub fn version(
+        &self,
+        version: Option<Version>,
+    ) -> Result<Option<Version>, error::UserFriendly> {
+        match version {
+            Some(version) => Ok(Some(version)),
+            None => self
+                .compat
+                .as_deref()
+                .map(str::parse)
+                .transpose()
+                .map_err(error::UserFriendly::new),
+        }
+    }
+
+    /// Gets how to talk to the management node, using `version` instead of the configured version if given.
+    pub fn compat(&self, version: Option<Version>) -> Result<Compat, error::UserFriendly> {
+        let compat = self
+            .version(version)?
+            .map_or_else(Compat::default, Compat::for_version);
+        Ok(match &self.api_version {
+            Some(api_version) => Compat {
+                api_version: api_version.clone(),
+                ..compat
+            },
+            None => compat,
+        })
+    }
+
+    /// Gets how long to wait for each read of a response body, using `seconds` instead of the configured timeout if given.
+    pub fn response_timeout(&self, seconds: Option<u64>) -> Option<Duration> {
+        seconds
+            .or(self.response_timeout_seconds)
+            .map(Duration::from_secs)
+    }
+
+    /// Gets the client certificate and private key to present, using `identity` instead of the configured pair if given.
+    pub fn client_identity(
+        &self,
+        identity: Option<(PathBuf, PathBuf)>,
+    ) -> Result<Option<(PathBuf, PathBuf)>, error::UserFriendly> {
+        if identity.is_some() {
+            return Ok(identity);
+        }
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => Ok(Some((cert.clone(), key.clone()))),
+            (None, None) => Ok(None),
+            _ => Err(error::UserFriendly::new(
+                "client_cert and client_key must be configured together",
+            )),
+        }
+    }
 }
 
 impl User {
@@ -96,6 +204,7 @@ impl User {
             }),
             current_user: false,
             last_used: None,
+            settings: UserSettings::default(),
         }
     }
 
@@ -109,6 +218,20 @@ impl User {
             }),
             current_user: false,
             last_used: None,
+            settings: UserSettings::default(),
+        }
+    }
+
+    pub fn new_bearer(address: String, token_name: String, token: SensitiveString) -> Self {
+        Self {
+            address,
+            credentials: Credentials::Bearer(BearerCredentials {
+                token_name,
+                bearer_token: Some(token),
+            }),
+            current_user: false,
+            last_used: None,
+            settings: UserSettings::default(),
         }
     }
 
@@ -124,6 +247,49 @@ impl User {
         let address = &self.address;
         format!("{credential}@{address}")
     }
+
+    /// Gets whether the password or bearer token of this user is stored in the config file instead of the keyring.
+    pub const fn has_plaintext_password(&self) -> bool {
+        matches!(
+            &self.credentials,
+            Credentials::Basic(BasicCredentials {
+                password: Some(_),
+                ..
+            }) | Credentials::Bearer(BearerCredentials {
+                bearer_token: Some(_),
+                ..
+            })
+        )
+    }
+
+    /// Gets whether the private key of this user is stored in the config file instead of the keyring.
+    pub const fn has_plaintext_private_key(&self) -> bool {
+        matches!(
+            &self.credentials,
+            Credentials::OAuth2(OAuth2Credentials {
+                private_key: Some(_),
+                ..
+            })
+        )
+    }
+
+    /// Gets whether this is an OAuth2 user whose stored token has expired or is about to expire.
+    pub fn is_token_expired(&self) -> bool {
+        self.token_expires_in()
+            .is_some_and(|expires_in| expires_in.num_seconds() <= TOKEN_EXPIRY_MARGIN_SECONDS)
+    }
+
+    /// Gets the time until the stored OAuth2 token expires, or zero if it has expired.
+    ///
+    /// Returns `None` for basic users and OAuth2 users without a stored token.
+    pub fn token_expires_in(&self) -> Option<TimeDelta> {
+        match &self.credentials {
+            Credentials::OAuth2(OAuth2Credentials {
+                token: Some(token), ..
+            }) => Some((token.expiry - Utc::now()).max(TimeDelta::zero())),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -134,6 +300,15 @@ mock! {
         fn get_log_file_path(&self) -> Option<PathBuf>;
         fn get_log_level(&self) -> Option<String>;
         fn get_log_to_stderr(&self) -> bool;
+        fn get_log_format(&self) -> Option<LogFormat>;
+        fn get_log_rotation(&self) -> Option<LogRotation>;
+        fn get_history_file_path(&self) -> Option<PathBuf>;
+        fn get_compress_cache(&self) -> bool;
+        fn get_enable_request_ids(&self) -> bool;
+        fn get_retries(&self) -> Option<usize>;
+        fn get_timeout(&self) -> Option<u64>;
+        fn get_ca_cert(&self) -> Option<PathBuf>;
+        fn get_schema_cache_ttl(&self) -> Option<u64>;
         fn get_current_user<'a>(&'a self) -> Result<&'a User, error::UserFriendly>;
         fn get_credentials_for_user(&self, user: &User) -> Result<Credentials, error::UserFriendly>;
         fn set_last_used(&mut self) -> Result<(), error::UserFriendly>;
@@ -149,6 +324,9 @@ mock! {
         ) -> Result<(), error::UserFriendly>;
         fn delete_user(&mut self, index: usize) -> Result<(), error::UserFriendly>;
         fn set_current_user(&mut self, user: &User);
+        fn get_aliases(&self) -> &BTreeMap<String, Vec<String>>;
+        fn add_alias(&mut self, name: String, command: Vec<String>);
+        fn delete_alias(&mut self, name: &str) -> Result<(), error::UserFriendly>;
     }
 }
 
@@ -163,6 +341,33 @@ pub trait Provider: Send + Sync {
     /// Gets whether logs should be written to STDERR.
     fn get_log_to_stderr(&self) -> bool;
 
+    /// Gets the configured format of the log file.
+    fn get_log_format(&self) -> Option<LogFormat>;
+
+    /// Gets when the log file should be rotated, if it should be.
+    fn get_log_rotation(&self) -> Option<LogRotation>;
+
+    /// Gets the file that a history of commands is recorded to, if set.
+    fn get_history_file_path(&self) -> Option<PathBuf>;
+
+    /// Gets whether the schema cache should be stored as gzip-compressed JSON.
+    fn get_compress_cache(&self) -> bool;
+
+    /// Gets whether every request should be sent with a generated `X-Request-ID` header.
+    fn get_enable_request_ids(&self) -> bool;
+
+    /// Gets the number of times to retry a `GET` request that failed with a transient error, if configured.
+    fn get_retries(&self) -> Option<usize>;
+
+    /// Gets the number of seconds to wait for each request to complete, if configured, where 0 means no timeout.
+    fn get_timeout(&self) -> Option<u64>;
+
+    /// Gets the PEM file of certificates to trust as well as the system's, if configured.
+    fn get_ca_cert(&self) -> Option<PathBuf>;
+
+    /// Gets the number of days after which the schema cache is considered stale, if configured, where 0 means never.
+    fn get_schema_cache_ttl(&self) -> Option<u64>;
+
     /// Gets the currently active user.
     /// Note that this user may be partially or entirely defined by environment variables.
     ///
@@ -201,18 +406,55 @@ pub trait Configurer: Send + Sync {
 
     /// Sets a given user as the currently active user.
     fn set_current_user(&mut self, user: &User);
+
+    /// Gets the configured command aliases.
+    fn get_aliases(&self) -> &BTreeMap<String, Vec<String>>;
+
+    /// Adds a command alias, replacing any existing alias with the same name.
+    fn add_alias(&mut self, name: String, command: Vec<String>);
+
+    /// Removes a command alias.
+    fn delete_alias(&mut self, name: &str) -> Result<(), error::UserFriendly>;
 }
 
+/// Address of the credential store entry used to check that the credential store is available.
+const KEYRING_CHECK_ADDRESS: &str = "pexshell.invalid";
+const KEYRING_CHECK_USERNAME: &str = "pexshell-doctor";
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Logging {
     file: Option<PathBuf>,
     level: Option<String>,
     stderr: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    format: Option<LogFormat>,
+    /// Size in bytes that the log file is rotated at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_size: Option<u64>,
+    /// Number of rotated log files to keep.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_files: Option<usize>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
     log: Option<Logging>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    aliases: BTreeMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    history_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    compress_cache: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    enable_request_ids: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retries: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ca_cert: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schema_cache_ttl: Option<u64>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     users: Vec<User>,
 }
@@ -225,10 +467,134 @@ impl Config {
                 file: Some(log_file_path),
                 level: None,
                 stderr: None,
+                format: None,
+                max_size: None,
+                max_files: None,
             }),
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
             users: Vec::new(),
         }
     }
+
+    /// Gets a copy of the config that is safe to share, with secrets redacted and no current user.
+    fn redacted(&self, include_addresses: bool) -> Self {
+        let redact = |secret: &mut Option<SensitiveString>| {
+            if secret.is_some() {
+                *secret = Some(SensitiveString::from(REDACTED_SECRET));
+            }
+        };
+
+        let mut config = self.clone();
+        for user in &mut config.users {
+            user.current_user = false;
+            if !include_addresses {
+                user.address = String::from(REDACTED_ADDRESS);
+            }
+            match &mut user.credentials {
+                Credentials::Basic(credentials) => redact(&mut credentials.password),
+                Credentials::Bearer(credentials) => redact(&mut credentials.bearer_token),
+                Credentials::OAuth2(credentials) => {
+                    redact(&mut credentials.private_key);
+                    if let Some(token) = &mut credentials.token {
+                        token.access_token = SensitiveString::from(REDACTED_SECRET);
+                    }
+                }
+            }
+        }
+        config
+    }
+
+    /// Merges the log settings and any new users of a template into the config.
+    ///
+    /// Users are matched by the identity of their credentials, preferring an existing user with the same address. If the
+    /// matching user has a different address or settings to the template, it is reported as a conflict and only updated
+    /// if `overwrite_conflicts` is set - a user whose address changes may need to log in again, as secrets in the system
+    /// store are kept by address. Existing credentials are never replaced, and secrets redacted by `config export` are
+    /// dropped so that they are asked for or read from the system store.
+    fn merge(&mut self, template: Self, overwrite_conflicts: bool) -> ImportSummary {
+        let unredact = |secret: &mut Option<SensitiveString>| {
+            if secret
+                .as_ref()
+                .is_some_and(|secret| secret.secret() == REDACTED_SECRET)
+            {
+                *secret = None;
+            }
+        };
+
+        let mut summary = ImportSummary::default();
+        if template.log.is_some() {
+            self.log = template.log;
+            summary.log_updated = true;
+        }
+
+        for mut user in template.users {
+            if user.address == REDACTED_ADDRESS {
+                summary.skipped.push(user.visual_id());
+                continue;
+            }
+
+            let unique_id = user.unique_id();
+            let credential_id = user.credentials.unique_id();
+            let existing = self
+                .users
+                .iter()
+                .position(|u| u.unique_id() == unique_id)
+                .or_else(|| {
+                    self.users
+                        .iter()
+                        .position(|u| u.credentials.unique_id() == credential_id)
+                });
+            match existing.map(|i| &mut self.users[i]) {
+                Some(existing)
+                    if existing.address == user.address && existing.settings == user.settings => {}
+                Some(existing) if overwrite_conflicts => {
+                    summary.overwritten.push(existing.visual_id());
+                    existing.address = user.address;
+                    existing.settings = user.settings;
+                }
+                Some(existing) => summary.conflicts.push(existing.visual_id()),
+                None => {
+                    user.current_user = false;
+                    user.last_used = None;
+                    match &mut user.credentials {
+                        Credentials::Basic(credentials) => unredact(&mut credentials.password),
+                        Credentials::Bearer(credentials) => {
+                            unredact(&mut credentials.bearer_token);
+                        }
+                        Credentials::OAuth2(credentials) => {
+                            unredact(&mut credentials.private_key);
+                            credentials.token = None;
+                        }
+                    }
+                    summary.added.push(user.visual_id());
+                    self.users.push(user);
+                }
+            }
+        }
+        summary
+    }
+}
+
+/// What changed when a template was merged into the config.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Whether the log settings were replaced by those of the template.
+    pub log_updated: bool,
+    /// Users that were added.
+    pub added: Vec<String>,
+    /// Existing users whose settings differ from the template and were left unchanged.
+    pub conflicts: Vec<String>,
+    /// Existing users whose settings differed from the template and were replaced.
+    pub overwritten: Vec<String>,
+    /// Template users that were skipped because their address was redacted.
+    pub skipped: Vec<String>,
 }
 
 pub struct Manager {
@@ -391,6 +757,67 @@ impl Manager {
         })
     }
 
+    /// Checks that the system credential store can be used, by looking up an entry that is not expected to exist.
+    pub fn check_keyring(&self) -> keyring::Result<()> {
+        match self
+            .keyring
+            .lock()
+            .retrieve(KEYRING_CHECK_ADDRESS, KEYRING_CHECK_USERNAME)
+        {
+            Ok(_) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Serialises the config with all secrets redacted, so that it can be shared.
+    ///
+    /// Management node addresses are also redacted unless `include_addresses` is set.
+    pub fn export(&self, include_addresses: bool) -> String {
+        toml::to_string(&self.config.redacted(include_addresses))
+            .expect("config serialisation should not fail")
+    }
+
+    /// Merges the log settings and any new users of a template config file into the config, without saving it.
+    ///
+    /// Existing users with different settings to the template are only updated if `overwrite_conflicts` is set.
+    pub fn import(
+        &mut self,
+        template: &str,
+        overwrite_conflicts: bool,
+    ) -> Result<ImportSummary, error::UserFriendly> {
+        let template: Config = toml::from_str(template)
+            .map_err(|e| error::UserFriendly::new(format!("template is invalid: {e}")))?;
+        Ok(self.config.merge(template, overwrite_conflicts))
+    }
+
+    /// Gets the metadata of the config file, e.g. to check its permissions.
+    pub fn file_metadata(&self) -> std::io::Result<std::fs::Metadata> {
+        self.file_handle.metadata()
+    }
+
+    /// Pins the schemas used for every user of the management node at `address` to `version`.
+    pub fn pin_schema_version(
+        &mut self,
+        address: &str,
+        version: &str,
+    ) -> Result<(), error::UserFriendly> {
+        let mut users = self
+            .config
+            .users
+            .iter_mut()
+            .filter(|user| user.address == address)
+            .peekable();
+        if users.peek().is_none() {
+            return Err(error::UserFriendly::new(format!(
+                "no users found for {address}"
+            )));
+        }
+        for user in users {
+            user.settings.pinned_schema_version = Some(version.to_owned());
+        }
+        Ok(())
+    }
+
     /// Writes the config to a file.
     ///
     /// Will return an Err if the config cannot be serialised or writing to the file fails.
@@ -418,7 +845,7 @@ impl Manager {
                 (Some(env_address), Some(env_username)) => {
                     self.config.users.iter().position(|u| u.address == *env_address && match &u.credentials {
                         Credentials::Basic(c) => c.username == *env_username,
-                        Credentials::OAuth2(_) => false,
+                        Credentials::OAuth2(_) | Credentials::Bearer(_) => false,
                     })
                     .map_or_else(|| Err(error::UserFriendly::new(format!(
                             "environment variables {ENV_USER_ADDRESS} and {ENV_USER_USERNAME} were set, \
@@ -449,6 +876,24 @@ impl Manager {
         }, |_env_user| Ok(UserConfigContext::Env))
     }
 
+    /// Loads environment variables from a dotenv file of `KEY=VALUE` lines.
+    ///
+    /// Variables that are already set in the environment take precedence over those in the file.
+    pub fn import_from_env_file(&mut self, path: &Path) -> Result<(), error::UserFriendly> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            error::UserFriendly::new(format!("failed to read {}: {e}", path.display()))
+        })?;
+        let variables = parse_env_file(&contents)
+            .map_err(|e| error::UserFriendly::new(format!("{}: {e}", path.display())))?;
+
+        debug!("Loaded {} variables from {:?}", variables.len(), path);
+        for (key, value) in variables {
+            self.env.entry(key).or_insert(value);
+        }
+        self.env_user = Self::get_env_user(&self.env);
+        Ok(())
+    }
+
     /// Gets a user entirely defined by environment variables (if they are all set)
     fn get_env_user(env: &HashMap<String, String>) -> Option<User> {
         let address = env.get(ENV_USER_ADDRESS)?.clone();
@@ -459,10 +904,44 @@ impl Manager {
             credentials: Credentials::Basic(BasicCredentials { username, password }),
             current_user: false,
             last_used: None,
+            settings: UserSettings::default(),
         })
     }
 }
 
+/// Parses the `KEY=VALUE` lines of a dotenv file.
+///
+/// Blank lines and lines starting with `#` are ignored, an `export` prefix is allowed, and values may be wrapped in
+/// single or double quotes.
+fn parse_env_file(contents: &str) -> Result<Vec<(String, String)>, String> {
+    let mut variables = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {} is not of the form KEY=VALUE", i + 1));
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(format!("line {} has no variable name", i + 1));
+        }
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|quote| {
+                value
+                    .strip_prefix(*quote)
+                    .and_then(|value| value.strip_suffix(*quote))
+            })
+            .unwrap_or(value);
+        variables.push((String::from(key), String::from(value)));
+    }
+    Ok(variables)
+}
+
 impl Provider for Manager {
     fn get_log_file_path(&self) -> Option<PathBuf> {
         self.env.get(ENV_LOG_FILE).map_or_else(
@@ -488,6 +967,54 @@ impl Provider for Manager {
             .unwrap_or(false)
     }
 
+    fn get_log_format(&self) -> Option<LogFormat> {
+        match self.env.get(ENV_LOG_FORMAT).map(String::as_str) {
+            Some("text") => Some(LogFormat::Text),
+            Some("json") => Some(LogFormat::Json),
+            Some(format) => {
+                warn!("ignoring unrecognised {ENV_LOG_FORMAT}: {format}");
+                self.config.log.as_ref().and_then(|l| l.format)
+            }
+            None => self.config.log.as_ref().and_then(|l| l.format),
+        }
+    }
+
+    fn get_log_rotation(&self) -> Option<LogRotation> {
+        let log = self.config.log.as_ref()?;
+        Some(LogRotation {
+            max_size: log.max_size?,
+            max_files: log.max_files.unwrap_or(DEFAULT_LOG_MAX_FILES),
+        })
+    }
+
+    fn get_history_file_path(&self) -> Option<PathBuf> {
+        self.config.history_file.clone()
+    }
+
+    fn get_compress_cache(&self) -> bool {
+        self.config.compress_cache.unwrap_or(false)
+    }
+
+    fn get_enable_request_ids(&self) -> bool {
+        self.config.enable_request_ids.unwrap_or(false)
+    }
+
+    fn get_retries(&self) -> Option<usize> {
+        self.config.retries
+    }
+
+    fn get_timeout(&self) -> Option<u64> {
+        self.config.timeout
+    }
+
+    fn get_ca_cert(&self) -> Option<PathBuf> {
+        self.config.ca_cert.clone()
+    }
+
+    fn get_schema_cache_ttl(&self) -> Option<u64> {
+        self.config.schema_cache_ttl
+    }
+
     fn get_current_user(&self) -> Result<&User, error::UserFriendly> {
         match self.get_current_user_config_context()? {
             UserConfigContext::File(i) => Ok(&self.config.users[i]),
@@ -543,6 +1070,25 @@ impl Provider for Manager {
                     token,
                 }))
             }
+            Credentials::Bearer(credentials) => {
+                let token = credentials.bearer_token.clone().map_or_else(
+                    || {
+                        self.keyring
+                            .lock()
+                            .retrieve(&user.address, &format!("{}-bearer", &credentials.token_name))
+                            .map_err(|e| {
+                                error::UserFriendly::new(format!(
+                                    "Bearer token is not configured and could not be retrieved from the system store: {e}"
+                                ))
+                            })
+                    },
+                    Ok,
+                )?;
+                Ok(Credentials::Bearer(BearerCredentials {
+                    token_name: credentials.token_name.clone(),
+                    bearer_token: Some(token),
+                }))
+            }
         }
     }
 
@@ -583,6 +1129,9 @@ impl Provider for Manager {
                 Credentials::Basic(_) => Err(error::UserFriendly::new(
                     "cannot update oauth2 token: expected oauth2 user, but found basic auth user",
                 )),
+                Credentials::Bearer(_) => Err(error::UserFriendly::new(
+                    "cannot update oauth2 token: expected oauth2 user, but found bearer token user",
+                )),
                 Credentials::OAuth2(credentials) => {
                     if credentials.private_key.is_some() {
                         // store token in plaintext
@@ -655,6 +1204,23 @@ impl Configurer for Manager {
                         })?;
                 }
             }
+            Credentials::Bearer(credentials) => {
+                assert!(credentials.bearer_token.is_some(), "No token specified!");
+                if !store_secrets_in_plaintext {
+                    self.keyring
+                        .lock()
+                        .save(
+                            &user.address,
+                            &format!("{}-bearer", &credentials.token_name),
+                            &credentials.bearer_token.take().unwrap(),
+                        )
+                        .map_err(|e| {
+                            error::UserFriendly::new(format!(
+                                "could not save bearer token to system credential store: {e}"
+                            ))
+                        })?;
+                }
+            }
             Credentials::OAuth2(credentials) => {
                 assert!(
                     credentials.private_key.is_some(),
@@ -711,6 +1277,21 @@ impl Configurer for Manager {
                         })?;
                 }
             }
+            Credentials::Bearer(credentials) => {
+                if credentials.bearer_token.is_none() {
+                    self.keyring
+                        .lock()
+                        .delete(
+                            &user.address,
+                            &format!("{}-bearer", &credentials.token_name),
+                        )
+                        .map_err(|e| {
+                            error::UserFriendly::new(format!(
+                                "could not delete bearer token from system credential store: {e}"
+                            ))
+                        })?;
+                }
+            }
             Credentials::OAuth2(credentials) => {
                 if credentials.private_key.is_none() {
                     self.keyring
@@ -750,6 +1331,22 @@ impl Configurer for Manager {
             }
         }
     }
+
+    fn get_aliases(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.config.aliases
+    }
+
+    fn add_alias(&mut self, name: String, command: Vec<String>) {
+        self.config.aliases.insert(name, command);
+    }
+
+    fn delete_alias(&mut self, name: &str) -> Result<(), error::UserFriendly> {
+        self.config
+            .aliases
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| error::UserFriendly::new(format!("alias not found: {name}")))
+    }
 }
 
 mod credentials {
@@ -929,7 +1526,18 @@ mod tests {
                     file: some(eq(Path::new("/path/to/some/pexshell.log"))),
                     level: some(eq("debug")),
                     stderr: none(),
+                    format: none(),
+                    max_size: none(),
+                    max_files: none(),
                 })),
+                aliases: empty(),
+                history_file: none(),
+                compress_cache: none(),
+                enable_request_ids: none(),
+                retries: none(),
+                timeout: none(),
+                ca_cert: none(),
+                schema_cache_ttl: none(),
                 users: elements_are![
                     pat!(User {
                         address: eq("test_address.test.com"),
@@ -939,6 +1547,7 @@ mod tests {
                         }))),
                         current_user: eq(&false),
                         last_used: none(),
+                        settings: anything(),
                     }),
                     pat!(User {
                         address: eq("test_address.testing.com"),
@@ -948,6 +1557,7 @@ mod tests {
                         }))),
                         current_user: eq(&true),
                         last_used: some(eq(&Utc.with_ymd_and_hms(2007, 10, 19, 7, 23, 4).unwrap())),
+                        settings: anything(),
                     }),
                 ],
             })
@@ -963,7 +1573,18 @@ mod tests {
                 file: Some(PathBuf::from("/path/to/some/pexshell.log")),
                 level: Some(String::from("debug")),
                 stderr: None,
+                format: None,
+                max_size: None,
+                max_files: None,
             }),
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
             users: vec![
                 User {
                     address: String::from("test_address.test.com"),
@@ -973,6 +1594,7 @@ mod tests {
                     }),
                     current_user: false,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
                 User {
                     address: String::from("test_address.testing.com"),
@@ -982,6 +1604,7 @@ mod tests {
                     }),
                     current_user: true,
                     last_used: Some(Utc.with_ymd_and_hms(2007, 10, 19, 7, 23, 4).unwrap()),
+                    settings: UserSettings::default(),
                 },
             ],
         };
@@ -1032,12 +1655,290 @@ last_used = 1192778584
         );
     }
 
+    #[test_case(false, "<MCU_ADDRESS>" ; "without addresses")]
+    #[test_case(true, "test_address.test.com" ; "with addresses")]
+    fn test_redacted(include_addresses: bool, expected_address: &str) {
+        // Arrange
+        let config = Config {
+            log: None,
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
+            users: vec![
+                User {
+                    address: String::from("test_address.test.com"),
+                    credentials: Credentials::Basic(BasicCredentials {
+                        username: String::from("admin"),
+                        password: Some(SensitiveString::from("some_admin_password")),
+                    }),
+                    current_user: true,
+                    last_used: None,
+                    settings: UserSettings::default(),
+                },
+                User {
+                    address: String::from("test_address.test.com"),
+                    credentials: Credentials::OAuth2(OAuth2Credentials {
+                        client_id: String::from("some_client_id"),
+                        private_key: Some(SensitiveString::from("some_private_key")),
+                        token: Some(OAuth2Token {
+                            access_token: SensitiveString::from("some_access_token"),
+                            expiry: Utc.with_ymd_and_hms(2007, 10, 19, 7, 23, 4).unwrap(),
+                        }),
+                    }),
+                    current_user: false,
+                    last_used: None,
+                    settings: UserSettings::default(),
+                },
+                User {
+                    address: String::from("test_address.test.com"),
+                    credentials: Credentials::Basic(BasicCredentials {
+                        username: String::from("a_user"),
+                        password: None,
+                    }),
+                    current_user: false,
+                    last_used: None,
+                    settings: UserSettings::default(),
+                },
+            ],
+        };
+
+        // Act
+        let exported = toml::to_string(&config.redacted(include_addresses)).unwrap();
+
+        // Assert
+        assert_that!(
+            exported,
+            eq(&format!(
+                r#"[[users]]
+address = "{expected_address}"
+username = "admin"
+password = "<REDACTED>"
+
+[[users]]
+address = "{expected_address}"
+client_id = "some_client_id"
+private_key = "<REDACTED>"
+
+[users.token]
+access_token = "<REDACTED>"
+expiry = 1192778584
+
+[[users]]
+address = "{expected_address}"
+username = "a_user"
+"#
+            ))
+        );
+    }
+
+    #[test_case(false, ImportSummary {
+        log_updated: true,
+        added: vec![String::from("new_user@mcu2.example.com")],
+        conflicts: vec![String::from("admin@mcu.example.com")],
+        overwritten: vec![],
+        skipped: vec![String::from("admin")],
+    }, None ; "keep conflicts")]
+    #[test_case(true, ImportSummary {
+        log_updated: true,
+        added: vec![String::from("new_user@mcu2.example.com")],
+        conflicts: vec![],
+        overwritten: vec![String::from("admin@mcu.example.com")],
+        skipped: vec![String::from("admin")],
+    }, Some("25") ; "overwrite conflicts")]
+    fn test_merge(
+        overwrite_conflicts: bool,
+        expected: ImportSummary,
+        expected_compat: Option<&str>,
+    ) {
+        // Arrange
+        let mut config: Config = toml::from_str(
+            r#"
+            [[users]]
+            address = "mcu.example.com"
+            username = "admin"
+            password = "admin_password"
+            current_user = true
+            "#,
+        )
+        .unwrap();
+        let template: Config = toml::from_str(
+            r#"
+            [log]
+            level = "debug"
+
+            [[users]]
+            address = "mcu.example.com"
+            username = "admin"
+            password = "<REDACTED>"
+            compat = "25"
+
+            [[users]]
+            address = "mcu2.example.com"
+            username = "new_user"
+            password = "<REDACTED>"
+            current_user = true
+
+            [[users]]
+            address = "<MCU_ADDRESS>"
+            username = "admin"
+            "#,
+        )
+        .unwrap();
+
+        // Act
+        let summary = config.merge(template, overwrite_conflicts);
+
+        // Assert
+        assert_that!(summary, eq(&expected));
+        assert_that!(
+            config,
+            matches_pattern!(Config {
+                log: some(pat!(Logging {
+                    level: some(eq("debug")),
+                    ..
+                })),
+                users: elements_are![
+                    pat!(User {
+                        credentials: pat!(Credentials::Basic(pat!(BasicCredentials {
+                            password: some(sensitive_string(eq("admin_password"))),
+                            ..
+                        }))),
+                        current_user: eq(&true),
+                        settings: eq(&UserSettings {
+                            compat: expected_compat.map(String::from),
+                            api_version: None,
+                            response_timeout_seconds: None,
+                            pinned_schema_version: None,
+                            client_cert: None,
+                            client_key: None,
+                        }),
+                        ..
+                    }),
+                    pat!(User {
+                        address: eq("mcu2.example.com"),
+                        credentials: pat!(Credentials::Basic(pat!(BasicCredentials {
+                            password: none(),
+                            ..
+                        }))),
+                        current_user: eq(&false),
+                        ..
+                    }),
+                ],
+                ..
+            })
+        );
+    }
+
+    #[test_case(false, "mcu.example.com", ImportSummary {
+        conflicts: vec![String::from("admin@mcu.example.com")],
+        ..Default::default()
+    } ; "keep conflicts")]
+    #[test_case(true, "mcu2.example.com", ImportSummary {
+        overwritten: vec![String::from("admin@mcu.example.com")],
+        ..Default::default()
+    } ; "overwrite conflicts")]
+    fn test_merge_user_with_different_address(
+        overwrite_conflicts: bool,
+        expected_address: &str,
+        expected: ImportSummary,
+    ) {
+        // Arrange
+        let mut config: Config = toml::from_str(
+            r#"
+            [[users]]
+            address = "mcu.example.com"
+            username = "admin"
+            password = "admin_password"
+            "#,
+        )
+        .unwrap();
+        let template: Config = toml::from_str(
+            r#"
+            [[users]]
+            address = "mcu2.example.com"
+            username = "admin"
+            password = "<REDACTED>"
+            "#,
+        )
+        .unwrap();
+
+        // Act
+        let summary = config.merge(template, overwrite_conflicts);
+
+        // Assert
+        assert_that!(summary, eq(&expected));
+        assert_that!(
+            config.users,
+            elements_are![pat!(User {
+                address: eq(expected_address),
+                credentials: pat!(Credentials::Basic(pat!(BasicCredentials {
+                    password: some(sensitive_string(eq("admin_password"))),
+                    ..
+                }))),
+                ..
+            })]
+        );
+    }
+
+    #[test]
+    fn test_parse_env_file() {
+        // Arrange
+        let contents = "# credentials\n\
+                        PEXSHELL_ADDRESS=mcu.example.com\n\
+                        \n\
+                        export PEXSHELL_USERNAME = admin\n\
+                        PEXSHELL_PASSWORD=\"pass=word # 1\"\n\
+                        PEXSHELL_LOG_LEVEL='debug'\n";
+
+        // Act
+        let variables = parse_env_file(contents);
+
+        // Assert
+        assert_that!(
+            variables,
+            ok(elements_are![
+                eq(&(
+                    String::from("PEXSHELL_ADDRESS"),
+                    String::from("mcu.example.com")
+                )),
+                eq(&(String::from("PEXSHELL_USERNAME"), String::from("admin"))),
+                eq(&(
+                    String::from("PEXSHELL_PASSWORD"),
+                    String::from("pass=word # 1")
+                )),
+                eq(&(String::from("PEXSHELL_LOG_LEVEL"), String::from("debug"))),
+            ])
+        );
+    }
+
+    #[test_case("PEXSHELL_ADDRESS" ; "no value")]
+    #[test_case("=admin" ; "no name")]
+    fn test_parse_invalid_env_file(contents: &str) {
+        assert_that!(
+            parse_env_file(&format!("# comment\n{contents}")),
+            err(starts_with("line 2 "))
+        );
+    }
+
     #[test]
     fn test_write_empty_config_file() {
         // Arrange
         let test_context = get_test_context();
         let config = Config {
             log: None,
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
             users: Vec::new(),
         };
 
@@ -1076,6 +1977,14 @@ last_used = 1192778584
         let test_context = get_test_context();
         let config = Config {
             log: None,
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
             users: vec![User {
                 address: String::from("test_address.test.com"),
                 credentials: Credentials::Basic(BasicCredentials {
@@ -1084,6 +1993,7 @@ last_used = 1192778584
                 }),
                 current_user: false,
                 last_used: None,
+                settings: UserSettings::default(),
             }],
         };
 
@@ -1125,7 +2035,18 @@ last_used = 1192778584
                 file: Some(PathBuf::from("/path/to/some/pexshell.log")),
                 level: Some(String::from("debug")),
                 stderr: None,
+                format: None,
+                max_size: None,
+                max_files: None,
             }),
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
             users: vec![
                 User {
                     address: String::from("test_address.test.com"),
@@ -1135,6 +2056,7 @@ last_used = 1192778584
                     }),
                     current_user: false,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
                 User {
                     address: String::from("test_address.testing.com"),
@@ -1144,6 +2066,7 @@ last_used = 1192778584
                     }),
                     current_user: true,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
             ],
         };
@@ -1190,6 +2113,7 @@ current_user = true
         );
     }
 
+    #[allow(clippy::too_many_lines)]
     #[test]
     fn test_add_user_with_plaintext_password() {
         // Arrange
@@ -1199,7 +2123,18 @@ current_user = true
                 file: Some(PathBuf::from("/path/to/some/pexshell.log")),
                 level: Some(String::from("debug")),
                 stderr: None,
+                format: None,
+                max_size: None,
+                max_files: None,
             }),
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
             users: vec![
                 User {
                     address: String::from("test_address.test.com"),
@@ -1209,6 +2144,7 @@ current_user = true
                     }),
                     current_user: false,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
                 User {
                     address: String::from("test_address.testing.com"),
@@ -1218,6 +2154,7 @@ current_user = true
                     }),
                     current_user: true,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
             ],
         };
@@ -1249,6 +2186,7 @@ current_user = true
             }),
             current_user: false,
             last_used: None,
+            settings: UserSettings::default(),
         };
 
         // Act
@@ -1267,6 +2205,7 @@ current_user = true
                     }))),
                     current_user: eq(&false),
                     last_used: none(),
+                    settings: anything(),
                 }),
                 pat!(User {
                     address: eq("test_address.testing.com"),
@@ -1276,6 +2215,7 @@ current_user = true
                     }))),
                     current_user: eq(&true),
                     last_used: none(),
+                    settings: anything(),
                 }),
                 pat!(User {
                     address: eq("new_address.testing.com"),
@@ -1285,11 +2225,13 @@ current_user = true
                     }))),
                     current_user: eq(&false),
                     last_used: none(),
+                    settings: anything(),
                 }),
             ]
         );
     }
 
+    #[allow(clippy::too_many_lines)]
     #[test]
     fn test_add_basic_auth_user_with_credential_store() {
         // Arrange
@@ -1299,7 +2241,18 @@ current_user = true
                 file: Some(PathBuf::from("/path/to/some/pexshell.log")),
                 level: Some(String::from("debug")),
                 stderr: None,
+                format: None,
+                max_size: None,
+                max_files: None,
             }),
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
             users: vec![
                 User {
                     address: String::from("test_address.test.com"),
@@ -1309,6 +2262,7 @@ current_user = true
                     }),
                     current_user: false,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
                 User {
                     address: String::from("test_address.testing.com"),
@@ -1318,6 +2272,7 @@ current_user = true
                     }),
                     current_user: true,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
             ],
         };
@@ -1358,6 +2313,7 @@ current_user = true
             }),
             current_user: false,
             last_used: None,
+            settings: UserSettings::default(),
         };
 
         // Act
@@ -1376,6 +2332,7 @@ current_user = true
                     }))),
                     current_user: eq(&false),
                     last_used: none(),
+                    settings: anything(),
                 }),
                 pat!(User {
                     address: eq("test_address.testing.com"),
@@ -1385,6 +2342,7 @@ current_user = true
                     }))),
                     current_user: eq(&true),
                     last_used: none(),
+                    settings: anything(),
                 }),
                 pat!(User {
                     address: eq("new_address.testing.com"),
@@ -1394,6 +2352,7 @@ current_user = true
                     }))),
                     current_user: eq(&false),
                     last_used: none(),
+                    settings: anything(),
                 }),
             ]
         );
@@ -1409,7 +2368,18 @@ current_user = true
                 file: Some(PathBuf::from("/path/to/some/pexshell.log")),
                 level: Some(String::from("debug")),
                 stderr: None,
+                format: None,
+                max_size: None,
+                max_files: None,
             }),
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
             users: vec![
                 User {
                     address: String::from("test_address.test.com"),
@@ -1419,6 +2389,7 @@ current_user = true
                     }),
                     current_user: false,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
                 User {
                     address: String::from("test_address.testing.com"),
@@ -1428,6 +2399,7 @@ current_user = true
                     }),
                     current_user: true,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
             ],
         };
@@ -1469,6 +2441,7 @@ current_user = true
             }),
             current_user: false,
             last_used: None,
+            settings: UserSettings::default(),
         };
 
         // Act
@@ -1489,6 +2462,7 @@ current_user = true
                 ))),
                 current_user: eq(&false),
                 last_used: none(),
+                settings: anything(),
             })
         );
         assert_that!(
@@ -1503,6 +2477,7 @@ current_user = true
                 ))),
                 current_user: eq(&true),
                 last_used: none(),
+                settings: anything(),
             })
         );
         assert_that!(
@@ -1518,6 +2493,7 @@ current_user = true
                 ))),
                 current_user: eq(&false),
                 last_used: none(),
+                settings: anything(),
             })
         );
     }
@@ -1532,7 +2508,18 @@ current_user = true
                 file: Some(PathBuf::from("/path/to/some/pexshell.log")),
                 level: Some(String::from("debug")),
                 stderr: None,
+                format: None,
+                max_size: None,
+                max_files: None,
             }),
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
             users: vec![
                 User {
                     address: String::from("test_address.test.com"),
@@ -1542,6 +2529,7 @@ current_user = true
                     }),
                     current_user: false,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
                 User {
                     address: String::from("test_address.testing.com"),
@@ -1551,6 +2539,7 @@ current_user = true
                     }),
                     current_user: true,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
             ],
         };
@@ -1620,6 +2609,7 @@ current_user = true
             }),
             current_user: false,
             last_used: Some(last_used),
+            settings: UserSettings::default(),
         };
 
         // Act
@@ -1638,6 +2628,7 @@ current_user = true
                     }))),
                     current_user: eq(&false),
                     last_used: none(),
+                    settings: anything(),
                 }),
                 pat!(User {
                     address: eq("test_address.testing.com"),
@@ -1647,6 +2638,7 @@ current_user = true
                     }))),
                     current_user: eq(&true),
                     last_used: none(),
+                    settings: anything(),
                 }),
                 pat!(User {
                     address: eq("new_address.testing.com"),
@@ -1657,11 +2649,13 @@ current_user = true
                     }))),
                     current_user: eq(&false),
                     last_used: some(eq(&last_used)),
+                    settings: anything(),
                 }),
             ]
         );
     }
 
+    #[allow(clippy::too_many_lines)]
     #[test]
     fn test_add_user_with_credential_store_fails() {
         // Arrange
@@ -1671,7 +2665,18 @@ current_user = true
                 file: Some(PathBuf::from("/path/to/some/pexshell.log")),
                 level: Some(String::from("debug")),
                 stderr: None,
+                format: None,
+                max_size: None,
+                max_files: None,
             }),
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
             users: vec![
                 User {
                     address: String::from("test_address.test.com"),
@@ -1681,6 +2686,7 @@ current_user = true
                     }),
                     current_user: false,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
                 User {
                     address: String::from("test_address.testing.com"),
@@ -1690,6 +2696,7 @@ current_user = true
                     }),
                     current_user: true,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
             ],
         };
@@ -1730,6 +2737,7 @@ current_user = true
             }),
             current_user: false,
             last_used: None,
+            settings: UserSettings::default(),
         };
 
         // Act
@@ -1750,6 +2758,7 @@ current_user = true
                     }))),
                     current_user: eq(&false),
                     last_used: none(),
+                    settings: anything(),
                 }),
                 pat!(User {
                     address: eq("test_address.testing.com"),
@@ -1759,6 +2768,7 @@ current_user = true
                     }))),
                     current_user: eq(&true),
                     last_used: none(),
+                    settings: anything(),
                 }),
             ]
         );
@@ -1809,7 +2819,18 @@ current_user = true
                 file: Some(PathBuf::from("/path/to/some/pexshell.log")),
                 level: Some(String::from("debug")),
                 stderr: None,
+                format: None,
+                max_size: None,
+                max_files: None,
             }),
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
             users: vec![
                 User {
                     address: String::from("test_address.test.com"),
@@ -1819,6 +2840,7 @@ current_user = true
                     }),
                     current_user: false,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
                 User {
                     address: String::from("test_address.testing.com"),
@@ -1828,6 +2850,7 @@ current_user = true
                     }),
                     current_user: false,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
             ],
         };
@@ -1915,6 +2938,7 @@ current_user = true
                 ))),
                 current_user: eq(&false),
                 last_used: none(),
+                settings: anything(),
             })))
         );
     }
@@ -1986,7 +3010,18 @@ current_user = true
                 file: Some(PathBuf::from("/path/to/some/pexshell.log")),
                 level: Some(String::from("debug")),
                 stderr: None,
+                format: None,
+                max_size: None,
+                max_files: None,
             }),
+            aliases: BTreeMap::new(),
+            history_file: None,
+            compress_cache: None,
+            enable_request_ids: None,
+            retries: None,
+            timeout: None,
+            ca_cert: None,
+            schema_cache_ttl: None,
             users: vec![
                 User {
                     address: String::from("test_address.test.com"),
@@ -1996,6 +3031,7 @@ current_user = true
                     }),
                     current_user: false,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
                 User {
                     address: String::from("test_address.testing.com"),
@@ -2005,6 +3041,7 @@ current_user = true
                     }),
                     current_user: true,
                     last_used: None,
+                    settings: UserSettings::default(),
                 },
             ],
         };
@@ -2050,7 +3087,323 @@ current_user = true
                 ))),
                 current_user: eq(&false),
                 last_used: none(),
+                settings: anything(),
+            })))
+        );
+    }
+
+    #[test_case(None, None, Compat::default() ; "default")]
+    #[test_case(Some("25"), None, Compat::for_version(Version { major: 25, minor: 0 }) ; "configured")]
+    #[test_case(Some("25"), Some(Version { major: 30, minor: 1 }), Compat::for_version(Version { major: 30, minor: 1 }) ; "overridden")]
+    fn test_user_settings_compat(
+        configured: Option<&str>,
+        version: Option<Version>,
+        expected: Compat,
+    ) {
+        // Arrange
+        let settings = UserSettings {
+            compat: configured.map(String::from),
+            ..Default::default()
+        };
+
+        // Act
+        let compat = settings.compat(version);
+
+        // Assert
+        assert_that!(compat, ok(eq(&expected)));
+    }
+
+    #[test]
+    fn test_user_settings_compat_invalid() {
+        let settings = UserSettings {
+            compat: Some(String::from("latest")),
+            ..Default::default()
+        };
+        assert_that!(
+            settings.compat(None),
+            err(displays_as(eq("invalid management node version: latest")))
+        );
+    }
+
+    #[test]
+    fn test_user_settings_compat_api_version() {
+        // Arrange
+        let settings = UserSettings {
+            compat: Some(String::from("25")),
+            api_version: Some(String::from("v2")),
+            ..Default::default()
+        };
+
+        // Act
+        let compat = settings.compat(None);
+
+        // Assert
+        assert_that!(
+            compat,
+            ok(eq(&Compat {
+                api_version: String::from("v2"),
+                ..Compat::for_version(Version {
+                    major: 25,
+                    minor: 0
+                })
+            }))
+        );
+    }
+
+    #[test_case(None, None, None ; "default")]
+    #[test_case(Some(30), None, Some(Duration::from_secs(30)) ; "configured")]
+    #[test_case(Some(30), Some(60), Some(Duration::from_secs(60)) ; "overridden")]
+    fn test_user_settings_response_timeout(
+        configured: Option<u64>,
+        seconds: Option<u64>,
+        expected: Option<Duration>,
+    ) {
+        // Arrange
+        let settings = UserSettings {
+            response_timeout_seconds: configured,
+            ..Default::default()
+        };
+
+        // Act
+        let timeout = settings.response_timeout(seconds);
+
+        // Assert
+        assert_that!(timeout, eq(expected));
+    }
+
+    #[test_case(None, None, None, None ; "default")]
+    #[test_case(Some("cert.pem"), Some("key.pem"), None, Some(("cert.pem", "key.pem")) ; "configured")]
+    #[test_case(Some("cert.pem"), Some("key.pem"), Some(("other.pem", "other.key")), Some(("other.pem", "other.key")) ; "overridden")]
+    #[test_case(None, None, Some(("other.pem", "other.key")), Some(("other.pem", "other.key")) ; "given")]
+    fn test_user_settings_client_identity(
+        cert: Option<&str>,
+        key: Option<&str>,
+        identity: Option<(&str, &str)>,
+        expected: Option<(&str, &str)>,
+    ) {
+        // Arrange
+        let settings = UserSettings {
+            client_cert: cert.map(PathBuf::from),
+            client_key: key.map(PathBuf::from),
+            ..Default::default()
+        };
+
+        // Act
+        let client_identity = settings
+            .client_identity(identity.map(|(cert, key)| (PathBuf::from(cert), PathBuf::from(key))));
+
+        // Assert
+        assert_that!(
+            client_identity,
+            ok(eq(&expected.map(|(cert, key)| (
+                PathBuf::from(cert),
+                PathBuf::from(key)
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_user_settings_client_identity_without_key() {
+        let settings = UserSettings {
+            client_cert: Some(PathBuf::from("cert.pem")),
+            ..Default::default()
+        };
+        assert_that!(
+            settings.client_identity(None),
+            err(displays_as(eq(
+                "client_cert and client_key must be configured together"
+            )))
+        );
+    }
+
+    #[test_case(Some(TimeDelta::hours(1)), false ; "valid")]
+    #[test_case(Some(TimeDelta::seconds(30)), true ; "expiring")]
+    #[test_case(Some(TimeDelta::hours(-1)), true ; "expired")]
+    #[test_case(None, false ; "no token")]
+    fn test_is_token_expired(expires_in: Option<TimeDelta>, expected: bool) {
+        // Arrange
+        let mut user = User::new_oauth2(
+            String::from("test.example.com"),
+            String::from("client_id"),
+            SensitiveString::from("private_key"),
+        );
+        if let Credentials::OAuth2(credentials) = &mut user.credentials {
+            credentials.token = expires_in.map(|expires_in| OAuth2Token {
+                access_token: SensitiveString::from("token"),
+                expiry: Utc::now() + expires_in,
+            });
+        }
+
+        // Act
+        let expired = user.is_token_expired();
+
+        // Assert
+        assert_that!(expired, eq(expected));
+    }
+
+    #[test]
+    fn test_token_expires_in() {
+        // Arrange
+        let mut user = User::new_oauth2(
+            String::from("test.example.com"),
+            String::from("client_id"),
+            SensitiveString::from("private_key"),
+        );
+        if let Credentials::OAuth2(credentials) = &mut user.credentials {
+            credentials.token = Some(OAuth2Token {
+                access_token: SensitiveString::from("token"),
+                expiry: Utc::now() + TimeDelta::hours(1),
+            });
+        }
+        let basic_user = User::new(
+            String::from("test.example.com"),
+            String::from("username"),
+            SensitiveString::from("password"),
+        );
+
+        // Act
+        let expires_in = user.token_expires_in();
+        let basic_expires_in = basic_user.token_expires_in();
+
+        // Assert
+        assert_that!(
+            expires_in,
+            some(all!(gt(TimeDelta::minutes(59)), le(TimeDelta::hours(1))))
+        );
+        assert_that!(basic_expires_in, none());
+    }
+
+    #[test_case(Ok(SensitiveString::from("unexpected")), true ; "entry exists")]
+    #[test_case(Err(keyring::Error::NoEntry), true ; "no entry")]
+    #[test_case(Err(keyring::Error::NoStorageAccess(Box::new(std::io::Error::other("locked")))), false ; "no access")]
+    fn test_check_keyring(retrieved: keyring::Result<SensitiveString>, expected_available: bool) {
+        // Arrange
+        let test_context = get_test_context();
+        let config_path = test_context.get_test_dir().join("config.toml");
+        let lock_path = test_context.get_test_dir().join("config.lock");
+        let mut keyring = credentials::MockProvider::new();
+        keyring
+            .expect_retrieve()
+            .with(
+                mp::eq(KEYRING_CHECK_ADDRESS),
+                mp::eq(KEYRING_CHECK_USERNAME),
+            )
+            .once()
+            .return_once(move |_, _| retrieved);
+        let mut console = Console::new(
+            false,
+            test_context.get_stdout_wrapper(),
+            false,
+            test_context.get_stderr_wrapper(),
+        );
+        let mgr = Manager::with_config_and_keyring(
+            Config::new(&test_context.get_directories()),
+            &config_path,
+            &lock_path,
+            HashMap::default(),
+            keyring,
+            &mut console,
+        )
+        .unwrap();
+
+        // Act
+        let result = mgr.check_keyring();
+
+        // Assert
+        assert_that!(result.is_ok(), eq(expected_available));
+    }
+
+    #[test]
+    fn test_bearer_token_is_stored_in_keyring() {
+        // Arrange
+        let test_context = get_test_context();
+        let config_path = test_context.get_test_dir().join("config.toml");
+        let lock_path = test_context.get_test_dir().join("config.lock");
+        let mut keyring = credentials::MockProvider::new();
+        keyring
+            .expect_save()
+            .with(
+                mp::eq("mcu.example.com"),
+                mp::eq("ci-bearer"),
+                mp::function(|s: &SensitiveString| s.secret() == "some_token"),
+            )
+            .once()
+            .return_once(|_, _, _| Ok(()));
+        keyring
+            .expect_retrieve()
+            .with(mp::eq("mcu.example.com"), mp::eq("ci-bearer"))
+            .once()
+            .return_once(|_, _| Ok(SensitiveString::from("some_token")));
+        let mut console = Console::new(
+            false,
+            test_context.get_stdout_wrapper(),
+            false,
+            test_context.get_stderr_wrapper(),
+        );
+        let mut mgr = Manager::with_config_and_keyring(
+            Config::new(&test_context.get_directories()),
+            &config_path,
+            &lock_path,
+            HashMap::default(),
+            keyring,
+            &mut console,
+        )
+        .unwrap();
+        let user = User::new_bearer(
+            String::from("mcu.example.com"),
+            String::from("ci"),
+            SensitiveString::from("some_token"),
+        );
+
+        // Act
+        mgr.add_user(user, false).unwrap();
+        let stored_user = mgr.get_users()[0].clone();
+        let credentials = mgr.get_credentials_for_user(&stored_user);
+
+        // Assert
+        assert_that!(
+            stored_user.credentials,
+            pat!(Credentials::Bearer(pat!(BearerCredentials {
+                token_name: eq("ci"),
+                bearer_token: none(),
             })))
         );
+        assert_that!(
+            credentials,
+            ok(pat!(Credentials::Bearer(pat!(BearerCredentials {
+                token_name: eq("ci"),
+                bearer_token: some(sensitive_string(eq("some_token"))),
+            }))))
+        );
+    }
+
+    #[test]
+    fn test_read_bearer_user() {
+        // Act
+        let config: Config = toml::from_str(
+            r#"
+            [[users]]
+            address = "mcu.example.com"
+            token_name = "ci"
+            bearer_token = "some_token"
+            current_user = true
+            "#,
+        )
+        .unwrap();
+
+        // Assert
+        assert_that!(
+            config.users,
+            elements_are![pat!(User {
+                address: eq("mcu.example.com"),
+                credentials: pat!(Credentials::Bearer(pat!(BearerCredentials {
+                    token_name: eq("ci"),
+                    bearer_token: some(sensitive_string(eq("some_token"))),
+                }))),
+                current_user: eq(&true),
+                last_used: none(),
+                settings: anything(),
+            })]
+        );
     }
 }
@@ -1,8 +1,19 @@
 pub const ENV_LOG_FILE: &str = "PEXSHELL_LOG_FILE";
 pub const ENV_LOG_LEVEL: &str = "PEXSHELL_LOG_LEVEL";
 pub const ENV_LOG_TO_STDERR: &str = "PEXSHELL_LOG_TO_STDERR";
+pub const ENV_LOG_FORMAT: &str = "PEXSHELL_LOG_FORMAT";
 pub const ENV_USER_ADDRESS: &str = "PEXSHELL_ADDRESS";
 pub const ENV_USER_USERNAME: &str = "PEXSHELL_USERNAME";
 pub const ENV_USER_PASSWORD: &str = "PEXSHELL_PASSWORD";
+pub const ENV_FILE: &str = "PEXSHELL_ENV_FILE";
 
+pub const EXIT_CODE_NOT_FOUND: i32 = 3;
+pub const EXIT_CODE_UNAUTHORIZED: i32 = 4;
+pub const EXIT_CODE_FORBIDDEN: i32 = 5;
 pub const EXIT_CODE_INTERRUPTED: i32 = 130;
+
+/// The number of days after which the schema cache is considered stale, if `schema_cache_ttl` isn't configured.
+pub const DEFAULT_SCHEMA_CACHE_TTL_DAYS: u64 = 30;
+
+/// The number of rotated log files kept, if `max_size` is set in the log section of the config but `max_files` isn't.
+pub const DEFAULT_LOG_MAX_FILES: usize = 5;
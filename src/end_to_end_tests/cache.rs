@@ -13,6 +13,7 @@ use wiremock::{
 use crate::{
     end_to_end_tests::configuration_helpers::{
         configure_config_test_user, configure_schemas_configuration_conference_only,
+        schemas_configuration_conference_only,
     },
     test_util::TestContextExtensions,
 };
@@ -116,6 +117,88 @@ async fn cache_conference_config() {
     assert_that!(output, eq(""));
 }
 
+#[tokio::test]
+async fn cache_single_resource() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+    let status_root_path = test_context
+        .get_cache_dir()
+        .join("schemas/status/root.json");
+    let status_root_schema = std::fs::read_to_string(&status_root_path).unwrap();
+
+    let configuration_conference_schema = test_context
+        .get_schema_builder()
+        .field("id", |f| f.unique(true).nullable(false))
+        .field("name", |f| f.unique(true).nullable(false))
+        .field("description", |f| f.nullable(true));
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/schema/"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(configuration_conference_schema.to_value()),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &["pexshell", "cache", "configuration", "conference"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let cached: Value = serde_json::from_str(
+        &std::fs::read_to_string(
+            test_context
+                .get_cache_dir()
+                .join("schemas/configuration/conference.json"),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_that!(cached["fields"].get("description").is_some(), eq(true));
+    assert_that!(
+        std::fs::read_to_string(&status_root_path).unwrap(),
+        eq(&status_root_schema)
+    );
+    assert_that!(test_context.take_stdout(), eq(""));
+}
+
+#[tokio::test]
+async fn cache_resource_requires_api() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "cache", "conference"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(result.is_err(), eq(true));
+    assert_that!(
+        test_context.take_stderr(),
+        contains_substring("invalid value 'conference'")
+    );
+}
+
 #[tokio::test]
 async fn clear_cache() {
     // Arrange
@@ -164,6 +247,81 @@ async fn clear_cache() {
     assert_that!(output, eq(""));
 }
 
+#[tokio::test]
+async fn cache_clear_subcommand() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+    let schema_dir = test_context.get_cache_dir().join("schemas");
+    std::fs::create_dir_all(schema_dir.join("localhost/36.0")).unwrap();
+
+    // Act
+    crate::run_with(
+        &["pexshell", "cache", "clear", "--yes"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(schema_dir.join("configuration").exists(), eq(false));
+    assert_that!(schema_dir.join("localhost/36.0").exists(), eq(true));
+    assert_that!(test_context.take_stdout(), eq(""));
+}
+
+#[tokio::test]
+async fn cache_clear_subcommand_include_pinned() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+    let schema_dir = test_context.get_cache_dir().join("schemas");
+    std::fs::create_dir_all(schema_dir.join("localhost/36.0")).unwrap();
+
+    // Act
+    crate::run_with(
+        &["pexshell", "cache", "clear", "--yes", "--include-pinned"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(schema_dir.exists(), eq(false));
+}
+
+#[tokio::test]
+async fn cache_clear_subcommand_without_cache() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+
+    // Act
+    crate::run_with(
+        &["pexshell", "cache", "clear"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(test_context.take_stdout(), eq("no schema cache found\n"));
+}
+
 #[tokio::test]
 async fn schema_field_with_dict_type_does_not_cause_crash() {
     // Arrange
@@ -266,3 +424,51 @@ async fn schema_field_with_dict_type_does_not_cause_crash() {
     let output = test_context.take_stderr();
     assert_that!(output, not(eq("")));
 }
+
+#[tokio::test]
+async fn refresh_cache_fetches_stale_schemas() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+    configure_schemas_configuration_conference_only(&test_context);
+
+    let configuration_conference_schema = test_context
+        .get_schema_builder()
+        .field("name", |f| f.unique(true).nullable(false));
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/schema/"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(configuration_conference_schema.to_value()),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &["pexshell", "--compat", "34", "cache", "--refresh"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let schema: Value = serde_json::from_str(
+        &std::fs::read_to_string(
+            test_context
+                .get_cache_dir()
+                .join("schemas/configuration/conference.json"),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_that!(schema["schema_version"], eq(&Value::from("34.0")));
+    let output = test_context.take_stdout();
+    assert_that!(output, eq(""));
+}
@@ -0,0 +1,107 @@
+#![allow(clippy::significant_drop_tightening)]
+
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+use test_case::test_case;
+use test_helpers::get_test_context;
+
+use crate::{
+    end_to_end_tests::{configuration_helpers::schemas_configuration_conference_only, json_schema},
+    test_util::TestContextExtensions,
+};
+
+#[tokio::test]
+async fn fish_completions_include_cached_endpoints() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+
+    // Act
+    crate::run_with(
+        &["pexshell", "completions", "fish"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output = test_context.take_stdout();
+    assert_that!(
+        output,
+        all!(
+            contains_substring("complete -c pexshell"),
+            contains_substring("-a \"conference\""),
+            contains_substring("-l name"),
+        )
+    );
+}
+
+#[test_case(
+    "powershell",
+    "Register-ArgumentCompleter -Native -CommandName 'pexshell'",
+    "'pexshell;configuration;conference'" ;
+    "powershell"
+)]
+#[test_case(
+    "nushell",
+    "export extern pexshell [",
+    "export extern \"pexshell configuration conference\"" ;
+    "nushell"
+)]
+#[tokio::test]
+async fn completions_include_cached_endpoints(shell: &str, command: &str, endpoint: &str) {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+
+    // Act
+    crate::run_with(
+        &["pexshell", "completions", shell].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output = test_context.take_stdout();
+    assert_that!(
+        output,
+        all!(contains_substring(command), contains_substring(endpoint))
+    );
+}
+
+#[test_case("fish", "besteffort\\t''\non\\t''\noff\\t''" ; "fish")]
+#[test_case("zsh", "(besteffort on off)" ; "zsh")]
+#[tokio::test]
+async fn completions_offer_valid_choices(shell: &str, choices: &str) {
+    // Arrange
+    let test_context = get_test_context();
+    test_context.seed_minimal_schemas();
+    test_context.seed_schema("configuration", "conference", &json_schema());
+
+    // Act
+    crate::run_with(
+        &["pexshell", "completions", shell].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output = test_context.take_stdout();
+    assert_that!(
+        output,
+        all!(
+            contains_substring("crypto_mode"),
+            contains_substring(choices)
+        )
+    );
+}
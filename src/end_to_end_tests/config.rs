@@ -0,0 +1,144 @@
+#![allow(clippy::significant_drop_tightening)]
+
+use std::{collections::HashMap, os::unix::fs::PermissionsExt};
+
+use googletest::prelude::*;
+use test_helpers::get_test_context;
+
+use crate::{
+    end_to_end_tests::configuration_helpers::{
+        configure_config_test_user, schemas_configuration_conference_only,
+    },
+    pexshell::ExitCode,
+    test_util::TestContextExtensions,
+};
+
+#[tokio::test]
+async fn config_audit_reports_plaintext_password() {
+    // Arrange
+    let test_context = get_test_context();
+    configure_config_test_user(&test_context, "test_address.test.com");
+    let config_path = test_context.get_config_dir().join("config.toml");
+    std::fs::set_permissions(&config_path, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+    // Act
+    crate::run_with(
+        &["pexshell", "config", "audit"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(
+        test_context.take_stdout(),
+        eq(concat!(
+            "WARN  passwords: passwords are stored in plaintext for: test_user@test_address.test.com\n",
+            "      hint: log in again without --store_passwords_in_plaintext to store passwords in the keyring\n",
+            "PASS  private keys: no private keys are stored in the config file\n",
+            "PASS  TLS: certificate verification is not disabled for any user\n",
+            "PASS  log file: logging to a file is disabled\n",
+            "PASS  permissions: config file is not readable by other users (mode 600)\n",
+        ))
+    );
+}
+
+#[tokio::test]
+async fn config_audit_fails_for_world_readable_config() {
+    // Arrange
+    let test_context = get_test_context();
+    configure_config_test_user(&test_context, "test_address.test.com");
+    let config_path = test_context.get_config_dir().join("config.toml");
+    std::fs::set_permissions(&config_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "config", "audit"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result
+            .unwrap_err()
+            .downcast_ref::<ExitCode>()
+            .map(ExitCode::code),
+        some(eq(1))
+    );
+    assert_that!(
+        test_context.take_stdout(),
+        contains_substring(concat!(
+            "FAIL  permissions: config file is readable by all users (mode 644)\n",
+            "      hint: restrict the permissions of the config file with: chmod 600 <config file>\n",
+        ))
+    );
+}
+
+#[tokio::test]
+async fn config_doctor_reports_usable_config() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, "test_address.test.com");
+
+    // Act
+    crate::run_with(
+        &["pexshell", "config", "doctor"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(
+        test_context.take_stdout(),
+        eq(concat!(
+            "PASS  credentials: credentials for test_user@test_address.test.com are available\n",
+            "PASS  schema cache: schema cache exists\n",
+        ))
+    );
+}
+
+#[tokio::test]
+async fn config_doctor_fails_without_schema_cache() {
+    // Arrange
+    let test_context = get_test_context();
+    configure_config_test_user(&test_context, "test_address.test.com");
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "config", "doctor"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result
+            .unwrap_err()
+            .downcast_ref::<ExitCode>()
+            .map(ExitCode::code),
+        some(eq(1))
+    );
+    let stdout = test_context.take_stdout();
+    assert_that!(
+        stdout,
+        contains_substring(concat!(
+            "FAIL  schema cache: schema cache is missing\n",
+            "      hint: generate the schema cache with: pexshell cache\n",
+        ))
+    );
+    assert_that!(stdout, not(contains_substring("test_password")));
+}
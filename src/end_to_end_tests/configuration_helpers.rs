@@ -1,5 +1,8 @@
 use serde_json::Value;
-use test_helpers::{fs::Configurer, TestContext};
+use test_helpers::{
+    fs::{Configurer, SchemaCacheBuilder},
+    TestContext,
+};
 
 pub fn configure_config_test_user(
     test_context: &TestContext,
@@ -16,7 +19,7 @@ pub fn configure_config_test_user(
 }
 
 pub fn configure_schemas_configuration_conference_only(test_context: &TestContext) {
-    test_context
+    let conference_schema = test_context
         .get_schema_builder()
         .field("id", |f| {
             f.blank(true)
@@ -24,27 +27,9 @@ pub fn configure_schemas_configuration_conference_only(test_context: &TestContex
                 .unique(true)
                 .default(Value::String(String::new()))
         })
-        .field("name", |f| f.unique(true).nullable(false))
-        .write("configuration/conference.json");
-    test_context
-        .get_root_schema_builder("/api/admin/configuration/v1/")
-        .entry("conference")
-        .write("configuration/root.json");
-    test_context
-        .get_root_schema_builder("/api/admin/status/v1/")
-        .write("status/root.json");
-    test_context
-        .get_root_schema_builder("/api/admin/history/v1/")
-        .write("history/root.json");
-    test_context
-        .get_root_schema_builder("/api/admin/command/v1/conference/")
-        .write("command/conference/root.json");
-    test_context
-        .get_root_schema_builder("/api/admin/command/v1/participant/")
-        .write("command/participant/root.json");
-    test_context
-        .get_root_schema_builder("/api/admin/command/v1/platform/")
-        .write("command/platform/root.json");
+        .field("name", |f| f.unique(true).nullable(false));
+    test_context.seed_schema("configuration", "conference", &conference_schema.to_value());
+    test_context.seed_minimal_schemas();
 }
 
 pub fn configure_schemas_command_conference_lock_only(test_context: &TestContext) {
@@ -78,3 +63,18 @@ pub fn configure_schemas_command_conference_lock_only(test_context: &TestContext
         .get_root_schema_builder("/api/admin/command/v1/platform/")
         .write("command/platform/root.json");
 }
+
+pub fn schemas_configuration_conference_only(
+    builder: SchemaCacheBuilder<'_>,
+) -> SchemaCacheBuilder<'_> {
+    builder.add_endpoint("configuration", "conference", |schema| {
+        schema
+            .field("id", |f| {
+                f.blank(true)
+                    .nullable(false)
+                    .unique(true)
+                    .default(Value::String(String::new()))
+            })
+            .field("name", |f| f.unique(true).nullable(false))
+    })
+}
@@ -3,6 +3,7 @@
 use std::collections::HashMap;
 
 use googletest::prelude::*;
+use test_case::test_case;
 use test_helpers::get_test_context;
 use wiremock::{
     matchers::{method, path},
@@ -12,6 +13,7 @@ use wiremock::{
 use crate::{
     end_to_end_tests::configuration_helpers::{
         configure_config_test_user, configure_schemas_configuration_conference_only,
+        schemas_configuration_conference_only,
     },
     test_util::TestContextExtensions,
 };
@@ -47,3 +49,44 @@ async fn delete_conference_config() {
     let output = test_context.take_stdout();
     assert_that!(output, eq(""));
 }
+
+#[test_case("--yes" ; "long")]
+#[test_case("-y" ; "short")]
+#[tokio::test]
+async fn delete_conference_config_with_yes(yes: &str) {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("DELETE"))
+        .and(path("/api/admin/configuration/v1/conference/52/"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "delete",
+            "52",
+            yes,
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output = test_context.take_stdout();
+    assert_that!(output, eq(""));
+}
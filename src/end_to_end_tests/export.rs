@@ -0,0 +1,243 @@
+#![allow(clippy::significant_drop_tightening)]
+
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+use serde_json::{json, Value};
+use test_helpers::{fs::SchemaCacheBuilder, get_test_context};
+use wiremock::{
+    matchers::{body_json, method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use crate::{
+    end_to_end_tests::configuration_helpers::configure_config_test_user,
+    test_util::TestContextExtensions,
+};
+
+fn schemas_conference_with_readonly_id(builder: SchemaCacheBuilder<'_>) -> SchemaCacheBuilder<'_> {
+    builder.add_endpoint("configuration", "conference", |schema| {
+        schema
+            .field("id", |f| {
+                f.readonly(true).unique(true).field_type("integer")
+            })
+            .field("name", |f| f.unique(true).nullable(false))
+    })
+}
+
+#[tokio::test]
+async fn export_writes_header_and_objects() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_conference_with_readonly_id);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+    let output_file = test_context.get_test_dir().join("backup.jsonl");
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"meta": {
+            "limit": 500,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total_count": 2,
+        }, "objects": [
+            {"id": 1, "name": "first"},
+            {"id": 2, "name": "second"},
+        ]})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "export",
+            "configuration",
+            "conference",
+            "--output",
+            output_file.to_str().unwrap(),
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let contents = std::fs::read_to_string(&output_file).unwrap();
+    let lines: Vec<Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_that!(
+        lines,
+        elements_are![
+            eq(&json!({
+                "pexshell_export": 1,
+                "api": "configuration",
+                "endpoint": "conference",
+                "count": 2,
+                "fields": ["id", "name"],
+            })),
+            eq(&json!({"id": 1, "name": "first"})),
+            eq(&json!({"id": 2, "name": "second"})),
+        ]
+    );
+    assert_that!(
+        test_context.take_stdout(),
+        contains_substring("Exported 2 objects")
+    );
+}
+
+#[tokio::test]
+async fn import_posts_objects_that_do_not_exist() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_conference_with_readonly_id);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+    let input_file = test_context.get_test_dir().join("backup.jsonl");
+    std::fs::write(
+        &input_file,
+        [
+            r#"{"pexshell_export":1,"api":"configuration","endpoint":"conference","count":2,"fields":["id","name"]}"#,
+            r#"{"id":1,"name":"first","resource_uri":"/api/admin/configuration/v1/conference/1/"}"#,
+            r#"{"id":2,"name":"second","resource_uri":"/api/admin/configuration/v1/conference/2/"}"#,
+        ]
+        .join("\n"),
+    )
+    .unwrap();
+
+    Mock::given(method("HEAD"))
+        .and(path("/api/admin/configuration/v1/conference/1/"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/api/admin/configuration/v1/conference/2/"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(body_json(json!({"name": "second"})))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .append_header("Location", "/api/admin/configuration/v1/conference/3/"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &["pexshell", "import", "--from", input_file.to_str().unwrap()].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(
+        test_context.take_stdout(),
+        eq("Imported 1 objects, skipped 1 objects that already exist\n")
+    );
+}
+
+#[tokio::test]
+async fn import_checks_unique_key_with_filter() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_conference_with_readonly_id);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+    let input_file = test_context.get_test_dir().join("backup.jsonl");
+    std::fs::write(
+        &input_file,
+        [
+            r#"{"pexshell_export":1,"api":"configuration","endpoint":"conference","count":1,"fields":["id","name"]}"#,
+            r#"{"id":1,"name":"first"}"#,
+        ]
+        .join("\n"),
+    )
+    .unwrap();
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(query_param("name", "first"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"meta": {
+            "limit": 1,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total_count": 1,
+        }, "objects": [{"id": 7, "name": "first"}]})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(201))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "import",
+            "--from",
+            input_file.to_str().unwrap(),
+            "--unique-key",
+            "name",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(
+        test_context.take_stdout(),
+        eq("Imported 0 objects, skipped 1 objects that already exist\n")
+    );
+}
+
+#[tokio::test]
+async fn import_rejects_file_without_header() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_conference_with_readonly_id);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+    let input_file = test_context.get_test_dir().join("backup.jsonl");
+    std::fs::write(&input_file, r#"{"id":1,"name":"first"}"#).unwrap();
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "import", "--from", input_file.to_str().unwrap()].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring("invalid export header")))
+    );
+}
@@ -7,6 +7,7 @@ use googletest::prelude::*;
 use jsonwebtoken::{DecodingKey, Validation};
 use log::info;
 use serde_json::{json, Value};
+use test_case::test_case;
 use test_helpers::{fs::OAuth2Credentials, get_test_context, logging::expect};
 use wiremock::{
     matchers::{header, method, path},
@@ -14,9 +15,12 @@ use wiremock::{
 };
 
 use crate::{
+    consts::{EXIT_CODE_FORBIDDEN, EXIT_CODE_NOT_FOUND, EXIT_CODE_UNAUTHORIZED},
     end_to_end_tests::configuration_helpers::{
         configure_config_test_user, configure_schemas_configuration_conference_only,
+        schemas_configuration_conference_only,
     },
+    pexshell::ExitCode,
     test_util::TestContextExtensions,
 };
 
@@ -184,3 +188,748 @@ async fn get_conference_config_oauth2() {
         }))
     );
 }
+
+#[tokio::test]
+async fn get_conference_config_rate_limited() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+    configure_schemas_configuration_conference_only(&test_context);
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": 5,
+            "name": "some_test_conference",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &["pexshell", "configuration", "conference", "get", "5"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let raw = test_context.take_stdout();
+    let output: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    assert_that!(
+        output,
+        eq(&json!({
+            "id": 5,
+            "name": "some_test_conference",
+        }))
+    );
+}
+
+#[tokio::test]
+async fn get_conference_config_rate_limited_retries_exhausted() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+    configure_schemas_configuration_conference_only(&test_context);
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "--retry",
+            "2",
+            "configuration",
+            "conference",
+            "get",
+            "5",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(result, err(anything()));
+}
+
+#[tokio::test]
+async fn get_conference_config_retried_after_transient_error() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "0"))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": 5,
+            "name": "some_test_conference",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &["pexshell", "configuration", "conference", "get", "5"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let raw = test_context.take_stdout();
+    let output: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    assert_that!(
+        output,
+        eq(&json!({
+            "id": 5,
+            "name": "some_test_conference",
+        }))
+    );
+}
+
+#[tokio::test]
+async fn get_conference_config_transient_retries_disabled_by_config() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    test_context.create_config_file(format!(
+        r#"
+        retries = 0
+
+        [[users]]
+        address = "{}"
+        username = "test_user"
+        password = "test_password"
+        current_user = true
+        "#,
+        server.uri(),
+    ));
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(502))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "configuration", "conference", "get", "5"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(result, err(anything()));
+}
+
+#[tokio::test]
+async fn get_conference_config_through_proxy_from_env() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let proxy = MockServer::start().await;
+
+    configure_config_test_user(&test_context, "http://mcu.invalid");
+
+    Mock::given(method("GET"))
+        .and(header("Host", "mcu.invalid"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 5})))
+        .expect(1)
+        .mount(&proxy)
+        .await;
+
+    // Act
+    crate::run_with(
+        &["pexshell", "configuration", "conference", "get", "5"].map(String::from),
+        HashMap::from([(String::from("HTTP_PROXY"), proxy.uri())]),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output: serde_json::Value = serde_json::from_str(&test_context.take_stdout()).unwrap();
+    assert_that!(output, eq(&json!({"id": 5})));
+}
+
+#[tokio::test]
+async fn get_conference_config_through_proxy_flag_overriding_env() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let proxy = MockServer::start().await;
+    let proxy_uri = proxy.uri();
+
+    configure_config_test_user(&test_context, "http://mcu.invalid");
+
+    Mock::given(method("GET"))
+        .and(header("Host", "mcu.invalid"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 5})))
+        .expect(1)
+        .mount(&proxy)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "--proxy",
+            proxy_uri.as_str(),
+            "configuration",
+            "conference",
+            "get",
+            "5",
+        ]
+        .map(String::from),
+        HashMap::from([
+            (
+                String::from("HTTP_PROXY"),
+                String::from("http://proxy.invalid"),
+            ),
+            (String::from("NO_PROXY"), String::from("mcu.invalid")),
+        ]),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output: serde_json::Value = serde_json::from_str(&test_context.take_stdout()).unwrap();
+    assert_that!(output, eq(&json!({"id": 5})));
+}
+
+#[tokio::test]
+async fn get_conference_config_rejects_invalid_proxy() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, "http://mcu.invalid");
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "configuration", "conference", "get", "5"].map(String::from),
+        HashMap::from([(String::from("HTTPS_PROXY"), String::from("not a url"))]),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring(
+            "invalid proxy in HTTPS_PROXY"
+        )))
+    );
+}
+
+#[tokio::test]
+async fn get_conference_config_rejects_missing_ca_cert() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, "https://mcu.invalid");
+    let ca_cert = test_context.get_test_dir().join("missing.pem");
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "--cacert",
+            ca_cert.to_str().unwrap(),
+            "configuration",
+            "conference",
+            "get",
+            "5",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring(
+            "failed to read CA certificates from"
+        )))
+    );
+}
+
+#[tokio::test]
+async fn get_conference_config_rejects_ca_cert_without_certificates() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, "https://mcu.invalid");
+    let ca_cert = test_context.get_test_dir().join("ca.pem");
+    std::fs::write(&ca_cert, "not a certificate\n").unwrap();
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "--cacert",
+            ca_cert.to_str().unwrap(),
+            "configuration",
+            "conference",
+            "get",
+            "5",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring(
+            "does not contain any PEM certificates"
+        )))
+    );
+}
+
+#[tokio::test]
+async fn get_conference_config_rejects_missing_client_key() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, "https://mcu.invalid");
+    let client_cert = test_context.get_test_dir().join("client.pem");
+    std::fs::write(&client_cert, "not a certificate\n").unwrap();
+    let client_key = test_context.get_test_dir().join("missing.key");
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "--client-cert",
+            client_cert.to_str().unwrap(),
+            "--client-key",
+            client_key.to_str().unwrap(),
+            "configuration",
+            "conference",
+            "get",
+            "5",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring(
+            "failed to read client key from"
+        )))
+    );
+}
+
+#[tokio::test]
+async fn get_conference_config_rejects_invalid_client_cert() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, "https://mcu.invalid");
+    let client_cert = test_context.get_test_dir().join("client.pem");
+    std::fs::write(&client_cert, "not a certificate\n").unwrap();
+    let client_key = test_context.get_test_dir().join("client.key");
+    std::fs::write(&client_key, "not a key\n").unwrap();
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "--client-cert",
+            client_cert.to_str().unwrap(),
+            "--client-key",
+            client_key.to_str().unwrap(),
+            "configuration",
+            "conference",
+            "get",
+            "5",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring(
+            "invalid client certificate or key"
+        )))
+    );
+}
+
+#[tokio::test]
+async fn get_conference_exists() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+    configure_schemas_configuration_conference_only(&test_context);
+
+    Mock::given(method("HEAD"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "get",
+            "5",
+            "--exists",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(test_context.take_stdout(), eq(""));
+}
+
+#[test_case(404, EXIT_CODE_NOT_FOUND ; "not found")]
+#[test_case(401, EXIT_CODE_UNAUTHORIZED ; "unauthorized")]
+#[test_case(403, EXIT_CODE_FORBIDDEN ; "forbidden")]
+#[tokio::test]
+async fn get_conference_exists_failure(status: u16, exit_code: i32) {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+    configure_schemas_configuration_conference_only(&test_context);
+
+    Mock::given(method("HEAD"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(status))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "get",
+            "5",
+            "--exists",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result
+            .unwrap_err()
+            .downcast_ref::<ExitCode>()
+            .map(ExitCode::code),
+        some(eq(exit_code))
+    );
+    assert_that!(test_context.take_stdout(), eq(""));
+}
+
+#[test_case(404, EXIT_CODE_NOT_FOUND ; "not found")]
+#[test_case(401, EXIT_CODE_UNAUTHORIZED ; "unauthorized")]
+#[test_case(403, EXIT_CODE_FORBIDDEN ; "forbidden")]
+#[test_case(500, 1 ; "server error")]
+#[tokio::test]
+async fn get_conference_failure_exit_code(status: u16, exit_code: i32) {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(status))
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "configuration", "conference", "get", "5"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        ExitCode::for_error(&result.unwrap_err()).map_or(1, |code| code.code()),
+        eq(exit_code)
+    );
+}
+
+#[tokio::test]
+async fn get_with_corrupt_schema_aborts() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+    configure_schemas_configuration_conference_only(&test_context);
+    let schema_path = test_context
+        .get_cache_dir()
+        .join("schemas/configuration/conference.json");
+    std::fs::write(&schema_path, "{ not json").unwrap();
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "--abort-on-schema-error",
+            "configuration",
+            "conference",
+            "get",
+            "5",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(all!(
+            starts_with(
+                "failed to read schemas from the cache - please regenerate it with: pexshell cache"
+            ),
+            contains_substring(schema_path.to_str().unwrap()),
+        )))
+    );
+}
+
+#[tokio::test]
+async fn get_sends_request_id() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .and(header("X-Request-ID", "some-request-id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 5})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "--request-id",
+            "some-request-id",
+            "configuration",
+            "conference",
+            "get",
+            "5",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(result, ok(anything()));
+}
+
+#[tokio::test]
+async fn get_generates_request_id_when_enabled() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    test_context.create_config_file(format!(
+        r#"
+        enable_request_ids = true
+
+        [[users]]
+        address = "{}"
+        username = "test_user"
+        password = "test_password"
+        current_user = true
+        "#,
+        server.uri(),
+    ));
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 5})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &["pexshell", "configuration", "conference", "get", "5"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let requests = server.received_requests().await.unwrap();
+    let request_id = requests[0].headers.get("X-Request-ID").unwrap();
+    assert_that!(
+        uuid::Uuid::parse_str(request_id.to_str().unwrap()),
+        ok(anything())
+    );
+}
+
+#[tokio::test]
+async fn get_reports_network_error() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = test_context
+        .simulate_network_error("/api/admin/configuration/v1/conference/5/", 1)
+        .await;
+    configure_config_test_user(&test_context, server.uri());
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "configuration", "conference", "get", "5"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring("error sending request")))
+    );
+    assert_that!(server.failures_remaining(), eq(0));
+}
+
+#[test_case("--print-curl", "<REDACTED>" ; "redacted")]
+#[test_case("--print-curl=unsafe", "Basic dGVzdF91c2VyOnRlc3RfcGFzc3dvcmQ=" ; "unsafe")]
+#[tokio::test]
+async fn get_conference_config_print_curl(flag: &str, authorization: &str) {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &["pexshell", flag, "configuration", "conference", "get", "5"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(
+        test_context.take_stdout(),
+        eq(format!(
+            "curl \\\n  '{}/api/admin/configuration/v1/conference/5/' \\\n  -H 'authorization: {authorization}'\n",
+            server.uri()
+        ))
+    );
+}
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 
 use googletest::prelude::*;
 use serde_json::json;
-use test_helpers::get_test_context;
+use test_helpers::{fs::SchemaCacheBuilder, get_test_context};
 use wiremock::{
     matchers::{method, path, query_param},
     Mock, MockServer, ResponseTemplate,
@@ -13,6 +13,7 @@ use wiremock::{
 use crate::{
     end_to_end_tests::configuration_helpers::{
         configure_config_test_user, configure_schemas_configuration_conference_only,
+        schemas_configuration_conference_only,
     },
     test_util::TestContextExtensions,
 };
@@ -290,3 +291,667 @@ async fn get_limited_to_first_page() {
         ]))
     );
 }
+
+#[tokio::test]
+async fn get_resumed_from_offset() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(query_param("limit", "2"))
+        .and(query_param("offset", "1000"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"meta": {
+            "limit": 2,
+            "next": "/api/admin/configuration/v1/conference/?limit=2&offset=1002",
+            "offset": 1000,
+            "previous": "/api/admin/configuration/v1/conference/?limit=2&offset=998",
+            "total_count": 1010,
+        }, "objects": [
+            {
+                "id": 1001,
+                "name": "test_1001",
+            },
+            {
+                "id": 1002,
+                "name": "test_1002",
+            }
+        ]})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "get",
+            "--page_size",
+            "2",
+            "--offset",
+            "1000",
+            "--limit",
+            "1",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let raw = test_context.take_stdout();
+    let output: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    assert_that!(
+        output,
+        eq(&json!([
+            {
+                "id": 1001,
+                "name": "test_1001",
+            },
+        ]))
+    );
+}
+
+#[tokio::test]
+async fn get_ordered_by_field_descending() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(query_param("order_by", "-name"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"meta": {
+            "limit": 500,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total_count": 2,
+        }, "objects": [
+            {
+                "id": 2,
+                "name": "test_2",
+            },
+            {
+                "id": 1,
+                "name": "test_1",
+            }
+        ]})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "get",
+            "--order-by",
+            "name",
+            "--desc",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let raw = test_context.take_stdout();
+    let output: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    assert_that!(
+        output,
+        eq(&json!([
+            {
+                "id": 2,
+                "name": "test_2",
+            },
+            {
+                "id": 1,
+                "name": "test_1",
+            },
+        ]))
+    );
+}
+
+#[tokio::test]
+async fn get_ordered_by_unknown_field_lists_allowed_fields() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, String::from("http://localhost"));
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "get",
+            "--order-by",
+            "tag",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(result, err(anything()));
+    assert_that!(
+        test_context.take_stderr(),
+        contains_substring("[possible values: id, name]")
+    );
+}
+
+#[cfg(all(feature = "transform", unix))]
+#[tokio::test]
+async fn get_transformed_by_command() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+    configure_schemas_configuration_conference_only(&test_context);
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(query_param("limit", "500"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"meta": {
+            "limit": 500,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total_count": 2,
+        }, "objects": [
+            {
+                "id": 1,
+                "name": "test_1",
+            },
+            {
+                "id": 2,
+                "name": "test_2",
+            },
+        ]})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "--transform",
+            "tr a-z A-Z",
+            "configuration",
+            "conference",
+            "get",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(
+        test_context.take_stdout(),
+        eq("{\"ID\":1,\"NAME\":\"TEST_1\"}\n{\"ID\":2,\"NAME\":\"TEST_2\"}\n")
+    );
+}
+
+#[cfg(all(feature = "transform", unix))]
+#[tokio::test]
+async fn get_transform_command_fails() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+    configure_schemas_configuration_conference_only(&test_context);
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"meta": {
+            "limit": 500,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total_count": 0,
+        }, "objects": []})))
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "--transform",
+            "echo 'something went wrong' >&2; exit 3",
+            "configuration",
+            "conference",
+            "get",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring("something went wrong")))
+    );
+}
+
+#[tokio::test]
+async fn get_count_prints_total_count() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(query_param("limit", "1"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"meta": {
+            "limit": 1,
+            "next": "/api/admin/configuration/v1/conference/?limit=1&offset=1",
+            "offset": 0,
+            "previous": null,
+            "total_count": 1234,
+        }, "objects": [{"id": 1, "name": "first"}]})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &["pexshell", "configuration", "conference", "get", "--count"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(test_context.take_stdout(), eq("1234\n"));
+}
+
+fn schemas_conference_with_filters(builder: SchemaCacheBuilder<'_>) -> SchemaCacheBuilder<'_> {
+    builder.add_endpoint("configuration", "conference", |schema| {
+        schema
+            .field("name", |f| f.unique(true).nullable(false))
+            .field("tag", |f| f.nullable(false))
+            .filtering("name", 1)
+            .filtering("tag", 1)
+    })
+}
+
+#[tokio::test]
+async fn get_with_filter_file() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_conference_with_filters);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+    let filter_file = test_context.get_test_dir().join("filters.json");
+    std::fs::write(
+        &filter_file,
+        json!({"name__startswith": "a", "tag": "from_file"}).to_string(),
+    )
+    .unwrap();
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(query_param("name__startswith", "a"))
+        .and(query_param("tag", "from_option"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"meta": {
+            "limit": 500,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total_count": 0,
+        }, "objects": []})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "get",
+            "--filter-file",
+            filter_file.to_str().unwrap(),
+            "--tag",
+            "from_option",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(result, ok(anything()));
+}
+
+#[tokio::test]
+async fn get_with_filter_file_rejects_unknown_filter() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_conference_with_filters);
+    configure_config_test_user(&test_context, String::from("http://localhost"));
+    let filter_file = test_context.get_test_dir().join("filters.json");
+    std::fs::write(&filter_file, json!({"description": "a"}).to_string()).unwrap();
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "get",
+            "--filter-file",
+            filter_file.to_str().unwrap(),
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring(
+            "description is not a valid filter for this endpoint"
+        )))
+    );
+}
+
+#[tokio::test]
+async fn get_with_fields_prints_only_those_fields() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"meta": {
+            "limit": 500,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total_count": 2,
+        }, "objects": [
+            {"id": 1, "name": "room one", "tag": "team"},
+            {"id": 2, "name": "room two"},
+        ]})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "get",
+            "--fields",
+            "name,tag",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output: serde_json::Value = serde_json::from_str(&test_context.take_stdout()).unwrap();
+    assert_that!(
+        output,
+        eq(&json!([
+            {"name": "room one", "tag": "team"},
+            {"name": "room two"},
+        ]))
+    );
+    assert_that!(
+        test_context.take_stderr(),
+        contains_substring("tag is not a field of this endpoint")
+    );
+}
+
+#[tokio::test]
+async fn get_writes_output_file_instead_of_stdout() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+    let output_file = test_context.get_test_dir().join("conferences.json");
+    std::fs::write(&output_file, "previous contents").unwrap();
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"meta": {
+            "limit": 500,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total_count": 1,
+        }, "objects": [{"id": 1, "name": "first"}]})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "--output-file",
+            output_file.to_str().unwrap(),
+            "configuration",
+            "conference",
+            "get",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(test_context.take_stdout(), eq(""));
+    let output: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&output_file).unwrap()).unwrap();
+    assert_that!(output, eq(&json!([{"id": 1, "name": "first"}])));
+}
+
+#[tokio::test]
+async fn get_fails_before_request_if_output_file_cannot_be_created() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+    let output_file = test_context
+        .get_test_dir()
+        .join("missing_dir")
+        .join("conferences.json");
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "--output-file",
+            output_file.to_str().unwrap(),
+            "configuration",
+            "conference",
+            "get",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring("failed to create")))
+    );
+}
+
+fn schemas_conference_with_related_theme(
+    builder: SchemaCacheBuilder<'_>,
+) -> SchemaCacheBuilder<'_> {
+    builder.add_endpoint("configuration", "conference", |schema| {
+        schema
+            .field("name", |f| f.unique(true).nullable(false))
+            .field("ivr_theme", |f| f.field_type("related"))
+    })
+}
+
+#[tokio::test]
+async fn get_expands_related_fields() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_conference_with_related_theme);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+    let theme_uri = "/api/admin/configuration/v1/ivr_theme/2/";
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"meta": {
+            "limit": 500,
+            "next": null,
+            "offset": 0,
+            "previous": null,
+            "total_count": 3,
+        }, "objects": [
+            {"name": "first", "ivr_theme": theme_uri},
+            {"name": "second", "ivr_theme": theme_uri},
+            {"name": "third", "ivr_theme": null},
+        ]})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(theme_uri))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"name": "theme", "resource_uri": theme_uri})),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "get",
+            "--expand",
+            "ivr_theme",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output: serde_json::Value = serde_json::from_str(&test_context.take_stdout()).unwrap();
+    let theme = json!({"name": "theme", "resource_uri": theme_uri});
+    assert_that!(
+        output,
+        eq(&json!([
+            {"name": "first", "ivr_theme": theme},
+            {"name": "second", "ivr_theme": theme},
+            {"name": "third", "ivr_theme": null},
+        ]))
+    );
+}
+
+#[tokio::test]
+async fn get_expand_rejects_fields_that_are_not_related() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_conference_with_related_theme);
+    configure_config_test_user(&test_context, String::from("http://localhost"));
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "get",
+            "--expand",
+            "name",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(result, err(anything()));
+}
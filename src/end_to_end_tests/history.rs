@@ -0,0 +1,108 @@
+#![allow(clippy::significant_drop_tightening)]
+
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+use serde_json::json;
+use test_helpers::get_test_context;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use crate::{
+    end_to_end_tests::configuration_helpers::configure_schemas_configuration_conference_only,
+    test_util::TestContextExtensions,
+};
+
+#[tokio::test]
+async fn history_records_successful_commands() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+    let history_file = test_context.get_test_dir().join("data/history.jsonl");
+
+    test_context
+        .get_config_builder()
+        .add_basic_user(server.uri(), "test_user", "test_password", true)
+        .history_file(&history_file)
+        .write();
+    configure_schemas_configuration_conference_only(&test_context);
+
+    Mock::given(method("PATCH"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "patch",
+            "5",
+            "--name",
+            "secret_room",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+    crate::run_with(
+        &["pexshell", "configuration", "conference", "get", "5"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap_err();
+    test_context.take_stdout();
+
+    // Act
+    crate::run_with(
+        &["pexshell", "history", "list"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output = test_context.take_stdout();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_that!(
+        lines,
+        elements_are![all!(
+            contains_substring(server.uri()),
+            ends_with("configuration conference patch 5 --name secret_room"),
+        )]
+    );
+
+    let raw = std::fs::read_to_string(&history_file).unwrap();
+    let entry: serde_json::Value = serde_json::from_str(raw.trim_end()).unwrap();
+    assert_that!(
+        entry["args"],
+        eq(&json!([
+            "configuration",
+            "conference",
+            "patch",
+            "5",
+            "--name",
+            "secret_room"
+        ]))
+    );
+    assert_that!(entry["exit_code"], eq(&json!(0)));
+}
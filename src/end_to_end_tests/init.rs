@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+use test_helpers::get_test_context;
+
+use crate::test_util::TestContextExtensions;
+
+#[tokio::test]
+async fn init_requires_interactive_terminal() {
+    // Arrange
+    let test_context = get_test_context();
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "init"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring(
+            "run pexshell login and then pexshell cache"
+        )))
+    );
+}
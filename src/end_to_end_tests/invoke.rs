@@ -0,0 +1,185 @@
+#![allow(clippy::significant_drop_tightening)]
+
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+use serde_json::json;
+use test_helpers::get_test_context;
+use wiremock::{
+    matchers::{basic_auth, body_json, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use crate::{
+    end_to_end_tests::configuration_helpers::configure_config_test_user,
+    test_util::TestContextExtensions,
+};
+
+#[tokio::test]
+async fn invoke_sends_raw_request() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("POST"))
+        .and(path("/api/admin/configuration/v1/custom/"))
+        .and(basic_auth("test_user", "test_password"))
+        .and(body_json(json!({"name": "test"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "invoke",
+            "--method",
+            "POST",
+            "--path",
+            "/api/admin/configuration/v1/custom/",
+            "--body",
+            r#"{"name": "test"}"#,
+            "--i-know-what-im-doing",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output: serde_json::Value = serde_json::from_str(&test_context.take_stdout()).unwrap();
+    assert_that!(output, eq(&json!({"id": 1})));
+    assert_that!(
+        test_context.take_stderr(),
+        contains_substring("bypasses schema validation")
+    );
+}
+
+#[tokio::test]
+async fn invoke_reads_body_from_file() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+    let body_file = test_context.get_test_dir().join("body.json");
+    std::fs::write(&body_file, r#"{"name": "from_file"}"#).unwrap();
+
+    Mock::given(method("PATCH"))
+        .and(path("/api/admin/configuration/v1/custom/1/"))
+        .and(body_json(json!({"name": "from_file"})))
+        .respond_with(ResponseTemplate::new(202))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "invoke",
+            "--method",
+            "PATCH",
+            "--path",
+            "/api/admin/configuration/v1/custom/1/",
+            "--body-file",
+            body_file.to_str().unwrap(),
+            "--i-know-what-im-doing",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(result, ok(anything()));
+}
+
+#[tokio::test]
+async fn invoke_requires_confirmation() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "invoke",
+            "--path",
+            "/api/admin/configuration/v1/custom/",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring("--i-know-what-im-doing")))
+    );
+}
+
+#[tokio::test]
+async fn raw_sends_request_relative_to_api_base() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("POST"))
+        .and(path("/api/admin/configuration/v1/custom/"))
+        .and(basic_auth("test_user", "test_password"))
+        .and(body_json(json!({"name": "test"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "raw",
+            "POST",
+            "configuration/v1/custom/",
+            "--body",
+            r#"{"name": "test"}"#,
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output: serde_json::Value = serde_json::from_str(&test_context.take_stdout()).unwrap();
+    assert_that!(output, eq(&json!({"id": 1})));
+    assert_that!(
+        test_context.take_stderr(),
+        contains_substring("bypasses schema validation")
+    );
+}
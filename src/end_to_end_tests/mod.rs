@@ -14,11 +14,25 @@ use crate::test_util::TestContextExtensions;
 mod configuration_helpers;
 
 mod cache;
+mod completions;
+#[cfg(unix)]
+mod config;
 mod delete;
+mod export;
 mod get;
 mod get_all;
+mod history;
+mod init;
+mod invoke;
 mod patch;
+mod pin_schema;
 mod post;
+mod put;
+mod schema;
+mod tee;
+mod token;
+mod upsert;
+mod watch;
 
 #[tokio::test]
 async fn basic_get() {
@@ -45,46 +59,8 @@ async fn basic_get() {
     );
 
     test_context.create_config_file(config);
-    std::fs::create_dir_all(test_context.get_cache_dir().join("schemas/configuration")).unwrap();
-    std::fs::write(
-        test_context
-            .get_cache_dir()
-            .join("schemas/configuration/root.json"),
-        serde_json::to_string(&json!({
-            "conference": {
-                "list_endpoint": "/api/admin/configuration/v1/conference/",
-                "schema": "/api/admin/configuration/v1/conference/schema/"
-            }
-        }))
-        .unwrap(),
-    )
-    .unwrap();
-    for dir in [
-        "status",
-        "history",
-        "command/conference",
-        "command/participant",
-        "command/platform",
-    ] {
-        std::fs::create_dir_all(test_context.get_cache_dir().join("schemas").join(dir)).unwrap();
-        std::fs::write(
-            test_context
-                .get_cache_dir()
-                .join("schemas")
-                .join(dir)
-                .join("root.json"),
-            "{}",
-        )
-        .unwrap();
-    }
-
-    std::fs::write(
-        test_context
-            .get_cache_dir()
-            .join("schemas/configuration/conference.json"),
-        serde_json::to_string(&json_schema()).unwrap(),
-    )
-    .unwrap();
+    test_context.seed_minimal_schemas();
+    test_context.seed_schema("configuration", "conference", &json_schema());
 
     crate::run_with(
         &["pexshell", "configuration", "conference", "get", "1"]
@@ -13,6 +13,7 @@ use wiremock::{
 use crate::{
     end_to_end_tests::configuration_helpers::{
         configure_config_test_user, configure_schemas_configuration_conference_only,
+        schemas_configuration_conference_only,
     },
     test_util::TestContextExtensions,
 };
@@ -58,3 +59,48 @@ async fn patch_conference_config() {
     let output = test_context.take_stdout();
     assert_that!(output, eq(""));
 }
+
+#[tokio::test]
+async fn patch_conference_config_dry_run() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("PATCH"))
+        .respond_with(wiremock::ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "--dry-run",
+            "configuration",
+            "conference",
+            "patch",
+            "89",
+            "--name",
+            "patch_test_conf",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(
+        test_context.take_stdout(),
+        eq(format!(
+            "PATCH {}/api/admin/configuration/v1/conference/89/\n{{\n  \"name\": \"patch_test_conf\"\n}}\n",
+            server.uri()
+        ))
+    );
+}
@@ -0,0 +1,194 @@
+#![allow(clippy::significant_drop_tightening)]
+
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+use test_helpers::get_test_context;
+
+use crate::{
+    end_to_end_tests::configuration_helpers::{
+        configure_config_test_user, schemas_configuration_conference_only,
+    },
+    test_util::TestContextExtensions,
+};
+
+#[tokio::test]
+async fn pin_schema_copies_cache_and_updates_config() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, String::from("http://localhost"));
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "pin-schema",
+            "--for",
+            "http://localhost",
+            "--version",
+            "36.0",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let config =
+        std::fs::read_to_string(test_context.get_config_dir().join("config.toml")).unwrap();
+    assert_that!(
+        config,
+        contains_substring(r#"pinned_schema_version = "36.0""#)
+    );
+    assert_that!(
+        test_context
+            .get_cache_dir()
+            .join("schemas/localhost/36.0/configuration/conference.json")
+            .exists(),
+        eq(true)
+    );
+    assert_that!(
+        test_context.take_stdout(),
+        eq("Pinned schemas for http://localhost to version 36.0\n")
+    );
+}
+
+#[tokio::test]
+async fn pinned_schemas_are_kept_when_cache_is_cleared() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, String::from("http://localhost"));
+    crate::run_with(
+        &[
+            "pexshell",
+            "pin-schema",
+            "--for",
+            "http://localhost",
+            "--version",
+            "36.0",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+    crate::run_with(
+        &["pexshell", "cache", "--clear"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+    test_context.take_stdout();
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "schema", "show", "configuration", "conference"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(result, ok(anything()));
+    assert_that!(
+        test_context.take_stdout(),
+        starts_with("FIELD  TYPE    NULLABLE")
+    );
+}
+
+#[tokio::test]
+async fn pin_schema_rejects_unknown_address() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, String::from("http://localhost"));
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "pin-schema",
+            "--for",
+            "mcu.example.com",
+            "--version",
+            "36.0",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring(
+            "no users found for mcu.example.com"
+        )))
+    );
+}
+
+#[tokio::test]
+async fn refresh_schema_is_ignored_when_schemas_are_pinned() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, String::from("http://localhost"));
+    crate::run_with(
+        &[
+            "pexshell",
+            "pin-schema",
+            "--for",
+            "http://localhost",
+            "--version",
+            "36.0",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+    test_context.take_stdout();
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "--refresh-schema",
+            "schema",
+            "show",
+            "configuration",
+            "conference",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(result, ok(anything()));
+    assert_that!(
+        test_context.take_stderr(),
+        contains_substring(
+            "--refresh-schema was ignored because the schemas of this management node are pinned"
+        )
+    );
+}
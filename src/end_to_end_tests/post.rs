@@ -13,7 +13,7 @@ use wiremock::{
 use crate::{
     end_to_end_tests::configuration_helpers::{
         configure_config_test_user, configure_schemas_command_conference_lock_only,
-        configure_schemas_configuration_conference_only,
+        configure_schemas_configuration_conference_only, schemas_configuration_conference_only,
     },
     test_util::TestContextExtensions,
 };
@@ -108,3 +108,383 @@ async fn post_conference_lock_command() {
     let output = test_context.take_stdout();
     assert_that!(output, eq(""));
 }
+
+#[tokio::test]
+async fn post_conference_lock_command_compat() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+    configure_schemas_command_conference_lock_only(&test_context);
+
+    Mock::given(method("POST"))
+        .and(path("/api/admin/command/v1/conference/lock/"))
+        .respond_with(
+            ResponseTemplate::new(202).set_body_json(json!({"data": null, "status": "success"})),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "--compat",
+            "29",
+            "command",
+            "conference",
+            "lock",
+            "--conference_id",
+            "22ec87ef-92e8-4100-a8be-d12da654f6c3",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output: serde_json::Value = serde_json::from_str(&test_context.take_stdout()).unwrap();
+    assert_that!(output, eq(&json!({"data": null, "status": "success"})));
+}
+
+#[tokio::test]
+async fn post_conference_config_from_file_with_option_override() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    let payload_file = test_context.get_test_dir().join("conference.json");
+    std::fs::write(&payload_file, r#"{"id": "7", "name": "file_conf"}"#).unwrap();
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("POST"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(body_json(json!({"id": "7", "name": "post_test_conf"})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("Location", "/api/admin/configuration/v1/conference/54/"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "post",
+            "--from-file",
+            payload_file.to_str().unwrap(),
+            "--name",
+            "post_test_conf",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output = test_context.take_stdout();
+    assert_that!(output, eq("/api/admin/configuration/v1/conference/54/\n"));
+}
+
+#[tokio::test]
+async fn post_conference_config_from_file_does_not_need_required_options() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    let payload_file = test_context.get_test_dir().join("conference.json");
+    std::fs::write(&payload_file, r#"{"name": "file_conf"}"#).unwrap();
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("POST"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(body_json(json!({"name": "file_conf"})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("Location", "/api/admin/configuration/v1/conference/54/"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "post",
+            "--from-file",
+            payload_file.to_str().unwrap(),
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output = test_context.take_stdout();
+    assert_that!(output, eq("/api/admin/configuration/v1/conference/54/\n"));
+}
+
+#[tokio::test]
+async fn post_conference_config_from_file_posts_each_object_of_array() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    let payload_file = test_context.get_test_dir().join("conferences.json");
+    std::fs::write(
+        &payload_file,
+        r#"[{"name": "first_conf"}, {"name": "second_conf"}]"#,
+    )
+    .unwrap();
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("POST"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(body_json(json!({"name": "first_conf"})))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .append_header("Location", "/api/admin/configuration/v1/conference/1/"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(body_json(json!({"name": "second_conf"})))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .append_header("Location", "/api/admin/configuration/v1/conference/2/"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "post",
+            "--from-file",
+            payload_file.to_str().unwrap(),
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(
+        test_context.take_stdout(),
+        eq("/api/admin/configuration/v1/conference/1/\n/api/admin/configuration/v1/conference/2/\n")
+    );
+}
+
+#[tokio::test]
+async fn post_conference_config_from_file_reports_failed_objects_of_array() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    let payload_file = test_context.get_test_dir().join("conferences.json");
+    std::fs::write(
+        &payload_file,
+        r#"[{"name": "taken_conf"}, {"name": "new_conf"}]"#,
+    )
+    .unwrap();
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("POST"))
+        .and(body_json(json!({"name": "taken_conf"})))
+        .respond_with(ResponseTemplate::new(400))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_json(json!({"name": "new_conf"})))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .append_header("Location", "/api/admin/configuration/v1/conference/2/"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "post",
+            "--from-file",
+            payload_file.to_str().unwrap(),
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(eq("failed to create 1 of 2 objects")))
+    );
+    assert_that!(
+        test_context.take_stdout(),
+        eq("/api/admin/configuration/v1/conference/2/\n")
+    );
+}
+
+#[tokio::test]
+async fn patch_conference_config_from_file_rejects_array() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    let payload_file = test_context.get_test_dir().join("conferences.json");
+    std::fs::write(&payload_file, r#"[{"name": "file_conf"}]"#).unwrap();
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("PATCH"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "patch",
+            "1",
+            "--from-file",
+            payload_file.to_str().unwrap(),
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring(
+            "the payload must be a single JSON object"
+        )))
+    );
+}
+
+#[tokio::test]
+async fn post_conference_config_from_file_rejects_invalid_payload() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    let payload_file = test_context.get_test_dir().join("conference.json");
+    std::fs::write(&payload_file, r#"{"id": "7"}"#).unwrap();
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "post",
+            "--from-file",
+            payload_file.to_str().unwrap(),
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring("name is required")))
+    );
+}
+
+#[tokio::test]
+async fn post_conference_config_not_retried_after_transient_error() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("POST"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "0"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "--retries",
+            "3",
+            "configuration",
+            "conference",
+            "post",
+            "--name",
+            "post_test_conf",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(result, err(anything()));
+}
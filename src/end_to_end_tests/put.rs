@@ -0,0 +1,61 @@
+#![allow(clippy::significant_drop_tightening)]
+
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+use serde_json::json;
+use test_helpers::{get_test_context, requests::BodyCapture};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer,
+};
+
+use crate::{
+    end_to_end_tests::configuration_helpers::{
+        configure_config_test_user, schemas_configuration_conference_only,
+    },
+    test_util::TestContextExtensions,
+};
+
+#[tokio::test]
+async fn put_conference_config() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+
+    configure_config_test_user(&test_context, server.uri());
+
+    let bodies = BodyCapture::new();
+    Mock::given(method("PUT"))
+        .and(path("/api/admin/configuration/v1/conference/89/"))
+        .and(bodies.clone())
+        .respond_with(wiremock::ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "put",
+            "89",
+            "--name",
+            "put_test_conf",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output = test_context.take_stdout();
+    assert_that!(output, eq(""));
+    assert_that!(bodies.single(), eq(&json!({"name": "put_test_conf"})));
+}
@@ -0,0 +1,202 @@
+#![allow(clippy::significant_drop_tightening)]
+
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+use serde_json::{json, Value};
+use test_helpers::get_test_context;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use crate::{
+    end_to_end_tests::configuration_helpers::{
+        configure_config_test_user, schemas_configuration_conference_only,
+    },
+    test_util::TestContextExtensions,
+};
+
+#[tokio::test]
+async fn schema_show_prints_fields_and_methods() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, String::from("http://localhost"));
+
+    // Act
+    crate::run_with(
+        &["pexshell", "schema", "show", "configuration", "conference"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(
+        test_context.take_stdout(),
+        eq(concat!(
+            "FIELD  TYPE    NULLABLE  READONLY  BLANK  DEFAULT  CHOICES\n",
+            "id     string  no        no        yes    \"\"       -\n",
+            "name   string  no        no        no     -        -\n",
+            "\n",
+            "List methods:   DELETE, GET, PATCH, POST, PUT\n",
+            "Detail methods: DELETE, GET, PATCH, POST, PUT\n",
+            "Filtering:      -\n",
+            "Ordering:       id, name\n",
+        ))
+    );
+}
+
+#[tokio::test]
+async fn schema_show_rejects_unknown_endpoint() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, String::from("http://localhost"));
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "schema", "show", "configuration", "alias"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring(
+            "unrecognised resource configuration alias"
+        )))
+    );
+}
+
+fn conference_schema() -> Value {
+    json!({
+        "allowed_detail_http_methods": ["get"],
+        "allowed_list_http_methods": ["get"],
+        "default_limit": 20,
+        "fields": {
+            "id": {
+                "blank": false,
+                "default": null,
+                "help_text": "The primary key.",
+                "nullable": false,
+                "readonly": true,
+                "type": "integer",
+                "unique": true
+            },
+            "name": {
+                "blank": false,
+                "default": null,
+                "help_text": "The name of the conference.",
+                "nullable": false,
+                "readonly": false,
+                "type": "string",
+                "unique": true
+            },
+            "tag": {
+                "blank": true,
+                "default": "",
+                "help_text": "A tag for the conference.",
+                "nullable": false,
+                "readonly": false,
+                "type": "string",
+                "unique": false
+            }
+        },
+        "filtering": {},
+        "ordering": []
+    })
+}
+
+#[tokio::test]
+async fn schema_fetch_writes_endpoint_schema() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/schema/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(conference_schema()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &["pexshell", "schema", "fetch", "configuration", "conference"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(
+        test_context.take_stdout(),
+        eq("Fetched 3 fields for configuration conference\n")
+    );
+    let cached: Value = serde_json::from_str(
+        &std::fs::read_to_string(
+            test_context
+                .get_cache_dir()
+                .join("schemas/configuration/conference.json"),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_that!(cached, eq(&conference_schema()));
+}
+
+#[tokio::test]
+async fn schema_fetch_dry_run_does_not_write_cache() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+    let cache_file = test_context
+        .get_cache_dir()
+        .join("schemas/configuration/conference.json");
+    let before = std::fs::read_to_string(&cache_file).unwrap();
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/schema/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(conference_schema()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "schema",
+            "fetch",
+            "configuration",
+            "conference",
+            "--dry-run",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    assert_that!(
+        test_context.take_stdout(),
+        eq("Fetched 3 fields for configuration conference (dry run - the cache was not changed)\n")
+    );
+    assert_that!(std::fs::read_to_string(&cache_file).unwrap(), eq(&before));
+}
@@ -0,0 +1,90 @@
+#![allow(clippy::significant_drop_tightening)]
+
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+use serde_json::json;
+use test_helpers::get_test_context;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use crate::{
+    end_to_end_tests::configuration_helpers::{
+        configure_config_test_user, schemas_configuration_conference_only,
+    },
+    test_util::TestContextExtensions,
+};
+
+#[tokio::test]
+async fn tee_writes_output_to_file_and_stdout() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+    let output_file = test_context.get_test_dir().join("output.sh");
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": 5,
+            "name": "some_test_conference",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "--output",
+            "kv",
+            "tee",
+            output_file.to_str().unwrap(),
+            "--",
+            "configuration",
+            "conference",
+            "get",
+            "5",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let stdout = test_context.take_stdout();
+    assert_that!(
+        stdout,
+        eq("export ID=\"5\"\nexport NAME=\"some_test_conference\"\n")
+    );
+    assert_that!(std::fs::read_to_string(&output_file).unwrap(), eq(&stdout));
+}
+
+#[tokio::test]
+async fn tee_requires_command() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_configuration_conference_only);
+    configure_config_test_user(&test_context, String::from("http://localhost"));
+    let output_file = test_context.get_test_dir().join("output.json");
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "tee", output_file.to_str().unwrap()].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(result, err(anything()));
+    assert_that!(output_file.exists(), eq(false));
+}
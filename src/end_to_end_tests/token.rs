@@ -0,0 +1,170 @@
+#![allow(clippy::significant_drop_tightening)]
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use googletest::prelude::*;
+use serde_json::json;
+use test_helpers::{fs::OAuth2Credentials, get_test_context};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use crate::{
+    cli::Console, end_to_end_tests::configuration_helpers::configure_config_test_user,
+    pexshell::PexShell, test_util::TestContextExtensions,
+};
+
+#[tokio::test]
+async fn token_prints_oauth2_token() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+
+    let oauth2_credentials = OAuth2Credentials::new("test_client_id");
+    test_context
+        .get_config_builder()
+        .add_oauth2_user(server.uri(), &oauth2_credentials, true)
+        .write();
+
+    Mock::given(method("POST"))
+        .and(path("/oauth/token/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "some_access_token",
+            "expires_in": 3600,
+            "token_type": "Bearer"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &["pexshell", "token", "--expires-at"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output = test_context.take_stdout();
+    let (token, expires_at) = output.split_once('\n').unwrap();
+    assert_that!(token, eq("some_access_token"));
+    let expires_at = DateTime::parse_from_rfc3339(expires_at).unwrap();
+    assert_that!(
+        (Utc::now() + Duration::hours(1)) - expires_at.with_timezone(&Utc),
+        lt(Duration::seconds(60))
+    );
+}
+
+#[tokio::test]
+async fn token_fails_for_basic_user() {
+    // Arrange
+    let test_context = get_test_context();
+    configure_config_test_user(&test_context, "test.example.com");
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "token"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring("does not use OAuth2")))
+    );
+    assert_that!(test_context.take_stdout(), eq(""));
+}
+
+#[tokio::test]
+async fn token_refuses_to_write_to_terminal() {
+    // Arrange
+    let test_context = get_test_context();
+    let oauth2_credentials = OAuth2Credentials::new("test_client_id");
+    test_context
+        .get_config_builder()
+        .add_oauth2_user("test.example.com", &oauth2_credentials, true)
+        .write();
+    let directories = test_context.get_directories();
+    let mut pexshell = PexShell::new(
+        &directories,
+        Console::new(
+            true,
+            test_context.get_stdout_wrapper(),
+            false,
+            test_context.get_stderr_wrapper(),
+        ),
+        HashMap::default(),
+    );
+
+    // Act
+    let result = pexshell
+        .run(["pexshell", "token"].map(String::from).to_vec())
+        .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring(
+            "pexshell token | some-command"
+        )))
+    );
+    assert_that!(test_context.take_stdout(), eq(""));
+}
+
+#[tokio::test]
+async fn token_writes_to_terminal_when_insecure() {
+    // Arrange
+    let test_context = get_test_context();
+    let server = MockServer::start().await;
+
+    let oauth2_credentials = OAuth2Credentials::new("test_client_id");
+    test_context
+        .get_config_builder()
+        .add_oauth2_user(server.uri(), &oauth2_credentials, true)
+        .write();
+
+    Mock::given(method("POST"))
+        .and(path("/oauth/token/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "some_access_token",
+            "expires_in": 3600,
+            "token_type": "Bearer"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let directories = test_context.get_directories();
+    let mut pexshell = PexShell::new(
+        &directories,
+        Console::new(
+            true,
+            test_context.get_stdout_wrapper(),
+            false,
+            test_context.get_stderr_wrapper(),
+        ),
+        HashMap::default(),
+    );
+
+    // Act
+    pexshell
+        .run(
+            ["pexshell", "token", "--insecure-write-to-stdout"]
+                .map(String::from)
+                .to_vec(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_that!(test_context.take_stdout(), eq("some_access_token"));
+}
@@ -0,0 +1,176 @@
+#![allow(clippy::significant_drop_tightening)]
+
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+use serde_json::{json, Value};
+use test_helpers::{fs::SchemaCacheBuilder, get_test_context, TestContext};
+use wiremock::{
+    matchers::{body_json, method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use crate::{
+    end_to_end_tests::configuration_helpers::configure_config_test_user,
+    test_util::TestContextExtensions,
+};
+
+fn schemas_conference_with_name_filter(builder: SchemaCacheBuilder<'_>) -> SchemaCacheBuilder<'_> {
+    builder.add_endpoint("configuration", "conference", |schema| {
+        schema
+            .field("id", |f| {
+                f.blank(true)
+                    .nullable(false)
+                    .unique(true)
+                    .default(Value::String(String::new()))
+            })
+            .field("name", |f| f.unique(true).nullable(false))
+            .field("description", |f| f.blank(true).nullable(false))
+            .filtering("name", 1)
+    })
+}
+
+fn conferences(objects: Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({"meta": {
+        "limit": 2,
+        "next": null,
+        "offset": 0,
+        "previous": null,
+        "total_count": objects.as_array().map_or(0, Vec::len),
+    }, "objects": objects}))
+}
+
+async fn run_upsert(test_context: &TestContext) -> anyhow::Result<()> {
+    crate::run_with(
+        &[
+            "pexshell",
+            "configuration",
+            "conference",
+            "upsert",
+            "--name",
+            "My VMR",
+            "--description",
+            "Updated",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn upsert_posts_when_no_object_matches() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_conference_with_name_filter);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(query_param("name", "My VMR"))
+        .respond_with(conferences(json!([])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(body_json(
+            json!({"name": "My VMR", "description": "Updated"}),
+        ))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .append_header("Location", "/api/admin/configuration/v1/conference/7/"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    run_upsert(&test_context).await.unwrap();
+
+    // Assert
+    assert_that!(
+        test_context.take_stdout(),
+        eq("/api/admin/configuration/v1/conference/7/\n")
+    );
+}
+
+#[tokio::test]
+async fn upsert_patches_the_matching_object() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_conference_with_name_filter);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .and(query_param("name", "My VMR"))
+        .respond_with(conferences(json!([
+            {"id": 5, "name": "My VMR", "description": ""},
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PATCH"))
+        .and(path("/api/admin/configuration/v1/conference/5/"))
+        .and(body_json(
+            json!({"name": "My VMR", "description": "Updated"}),
+        ))
+        .respond_with(ResponseTemplate::new(202))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(201))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = run_upsert(&test_context).await;
+
+    // Assert
+    assert_that!(result, ok(anything()));
+}
+
+#[tokio::test]
+async fn upsert_fails_when_more_than_one_object_matches() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_conference_with_name_filter);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/configuration/v1/conference/"))
+        .respond_with(conferences(json!([
+            {"id": 5, "name": "My VMR"},
+            {"id": 6, "name": "My VMR"},
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PATCH"))
+        .respond_with(ResponseTemplate::new(202))
+        .expect(0)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(201))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = run_upsert(&test_context).await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(eq(
+            "more than one conference object has name My VMR - use --match-on with a field that is unique"
+        )))
+    );
+}
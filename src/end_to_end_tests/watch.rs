@@ -0,0 +1,160 @@
+#![allow(clippy::significant_drop_tightening)]
+
+use std::collections::HashMap;
+
+use googletest::prelude::*;
+use serde_json::{json, Value};
+use test_helpers::{fs::SchemaCacheBuilder, get_test_context};
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use crate::{
+    end_to_end_tests::configuration_helpers::configure_config_test_user,
+    test_util::TestContextExtensions,
+};
+
+fn schemas_participant_with_filters(builder: SchemaCacheBuilder<'_>) -> SchemaCacheBuilder<'_> {
+    builder.add_endpoint("status", "participant", |schema| {
+        schema
+            .field("conference", |f| f.nullable(false))
+            .field("display_name", |f| f.nullable(false))
+            .filtering("conference", 1)
+    })
+}
+
+fn participants(objects: Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({"meta": {
+        "limit": 500,
+        "next": null,
+        "offset": 0,
+        "previous": null,
+        "total_count": objects.as_array().map_or(0, Vec::len),
+    }, "objects": objects}))
+}
+
+#[tokio::test]
+async fn watch_repeats_get_until_an_object_matches() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_participant_with_filters);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/status/v1/participant/"))
+        .and(query_param("conference", "My VMR"))
+        .respond_with(participants(json!([])))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/admin/status/v1/participant/"))
+        .and(query_param("conference", "My VMR"))
+        .respond_with(participants(json!([
+            {"conference": "My VMR", "display_name": "Alice"},
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    crate::run_with(
+        &[
+            "pexshell",
+            "watch",
+            "--interval",
+            "1",
+            "--timeout",
+            "30",
+            "status",
+            "participant",
+            "get",
+            "--conference",
+            "My VMR",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let output: Value = serde_json::from_str(&test_context.take_stdout()).unwrap();
+    assert_that!(
+        output,
+        eq(&json!([{"conference": "My VMR", "display_name": "Alice"}]))
+    );
+}
+
+#[tokio::test]
+async fn watch_fails_after_timeout() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_participant_with_filters);
+    let server = MockServer::start().await;
+    configure_config_test_user(&test_context, server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/api/admin/status/v1/participant/"))
+        .respond_with(participants(json!([])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Act
+    let result = crate::run_with(
+        &[
+            "pexshell",
+            "watch",
+            "--timeout",
+            "0",
+            "status",
+            "participant",
+            "get",
+        ]
+        .map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(eq(
+            "no participant objects matched within 0 seconds"
+        )))
+    );
+    assert_that!(test_context.take_stdout(), eq(""));
+}
+
+#[tokio::test]
+async fn watch_rejects_requests_other_than_get_all() {
+    // Arrange
+    let test_context = get_test_context().with_schema(schemas_participant_with_filters);
+    configure_config_test_user(&test_context, String::from("http://localhost"));
+
+    // Act
+    let result = crate::run_with(
+        &["pexshell", "watch", "status", "participant", "get", "1"].map(String::from),
+        HashMap::default(),
+        &test_context.get_directories(),
+        test_context.get_stdout_wrapper(),
+        test_context.get_stderr_wrapper(),
+    )
+    .await;
+
+    // Assert
+    assert_that!(
+        result,
+        err(displays_as(contains_substring(
+            "watch can only repeat a get of all objects"
+        )))
+    );
+}
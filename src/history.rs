@@ -0,0 +1,272 @@
+use std::{
+    fs::OpenOptions,
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use lib::{
+    error,
+    util::{is_sensitive, REDACTED},
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+/// A record of a single pexshell invocation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub timestamp: DateTime<Utc>,
+    pub args: Vec<String>,
+    pub server: Option<String>,
+    pub exit_code: i32,
+}
+
+impl Entry {
+    /// Creates a history entry for the given arguments, redacting any secrets.
+    pub fn new(
+        timestamp: DateTime<Utc>,
+        args: &[String],
+        server: Option<String>,
+        exit_code: i32,
+    ) -> Self {
+        Self {
+            timestamp,
+            args: redact_args(args),
+            server,
+            exit_code,
+        }
+    }
+}
+
+/// Gets the file that history is recorded to, from the `history_file` set in the config file or `history.jsonl` in the
+/// data directory if it isn't set. History is disabled if `history_file` is set to an empty path.
+pub fn file_path(configured: Option<PathBuf>, data_dir: &Path) -> Option<PathBuf> {
+    let path = configured.unwrap_or_else(|| data_dir.join("history.jsonl"));
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+/// Appends an entry to the history file, creating the file if it does not exist.
+pub fn append(path: &Path, entry: &Entry) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(&line)
+}
+
+/// Reads the most recent entries from the history file, oldest first.
+///
+/// Lines that cannot be parsed are skipped.
+pub fn read_recent(path: &Path, count: usize) -> Result<Vec<Entry>, error::UserFriendly> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(error::UserFriendly::new(format!(
+                "failed to read history file {}: {e}",
+                path.display()
+            )))
+        }
+    };
+
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| {
+            error::UserFriendly::new(format!(
+                "failed to read history file {}: {e}",
+                path.display()
+            ))
+        })?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => warn!("skipping invalid history entry: {e}"),
+        }
+    }
+
+    let skip = entries.len().saturating_sub(count);
+    entries.drain(..skip);
+    Ok(entries)
+}
+
+fn is_secret(arg: &str) -> bool {
+    is_sensitive(arg.trim_start_matches('-'))
+}
+
+/// Redacts the values of options whose names suggest they contain secrets.
+fn redact_args(args: &[String]) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if !arg.starts_with("--") {
+            redacted.push(arg.clone());
+            continue;
+        }
+
+        if let Some((name, _)) = arg.split_once('=') {
+            if is_secret(name) {
+                redacted.push(format!("{name}={REDACTED}"));
+            } else {
+                redacted.push(arg.clone());
+            }
+        } else {
+            redacted.push(arg.clone());
+            if is_secret(arg) && iter.next().is_some() {
+                redacted.push(String::from(REDACTED));
+            }
+        }
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use googletest::prelude::*;
+    use test_case::test_case;
+    use test_helpers::get_test_context;
+
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|&s| String::from(s)).collect()
+    }
+
+    #[test]
+    fn test_file_path_default() {
+        // Arrange
+        let data_dir = Path::new("/data");
+
+        // Act
+        let path = file_path(None, data_dir);
+
+        // Assert
+        assert_that!(path, some(eq(data_dir.join("history.jsonl"))));
+    }
+
+    #[test_case("/other/history.jsonl", Some("/other/history.jsonl") ; "configured")]
+    #[test_case("", None ; "disabled")]
+    fn test_file_path_configured(configured: &str, expected: Option<&str>) {
+        // Act
+        let path = file_path(Some(PathBuf::from(configured)), Path::new("/data"));
+
+        // Assert
+        assert_that!(path, eq(expected.map(PathBuf::from)));
+    }
+
+    #[test]
+    fn test_redact_args() {
+        // Arrange
+        let args = args(&[
+            "pexshell",
+            "configuration",
+            "conference",
+            "post",
+            "--name",
+            "test",
+            "--pin",
+            "1234",
+            "--guest_pin=5678",
+            "--client_secret",
+            "abc",
+        ]);
+
+        // Act
+        let redacted = redact_args(&args);
+
+        // Assert
+        assert_that!(
+            redacted,
+            elements_are![
+                eq("pexshell"),
+                eq("configuration"),
+                eq("conference"),
+                eq("post"),
+                eq("--name"),
+                eq("test"),
+                eq("--pin"),
+                eq(REDACTED),
+                eq(&format!("--guest_pin={REDACTED}")),
+                eq("--client_secret"),
+                eq(REDACTED),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_redact_args_keeps_names_containing_secret_words() {
+        // Arrange
+        let args = args(&["pexshell", "--mapping", "sip:meet@example.com"]);
+
+        // Act
+        let redacted = redact_args(&args);
+
+        // Assert
+        assert_that!(redacted, eq(&args));
+    }
+
+    #[test]
+    fn test_append_and_read_recent() {
+        // Arrange
+        let test_context = get_test_context();
+        let path = test_context.get_test_dir().join("data/history.jsonl");
+        let timestamp = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        for i in 0..3 {
+            let entry = Entry::new(
+                timestamp,
+                &args(&[
+                    "pexshell",
+                    "configuration",
+                    "conference",
+                    "get",
+                    &i.to_string(),
+                ]),
+                Some(String::from("test.example.com")),
+                0,
+            );
+            append(&path, &entry).unwrap();
+        }
+
+        // Act
+        let entries = read_recent(&path, 2).unwrap();
+
+        // Assert
+        assert_that!(
+            entries,
+            elements_are![
+                pat!(Entry {
+                    timestamp: eq(&timestamp),
+                    args: contains(eq("1")),
+                    server: some(eq("test.example.com")),
+                    exit_code: eq(&0),
+                }),
+                pat!(Entry {
+                    timestamp: eq(&timestamp),
+                    args: contains(eq("2")),
+                    server: some(eq("test.example.com")),
+                    exit_code: eq(&0),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_recent_missing_file() {
+        // Arrange
+        let test_context = get_test_context();
+        let path = test_context.get_test_dir().join("history.jsonl");
+
+        // Act
+        let entries = read_recent(&path, 10);
+
+        // Assert
+        assert_that!(entries, ok(empty()));
+    }
+}
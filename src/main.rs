@@ -11,6 +11,7 @@ mod config;
 mod consts;
 #[cfg(test)]
 mod end_to_end_tests;
+mod history;
 mod pexshell;
 #[cfg(test)]
 mod test_util;
@@ -27,7 +28,7 @@ use lib::{
 use log::{error, warn, LevelFilter};
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
-use serde_json::Value;
+use serde_json::{Map, Value};
 #[cfg(unix)]
 use simple_signal::Signal;
 use std::{collections::HashMap, path::PathBuf, process::ExitCode};
@@ -53,11 +54,131 @@ fn pexshell_version() -> String {
         .to_owned()
 }
 
-#[allow(clippy::too_many_lines)]
-fn api_request_from_matches(
-    matches: &ArgMatches,
-    schemas: &HashMap<Api, HashMap<String, schema::Endpoint>>,
-) -> Result<(mcu::ApiRequest, bool), error::UserFriendly> {
+/// Gets the matches of the innermost subcommand, e.g. `get` in `pexshell configuration conference get`.
+fn leaf_matches(matches: &ArgMatches) -> &ArgMatches {
+    let mut sub_m = matches;
+    while let Some((_, next)) = sub_m.subcommand() {
+        sub_m = next;
+    }
+    sub_m
+}
+
+/// Gets the fields of each object to print, given to `get` with `--fields`.
+fn fields_from_matches(matches: &ArgMatches) -> Option<Vec<String>> {
+    leaf_matches(matches)
+        .try_get_many::<String>("fields")
+        .ok()
+        .flatten()
+        .map(|fields| fields.cloned().collect())
+}
+
+/// Gets the related fields to inline the objects of, given to `get` with `--expand`.
+fn expand_from_matches(matches: &ArgMatches) -> Option<Vec<String>> {
+    leaf_matches(matches)
+        .try_get_many::<String>("expand")
+        .ok()
+        .flatten()
+        .map(|fields| fields.cloned().collect())
+}
+
+/// Gets whether `get` was given `--parallel`, to fetch several pages at once.
+fn parallel_from_matches(matches: &ArgMatches) -> bool {
+    leaf_matches(matches)
+        .try_get_one::<bool>("parallel")
+        .ok()
+        .flatten()
+        .is_some_and(|&parallel| parallel)
+}
+
+/// Gets whether `get` was given `--count`, to print the number of objects instead of the objects.
+fn count_from_matches(matches: &ArgMatches) -> bool {
+    leaf_matches(matches)
+        .try_get_one::<bool>("count")
+        .ok()
+        .flatten()
+        .is_some_and(|&count| count)
+}
+
+/// Gets whether `delete` was given `--yes`, to delete without asking for confirmation.
+fn yes_from_matches(matches: &ArgMatches) -> bool {
+    leaf_matches(matches)
+        .try_get_one::<bool>("yes")
+        .ok()
+        .flatten()
+        .is_some_and(|&yes| yes)
+}
+
+/// Reads the fields of a `post` or `patch` request given as JSON with `--from-file` or `--from-stdin`, if any.
+async fn read_payload(sub_m: &ArgMatches) -> Result<Map<String, Value>, error::UserFriendly> {
+    match read_json_payload(sub_m).await? {
+        Value::Object(fields) => Ok(fields),
+        Value::Array(_) => Err(error::UserFriendly::new(
+            "the payload must be a single JSON object of field names and values, not an array",
+        )),
+        _ => Err(error::UserFriendly::new(
+            "the payload must be a JSON object of field names and values",
+        )),
+    }
+}
+
+/// Reads the JSON given with `--from-file` or `--from-stdin`, which is an empty object if neither is given.
+async fn read_json_payload(sub_m: &ArgMatches) -> Result<Value, error::UserFriendly> {
+    let payload = if let Some(path) = sub_m.get_one::<PathBuf>("from_file") {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            error::UserFriendly::new(format!("failed to read {}: {e}", path.display()))
+        })?;
+        serde_json::from_str(&contents).map_err(|e| {
+            error::UserFriendly::new(format!("{} is not valid JSON: {e}", path.display()))
+        })?
+    } else if sub_m.get_flag("from_stdin") {
+        read_stdin_to_json()
+            .await
+            .map_err(|e| error::UserFriendly::new(format!("failed to read JSON from STDIN: {e}")))?
+            .unwrap_or_else(|| Value::Object(Map::new()))
+    } else {
+        Value::Object(Map::new())
+    };
+    Ok(payload)
+}
+
+/// Merges the fields given as options into each object of a JSON array given to `post`, checking each of them.
+fn bulk_post_payload(
+    endpoint: &schema::Endpoint,
+    objects: Vec<Value>,
+    options: &Value,
+) -> Result<Value, error::UserFriendly> {
+    objects
+        .into_iter()
+        .enumerate()
+        .map(|(index, object)| {
+            let Value::Object(fields) = object else {
+                return Err(error::UserFriendly::new(format!(
+                    "element {index} of the payload array must be a JSON object of field names and values"
+                )));
+            };
+            let object = merge_payload(fields, options.clone());
+            cli::validate_payload(endpoint, &object, true).map_err(|e| {
+                error::UserFriendly::new(format!("element {index} of the payload array: {e}"))
+            })?;
+            Ok(object)
+        })
+        .collect::<Result<_, _>>()
+        .map(Value::Array)
+}
+
+/// Merges the fields given as options into the fields read from JSON, with the options taking precedence.
+fn merge_payload(mut fields: Map<String, Value>, payload: Value) -> Value {
+    if let Value::Object(options) = payload {
+        fields.extend(options);
+    }
+    Value::Object(fields)
+}
+
+/// Finds the API, resource and endpoint schema of an API request, with the matches for the resource's subcommand.
+fn endpoint_from_matches<'a>(
+    matches: &'a ArgMatches,
+    schemas: &'a HashMap<Api, HashMap<String, schema::Endpoint>>,
+) -> Result<(Api, &'a str, &'a schema::Endpoint, &'a ArgMatches), error::UserFriendly> {
     let (api, sub_m) = match matches.subcommand() {
         Some(("configuration", sub_m)) => Ok((mcu::Api::Configuration, sub_m)),
         Some(("status", sub_m)) => Ok((mcu::Api::Status, sub_m)),
@@ -89,8 +210,73 @@ fn api_request_from_matches(
         .get(resource)
         .ok_or_else(|| error::UserFriendly::new(format!("unrecognised resource {resource}")))?;
 
+    Ok((api, resource, endpoint, sub_m))
+}
+
+/// Gets a `Delete` request for each of the object IDs piped to STDIN, one per line, if `delete` is given without an
+/// object ID.
+async fn delete_requests_from_stdin(
+    matches: &ArgMatches,
+    schemas: &HashMap<Api, HashMap<String, schema::Endpoint>>,
+) -> Result<Option<Vec<mcu::ApiRequest>>, error::UserFriendly> {
+    let (api, resource, _, sub_m) = endpoint_from_matches(matches, schemas)?;
+    let Some(("delete", sub_m)) = sub_m.subcommand() else {
+        return Ok(None);
+    };
+    if sub_m.contains_id("object_id") {
+        return Ok(None);
+    }
+    if std::io::stdin().is_terminal() {
+        return Err(error::UserFriendly::new(
+            "an object ID is required, unless the IDs to delete are piped to STDIN",
+        ));
+    }
+
+    let mut contents = String::new();
+    tokio::io::stdin()
+        .read_to_string(&mut contents)
+        .await
+        .map_err(|e| error::UserFriendly::new(format!("failed to read IDs from STDIN: {e}")))?;
+    Ok(Some(
+        cli::parse_object_ids(&contents)
+            .map(|object_id| mcu::ApiRequest::Delete {
+                api,
+                resource: resource.to_string(),
+                object_id,
+            })
+            .collect(),
+    ))
+}
+
+/// Gets the upsert given by an `upsert` subcommand, along with the schema of its endpoint, if the command is one.
+async fn upsert_from_matches<'a>(
+    matches: &'a ArgMatches,
+    schemas: &'a HashMap<Api, HashMap<String, schema::Endpoint>>,
+) -> Result<Option<(cli::upsert::Upsert, &'a schema::Endpoint)>, error::UserFriendly> {
+    let (api, resource, endpoint, sub_m) = endpoint_from_matches(matches, schemas)?;
+    let Some(("upsert", sub_m)) = sub_m.subcommand() else {
+        return Ok(None);
+    };
+    let match_on = sub_m
+        .get_one::<String>("match_on")
+        .expect("match_on is required unless it has a default");
+
+    let fields = read_payload(sub_m).await?;
+    let post_payload = merge_payload(fields.clone(), cli::create_post_payload(endpoint, sub_m)?);
+    let patch_payload = merge_payload(fields, cli::create_patch_payload(endpoint, sub_m)?);
+    let upsert = cli::upsert::Upsert::new(api, resource, match_on, post_payload, patch_payload)?;
+    Ok(Some((upsert, endpoint)))
+}
+
+#[allow(clippy::too_many_lines)]
+async fn api_request_from_matches(
+    matches: &ArgMatches,
+    schemas: &HashMap<Api, HashMap<String, schema::Endpoint>>,
+) -> Result<(mcu::ApiRequest, bool), error::UserFriendly> {
+    let (api, resource, endpoint, sub_m) = endpoint_from_matches(matches, schemas)?;
+
     let api_request = if let Api::Command(_) = &api {
-        let payload = cli::create_post_payload(endpoint, sub_m);
+        let payload = cli::create_post_payload(endpoint, sub_m)?;
         Ok((
             mcu::ApiRequest::Post {
                 api,
@@ -103,38 +289,91 @@ fn api_request_from_matches(
         match sub_m.subcommand() {
             Some(("get", sub_m)) => sub_m.get_one::<String>("object_id").map_or_else(
                 || {
-                    let page_size = *sub_m
-                        .get_one::<usize>("page_size")
-                        .expect("clap should validate page_size");
+                    // counting only needs the total count of the first page, not its objects
+                    let page_size = if sub_m.get_flag("count") {
+                        1
+                    } else {
+                        *sub_m
+                            .get_one::<usize>("page_size")
+                            .expect("clap should validate page_size")
+                    };
                     let limit = *sub_m
                         .get_one::<usize>("limit")
                         .expect("clap should validate limit");
+                    let offset = *sub_m
+                        .get_one::<usize>("offset")
+                        .expect("clap should validate offset");
+                    // the ordering arguments are only generated for endpoints that can be ordered
+                    let order_by =
+                        sub_m
+                            .try_get_one::<String>("order_by")
+                            .ok()
+                            .flatten()
+                            .map(|field| {
+                                if sub_m.try_get_one::<bool>("desc").ok().flatten() == Some(&true) {
+                                    format!("-{field}")
+                                } else {
+                                    field.clone()
+                                }
+                            });
                     let stream = sub_m.get_flag("stream");
+                    let mut filter_args = sub_m
+                        .get_one::<PathBuf>("filter_file")
+                        .map(|path| cli::read_filter_file(endpoint, path))
+                        .transpose()?
+                        .unwrap_or_default();
+                    filter_args.extend(cli::create_get_filters(endpoint, sub_m));
                     Ok((
                         mcu::ApiRequest::GetAll {
                             api,
                             resource: resource.to_string(),
-                            filter_args: cli::create_get_filters(endpoint, sub_m),
+                            filter_args,
                             page_size,
                             limit,
-                            offset: 0,
+                            offset,
+                            order_by,
                         },
                         stream,
                     ))
                 },
                 |id| {
-                    Ok((
-                        mcu::ApiRequest::Get {
-                            api,
-                            resource: String::from(resource),
-                            object_id: String::from(id),
-                        },
-                        false,
-                    ))
+                    if sub_m.get_flag("exists") {
+                        Ok((
+                            mcu::ApiRequest::Head {
+                                api,
+                                resource: String::from(resource),
+                                object_id: String::from(id),
+                            },
+                            false,
+                        ))
+                    } else {
+                        Ok((
+                            mcu::ApiRequest::Get {
+                                api,
+                                resource: String::from(resource),
+                                object_id: String::from(id),
+                            },
+                            false,
+                        ))
+                    }
                 },
             ),
             Some(("post", sub_m)) => {
-                let payload = cli::create_post_payload(endpoint, sub_m);
+                let options = cli::create_post_payload(endpoint, sub_m)?;
+                // an array of objects is split into a request for each object when it's sent
+                let payload = match read_json_payload(sub_m).await? {
+                    Value::Array(objects) => bulk_post_payload(endpoint, objects, &options)?,
+                    Value::Object(fields) => {
+                        let payload = merge_payload(fields, options);
+                        cli::validate_payload(endpoint, &payload, true)?;
+                        payload
+                    }
+                    _ => {
+                        return Err(error::UserFriendly::new(
+                            "the payload must be a JSON object of field names and values, or an array of them",
+                        ))
+                    }
+                };
                 Ok((
                     mcu::ApiRequest::Post {
                         api,
@@ -145,7 +384,11 @@ fn api_request_from_matches(
                 ))
             }
             Some(("patch", sub_m)) => {
-                let payload = cli::create_patch_payload(endpoint, sub_m);
+                let payload = merge_payload(
+                    read_payload(sub_m).await?,
+                    cli::create_patch_payload(endpoint, sub_m)?,
+                );
+                cli::validate_payload(endpoint, &payload, false)?;
                 Ok((
                     mcu::ApiRequest::Patch {
                         api,
@@ -160,6 +403,22 @@ fn api_request_from_matches(
                     false,
                 ))
             }
+            Some(("put", sub_m)) => {
+                let payload = cli::create_put_payload(endpoint, sub_m)?;
+                Ok((
+                    mcu::ApiRequest::Put {
+                        api,
+                        resource: resource.to_string(),
+                        object_id: String::from(
+                            sub_m
+                                .get_one::<String>("object_id")
+                                .expect("clap should validate object_id"),
+                        ),
+                        args: payload,
+                    },
+                    false,
+                ))
+            }
             Some(("delete", sub_m)) => Ok((
                 mcu::ApiRequest::Delete {
                     api,
@@ -167,7 +426,7 @@ fn api_request_from_matches(
                     object_id: String::from(
                         sub_m
                             .get_one::<String>("object_id")
-                            .expect("clap should validate object_id"),
+                            .expect("IDs piped to STDIN should be deleted without an API request"),
                     ),
                 },
                 false,
@@ -179,7 +438,6 @@ fn api_request_from_matches(
     Ok(api_request)
 }
 
-#[allow(dead_code)]
 async fn read_stdin_to_json() -> anyhow::Result<Option<Value>> {
     let mut contents = String::new();
     let _bytes_read = tokio::io::stdin().read_to_string(&mut contents).await?;
@@ -198,6 +456,7 @@ fn set_abort_on_interrupt(abort_on_interrupt: bool) {
 pub struct Directories {
     pub config_dir: PathBuf,
     pub cache_dir: PathBuf,
+    pub data_dir: PathBuf,
     pub tmp_dir: PathBuf,
 }
 
@@ -207,6 +466,7 @@ impl Default for Directories {
         Self {
             config_dir: base_dirs.config_dir().join("pexip/pexshell"),
             cache_dir: base_dirs.cache_dir().join("pexip/pexshell"),
+            data_dir: base_dirs.data_dir().join("pexshell"),
             tmp_dir: std::env::temp_dir().join("pexip/pexshell"),
         }
     }
@@ -255,7 +515,9 @@ async fn main() -> ExitCode {
             console::Style::new()
         };
         eprintln!("{}", style.apply_to(e.to_string()));
-        ExitCode::FAILURE
+        let code = pexshell::ExitCode::for_error(&e).map_or(1, |code| code.code());
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        ExitCode::from(code as u8)
     } else {
         ExitCode::SUCCESS
     }
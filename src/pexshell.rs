@@ -2,13 +2,23 @@
 
 use crate::{
     argparse,
-    cli::{login, Console},
-    config::{Config, Manager as ConfigManager, Provider as ConfigProvider},
-    Directories, LOGGER,
+    cli::{
+        self,
+        login::{self, Interact as _},
+        output::DateFormatter,
+        Console,
+    },
+    config::{Config, Configurer as _, Manager as ConfigManager, Provider as ConfigProvider},
+    consts::{
+        DEFAULT_SCHEMA_CACHE_TTL_DAYS, ENV_FILE, EXIT_CODE_FORBIDDEN, EXIT_CODE_NOT_FOUND,
+        EXIT_CODE_UNAUTHORIZED,
+    },
+    history, Directories, LOGGER,
 };
 
 use anyhow::anyhow;
-use futures::TryStreamExt;
+use chrono::Utc;
+use is_terminal::IsTerminal;
 use lib::{
     error,
     mcu::{
@@ -16,10 +26,16 @@ use lib::{
         schema::{self, cache_exists},
         ApiResponse, IApiClient,
     },
+    util::StreamWrapper,
+};
+use log::{debug, trace, warn, LevelFilter};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write as _},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
 };
-use log::{debug, trace, LevelFilter};
-use serde_json::Value;
-use std::{collections::HashMap, fmt::Display, future, io::Write, path::PathBuf};
 
 fn read_config(
     dirs: &Directories,
@@ -36,28 +52,37 @@ fn read_config(
     debug!("Reading config from file: {:?}", &config_file_path);
 
     if !config_file_path.exists() {
-        return Ok(ConfigManager::with_config(
+        let mut config = ConfigManager::with_config(
             Config::new(dirs),
             &config_file_path,
             &config_lock_file_path,
             env.clone(),
             console,
-        )?);
+        )?;
+        load_env_file(&mut config, env)?;
+        return Ok(config);
     }
 
-    let config = ConfigManager::read_from_file(
+    let mut config = ConfigManager::read_from_file(
         &config_file_path,
         &config_lock_file_path,
         env.clone(),
         console,
     )?;
+    load_env_file(&mut config, env)?;
 
     LOGGER.set_log_to_stderr(config.get_log_to_stderr());
 
+    if let Some(log_format) = config.get_log_format() {
+        LOGGER.set_log_format(log_format);
+    }
+
     if let Some(log) = config.get_log_file_path() {
         LOGGER.set_log_file(Some(log))?;
     }
 
+    LOGGER.set_log_rotation(config.get_log_rotation())?;
+
     if let Some(log_level) = config.get_log_level() {
         LOGGER.set_max_level(match log_level.as_str() {
             "max" => LevelFilter::max(),
@@ -74,9 +99,183 @@ fn read_config(
     Ok(config)
 }
 
+/// Loads the dotenv file set by `PEXSHELL_ENV_FILE`, if any, so that its variables are used like environment variables.
+fn load_env_file(
+    config: &mut ConfigManager,
+    env: &HashMap<String, String>,
+) -> Result<(), error::UserFriendly> {
+    match env.get(ENV_FILE) {
+        Some(env_file) => config.import_from_env_file(Path::new(env_file)),
+        None => Ok(()),
+    }
+}
+
+/// Reads the certificates in a PEM file, to trust as well as the system's certificates.
+fn read_ca_certs(path: &Path) -> Result<Vec<reqwest::Certificate>, error::UserFriendly> {
+    let contents = std::fs::read(path).map_err(|e| {
+        error::UserFriendly::new(format!(
+            "failed to read CA certificates from {}: {e}",
+            path.display()
+        ))
+    })?;
+    let certs = reqwest::Certificate::from_pem_bundle(&contents).map_err(|e| {
+        error::UserFriendly::new(format!("invalid CA certificate in {}: {e}", path.display()))
+    })?;
+    if certs.is_empty() {
+        return Err(error::UserFriendly::new(format!(
+            "{} does not contain any PEM certificates",
+            path.display()
+        )));
+    }
+    Ok(certs)
+}
+
+/// Reads a client certificate and its private key from PEM files, to present to the management node for mutual TLS.
+fn read_client_identity(cert: &Path, key: &Path) -> Result<reqwest::Identity, error::UserFriendly> {
+    let read = |path: &Path, what: &str| {
+        std::fs::read(path).map_err(|e| {
+            error::UserFriendly::new(format!(
+                "failed to read client {what} from {}: {e}",
+                path.display()
+            ))
+        })
+    };
+
+    let mut pem = read(key, "key")?;
+    pem.push(b'\n');
+    pem.extend(read(cert, "certificate")?);
+    reqwest::Identity::from_pem(&pem).map_err(|e| {
+        error::UserFriendly::new(format!(
+            "invalid client certificate or key in {} and {}: {e}",
+            cert.display(),
+            key.display()
+        ))
+    })
+}
+
+/// Gets the value of a proxy environment variable, which may be given in upper or lower case.
+fn proxy_env_var<'a>(env: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    env.get(name)
+        .or_else(|| env.get(&name.to_lowercase()))
+        .map(String::as_str)
+        .filter(|value| !value.is_empty())
+}
+
+/// Configures the proxy that requests are sent through, given with `--proxy` or else by the standard `HTTPS_PROXY`,
+/// `HTTP_PROXY`, `ALL_PROXY` and `NO_PROXY` environment variables.
+fn with_proxy(
+    client: reqwest::ClientBuilder,
+    proxy: Option<&str>,
+    env: &HashMap<String, String>,
+) -> Result<reqwest::ClientBuilder, error::UserFriendly> {
+    // reqwest would read the environment of the process, rather than the environment pexshell was run with
+    let mut client = client.no_proxy();
+    let invalid = |source: &str, e: reqwest::Error| {
+        error::UserFriendly::new(format!("invalid proxy in {source}: {e}"))
+    };
+
+    if let Some(proxy) = proxy {
+        return Ok(client.proxy(reqwest::Proxy::all(proxy).map_err(|e| invalid("--proxy", e))?));
+    }
+
+    let no_proxy = proxy_env_var(env, "NO_PROXY").and_then(reqwest::NoProxy::from_string);
+    if let Some(proxy) = proxy_env_var(env, "HTTPS_PROXY") {
+        let proxy = reqwest::Proxy::https(proxy).map_err(|e| invalid("HTTPS_PROXY", e))?;
+        client = client.proxy(proxy.no_proxy(no_proxy.clone()));
+    }
+    if let Some(proxy) = proxy_env_var(env, "HTTP_PROXY") {
+        let proxy = reqwest::Proxy::http(proxy).map_err(|e| invalid("HTTP_PROXY", e))?;
+        client = client.proxy(proxy.no_proxy(no_proxy.clone()));
+    }
+    if let Some(proxy) = proxy_env_var(env, "ALL_PROXY") {
+        let proxy = reqwest::Proxy::all(proxy).map_err(|e| invalid("ALL_PROXY", e))?;
+        client = client.proxy(proxy.no_proxy(no_proxy));
+    }
+    Ok(client)
+}
+
+/// Maps a failed existence check to an exit code, so that scripts can tell a missing object apart from other errors.
+fn exists_error(error: anyhow::Error) -> anyhow::Error {
+    ExitCode::for_error(&error).map_or(error, |code| anyhow!(code))
+}
+
+/// Gets a warning to regenerate the schema cache if it was generated more than `ttl_days` days ago, where 0 means never.
+fn stale_cache_warning(modified: SystemTime, now: SystemTime, ttl_days: u64) -> Option<String> {
+    const DAY: u64 = 24 * 60 * 60;
+    let age = now.duration_since(modified).unwrap_or_default();
+    if ttl_days == 0 || age <= Duration::from_secs(ttl_days.saturating_mul(DAY)) {
+        return None;
+    }
+    Some(format!(
+        "schema cache was generated {} days ago - please regenerate it with: pexshell cache",
+        age.as_secs() / DAY
+    ))
+}
+
+/// Reads the cached schemas, which are empty if the schema cache hasn't been generated.
+async fn read_schemas(schema_dir: &Path) -> std::io::Result<schema::CachedSchemas> {
+    if schema::cache_exists(schema_dir) {
+        schema::read_all_schemas(schema_dir, None).await
+    } else {
+        Ok(schema::CachedSchemas::default())
+    }
+}
+
+/// Fails if any schemas in the cache could not be read and `--abort-on-schema-error` was used.
+///
+/// This runs before the arguments are validated, as the endpoints with corrupt schemas will be missing from the
+/// command. The cache command is allowed to run so that the cache can be regenerated.
+fn check_schema_errors(
+    command: &clap::Command,
+    args: &[String],
+    corrupt_schemas: &[schema::CorruptSchema],
+) -> Result<(), error::UserFriendly> {
+    if corrupt_schemas.is_empty() {
+        return Ok(());
+    }
+    let Ok(matches) = command
+        .clone()
+        .ignore_errors(true)
+        .try_get_matches_from(args)
+    else {
+        return Ok(());
+    };
+    if !argparse::CommandGen::abort_on_schema_error(&matches)
+        || matches.subcommand_name() == Some(argparse::Cache.to_string().as_str())
+    {
+        return Ok(());
+    }
+
+    let mut message = String::from(
+        "failed to read schemas from the cache - please regenerate it with: pexshell cache",
+    );
+    for corrupt_schema in corrupt_schemas {
+        write!(message, "\n  {corrupt_schema}").unwrap();
+    }
+    Err(error::UserFriendly::new(message))
+}
+
 pub struct ExitCode(i32);
 
 impl ExitCode {
+    /// Gets the exit code for a command that failed with an error response from the management node, so that scripts
+    /// can tell e.g. a missing object apart from other errors.
+    pub fn for_error(error: &anyhow::Error) -> Option<Self> {
+        let api_error =
+            error
+                .chain()
+                .find_map(|e| match e.downcast_ref::<mcu::ApiClientError>() {
+                    Some(mcu::ApiClientError::ApiError(api_error)) => Some(api_error),
+                    _ => e.downcast_ref::<mcu::ApiError>(),
+                })?;
+        match api_error.kind() {
+            mcu::ApiErrorKind::NotFound => Some(Self(EXIT_CODE_NOT_FOUND)),
+            mcu::ApiErrorKind::Unauthorized => Some(Self(EXIT_CODE_UNAUTHORIZED)),
+            mcu::ApiErrorKind::Forbidden => Some(Self(EXIT_CODE_FORBIDDEN)),
+            mcu::ApiErrorKind::Other => None,
+        }
+    }
+
     pub fn code(&self) -> i32 {
         self.0
     }
@@ -131,95 +330,362 @@ impl<'a> PexShell<'a> {
     ) -> anyhow::Result<()> {
         let mut user = config.get_current_user()?.clone();
         let mcu_address = user.address.clone();
+        let compat = user
+            .settings
+            .compat(argparse::CommandGen::compat_version(matches))?;
 
+        let request_id = argparse::CommandGen::request_id(matches, config.get_enable_request_ids());
+        let retry_policy = argparse::CommandGen::retry_policy(matches, config.get_retries());
         let api_client = mcu::ApiClient::new(
             client.clone(),
             &mcu_address,
             login::auth_for_user(client, &mut user, config, true)?,
-        );
-        let (api_request, stream_output) = crate::api_request_from_matches(matches, &schemas.0)?;
+        )
+        .with_retry_policy(retry_policy)
+        .with_compat(compat)
+        .with_max_concurrent_pages(argparse::CommandGen::max_concurrent_pages(
+            matches,
+            crate::parallel_from_matches(matches),
+        ))
+        .with_request_id(request_id);
+        if let Some(requests) = crate::delete_requests_from_stdin(matches, &schemas.0).await? {
+            let Some(requests) = self.preview_all(&api_client, requests, matches).await? else {
+                return Ok(());
+            };
+            let result = self.delete_all(&api_client, requests).await;
+            drop(api_client);
+            config.set_last_used()?;
+            return result;
+        }
+        let (api_request, stream_output) =
+            Self::request_from_matches(&api_client, matches, schemas).await?;
+        if let mcu::ApiRequest::Post {
+            api,
+            resource,
+            args: serde_json::Value::Array(objects),
+        } = &api_request
+        {
+            let requests: Vec<mcu::ApiRequest> = objects
+                .iter()
+                .map(|object| mcu::ApiRequest::Post {
+                    api: *api,
+                    resource: resource.clone(),
+                    args: object.clone(),
+                })
+                .collect();
+            let Some(requests) = self.preview_all(&api_client, requests, matches).await? else {
+                return Ok(());
+            };
+            let result = self.post_all(&api_client, requests).await;
+            drop(api_client);
+            config.set_last_used()?;
+            return result;
+        }
+        if matches.get_flag("dry_run") {
+            write!(self.console, "{}", api_client.preview(&api_request))?;
+            return Ok(());
+        }
+        if let Some(print_curl) = matches.get_one::<String>("print_curl") {
+            return self
+                .print_curl(&api_client, api_request, matches, print_curl)
+                .await;
+        }
+        if !self.confirm_delete(&api_request, matches) {
+            return Ok(());
+        }
 
-        match api_client.send(api_request).await? {
-            ApiResponse::ContentStream(response_content) => {
-                if stream_output {
-                    response_content
-                        .try_for_each(|x| {
-                            self.console.pretty_print_json(&x);
-                            future::ready(Ok(()))
-                        })
-                        .await?;
-                } else {
-                    let objects: Vec<Value> = response_content.try_collect().await?;
-                    let json = serde_json::to_value(objects)?;
-                    self.console.pretty_print_json(&json);
+        if crate::count_from_matches(matches) {
+            let count = api_client.count(api_request).await?;
+            writeln!(self.console, "{count}")?;
+            drop(api_client);
+            config.set_last_used()?;
+            return Ok(());
+        }
+
+        let check_exists = matches!(api_request, mcu::ApiRequest::Head { .. });
+        let endpoint = api_request
+            .resource()
+            .and_then(|(api, resource)| schemas.0.get(&api)?.get(resource));
+        self.set_output_options(&api_request, endpoint, matches);
+
+        let expander = crate::expand_from_matches(matches)
+            .map(|fields| cli::expand::Expander::new(&api_client, fields));
+        let response = match api_client.send(api_request).await {
+            Err(error) if check_exists => return Err(exists_error(error)),
+            response => response?,
+        };
+        let response = match (response, &expander) {
+            (ApiResponse::Content(mut object), Some(expander)) => {
+                expander.expand(&mut object).await?;
+                ApiResponse::Content(object)
+            }
+            (ApiResponse::ContentStream(objects), Some(expander)) => ApiResponse::ContentStream(
+                StreamWrapper::new(Box::pin(expander.expand_all(objects))),
+            ),
+            (response, _) => response,
+        };
+        self.write_response(response, stream_output).await?;
+
+        drop(api_client);
+
+        config.set_last_used()?;
+
+        Ok(())
+    }
+
+    /// Builds the request to send from the command line, and whether its response should be streamed.
+    ///
+    /// For an upsert, the existing object is looked up even for a dry run, to show whether it would be patched or
+    /// posted.
+    async fn request_from_matches(
+        api_client: &mcu::ApiClient<'_>,
+        matches: &clap::ArgMatches,
+        schemas: &argparse::CommandGen,
+    ) -> anyhow::Result<(mcu::ApiRequest, bool)> {
+        if let Some((upsert, endpoint)) = crate::upsert_from_matches(matches, &schemas.0).await? {
+            let existing = api_client.send_paginated_all(upsert.find_request()).await?;
+            Ok((upsert.into_request(endpoint, &existing)?, false))
+        } else {
+            Ok(crate::api_request_from_matches(matches, &schemas.0).await?)
+        }
+    }
+
+    /// Writes the previews or `curl` commands of the requests for `--dry-run` or `--print-curl`, or returns the
+    /// requests to send them.
+    async fn preview_all(
+        &mut self,
+        api_client: &mcu::ApiClient<'_>,
+        requests: Vec<mcu::ApiRequest>,
+        matches: &clap::ArgMatches,
+    ) -> anyhow::Result<Option<Vec<mcu::ApiRequest>>> {
+        if matches.get_flag("dry_run") {
+            for request in &requests {
+                write!(self.console, "{}", api_client.preview(request))?;
+            }
+            return Ok(None);
+        }
+        if let Some(print_curl) = matches.get_one::<String>("print_curl") {
+            for request in requests {
+                self.print_curl(api_client, request, matches, print_curl)
+                    .await?;
+            }
+            return Ok(None);
+        }
+        Ok(Some(requests))
+    }
+
+    /// Asks whether to delete the object, if `request` is a `Delete` and someone is there to answer.
+    ///
+    /// Returns whether the request should be sent.
+    fn confirm_delete(&self, request: &mcu::ApiRequest, matches: &clap::ArgMatches) -> bool {
+        let mcu::ApiRequest::Delete {
+            resource,
+            object_id,
+            ..
+        } = request
+        else {
+            return true;
+        };
+        if crate::yes_from_matches(matches)
+            || !self.console.is_stderr_interactive()
+            || !std::io::stdin().is_terminal()
+        {
+            return true;
+        }
+        login::Interactive {}.confirm(&format!("Delete {resource}/{object_id}?"))
+    }
+
+    /// Sets the output format, date format and fields to write the response to `request` with.
+    fn set_output_options(
+        &mut self,
+        request: &mcu::ApiRequest,
+        endpoint: Option<&schema::Endpoint>,
+        matches: &clap::ArgMatches,
+    ) {
+        let output_format = argparse::CommandGen::output_format(matches);
+        let output_format = match request.resource() {
+            Some((api, resource)) => output_format.for_resource(api, resource),
+            None => output_format,
+        }
+        .for_endpoint(endpoint);
+        let dates = argparse::CommandGen::date_format(matches)
+            .map(|format| DateFormatter::for_endpoint(format, endpoint));
+        let fields = crate::fields_from_matches(matches);
+        if let (Some(fields), Some(endpoint)) = (&fields, endpoint) {
+            for field in fields
+                .iter()
+                .filter(|field| !endpoint.fields.contains_key(*field))
+            {
+                self.console
+                    .display_warning(&format!("{field} is not a field of this endpoint"));
+            }
+        }
+
+        self.console.set_output_format(output_format);
+        self.console.set_date_formatter(dates);
+        self.console
+            .set_fields(fields.map(|fields| fields.into_iter().collect()));
+    }
+
+    /// Prints a `curl` command equivalent to the first HTTP request that sending `request` would make, for
+    /// `--print-curl`.
+    async fn print_curl(
+        &mut self,
+        api_client: &mcu::ApiClient,
+        request: mcu::ApiRequest,
+        matches: &clap::ArgMatches,
+        mode: &str,
+    ) -> anyhow::Result<()> {
+        let request = api_client.prepare_request(request).await?;
+        writeln!(
+            self.console,
+            "{}",
+            cli::curl::curl_command(&request, matches.get_flag("insecure"), mode == "unsafe")
+        )?;
+        Ok(())
+    }
+
+    /// Sends each of the `Delete` requests, reporting which objects were deleted once all of them have been sent.
+    ///
+    /// A failure to delete one object does not stop the others from being deleted, but the command fails at the end.
+    async fn delete_all(
+        &mut self,
+        api_client: &mcu::ApiClient,
+        requests: Vec<mcu::ApiRequest>,
+    ) -> anyhow::Result<()> {
+        let object_ids: Vec<String> = requests
+            .iter()
+            .filter_map(|request| match request {
+                mcu::ApiRequest::Delete { object_id, .. } => Some(object_id.clone()),
+                _ => None,
+            })
+            .collect();
+        let responses = api_client.pipeline_requests(requests).await;
+
+        let mut failed = 0;
+        for (object_id, response) in object_ids.iter().zip(responses) {
+            match response {
+                Ok(_) => writeln!(self.console, "deleted {object_id}").unwrap(),
+                Err(error) => {
+                    failed += 1;
+                    self.console
+                        .display_warning(&format!("failed to delete {object_id}: {error}"));
+                }
+            }
+        }
+
+        if failed > 0 {
+            return Err(error::UserFriendly::new(format!(
+                "failed to delete {failed} of {} objects",
+                object_ids.len()
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Sends each of the `Post` requests, printing the location of each object created once all of them have been sent.
+    ///
+    /// A failure to create one object does not stop the others from being created, but the command fails at the end.
+    async fn post_all(
+        &mut self,
+        api_client: &mcu::ApiClient,
+        requests: Vec<mcu::ApiRequest>,
+    ) -> anyhow::Result<()> {
+        let total = requests.len();
+        let responses = api_client.pipeline_requests(requests).await;
+
+        let mut failed = 0;
+        for (index, response) in responses.into_iter().enumerate() {
+            match response {
+                Ok(ApiResponse::Location(location)) => {
+                    writeln!(self.console, "{location}").unwrap();
+                }
+                Ok(_) => (),
+                Err(error) => {
+                    failed += 1;
+                    self.console.display_warning(&format!(
+                        "failed to create element {index} of the payload array: {error}"
+                    ));
                 }
             }
+        }
+
+        if failed > 0 {
+            return Err(error::UserFriendly::new(format!(
+                "failed to create {failed} of {total} objects"
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Writes an API response to stdout in the chosen output format.
+    pub async fn write_response(
+        &mut self,
+        response: ApiResponse<'_>,
+        stream_output: bool,
+    ) -> anyhow::Result<()> {
+        match response {
+            ApiResponse::ContentStream(response_content) => {
+                self.console
+                    .write_objects(response_content, stream_output)
+                    .await?;
+            }
             ApiResponse::Content(response_content) => {
-                self.console.pretty_print_json(&response_content);
+                self.console.write_value(response_content).await?;
             }
             ApiResponse::Location(location) => {
                 writeln!(self.console, "{location}").unwrap();
             }
             ApiResponse::Nothing => (),
         };
-
-        drop(api_client);
-
-        config.set_last_used()?;
-
         Ok(())
     }
 
     pub async fn run(&mut self, args: Vec<String>) -> anyhow::Result<()> {
         // File lock option to store the config file lock to maintain the lifetime
         // Read config file
-        let mut config = read_config(self.directories, &self.env, &mut self.console)?;
+        let mut config = match read_config(self.directories, &self.env, &mut self.console) {
+            Ok(config) => config,
+            Err(error) if argparse::Doctor.is_requested(&args) => {
+                return self.run_doctor(Err(&error), &reqwest::Client::new()).await;
+            }
+            Err(error) => return Err(error),
+        };
 
         // Read schema from cache directory
         let cache_dir = self.directories.cache_dir.join("schemas");
-        let all_schemas = if schema::cache_exists(&cache_dir) {
-            schema::read_all_schemas(&cache_dir).await?
-        } else {
-            HashMap::new()
-        };
-        let schemas = argparse::CommandGen(all_schemas);
+        let schema_dir = self.schema_dir(&config, &cache_dir);
+        let cached_schemas = read_schemas(&schema_dir).await?;
+        let schemas = argparse::CommandGen(cached_schemas.schemas);
 
         // Setup clap command based on schema
-        let command = schemas.command();
-        let matches = match command.clone().try_get_matches_from(args) {
+        let command = argparse::alias::with_alias_help(schemas.command(), config.get_aliases());
+        let args = argparse::alias::expand_alias(&command, config.get_aliases(), args)?;
+        check_schema_errors(&command, &args, &cached_schemas.corrupt)?;
+        let matches = match command.clone().try_get_matches_from(&args) {
             Ok(matches) => matches,
-            Err(error) => {
-                if error.kind() != clap::error::ErrorKind::DisplayVersion
-                    && !cache_exists(&cache_dir)
-                {
-                    self.console.display_warning(
-                        "schema cache is missing - please generate it with: pexshell cache",
-                    );
-                }
-
-                if self.console.is_stderr_interactive() {
-                    writeln!(self.console.stderr(), "{}", error.render().ansi())?;
-                } else {
-                    writeln!(self.console.stderr(), "{}", error.render())?;
-                }
-                if error.exit_code() == 0 {
-                    return Ok(());
-                }
-                return Err(anyhow!(ExitCode::from(error.exit_code())));
-            }
+            Err(error) => return self.display_parse_error(&error, &cache_dir),
         };
 
-        // Log to file
-        if let Some(log_file) = matches.get_one::<PathBuf>("log") {
-            LOGGER.set_log_file(Some(log_file.clone()))?;
+        self.apply_global_options(&matches)?;
+        let client = self.build_client(&config, &matches)?;
+
+        // tee
+        if let Some(tee_sub) = matches.subcommand_matches(&argparse::Tee.to_string()) {
+            let args = argparse::Tee.start(self, &args, tee_sub)?;
+            // release the config file lock before the command reads the config again
+            drop(config);
+            return Box::pin(self.run(args)).await;
         }
 
-        // Setup web client
-        let client = {
-            let unsafe_client = matches.get_flag("insecure");
-            let client = reqwest::Client::builder().danger_accept_invalid_certs(unsafe_client);
-            client.build()
-        }?;
+        // init
+        if let Some(init_sub) = matches.subcommand_matches(&argparse::Init.to_string()) {
+            return self
+                .run_init(&mut config, &cache_dir, client, &matches, init_sub)
+                .await;
+        }
 
         // login
         if let Some(login_sub) = matches.subcommand_matches(&argparse::Login.to_string()) {
@@ -229,36 +695,445 @@ impl<'a> PexShell<'a> {
             return Ok(());
         }
 
+        // alias
+        if let Some(alias_sub) = matches.subcommand_matches(&argparse::Alias.to_string()) {
+            argparse::Alias.run(self, &mut config, &command, alias_sub)?;
+            return Ok(());
+        }
+
+        // config
+        if let Some(config_sub) = matches.subcommand_matches(&argparse::Config.to_string()) {
+            argparse::Config.run(self, &mut config, &schema_dir, config_sub)?;
+            return Ok(());
+        }
+
+        // history
+        if let Some(list_sub) = argparse::History.list_matches(&matches) {
+            let directories = self.directories;
+            argparse::History.run(self, &config, list_sub, directories)?;
+            return Ok(());
+        }
+
+        // doctor
+        if matches
+            .subcommand_matches(&argparse::Doctor.to_string())
+            .is_some()
+        {
+            return self.run_doctor(Ok(&config), &client).await;
+        }
+
+        // invoke and raw
+        if self
+            .run_unchecked_request(&mut config, client.clone(), &matches, &args)
+            .await?
+        {
+            return Ok(());
+        }
+
+        // token
+        if let Some(token_sub) = matches.subcommand_matches(&argparse::Token.to_string()) {
+            argparse::Token
+                .run(self, &mut config, client, token_sub)
+                .await?;
+            return Ok(());
+        }
+
         // cache
         if let Some(cache_matches) = matches.subcommand_matches(&argparse::Cache.to_string()) {
-            argparse::Cache
-                .run(&mut config, &cache_dir, client, cache_matches)
+            self.run_cache(&mut config, &cache_dir, client, &matches, cache_matches)
                 .await?;
+            self.record_history(&config, &args);
             return Ok(());
-        } else if !cache_exists(&cache_dir) {
-            config.get_current_user()?; // show config error instead of schema cache error if no current user
-            self.console.display_warning(
-                "schema cache is missing - please generate it with: pexshell cache",
-            );
-            return Err(error::UserFriendly::new(
-                "schema cache is missing - please generate it with: pexshell cache",
+        }
+        let schemas = self
+            .check_schema_cache(
+                &mut config,
+                &cache_dir,
+                &schema_dir,
+                &client,
+                &matches,
+                schemas,
             )
-            .into());
+            .await?;
+
+        self.run_with_schemas(
+            &mut config,
+            client,
+            &command,
+            &matches,
+            &args,
+            &cache_dir,
+            &schemas,
+        )
+        .await
+    }
+
+    /// Runs the subcommands and API requests that need the schemas, once the schema cache has been checked.
+    #[allow(clippy::too_many_arguments)]
+    async fn run_with_schemas(
+        &mut self,
+        config: &mut ConfigManager,
+        client: reqwest::Client,
+        command: &clap::Command,
+        matches: &clap::ArgMatches,
+        args: &[String],
+        cache_dir: &Path,
+        schemas: &argparse::CommandGen,
+    ) -> anyhow::Result<()> {
+        // pin-schema
+        if let Some(pin_sub) = matches.subcommand_matches(&argparse::PinSchema.to_string()) {
+            argparse::PinSchema.run(self, config, cache_dir, pin_sub)?;
+            return Ok(());
+        }
+
+        // export
+        if let Some(export_sub) = matches.subcommand_matches(&argparse::Export.to_string()) {
+            argparse::Export
+                .run(self, config, client, &schemas.0, matches, export_sub)
+                .await?;
+            self.record_history(config, args);
+            return Ok(());
+        }
+
+        // import
+        if let Some(import_sub) = matches.subcommand_matches(&argparse::Import.to_string()) {
+            argparse::Import
+                .run(self, config, client, &schemas.0, matches, import_sub)
+                .await?;
+            self.record_history(config, args);
+            return Ok(());
+        }
+
+        // watch
+        if let Some(watch_sub) = matches.subcommand_matches(&argparse::Watch.to_string()) {
+            argparse::Watch
+                .run(self, config, client, &schemas.0, matches, watch_sub)
+                .await?;
+            self.record_history(config, args);
+            return Ok(());
+        }
+
+        // schema
+        if let Some(schema_sub) = matches.subcommand_matches(&argparse::Schema.to_string()) {
+            return self
+                .run_schema(config, client, cache_dir, schemas, matches, schema_sub)
+                .await;
         }
 
         // completions
         if let Some(completions_sub) =
             matches.subcommand_matches(&argparse::Completions.to_string())
         {
-            argparse::Completions.run(self, &command, completions_sub);
+            argparse::Completions.run(self, command, completions_sub)?;
             return Ok(());
         }
 
         // api request
-        self.api_request(client, &mut config, &matches, &schemas)
-            .await?;
+        self.api_request(client, config, matches, schemas).await?;
+        self.record_history(config, args);
         Ok(())
     }
+
+    /// Writes an error from parsing the command line to stderr, failing with its exit code unless it was a request
+    /// for help or the version.
+    fn display_parse_error(&mut self, error: &clap::Error, cache_dir: &Path) -> anyhow::Result<()> {
+        if error.kind() != clap::error::ErrorKind::DisplayVersion && !cache_exists(cache_dir) {
+            self.console.display_warning(
+                "schema cache is missing - please generate it with: pexshell cache",
+            );
+        }
+
+        if self.console.is_stderr_interactive() {
+            writeln!(self.console.stderr(), "{}", error.render().ansi())?;
+        } else {
+            writeln!(self.console.stderr(), "{}", error.render())?;
+        }
+        if error.exit_code() == 0 {
+            return Ok(());
+        }
+        Err(anyhow!(ExitCode::from(error.exit_code())))
+    }
+
+    /// Applies the global logging and output options.
+    fn apply_global_options(&mut self, matches: &clap::ArgMatches) -> anyhow::Result<()> {
+        // Log format
+        if let Some(log_format) = argparse::CommandGen::log_format(matches) {
+            LOGGER.set_log_format(log_format);
+        }
+
+        // Log to file
+        if let Some(log_file) = matches.get_one::<PathBuf>("log") {
+            LOGGER.set_log_file(Some(log_file.clone()))?;
+        }
+
+        // Log level
+        if let Some(log_level) = argparse::CommandGen::log_level(matches) {
+            LOGGER.set_max_level(log_level);
+        }
+
+        // Output file
+        if let Some(path) = matches.get_one::<PathBuf>("output_file") {
+            let file = std::fs::File::create(path).map_err(|e| {
+                error::UserFriendly::new(format!("failed to create {}: {e}", path.display()))
+            })?;
+            self.console.redirect_stdout(file);
+        }
+        Ok(())
+    }
+
+    /// Builds the web client from the global options and the settings of the current user.
+    fn build_client(
+        &self,
+        config: &ConfigManager,
+        matches: &clap::ArgMatches,
+    ) -> anyhow::Result<reqwest::Client> {
+        let unsafe_client = matches.get_flag("insecure");
+        let response_timeout = argparse::CommandGen::response_timeout(matches);
+        let client_identity = argparse::CommandGen::client_identity(matches);
+        let (response_timeout, client_identity) = match config.get_current_user() {
+            Ok(user) => (
+                user.settings.response_timeout(response_timeout),
+                user.settings.client_identity(client_identity)?,
+            ),
+            Err(_) => (response_timeout.map(Duration::from_secs), client_identity),
+        };
+        let mut client = with_proxy(
+            reqwest::Client::builder().danger_accept_invalid_certs(unsafe_client),
+            matches.get_one::<String>("proxy").map(String::as_str),
+            &self.env,
+        )?;
+        let ca_cert = matches
+            .get_one::<PathBuf>("cacert")
+            .cloned()
+            .or_else(|| config.get_ca_cert());
+        if let Some(ca_cert) = ca_cert {
+            for cert in read_ca_certs(&ca_cert)? {
+                client = client.add_root_certificate(cert);
+            }
+        }
+        if let Some((cert, key)) = client_identity {
+            client = client.identity(read_client_identity(&cert, &key)?);
+        }
+        if let Some(response_timeout) = response_timeout {
+            client = client.read_timeout(response_timeout);
+        }
+        if let Some(timeout) = argparse::CommandGen::timeout(matches, config.get_timeout()) {
+            client = client.timeout(timeout);
+        }
+        Ok(client.build()?)
+    }
+
+    /// Runs `pexshell init`, which sets up the config and schema cache interactively.
+    async fn run_init(
+        &mut self,
+        config: &mut ConfigManager,
+        cache_dir: &Path,
+        client: reqwest::Client,
+        matches: &clap::ArgMatches,
+        init_sub: &clap::ArgMatches,
+    ) -> anyhow::Result<()> {
+        let retry_policy = argparse::CommandGen::retry_policy(matches, config.get_retries());
+        argparse::Init
+            .run(
+                self,
+                config,
+                cache_dir,
+                client,
+                retry_policy,
+                argparse::CommandGen::compat_version(matches),
+                init_sub,
+            )
+            .await
+    }
+
+    /// Runs `pexshell doctor`, which still runs the checks that don't need the config if it couldn't be read.
+    async fn run_doctor(
+        &mut self,
+        config: Result<&ConfigManager, &anyhow::Error>,
+        client: &reqwest::Client,
+    ) -> anyhow::Result<()> {
+        let directories = self.directories;
+        argparse::Doctor
+            .run(self, config, client, directories)
+            .await
+    }
+
+    /// Runs `pexshell invoke` or `pexshell raw`, which send a request that isn't checked against the schema.
+    ///
+    /// Returns whether either of them was run.
+    async fn run_unchecked_request(
+        &mut self,
+        config: &mut ConfigManager,
+        client: reqwest::Client,
+        matches: &clap::ArgMatches,
+        args: &[String],
+    ) -> anyhow::Result<bool> {
+        if let Some(invoke_sub) = matches.subcommand_matches(&argparse::Invoke.to_string()) {
+            argparse::Invoke
+                .run(self, config, client, matches, invoke_sub)
+                .await?;
+        } else if let Some(raw_sub) = matches.subcommand_matches(&argparse::Raw.to_string()) {
+            argparse::Raw
+                .run(self, config, client, matches, raw_sub)
+                .await?;
+        } else {
+            return Ok(false);
+        }
+        self.record_history(config, args);
+        Ok(true)
+    }
+
+    /// Runs `pexshell cache`, which clears or regenerates the schema cache.
+    async fn run_cache(
+        &mut self,
+        config: &mut ConfigManager,
+        cache_dir: &Path,
+        client: reqwest::Client,
+        matches: &clap::ArgMatches,
+        cache_matches: &clap::ArgMatches,
+    ) -> anyhow::Result<()> {
+        if argparse::Cache.is_clear(cache_matches) {
+            argparse::Cache.clear(self, cache_dir, cache_matches)?;
+            return Ok(());
+        }
+        let retry_policy = argparse::CommandGen::retry_policy(matches, config.get_retries());
+        argparse::Cache
+            .run(
+                config,
+                cache_dir,
+                client,
+                retry_policy,
+                argparse::CommandGen::compat_version(matches),
+                cache_matches,
+            )
+            .await
+    }
+
+    /// Refreshes the schema cache for `--refresh-schema`, or otherwise checks that it exists and warns if it is stale.
+    ///
+    /// Returns the schemas to generate requests from, which are re-read if the cache was refreshed.
+    async fn check_schema_cache(
+        &mut self,
+        config: &mut ConfigManager,
+        cache_dir: &Path,
+        schema_dir: &Path,
+        client: &reqwest::Client,
+        matches: &clap::ArgMatches,
+        schemas: argparse::CommandGen,
+    ) -> anyhow::Result<argparse::CommandGen> {
+        if matches.get_flag("refresh_schema") && schema_dir != cache_dir {
+            // the refreshed schema cache wouldn't be read
+            self.console.display_warning(
+                "--refresh-schema was ignored because the schemas of this management node are pinned - \
+                 remove pinned_schema_version from the config file to use the schema cache",
+            );
+        } else if matches.get_flag("refresh_schema") {
+            let retry_policy = argparse::CommandGen::retry_policy(matches, config.get_retries());
+            let fetched = argparse::Cache
+                .fetch(
+                    config,
+                    cache_dir,
+                    client.clone(),
+                    retry_policy,
+                    argparse::CommandGen::compat_version(matches),
+                    argparse::FetchOptions::default(),
+                )
+                .await;
+            // the endpoints whose schemas were cached can still be used
+            match fetched {
+                Err(e) if e.is::<schema::FailedSchemas>() => {
+                    self.console.display_warning(&e.to_string());
+                }
+                fetched => fetched?,
+            }
+            return Ok(argparse::CommandGen(
+                schema::read_all_schemas(schema_dir, None).await?.schemas,
+            ));
+        } else if !cache_exists(cache_dir) {
+            config.get_current_user()?; // show config error instead of schema cache error if no current user
+            self.console.display_warning(
+                "schema cache is missing - please generate it with: pexshell cache",
+            );
+            return Err(error::UserFriendly::new(
+                "schema cache is missing - please generate it with: pexshell cache",
+            )
+            .into());
+        } else if self.console.is_stderr_interactive() {
+            let ttl_days = config
+                .get_schema_cache_ttl()
+                .unwrap_or(DEFAULT_SCHEMA_CACHE_TTL_DAYS);
+            if let Some(warning) = schema::cache_modified(cache_dir)
+                .ok()
+                .and_then(|modified| stale_cache_warning(modified, SystemTime::now(), ttl_days))
+            {
+                self.console.display_warning(&warning);
+            }
+        }
+        Ok(schemas)
+    }
+
+    /// Runs `pexshell schema`, which fetches the schema of an endpoint or shows a cached one.
+    async fn run_schema(
+        &mut self,
+        config: &mut ConfigManager,
+        client: reqwest::Client,
+        cache_dir: &Path,
+        schemas: &argparse::CommandGen,
+        matches: &clap::ArgMatches,
+        schema_sub: &clap::ArgMatches,
+    ) -> anyhow::Result<()> {
+        if let Some(fetch_sub) = schema_sub.subcommand_matches("fetch") {
+            argparse::Schema
+                .fetch(
+                    self, config, client, cache_dir, &schemas.0, matches, fetch_sub,
+                )
+                .await
+        } else {
+            argparse::Schema.run(self, &schemas.0, schema_sub)?;
+            Ok(())
+        }
+    }
+
+    /// Gets the directory to read schemas from, which is a copy of the schema cache if the current user has pinned
+    /// the schemas to a version of their management node.
+    fn schema_dir(&mut self, config: &impl ConfigProvider, cache_dir: &Path) -> PathBuf {
+        let Some((address, version)) = config.get_current_user().ok().and_then(|user| {
+            let version = user.settings.pinned_schema_version.clone()?;
+            Some((user.address.clone(), version))
+        }) else {
+            return cache_dir.to_owned();
+        };
+        let pinned_dir = schema::get_pinned_cache_dir(cache_dir, &address, &version);
+        if schema::cache_exists(&pinned_dir) {
+            pinned_dir
+        } else {
+            self.console.display_warning(&format!(
+                "schemas pinned to version {version} are missing - using the schema cache instead"
+            ));
+            cache_dir.to_owned()
+        }
+    }
+
+    /// Records a successfully completed command in the history file, if enabled.
+    #[allow(clippy::unused_self)]
+    fn record_history(&self, config: &ConfigManager, args: &[String]) {
+        let Some(history_file) =
+            history::file_path(config.get_history_file_path(), &self.directories.data_dir)
+        else {
+            return;
+        };
+        let server = config
+            .get_current_user()
+            .ok()
+            .map(|user| user.address.clone());
+        let entry = history::Entry::new(Utc::now(), args.get(1..).unwrap_or_default(), server, 0);
+        if let Err(e) = history::append(&history_file, &entry) {
+            warn!(
+                "failed to write to history file {}: {e}",
+                history_file.display()
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -266,9 +1141,15 @@ mod tests {
     use googletest::prelude::*;
     use std::collections::HashMap;
 
-    use crate::{cli::Console, pexshell::read_config, test_util::TestContextExtensions};
+    use crate::{
+        cli::Console,
+        pexshell::{read_config, stale_cache_warning},
+        test_util::TestContextExtensions,
+    };
     use lib::util::SimpleLogger;
     use log::{Level, Log, Record};
+    use std::time::{Duration, SystemTime};
+    use test_case::test_case;
     use test_helpers::get_test_context;
 
     /// Make sure logging enabled logic is working in the shell crate
@@ -314,21 +1195,39 @@ mod tests {
 
         // Assert
         assert_that!(config_path.exists(), eq(true));
-        let log_file_path = String::from(dirs.tmp_dir.join("pexshell.log").to_str().unwrap());
+        let toml_path = |path: std::path::PathBuf| {
+            let path = String::from(path.to_str().unwrap());
+            if path.contains('\\') {
+                format!("'{path}'")
+            } else {
+                format!("\"{path}\"")
+            }
+        };
         assert_that!(
             std::fs::read_to_string(&config_path),
             ok(eq(&format!(
                 r#"[log]
-file = {file_path}
+file = {log_file_path}
 "#,
-                file_path = if log_file_path.contains('\\') {
-                    format!("'{log_file_path}'")
-                } else {
-                    format!("\"{log_file_path}\"")
-                }
+                log_file_path = toml_path(dirs.tmp_dir.join("pexshell.log")),
             )))
         );
 
         std::fs::remove_file(config_path).unwrap();
     }
+
+    #[test_case(29, 30, None ; "within ttl")]
+    #[test_case(31, 30, Some("schema cache was generated 31 days ago - please regenerate it with: pexshell cache") ; "past ttl")]
+    #[test_case(400, 0, None ; "ttl disabled")]
+    fn test_stale_cache_warning(age_days: u64, ttl_days: u64, expected: Option<&str>) {
+        // Arrange
+        let now = SystemTime::now();
+        let modified = now - Duration::from_secs(age_days * 24 * 60 * 60);
+
+        // Act
+        let warning = stale_cache_warning(modified, now, ttl_days);
+
+        // Assert
+        assert_that!(warning.as_deref(), eq(expected));
+    }
 }
@@ -13,6 +13,7 @@ impl TestContextExtensions for TestContext {
         Directories {
             config_dir: self.get_config_dir().to_path_buf(),
             cache_dir: self.get_cache_dir().to_path_buf(),
+            data_dir: self.get_test_dir().join("data"),
             tmp_dir: self.get_test_dir().join("tmp"),
         }
     }
@@ -9,6 +9,26 @@ use serde_with::skip_serializing_none;
 
 use crate::TestContext;
 
+/// The cache paths of the APIs in the schema cache, relative to the schemas directory.
+pub const SCHEMA_APIS: [&str; 6] = [
+    "configuration",
+    "history",
+    "status",
+    "command/conference",
+    "command/participant",
+    "command/platform",
+];
+
+/// Gets the path of an API on the management node from its path in the schema cache, e.g.
+/// `command/conference` -> `/api/admin/command/v1/conference/`.
+#[must_use]
+pub fn api_path(api: &str) -> String {
+    match api.split_once('/') {
+        Some((api, sub_api)) => format!("/api/admin/{api}/v1/{sub_api}/"),
+        None => format!("/api/admin/{api}/v1/"),
+    }
+}
+
 #[skip_serializing_none]
 #[derive(Clone, Serialize)]
 struct RootSchemaEntry {
@@ -210,6 +230,13 @@ impl SchemaBuilder {
         self
     }
 
+    /// Allows filtering on a field, where `level` is `1` for all filters or `2` for all filters and related fields.
+    #[must_use]
+    pub fn filtering(mut self, name: impl Into<String>, level: isize) -> Self {
+        self.schema.filtering.insert(name.into(), level);
+        self
+    }
+
     pub fn write(&self, rel_path: impl AsRef<Path>) {
         let path = self.base_path.join(rel_path);
         std::fs::create_dir_all(path.parent().unwrap()).unwrap();
@@ -227,3 +254,42 @@ impl SchemaBuilder {
         serde_json::to_string(&self.schema).unwrap()
     }
 }
+
+/// Collects endpoint schemas to write to the schema cache together.
+///
+/// Used through [`TestContext::with_schema`].
+pub struct SchemaCacheBuilder<'a> {
+    test_context: &'a TestContext,
+    endpoints: Vec<(String, String, SchemaBuilder)>,
+}
+
+impl<'a> SchemaCacheBuilder<'a> {
+    pub(crate) fn new(test_context: &'a TestContext) -> Self {
+        Self {
+            test_context,
+            endpoints: vec![],
+        }
+    }
+
+    /// Adds an endpoint schema, where `api` is the path of the API in the schema cache, e.g. `configuration` or
+    /// `command/conference`.
+    #[must_use]
+    pub fn add_endpoint(
+        mut self,
+        api: impl Into<String>,
+        resource: impl Into<String>,
+        f: impl FnOnce(SchemaBuilder) -> SchemaBuilder,
+    ) -> Self {
+        let schema = f(SchemaBuilder::new(self.test_context));
+        self.endpoints.push((api.into(), resource.into(), schema));
+        self
+    }
+
+    pub(crate) fn write(self) {
+        for (api, resource, schema) in &self.endpoints {
+            self.test_context
+                .seed_schema(api, resource, &schema.to_value());
+        }
+        self.test_context.seed_minimal_schemas();
+    }
+}
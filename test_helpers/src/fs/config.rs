@@ -32,6 +32,8 @@ struct User {
 
 #[derive(Serialize)]
 struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    history_file: Option<PathBuf>,
     users: Vec<User>,
 }
 
@@ -44,7 +46,10 @@ impl Configurer {
     pub(crate) fn new(test_context: &TestContext) -> Self {
         Self {
             config_path: test_context.get_config_dir().join("config.toml"),
-            config: Config { users: vec![] },
+            config: Config {
+                history_file: None,
+                users: vec![],
+            },
         }
     }
 
@@ -92,6 +97,12 @@ impl Configurer {
         self
     }
 
+    #[must_use]
+    pub fn history_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.history_file = Some(path.into());
+        self
+    }
+
     /// Writes the config file to disk. If any parent directories do not exist, they are created.
     ///
     /// # Panics
@@ -13,6 +13,7 @@ pub mod fs;
 pub mod future;
 pub mod googletest;
 pub mod logging;
+pub mod requests;
 
 use std::{
     io::Write,
@@ -20,11 +21,12 @@ use std::{
     sync::Arc,
 };
 
-use fs::{Configurer, RootSchemaBuilder, SchemaBuilder};
+use fs::{Configurer, RootSchemaBuilder, SchemaBuilder, SchemaCacheBuilder};
 use log::{info, warn, LevelFilter};
 use logging::{TestLogger, TestLoggerContext, TestLoggerPermit};
 use once_cell::sync::OnceCell;
 use parking_lot::Mutex;
+use requests::FlakyServer;
 use uuid::Uuid;
 
 #[must_use]
@@ -294,17 +296,127 @@ impl TestContext {
         SchemaBuilder::new(self)
     }
 
+    /// Writes the endpoint schemas added by `f` to the schema cache, along with an empty root schema for each other
+    /// API, before returning the context.
+    ///
+    /// # Example
+    /// ```
+    /// use test_helpers::get_test_context;
+    ///
+    /// let test_context = get_test_context().with_schema(|builder| {
+    ///     builder
+    ///         .add_endpoint("configuration", "conference", |schema| schema.field("name", |f| f))
+    ///         .add_endpoint("command/conference", "lock", |schema| schema)
+    /// });
+    /// assert!(test_context.get_cache_dir().join("schemas/command/conference/lock.json").exists());
+    /// assert!(test_context.get_cache_dir().join("schemas/status/root.json").exists());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if reading or writing the schema cache fails.
+    #[must_use]
+    pub fn with_schema(
+        self,
+        f: impl FnOnce(SchemaCacheBuilder<'_>) -> SchemaCacheBuilder<'_>,
+    ) -> Self {
+        f(SchemaCacheBuilder::new(&self)).write();
+        self
+    }
+
     /// Used to build a pexshell root schema cache file programmatically.
     pub fn get_root_schema_builder(&self, api_path: impl Into<String>) -> RootSchemaBuilder {
         RootSchemaBuilder::new(self, api_path)
     }
 
+    /// Writes an endpoint schema to the schema cache and adds the endpoint to the root schema of its API.
+    ///
+    /// `api` is the path of the API in the schema cache, e.g. `configuration` or `command/conference`.
+    ///
+    /// # Example
+    /// ```
+    /// use test_helpers::get_test_context;
+    ///
+    /// let test_context = get_test_context();
+    /// test_context.seed_minimal_schemas();
+    /// test_context.seed_schema("configuration", "conference", &test_context.get_schema_builder().to_value());
+    /// assert!(test_context.get_cache_dir().join("schemas/configuration/conference.json").exists());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if reading or writing the schema cache fails.
+    pub fn seed_schema(&self, api: &str, resource: &str, schema_json: &serde_json::Value) {
+        let api_dir = self.cache_dir.join("schemas").join(api);
+        std::fs::create_dir_all(&api_dir).unwrap();
+        std::fs::write(
+            api_dir.join(format!("{resource}.json")),
+            serde_json::to_string(schema_json).unwrap(),
+        )
+        .unwrap();
+
+        let root_path = api_dir.join("root.json");
+        let mut root_schema: serde_json::Map<String, serde_json::Value> =
+            match std::fs::read_to_string(&root_path) {
+                Ok(contents) => serde_json::from_str(&contents).unwrap(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_json::Map::new(),
+                Err(e) => panic!("failed to read {}: {e}", root_path.display()),
+            };
+        let entry = self
+            .get_root_schema_builder(fs::api_path(api))
+            .entry(resource)
+            .to_value();
+        root_schema.insert(String::from(resource), entry[resource].clone());
+        std::fs::write(root_path, serde_json::to_string(&root_schema).unwrap()).unwrap();
+    }
+
+    /// Creates an empty root schema for each API that does not already have one, so that the schema cache is complete.
+    ///
+    /// # Panics
+    /// Panics if writing the schema cache fails.
+    pub fn seed_minimal_schemas(&self) {
+        for api in fs::SCHEMA_APIS {
+            if !self
+                .cache_dir
+                .join("schemas")
+                .join(api)
+                .join("root.json")
+                .exists()
+            {
+                self.get_root_schema_builder(fs::api_path(api))
+                    .write(format!("{api}/root.json"));
+            }
+        }
+    }
+
     #[allow(clippy::unused_self)]
     pub fn logger(&self) -> &TestLoggerContext {
         self.logging_context
             .get_or_init(|| self.logging_permit.lock().take().unwrap().promote())
     }
 
+    /// Starts a server that closes the connection without responding to the first `n_failures` requests to `path`,
+    /// and forwards every other request to a mock server.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async fn example() {
+    /// use test_helpers::get_test_context;
+    ///
+    /// let test_context = get_test_context();
+    /// let server = test_context
+    ///     .simulate_network_error("/api/admin/status/v1/worker_vm/", 1)
+    ///     .await;
+    /// // mount mocks on server.mock_server() and send requests to server.uri()
+    /// # }
+    /// ```
+    #[allow(clippy::unused_self)]
+    pub async fn simulate_network_error(
+        &self,
+        path: impl Into<String>,
+        n_failures: usize,
+    ) -> FlakyServer {
+        FlakyServer::start(path, n_failures).await
+    }
+
     pub fn get_stdout_wrapper(&self) -> impl std::io::Write {
         let buffer = Arc::clone(&self.stdout_buffer);
         VirtualFile { buffer }
@@ -36,6 +36,12 @@ pub fn level(level: log::Level) -> impl Expectation {
     Level { level }
 }
 
+/// Matches logs more severe than a specific log level, e.g. `above(log::Level::Info)` matches warnings and errors.
+#[must_use]
+pub fn above(level: log::Level) -> impl Expectation {
+    Above { level }
+}
+
 /// Matches log messages that contain the specified substring.
 #[must_use]
 pub fn contains(substring: &str) -> impl Expectation {
@@ -63,6 +69,15 @@ macro_rules! predicate {
     }};
 }
 
+/// Matches the same log messages as `inner`, but refers to it by `name` in failure messages.
+#[must_use]
+pub fn named(name: impl Into<String>, inner: impl Expectation) -> impl Expectation {
+    Named {
+        name: name.into(),
+        inner,
+    }
+}
+
 /// Matches if and only if all expectations completely match (`MatchResult::Complete`) on the given log message.
 /// Partial matches (`MatchResult::Match`) are ignored.
 #[must_use]
@@ -280,6 +295,28 @@ impl Expectation for Level {
     fn reset(&mut self) {}
 }
 
+struct Above {
+    level: log::Level,
+}
+
+impl Debug for Above {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Above").field("level", &self.level).finish()
+    }
+}
+
+impl Expectation for Above {
+    fn matches(&mut self, record: &log::Record) -> MatchResult {
+        if record.level() < self.level {
+            MatchResult::Complete
+        } else {
+            MatchResult::NotMatch
+        }
+    }
+
+    fn reset(&mut self) {}
+}
+
 struct Contains {
     substring: String,
 }
@@ -329,6 +366,27 @@ impl Expectation for Predicate {
     fn reset(&mut self) {}
 }
 
+struct Named<E> {
+    name: String,
+    inner: E,
+}
+
+impl<E: Expectation> Debug for Named<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expectation '{}': {:?}", self.name, self.inner)
+    }
+}
+
+impl<E: Expectation> Expectation for Named<E> {
+    fn matches(&mut self, record: &log::Record) -> MatchResult {
+        self.inner.matches(record)
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
 struct All {
     expectations: Vec<Box<dyn Expectation>>,
 }
@@ -702,6 +760,38 @@ mod tests {
         assert_that!(exact.matches(&record), eq(match_result));
     }
 
+    #[test_case("message", MatchResult::Complete)]
+    #[test_case("other", MatchResult::NotMatch)]
+    fn test_named(substring: &str, match_result: MatchResult) {
+        let record = Record::builder().args(format_args!("message")).build();
+
+        let mut named = named("message logged", contains(substring));
+
+        assert_that!(named.matches(&record), eq(match_result));
+    }
+
+    #[test]
+    fn test_named_debug() {
+        let named = named("message logged", contains("message"));
+
+        assert_that!(
+            format!("{named:?}"),
+            eq("expectation 'message logged': Contains { substring: \"message\" }")
+        );
+    }
+
+    #[test_case(log::Level::Error, MatchResult::Complete)]
+    #[test_case(log::Level::Warn, MatchResult::Complete)]
+    #[test_case(log::Level::Info, MatchResult::NotMatch)]
+    #[test_case(log::Level::Debug, MatchResult::NotMatch)]
+    fn test_above(level: log::Level, match_result: MatchResult) {
+        let record = Record::builder().level(level).build();
+
+        let mut above = above(log::Level::Info);
+
+        assert_that!(above.matches(&record), eq(match_result));
+    }
+
     #[test_case(
         Some("some_file.log"),
         Some(1),
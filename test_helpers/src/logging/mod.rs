@@ -1,10 +1,13 @@
-use std::io::Write;
+use std::{
+    io::Write,
+    sync::{Arc, Weak},
+};
 
 use chrono::{SecondsFormat, Utc};
 use console::Color;
 use expect::Expectation;
 use log::Level;
-use parking_lot::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use parking_lot::{MappedMutexGuard, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 pub mod expect;
 
@@ -61,21 +64,34 @@ impl TestLoggerContext<'_> {
         self.test_logger.expect(expectation);
     }
 
-    /// Verify all expectations have been met.
+    /// Forbid logs matching an expectation, e.g. `forbid(expect::above(log::Level::Info))` to check that nothing was
+    /// logged at warn or above.
+    ///
+    /// Matching logs are recorded and cause [`TestLoggerContext::verify`] to panic.
+    pub fn forbid(&self, expectation: impl Expectation) {
+        self.test_logger.forbid(expectation);
+    }
+
+    /// Start recording all logs for assertions after they have been logged.
+    pub fn capture(&self) -> LogCapture {
+        self.test_logger.capture()
+    }
+
+    /// Verify all expectations have been met and no forbidden logs were observed.
     ///
     /// # Panics
-    /// Panics if any expectations have not been met.
+    /// Panics if any expectations have not been met or any forbidden logs were observed.
     pub fn verify(&self) {
         self.test_logger.verify();
     }
 
-    /// Check if expectations have been met without panicking if they haven't.
+    /// Check if expectations have been met and no forbidden logs were observed without panicking otherwise.
     #[must_use]
     pub fn expectations_met(&self) -> bool {
         self.test_logger.expectations_met()
     }
 
-    /// Clear all unmet expectations without verifying them.
+    /// Clear all unmet expectations and forbidden logs without verifying them.
     pub fn clear(&self) {
         self.test_logger.clear();
     }
@@ -87,9 +103,62 @@ impl Drop for TestLoggerContext<'_> {
     }
 }
 
+/// A log record captured by a [`LogCapture`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapturedRecord {
+    pub level: log::Level,
+    pub target: String,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+impl CapturedRecord {
+    fn new(record: &log::Record) -> Self {
+        Self {
+            level: record.level(),
+            target: String::from(record.target()),
+            message: record.args().to_string(),
+            file: record.file().map(String::from),
+            line: record.line(),
+        }
+    }
+}
+
+/// Records all logs from when it was created until it is dropped.
+///
+/// # Example
+/// ```
+/// use googletest::prelude::*;
+/// use test_helpers::get_test_context;
+///
+/// let test_context = get_test_context();
+/// let capture = test_context.logger().capture();
+/// log::info!("hello");
+/// assert_that!(capture.records().iter().map(|r| r.message.as_str()).collect::<Vec<_>>(), contains(eq("hello")));
+/// ```
+pub struct LogCapture {
+    records: Arc<Mutex<Vec<CapturedRecord>>>,
+}
+
+impl LogCapture {
+    /// Gets the logs captured so far, oldest first.
+    pub fn records(&self) -> MappedMutexGuard<[CapturedRecord]> {
+        MutexGuard::map(self.records.lock(), |records| records.as_mut_slice())
+    }
+}
+
+/// An expectation that must not match, along with the logs it matched.
+struct Forbidden {
+    expectation: Box<dyn Expectation>,
+    observed: Vec<String>,
+}
+
 pub struct TestLogger {
     config: RwLock<TestLoggerConfig>,
     expectations: Mutex<Vec<Box<dyn Expectation>>>,
+    forbidden: Mutex<Vec<Forbidden>>,
+    captures: Mutex<Vec<Weak<Mutex<Vec<CapturedRecord>>>>>,
     // Required due to logging being global
     test_lock: RwLock<()>,
 }
@@ -102,6 +171,8 @@ impl TestLogger {
                 log_level: log::LevelFilter::Info,
             }),
             expectations: Mutex::new(Vec::new()),
+            forbidden: Mutex::new(Vec::new()),
+            captures: Mutex::new(Vec::new()),
             test_lock: RwLock::new(()),
         }
     }
@@ -140,26 +211,56 @@ impl TestLogger {
         self.expectations.lock().push(Box::new(expectation));
     }
 
+    fn forbid(&self, expectation: impl Expectation) {
+        self.forbidden.lock().push(Forbidden {
+            expectation: Box::new(expectation),
+            observed: Vec::new(),
+        });
+    }
+
+    /// Start recording all logs for assertions after they have been logged.
+    ///
+    /// Logs are recorded until the returned [`LogCapture`] is dropped or the logger is cleared.
+    pub fn capture(&self) -> LogCapture {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        self.captures.lock().push(Arc::downgrade(&records));
+        LogCapture { records }
+    }
+
     #[allow(clippy::significant_drop_tightening)]
     fn verify(&self) {
         let expectations = self.expectations.lock();
-        if expectations.is_empty() {
-            return;
+        let forbidden = self.forbidden.lock();
+        let mut message = String::new();
+        if !expectations.is_empty() {
+            message += "Some logging expectations were not met:\n";
+            for expectation in &*expectations {
+                message += &format!("{expectation:?}").indent(4);
+            }
         }
-        let mut expectation_message_list = String::new();
-        for expectation in &*expectations {
-            expectation_message_list += &format!("{expectation:?}").indent(4);
+        for forbidden in forbidden.iter().filter(|f| !f.observed.is_empty()) {
+            if !message.is_empty() {
+                message += "\n";
+            }
+            message += &format!(
+                "Forbidden logs matching {:?} were observed:{}",
+                forbidden.expectation,
+                forbidden.observed.join("\n").indent(4)
+            );
         }
-        panic!("Some logging expectations were not met:\n{expectation_message_list}");
+        assert!(message.is_empty(), "{message}");
     }
 
     #[must_use]
     fn expectations_met(&self) -> bool {
         self.expectations.lock().is_empty()
+            && self.forbidden.lock().iter().all(|f| f.observed.is_empty())
     }
 
     fn clear(&self) {
         self.expectations.lock().clear();
+        self.forbidden.lock().clear();
+        self.captures.lock().clear();
     }
 }
 
@@ -193,7 +294,21 @@ impl log::Log for TestLogger {
         } else {
             console::Style::new()
         };
-        eprintln!("{}", style.apply_to(log));
+        eprintln!("{}", style.apply_to(&log));
+
+        self.captures.lock().retain(|capture| {
+            capture.upgrade().is_some_and(|records| {
+                records.lock().push(CapturedRecord::new(record));
+                true
+            })
+        });
+
+        for forbidden in &mut *self.forbidden.lock() {
+            if forbidden.expectation.matches(record) == MatchResult::Complete {
+                forbidden.observed.push(log.clone());
+                forbidden.expectation.reset();
+            }
+        }
 
         let mut expectations = self.expectations.lock();
         let mut maybe_remove = None;
@@ -217,3 +332,85 @@ impl log::Log for TestLogger {
         std::io::stderr().flush().unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::panic::AssertUnwindSafe;
+
+    use googletest::prelude::*;
+    use log::{Log, Record};
+
+    use super::*;
+
+    fn log_message(logger: &TestLogger, level: log::Level, message: &str) {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .args(format_args!("{message}"))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn test_forbid() {
+        // Arrange
+        let logger = TestLogger::new();
+        logger.forbid(expect::above(log::Level::Info));
+
+        // Act
+        log_message(&logger, log::Level::Info, "all good");
+        let met_before_warning = logger.expectations_met();
+        log_message(&logger, log::Level::Warn, "something went wrong");
+
+        // Assert
+        assert_that!(met_before_warning, eq(true));
+        assert_that!(logger.expectations_met(), eq(false));
+        let panic = std::panic::catch_unwind(AssertUnwindSafe(|| logger.verify())).unwrap_err();
+        assert_that!(
+            panic.downcast_ref::<String>(),
+            some(contains_substring("something went wrong"))
+        );
+    }
+
+    #[test]
+    fn test_capture() {
+        // Arrange
+        let logger = TestLogger::new();
+        log_message(&logger, log::Level::Info, "before capture");
+        let capture = logger.capture();
+
+        // Act
+        log_message(&logger, log::Level::Info, "first");
+        log_message(&logger, log::Level::Warn, "second");
+
+        // Assert
+        assert_that!(
+            capture.records().to_vec(),
+            elements_are![
+                pat!(CapturedRecord {
+                    level: eq(&log::Level::Info),
+                    message: eq("first"),
+                    ..
+                }),
+                pat!(CapturedRecord {
+                    level: eq(&log::Level::Warn),
+                    message: eq("second"),
+                    ..
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dropped_capture_is_removed() {
+        // Arrange
+        let logger = TestLogger::new();
+        drop(logger.capture());
+
+        // Act
+        log_message(&logger, log::Level::Info, "message");
+
+        // Assert
+        assert_that!(logger.captures.lock().len(), eq(0));
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use parking_lot::Mutex;
+use serde_json::Value;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+use wiremock::{Match, MockServer, Request};
+
+/// Captures the JSON bodies of requests received by a mock server, so that tests can assert on the exact payload
+/// that was sent.
+///
+/// Add a clone of the capture as the last matcher of a mock, so that only requests matching the rest of the mock
+/// are captured. The capture itself matches every request. Requests without a JSON body are captured as
+/// [`Value::Null`].
+///
+/// ```
+/// # use test_helpers::requests::BodyCapture;
+/// # use wiremock::{matchers::method, Mock, ResponseTemplate};
+/// let bodies = BodyCapture::new();
+/// let mock = Mock::given(method("POST"))
+///     .and(bodies.clone())
+///     .respond_with(ResponseTemplate::new(201));
+/// ```
+#[derive(Clone, Default)]
+pub struct BodyCapture {
+    bodies: Arc<Mutex<Vec<Value>>>,
+}
+
+impl BodyCapture {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the bodies of all captured requests, in the order they were received.
+    #[must_use]
+    pub fn bodies(&self) -> Vec<Value> {
+        self.bodies.lock().clone()
+    }
+
+    /// Gets the body of the only captured request.
+    ///
+    /// # Panics
+    /// Panics if no requests or more than one request were captured.
+    #[must_use]
+    pub fn single(&self) -> Value {
+        let bodies = self.bodies.lock();
+        assert!(
+            bodies.len() == 1,
+            "expected exactly one request to be captured, but {} were",
+            bodies.len()
+        );
+        bodies[0].clone()
+    }
+}
+
+impl Match for BodyCapture {
+    fn matches(&self, request: &Request) -> bool {
+        let body = serde_json::from_slice(&request.body).unwrap_or(Value::Null);
+        self.bodies.lock().push(body);
+        true
+    }
+}
+
+/// A server that closes the connection without responding to the first few requests for a path, to simulate network
+/// errors. Every other request is forwarded to a [`MockServer`], which the mocks for the test should be mounted on.
+///
+/// Only the first request sent on each connection is checked, so a client that reuses a connection for a later
+/// request to the path will reach the mock server.
+pub struct FlakyServer {
+    address: SocketAddr,
+    mock_server: MockServer,
+    failures: Arc<AtomicUsize>,
+    task: JoinHandle<()>,
+}
+
+impl FlakyServer {
+    /// Starts a server that closes the connection for the first `n_failures` requests to `path`.
+    ///
+    /// # Panics
+    /// Panics if the server cannot listen on a local port.
+    pub async fn start(path: impl Into<String>, n_failures: usize) -> Self {
+        let mock_server = MockServer::start().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let failures = Arc::new(AtomicUsize::new(n_failures));
+        let task = tokio::spawn(accept_connections(
+            listener,
+            *mock_server.address(),
+            path.into(),
+            Arc::clone(&failures),
+        ));
+        Self {
+            address,
+            mock_server,
+            failures,
+            task,
+        }
+    }
+
+    /// Gets the base URI of the server, which requests should be sent to instead of the mock server.
+    #[must_use]
+    pub fn uri(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Gets the mock server that requests which aren't failed are forwarded to.
+    #[must_use]
+    pub const fn mock_server(&self) -> &MockServer {
+        &self.mock_server
+    }
+
+    /// Gets how many more requests to the path will be failed.
+    #[must_use]
+    pub fn failures_remaining(&self) -> usize {
+        self.failures.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for FlakyServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn accept_connections(
+    listener: TcpListener,
+    mock_server: SocketAddr,
+    path: String,
+    failures: Arc<AtomicUsize>,
+) {
+    while let Ok((mut stream, _)) = listener.accept().await {
+        let path = path.clone();
+        let failures = Arc::clone(&failures);
+        tokio::spawn(async move {
+            if request_path(&stream).await.as_deref() == Some(path.as_str())
+                && failures
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                    .is_ok()
+            {
+                // dropping the stream with the request unread resets the connection
+                return;
+            }
+            if let Ok(mut upstream) = TcpStream::connect(mock_server).await {
+                let _ = tokio::io::copy_bidirectional(&mut stream, &mut upstream).await;
+            }
+        });
+    }
+}
+
+/// Peeks at the path of the first request sent on a connection, without removing it from the stream.
+async fn request_path(stream: &TcpStream) -> Option<String> {
+    let mut buffer = [0; 4096];
+    loop {
+        let read = stream.peek(&mut buffer).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        if let Some(end) = buffer[..read].windows(2).position(|w| w == b"\r\n") {
+            let request_line = std::str::from_utf8(&buffer[..end]).ok()?;
+            let target = request_line.split(' ').nth(1)?;
+            return target.split('?').next().map(String::from);
+        }
+        if read == buffer.len() {
+            return None;
+        }
+        // the rest of the request line hasn't arrived yet
+        tokio::task::yield_now().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use googletest::prelude::*;
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_body_capture() {
+        // Arrange
+        let server = MockServer::start().await;
+        let bodies = BodyCapture::new();
+        Mock::given(method("POST"))
+            .and(bodies.clone())
+            .respond_with(ResponseTemplate::new(201))
+            .mount(&server)
+            .await;
+        let client = reqwest::Client::new();
+
+        // Act
+        client
+            .post(server.uri())
+            .json(&json!({"name": "first"}))
+            .send()
+            .await
+            .unwrap();
+        client
+            .post(server.uri())
+            .body("not json")
+            .send()
+            .await
+            .unwrap();
+        client.get(server.uri()).send().await.unwrap();
+
+        // Assert
+        assert_that!(
+            bodies.bodies(),
+            elements_are![eq(&json!({"name": "first"})), eq(&Value::Null)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_body_capture_single() {
+        // Arrange
+        let server = MockServer::start().await;
+        let bodies = BodyCapture::new();
+        Mock::given(method("PATCH"))
+            .and(bodies.clone())
+            .respond_with(ResponseTemplate::new(202))
+            .mount(&server)
+            .await;
+
+        // Act
+        reqwest::Client::new()
+            .patch(server.uri())
+            .json(&json!({"id": 1}))
+            .send()
+            .await
+            .unwrap();
+
+        // Assert
+        assert_that!(bodies.single(), eq(&json!({"id": 1})));
+    }
+
+    #[tokio::test]
+    async fn test_flaky_server_fails_first_requests() {
+        // Arrange
+        let server = FlakyServer::start("/flaky/", 2).await;
+        Mock::given(path("/flaky/"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(server.mock_server())
+            .await;
+        let client = reqwest::Client::new();
+        let uri = format!("{}/flaky/", server.uri());
+
+        // Act
+        let first = client.get(&uri).send().await;
+        let second = client.get(&uri).send().await;
+        let third = client.get(&uri).send().await;
+
+        // Assert
+        assert_that!(first, err(anything()));
+        assert_that!(second, err(anything()));
+        assert_that!(third.map(|r| r.status().as_u16()), ok(eq(200)));
+        assert_that!(server.failures_remaining(), eq(0));
+    }
+
+    #[tokio::test]
+    async fn test_flaky_server_forwards_other_paths() {
+        // Arrange
+        let server = FlakyServer::start("/flaky/", 1).await;
+        Mock::given(path("/other/"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(server.mock_server())
+            .await;
+
+        // Act
+        let response = reqwest::Client::new()
+            .get(format!("{}/other/?a=b", server.uri()))
+            .send()
+            .await;
+
+        // Assert
+        assert_that!(response.map(|r| r.status().as_u16()), ok(eq(204)));
+        assert_that!(server.failures_remaining(), eq(1));
+    }
+}